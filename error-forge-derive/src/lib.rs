@@ -28,6 +28,135 @@ use syn::{parse_macro_input, Data, DeriveInput, Fields};
 ///     #[error_display("Transaction error")]
 ///     #[error_http_status(400)]
 ///     TransactionError,
+///
+///     #[error_display("I/O failure on {path}")]
+///     Io { path: String, source: std::io::Error },
+///
+///     #[error_display("Replica lagging")]
+///     #[error_retryable]
+///     #[error_severity(Info)]
+///     #[error_class(RateLimited)]
+///     #[error_docs("https://docs.example.com/errors/replica-lag")]
+///     ReplicaLag,
+/// }
+/// ```
+///
+/// Placeholders such as `{reason}` or `{reason:?}` name fields of the
+/// variant; positional placeholders such as `{0}` refer to tuple fields.
+/// Structs take `#[error_display(...)]` the same way:
+///
+/// ```ignore
+/// #[derive(Debug, ModError)]
+/// #[error_prefix("Quota")]
+/// #[error_display("{used} of {limit} requests used")]
+/// pub struct QuotaError {
+///     used: u32,
+///     limit: u32,
+/// }
+/// ```
+///
+/// `#[error_kind(...)]` replaces the kind, which is otherwise the
+/// variant or struct name, and takes a bare word or a string.
+/// `#[error_caption("...")]` replaces the default `"<prefix>: Error"`
+/// caption. Both work on variants and on structs, as do the status,
+/// exit code, severity, class, docs, and code attributes; a struct's
+/// `#[error_from]` field generates a `From` impl like a variant's.
+///
+/// `#[error_user_message("...")]` sets `ForgeError::user_message`
+/// separately from `Display`, with the fields bound the same way, so
+/// end users see a safe message while logs keep the technical one. It
+/// works on variants and on structs.
+///
+/// `#[error_retryable]` and `#[error_fatal]` also take an explicit
+/// value, as in `#[error_fatal(false)]`. On an enum they set the
+/// default for every variant; on a variant or struct they set its own.
+///
+/// `#[error_constructors]` on an enum generates a constructor per
+/// variant, named after the variant in lowercase as in
+/// `define_errors!`, that takes the variant's fields in order, captures
+/// any `#[error_backtrace]` field, and fires the error hook.
+/// `#[error_constructor(false)]` on a variant skips its constructor,
+/// and `#[error_constructor(name)]` renames it. Transparent variants
+/// take the wrapped error and do not fire the hook again.
+///
+/// ```ignore
+/// #[derive(Debug, ModError)]
+/// #[error_constructors]
+/// pub enum DbError {
+///     #[error_display("Connection to {0} failed")]
+///     ConnectionFailed(String),
+/// }
+///
+/// let err = DbError::connectionfailed("db.internal".to_string());
+/// ```
+///
+/// `#[error_code("DB-003")]` on a variant is returned from
+/// `ForgeError::code()` and registered in the global `ErrorRegistry`,
+/// described by the display format or the variant's doc comment, the
+/// first time `code()` is called. The generated
+/// `register_error_codes()` registers every variant's code up front.
+///
+/// Generic types and lifetimes are supported. The `Display` impl uses
+/// the type's own bounds, so `DbError<T: Display>` can show a `T`
+/// field; `Error` additionally needs `Debug`, and `ForgeError` needs
+/// `Send + Sync + 'static`, so those impls apply to the instantiations
+/// that satisfy them.
+///
+/// `#[error_severity(...)]` takes `Debug`, `Info`, `Warning`, `Error`,
+/// or `Critical`; variants without it use the default
+/// `ForgeError::severity` derived from their fatal/retryable flags.
+///
+/// `#[error_class(...)]` takes `Transient`, `Permanent`, `RateLimited`,
+/// `Cancelled`, or `Unknown`; variants without it use the default
+/// `ForgeError::class` derived from their retryable flag.
+///
+/// `#[error_docs("...")]` sets the link returned by
+/// `ForgeError::docs_url`.
+///
+/// `Error::source()` returns the field marked `#[error_source]`, or
+/// else a field named `source`. The field may hold any error type,
+/// a `Box<dyn Error + ...>`, or an `Option` of either.
+///
+/// `#[error_from]` on the only field of a variant, like thiserror's
+/// `#[from]`, generates `From<FieldType>` for the enum so `?` converts
+/// the field's error into the variant. The field is also the source.
+///
+/// ```ignore
+/// #[derive(Debug, ModError)]
+/// pub enum LoadError {
+///     #[error_display("read failed")]
+///     Io(#[error_from] std::io::Error),
+/// }
+/// ```
+///
+/// `#[error_backtrace]` marks a `std::backtrace::Backtrace` or
+/// `Option<Backtrace>` field returned from `ForgeError::backtrace()`
+/// when it holds a captured trace. A variant may carry one next to its
+/// `#[error_from]` field; the generated `From` impl then fills it with
+/// `Backtrace::capture()`, which follows `RUST_BACKTRACE`.
+///
+/// ```ignore
+/// #[derive(Debug, ModError)]
+/// pub enum LoadError {
+///     #[error_display("read failed")]
+///     Io(#[error_from] std::io::Error, #[error_backtrace] Backtrace),
+/// }
+/// ```
+///
+/// `#[error_transparent]` on a variant with a single field forwards
+/// `Display` and `Error::source()` to that field, like thiserror's
+/// `#[error(transparent)]`. When the field is itself a `ForgeError`, its
+/// kind, caption, retryable and fatal flags, status and exit codes,
+/// severity, class, docs link, and code are forwarded as well; the
+/// variant's own attributes apply only to fields that are not, except
+/// `#[error_severity]`, `#[error_class]`, and `#[error_docs]`, which
+/// always take precedence.
+///
+/// ```ignore
+/// #[derive(Debug, ModError)]
+/// pub enum ServiceError {
+///     #[error_transparent]
+///     App(#[error_from] AppError),
 /// }
 /// ```
 ///
@@ -43,34 +172,275 @@ use syn::{parse_macro_input, Data, DeriveInput, Fields};
         error_retryable,
         error_http_status,
         error_exit_code,
-        error_fatal
+        error_fatal,
+        error_severity,
+        error_class,
+        error_docs,
+        error_user_message,
+        error_source,
+        error_from,
+        error_transparent,
+        error_backtrace,
+        error_constructors,
+        error_constructor,
+        error_code
     )
 )]
 pub fn derive_mod_error(input: TokenStream) -> TokenStream {
     // Parse the input
     let input = parse_macro_input!(input as DeriveInput);
 
-    // Check if this is an enum or struct
-    let is_enum = match &input.data {
-        Data::Enum(_) => true,
-        Data::Struct(_) => false,
-        Data::Union(_) => panic!("ModError cannot be derived for unions"),
-    };
+    // Reject misplaced, repeated, or malformed attributes up front, so
+    // the generators below can rely on them
+    if let Err(err) = check_attributes(&input) {
+        return err.to_compile_error().into();
+    }
 
     // Get the error prefix from attributes
     let error_prefix = get_error_prefix(&input.attrs);
 
     // Generate implementation based on whether it's an enum or struct
-    let implementation = if is_enum {
-        implement_for_enum(&input, &error_prefix)
-    } else {
-        implement_for_struct(&input, &error_prefix)
+    let implementation = match &input.data {
+        Data::Enum(data) => implement_for_enum(&input, data, &error_prefix),
+        _ => implement_for_struct(&input, &error_prefix),
     };
 
     // Return the generated implementation
     TokenStream::from(implementation)
 }
 
+/// Attribute macro for `fn main() -> Result<(), E>` where `E`
+/// implements `ForgeError`.
+///
+/// On error, the attribute prints the error in the negotiated output
+/// format (`--output <format>` flag, then the `ERROR_FORGE_OUTPUT`
+/// environment variable, then themed text) and exits with the
+/// error's `exit_code()`.
+///
+/// ```ignore
+/// use error_forge::AppError;
+///
+/// #[error_forge::main]
+/// fn main() -> Result<(), AppError> {
+///     Err(AppError::config("missing DATABASE_URL"))
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn main(args: TokenStream, input: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "#[error_forge::main] does not take arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let function = parse_macro_input!(input as syn::ItemFn);
+    if let Err(err) = check_main(&function) {
+        return err.to_compile_error().into();
+    }
+
+    let attrs = &function.attrs;
+    let vis = &function.vis;
+    let name = &function.sig.ident;
+    let output = &function.sig.output;
+    let body = &function.block;
+
+    let expanded = quote! {
+        #(#attrs)*
+        #vis fn #name() -> ::std::process::ExitCode {
+            fn __error_forge_main() #output #body
+
+            match __error_forge_main() {
+                ::std::result::Result::Ok(()) => ::std::process::ExitCode::SUCCESS,
+                ::std::result::Result::Err(err) => ::error_forge::output::report(&err),
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+// The wrapper calls the function synchronously with no arguments.
+fn check_main(function: &syn::ItemFn) -> syn::Result<()> {
+    if function.sig.asyncness.is_some() {
+        return Err(syn::Error::new_spanned(
+            function.sig.fn_token,
+            "#[error_forge::main] does not support async functions",
+        ));
+    }
+    if !function.sig.inputs.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &function.sig.inputs,
+            "#[error_forge::main] functions take no parameters; read arguments with std::env::args",
+        ));
+    }
+    Ok(())
+}
+
+// Where a derive attribute is written
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Site {
+    Enum,
+    Struct,
+    Variant,
+    Field,
+}
+
+impl Site {
+    fn describe(self) -> &'static str {
+        match self {
+            Site::Enum => "an enum",
+            Site::Struct => "a struct",
+            Site::Variant => "a variant",
+            Site::Field => "a field",
+        }
+    }
+}
+
+// Check every derive attribute on the type, its variants, and their
+// fields: each must be in a place it applies to, appear at most once
+// there, and hold a value of the right type.
+fn check_attributes(input: &DeriveInput) -> syn::Result<()> {
+    match &input.data {
+        Data::Enum(data) => {
+            check_item(&input.attrs, Site::Enum)?;
+            let constructors = input
+                .attrs
+                .iter()
+                .any(|attr| has_flag_attribute(attr, "error_constructors"));
+            for variant in &data.variants {
+                check_item(&variant.attrs, Site::Variant)?;
+                check_fields(&variant.fields)?;
+                let renamed = variant
+                    .attrs
+                    .iter()
+                    .find(|attr| attr.path.is_ident("error_constructor"));
+                if let (Some(attr), false) = (renamed, constructors) {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "#[error_constructor] has no effect without #[error_constructors] on the enum",
+                    ));
+                }
+            }
+        }
+        Data::Struct(data) => {
+            check_item(&input.attrs, Site::Struct)?;
+            check_fields(&data.fields)?;
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                "ModError cannot be derived for unions",
+            ))
+        }
+    }
+    Ok(())
+}
+
+fn check_fields(fields: &Fields) -> syn::Result<()> {
+    let mut source = None;
+    let mut backtrace = None;
+    for field in fields {
+        check_item(&field.attrs, Site::Field)?;
+        for attr in &field.attrs {
+            let seen = if has_flag_attribute(attr, "error_backtrace") {
+                &mut backtrace
+            } else if has_flag_attribute(attr, "error_source")
+                || has_flag_attribute(attr, "error_from")
+            {
+                &mut source
+            } else {
+                continue;
+            };
+            match seen {
+                Some(previous) if !std::ptr::eq(*previous, field) => {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "only one field can be the source, and only one the backtrace",
+                    ))
+                }
+                _ => *seen = Some(field),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_item(attrs: &[syn::Attribute], site: Site) -> syn::Result<()> {
+    use Site::*;
+
+    let mut seen = Vec::new();
+    for attr in attrs {
+        let Some(name) = attr.path.get_ident().map(ToString::to_string) else {
+            continue;
+        };
+        let allowed: &[Site] = match name.as_str() {
+            "error_prefix" => &[Enum, Struct],
+            "error_constructors" => &[Enum],
+            "error_retryable" | "error_fatal" => &[Enum, Struct, Variant],
+            "error_transparent" | "error_constructor" => &[Variant],
+            "error_source" | "error_from" | "error_backtrace" => &[Field],
+            "error_display" | "error_kind" | "error_caption" | "error_http_status"
+            | "error_exit_code" | "error_severity" | "error_class" | "error_docs"
+            | "error_code" | "error_user_message" => &[Struct, Variant],
+            _ => continue,
+        };
+        if !allowed.contains(&site) {
+            return Err(syn::Error::new_spanned(
+                attr,
+                format!("#[{name}] cannot be used on {}", site.describe()),
+            ));
+        }
+        if seen.contains(&name) {
+            return Err(syn::Error::new_spanned(
+                attr,
+                format!("duplicate #[{name}]"),
+            ));
+        }
+        check_value(attr, &name)?;
+        seen.push(name);
+    }
+    Ok(())
+}
+
+// Check that `attr` holds the kind of value `name` takes
+fn check_value(attr: &syn::Attribute, name: &str) -> syn::Result<()> {
+    let expected = match name {
+        "error_retryable" | "error_fatal" => return parse_flag_attribute(attr).map(drop),
+        "error_severity" => return parse_severity_attribute(attr).map(drop),
+        "error_class" => return parse_class_attribute(attr).map(drop),
+        // Checked where the constructors are generated
+        "error_constructor" => return Ok(()),
+        "error_kind" => match parse_word_attribute(attr)? {
+            Some(_) => return Ok(()),
+            None => "a name, as in #[error_kind(Network)]".to_string(),
+        },
+        "error_http_status" => match parse_int_attribute::<u16>(attr) {
+            Some(_) => return Ok(()),
+            None => "an HTTP status code, as in #[error_http_status(404)]".to_string(),
+        },
+        "error_exit_code" => match parse_int_attribute::<i32>(attr) {
+            Some(_) => return Ok(()),
+            None => "an integer exit code, as in #[error_exit_code(2)]".to_string(),
+        },
+        "error_constructors" | "error_transparent" | "error_source" | "error_from"
+        | "error_backtrace" => match attr.parse_meta()? {
+            syn::Meta::Path(_) => return Ok(()),
+            _ => format!("#[{name}] without arguments"),
+        },
+        _ => match parse_string_attribute(attr) {
+            Some(_) => return Ok(()),
+            None => format!("a string, as in #[{name}(\"...\")]"),
+        },
+    };
+    Err(syn::Error::new_spanned(
+        attr,
+        format!("expected {expected}"),
+    ))
+}
+
 // Extract error_prefix attribute value
 fn get_error_prefix(attrs: &[syn::Attribute]) -> String {
     for attr in attrs {
@@ -115,18 +485,386 @@ where
     }
 }
 
+// The single word in `#[attr(Word)]`, `#[attr("word")]`, or
+// `#[attr = "word"]`
+fn parse_word_attribute(attr: &syn::Attribute) -> syn::Result<Option<String>> {
+    Ok(match attr.parse_meta()? {
+        syn::Meta::List(meta) => match meta.nested.iter().next() {
+            Some(syn::NestedMeta::Meta(syn::Meta::Path(path))) => {
+                path.get_ident().map(|ident| ident.to_string())
+            }
+            Some(syn::NestedMeta::Lit(syn::Lit::Str(lit))) => Some(lit.value()),
+            _ => None,
+        },
+        syn::Meta::NameValue(meta) => match meta.lit {
+            syn::Lit::Str(lit) => Some(lit.value()),
+            _ => None,
+        },
+        syn::Meta::Path(_) => None,
+    })
+}
+
+// Parse `#[error_severity(Warning)]` or `#[error_severity("warning")]`
+// into the matching `ErrorLevel` variant name
+fn parse_severity_attribute(attr: &syn::Attribute) -> syn::Result<proc_macro2::Ident> {
+    let value = parse_word_attribute(attr)?;
+    let level = match value.as_deref().map(str::to_ascii_lowercase).as_deref() {
+        Some("debug") => "Debug",
+        Some("info") => "Info",
+        Some("warning") | Some("warn") => "Warning",
+        Some("error") => "Error",
+        Some("critical") | Some("fatal") => "Critical",
+        _ => {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "expected #[error_severity(Debug | Info | Warning | Error | Critical)]",
+            ))
+        }
+    };
+    Ok(proc_macro2::Ident::new(
+        level,
+        proc_macro2::Span::call_site(),
+    ))
+}
+
+// Parse `#[error_class(RateLimited)]` or `#[error_class("rate_limited")]`
+// into the matching `ErrorClass` variant name
+fn parse_class_attribute(attr: &syn::Attribute) -> syn::Result<proc_macro2::Ident> {
+    let value = parse_word_attribute(attr)?;
+    let class = match value
+        .as_deref()
+        .map(|value| value.to_ascii_lowercase().replace(['-', '_'], ""))
+        .as_deref()
+    {
+        Some("transient") => "Transient",
+        Some("permanent") => "Permanent",
+        Some("ratelimited") => "RateLimited",
+        Some("cancelled") | Some("canceled") => "Cancelled",
+        Some("unknown") => "Unknown",
+        _ => return Err(syn::Error::new_spanned(
+            attr,
+            "expected #[error_class(Transient | Permanent | RateLimited | Cancelled | Unknown)]",
+        )),
+    };
+    Ok(proc_macro2::Ident::new(
+        class,
+        proc_macro2::Span::call_site(),
+    ))
+}
+
 fn has_flag_attribute(attr: &syn::Attribute, name: &str) -> bool {
     attr.path.is_ident(name)
 }
 
-// Implement ModError for an enum
-fn implement_for_enum(input: &DeriveInput, error_prefix: &str) -> proc_macro2::TokenStream {
-    let name = &input.ident;
-    let data_enum = match &input.data {
-        Data::Enum(data) => data,
-        _ => panic!("Expected enum"),
+// Parse a boolean flag: `#[name]`, `#[name(false)]`, or `#[name = false]`
+fn parse_flag_attribute(attr: &syn::Attribute) -> syn::Result<bool> {
+    let value = match attr.parse_meta()? {
+        syn::Meta::Path(_) => return Ok(true),
+        syn::Meta::NameValue(meta) => match meta.lit {
+            syn::Lit::Bool(flag) => Some(flag.value),
+            _ => None,
+        },
+        syn::Meta::List(meta) if meta.nested.len() == 1 => match meta.nested.first() {
+            Some(syn::NestedMeta::Lit(syn::Lit::Bool(flag))) => Some(flag.value),
+            _ => None,
+        },
+        syn::Meta::List(_) => None,
+    };
+    value.ok_or_else(|| syn::Error::new_spanned(attr, "expected `true` or `false`"))
+}
+
+// `#[error_retryable]` and `#[error_fatal]` flags in `attrs`, starting
+// from the given defaults
+fn parse_flags(
+    attrs: &[syn::Attribute],
+    mut retryable: bool,
+    mut fatal: bool,
+) -> syn::Result<(bool, bool)> {
+    for attr in attrs {
+        if attr.path.is_ident("error_retryable") {
+            retryable = parse_flag_attribute(attr)?;
+        } else if attr.path.is_ident("error_fatal") {
+            fatal = parse_flag_attribute(attr)?;
+        }
+    }
+    Ok((retryable, fatal))
+}
+
+// Find the field `Error::source()` should return: the one marked
+// `#[error_source]` or `#[error_from]`, otherwise one named `source`
+fn find_source_field(fields: &Fields) -> Option<(usize, &syn::Field)> {
+    let marked = fields.iter().enumerate().find(|(_, field)| {
+        field.attrs.iter().any(|attr| {
+            has_flag_attribute(attr, "error_source") || has_flag_attribute(attr, "error_from")
+        })
+    });
+
+    marked.or_else(|| {
+        fields
+            .iter()
+            .enumerate()
+            .find(|(_, field)| field.ident.as_ref().is_some_and(|ident| ident == "source"))
+    })
+}
+
+// A match arm formatting `format` with the named or positional
+// fields of `path` it uses
+fn format_fields(
+    path: proc_macro2::TokenStream,
+    fields: &Fields,
+    format: &str,
+) -> proc_macro2::TokenStream {
+    match fields {
+        Fields::Named(fields) => {
+            let used: Vec<_> = fields
+                .named
+                .iter()
+                .filter_map(|f| f.ident.as_ref())
+                .filter(|name| format_uses_field(format, &name.to_string()))
+                .collect();
+            quote! { #path { #(#used,)* .. } => format!(#format #(, #used = #used)*) }
+        }
+        Fields::Unnamed(fields) => {
+            let count = fields.unnamed.len().min(format_positional_count(format));
+            let names: Vec<_> = (0..count).map(|i| format_ident!("_{}", i)).collect();
+            quote! { #path(#(#names,)* ..) => format!(#format #(, #names)*) }
+        }
+        Fields::Unit => quote! { #path => format!(#format) },
+    }
+}
+
+// Find the field marked `#[error_backtrace]`
+fn find_backtrace_field(fields: &Fields) -> Option<(usize, &syn::Field)> {
+    fields.iter().enumerate().find(|(_, field)| {
+        field
+            .attrs
+            .iter()
+            .any(|attr| has_flag_attribute(attr, "error_backtrace"))
+    })
+}
+
+// Build the `Option<&Backtrace>` expression for a backtrace field,
+// given a place expression that names the field's value. Traces that
+// were not captured are reported as `None`.
+fn backtrace_expression(
+    place: proc_macro2::TokenStream,
+    ty: &syn::Type,
+) -> proc_macro2::TokenStream {
+    let backtrace = if is_option_type(ty) {
+        quote! { #place.as_ref() }
+    } else {
+        quote! { ::std::option::Option::Some(&#place) }
+    };
+    quote! {
+        #backtrace.filter(|backtrace| {
+            backtrace.status() == ::std::backtrace::BacktraceStatus::Captured
+        })
+    }
+}
+
+// The value a generated constructor stores in a backtrace field
+fn backtrace_capture(ty: &syn::Type) -> proc_macro2::TokenStream {
+    if is_option_type(ty) {
+        quote! { ::std::option::Option::Some(::std::backtrace::Backtrace::capture()) }
+    } else {
+        quote! { ::std::backtrace::Backtrace::capture() }
+    }
+}
+
+// Whether `format` contains a `{field}` or `{field:...}` placeholder,
+// not counting escaped `{{field}}` text
+fn format_uses_field(format: &str, field: &str) -> bool {
+    let mut rest = format;
+    while let Some(index) = rest.find('{') {
+        rest = &rest[index + 1..];
+        if let Some(after) = rest.strip_prefix('{') {
+            rest = after;
+            continue;
+        }
+        let end = rest.find(['}', ':']).unwrap_or(rest.len());
+        if &rest[..end] == field {
+            return true;
+        }
+    }
+    false
+}
+
+// Number of positional arguments `format` consumes: the larger of the
+// highest `{N}` index plus one and the count of implicit `{}` placeholders
+fn format_positional_count(format: &str) -> usize {
+    let mut explicit = 0;
+    let mut implicit = 0;
+    let mut rest = format;
+    while let Some(index) = rest.find('{') {
+        rest = &rest[index + 1..];
+        if let Some(after) = rest.strip_prefix('{') {
+            rest = after;
+            continue;
+        }
+        let end = rest.find(['}', ':']).unwrap_or(rest.len());
+        let argument = &rest[..end];
+        if argument.is_empty() {
+            implicit += 1;
+        } else if let Ok(position) = argument.parse::<usize>() {
+            explicit = explicit.max(position + 1);
+        }
+    }
+    explicit.max(implicit)
+}
+
+fn is_option_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+// Build the `Option<&dyn Error>` expression for a source field, given
+// a place expression that names the field's value
+fn source_expression(place: proc_macro2::TokenStream, ty: &syn::Type) -> proc_macro2::TokenStream {
+    if is_option_type(ty) {
+        quote! {
+            #place.as_ref().map(|source| (*source).as_dyn_error())
+        }
+    } else {
+        quote! {
+            ::std::option::Option::Some(#place.as_dyn_error())
+        }
+    }
+}
+
+// The constructor `#[error_constructor(...)]` asks for on a variant:
+// `None` for `false`, otherwise the given name or the variant's name in
+// lowercase
+fn constructor_name(variant: &syn::Variant) -> syn::Result<Option<proc_macro2::Ident>> {
+    let attr = variant
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("error_constructor"));
+    let name = match attr {
+        Some(attr) => match parse_word_attribute(attr)? {
+            Some(name) => name,
+            None if matches!(
+                attr.parse_meta()?,
+                syn::Meta::List(meta) if matches!(
+                    meta.nested.first(),
+                    Some(syn::NestedMeta::Lit(syn::Lit::Bool(flag))) if !flag.value
+                )
+            ) =>
+            {
+                return Ok(None)
+            }
+            None => {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "expected #[error_constructor(false)] or #[error_constructor(name)]",
+                ))
+            }
+        },
+        None => variant.ident.to_string().to_lowercase(),
+    };
+    // Variants such as `Type` would otherwise produce a keyword.
+    Ok(Some(match syn::parse_str::<proc_macro2::Ident>(&name) {
+        Ok(ident) => ident,
+        Err(_) => proc_macro2::Ident::new_raw(&name, proc_macro2::Span::call_site()),
+    }))
+}
+
+// A constructor for `variant` taking every field but the backtrace,
+// which is captured. Unless `transparent`, it fires the error hook.
+fn constructor(
+    variant: &syn::Variant,
+    name: proc_macro2::Ident,
+    transparent: bool,
+) -> proc_macro2::TokenStream {
+    let variant_name = &variant.ident;
+    let backtrace = find_backtrace_field(&variant.fields).map(|(index, _)| index);
+    let mut params = Vec::new();
+    let mut values = Vec::new();
+    for (index, field) in variant.fields.iter().enumerate() {
+        let binding = match &field.ident {
+            Some(ident) => ident.clone(),
+            None => format_ident!("field_{}", index),
+        };
+        if backtrace == Some(index) {
+            values.push(backtrace_capture(&field.ty));
+        } else {
+            let ty = &field.ty;
+            params.push(quote! { #binding: #ty });
+            values.push(quote! { #binding });
+        }
+    }
+    let construct = match &variant.fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|field| &field.ident);
+            quote! { Self::#variant_name { #(#names: #values),* } }
+        }
+        Fields::Unnamed(_) => quote! { Self::#variant_name(#(#values),*) },
+        Fields::Unit => quote! { Self::#variant_name },
     };
 
+    if transparent {
+        let doc = format!("Wraps an error in [`Self::{}`].", variant_name);
+        return quote! {
+            #[doc = #doc]
+            pub fn #name(#(#params),*) -> Self {
+                #construct
+            }
+        };
+    }
+    let doc = format!(
+        "Creates [`Self::{}`] and fires the error hook.",
+        variant_name
+    );
+    quote! {
+        #[doc = #doc]
+        #[track_caller]
+        pub fn #name(#(#params),*) -> Self {
+            let error = #construct;
+            ::error_forge::macros::call_error_hook_with_level(
+                ::error_forge::error::ForgeError::severity(&error),
+                ::error_forge::error::ForgeError::caption(&error),
+                ::error_forge::error::ForgeError::kind(&error),
+                ::error_forge::error::ForgeError::is_fatal(&error),
+                ::error_forge::error::ForgeError::is_retryable(&error),
+            );
+            error
+        }
+    }
+}
+
+// The where clause of `generics` with `Self: bounds` added, so impls
+// on generic types only apply where the trait's supertraits hold
+fn where_self(
+    generics: &syn::Generics,
+    bounds: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let mut generics = generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(syn::parse_quote!(Self: #bounds));
+    let (_, _, where_clause) = generics.split_for_impl();
+    quote! { #where_clause }
+}
+
+// Implement ModError for an enum
+fn implement_for_enum(
+    input: &DeriveInput,
+    data_enum: &syn::DataEnum,
+    error_prefix: &str,
+) -> proc_macro2::TokenStream {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let error_where = where_self(&input.generics, quote! { ::std::fmt::Debug });
+    let forge_where = where_self(
+        &input.generics,
+        quote! { ::std::fmt::Debug + ::std::marker::Send + ::std::marker::Sync + 'static },
+    );
     // Generate match arms for each variant
     let mut kind_match_arms = Vec::new();
     let mut caption_match_arms = Vec::new();
@@ -135,6 +873,26 @@ fn implement_for_enum(input: &DeriveInput, error_prefix: &str) -> proc_macro2::T
     let mut fatal_match_arms = Vec::new();
     let mut status_code_match_arms = Vec::new();
     let mut exit_code_match_arms = Vec::new();
+    let mut severity_match_arms = Vec::new();
+    let mut class_match_arms = Vec::new();
+    let mut docs_match_arms = Vec::new();
+    let mut user_message_match_arms = Vec::new();
+    let mut code_match_arms = Vec::new();
+    let mut backtrace_match_arms = Vec::new();
+    let mut constructors = Vec::new();
+    let mut code_registrations = Vec::new();
+    let generate_constructors = input
+        .attrs
+        .iter()
+        .any(|attr| has_flag_attribute(attr, "error_constructors"));
+    // `#[error_retryable]` and `#[error_fatal]` on the enum set the
+    // default for every variant
+    let (default_retryable, default_fatal) = match parse_flags(&input.attrs, false, false) {
+        Ok(flags) => flags,
+        Err(err) => return err.to_compile_error(),
+    };
+    let mut from_impls = Vec::new();
+    let mut source_match_arms = Vec::new();
 
     // Process each variant
     for variant in &data_enum.variants {
@@ -145,29 +903,34 @@ fn implement_for_enum(input: &DeriveInput, error_prefix: &str) -> proc_macro2::T
         let mut display_format = variant_name_str.clone();
         let mut kind_name = variant_name_str.clone();
         let mut caption = format!("{}: Error", error_prefix);
-        let mut retryable = false;
-        let mut fatal = false;
+        let (retryable, fatal) = match parse_flags(&variant.attrs, default_retryable, default_fatal)
+        {
+            Ok(flags) => flags,
+            Err(err) => return err.to_compile_error(),
+        };
         let mut status_code: u16 = 500;
         let mut exit_code: i32 = 1;
+        let mut code = None;
+        let mut docs_url = None;
+        let mut has_display = false;
 
         // Extract attributes
         for attr in &variant.attrs {
             if attr.path.is_ident("error_display") {
                 if let Some(value) = parse_string_attribute(attr) {
                     display_format = value;
+                    has_display = true;
                 }
             } else if attr.path.is_ident("error_kind") {
-                if let Some(value) = parse_string_attribute(attr) {
-                    kind_name = value;
+                match parse_word_attribute(attr) {
+                    Ok(Some(value)) => kind_name = value,
+                    Ok(None) => {}
+                    Err(err) => return err.to_compile_error(),
                 }
             } else if attr.path.is_ident("error_caption") {
                 if let Some(value) = parse_string_attribute(attr) {
                     caption = value;
                 }
-            } else if attr.path.is_ident("error_retryable") {
-                retryable = true;
-            } else if has_flag_attribute(attr, "error_fatal") {
-                fatal = true;
             } else if attr.path.is_ident("error_http_status") {
                 if let Some(value) = parse_int_attribute(attr) {
                     status_code = value;
@@ -176,9 +939,263 @@ fn implement_for_enum(input: &DeriveInput, error_prefix: &str) -> proc_macro2::T
                 if let Some(value) = parse_int_attribute(attr) {
                     exit_code = value;
                 }
+            } else if attr.path.is_ident("error_severity") {
+                let level = match parse_severity_attribute(attr) {
+                    Ok(level) => level,
+                    Err(err) => return err.to_compile_error(),
+                };
+                severity_match_arms.push(quote! {
+                    Self::#variant_name { .. } => ::error_forge::macros::ErrorLevel::#level
+                });
+            } else if attr.path.is_ident("error_class") {
+                let class = match parse_class_attribute(attr) {
+                    Ok(class) => class,
+                    Err(err) => return err.to_compile_error(),
+                };
+                class_match_arms.push(quote! {
+                    Self::#variant_name { .. } => ::error_forge::error::ErrorClass::#class
+                });
+            } else if attr.path.is_ident("error_docs") {
+                if let Some(url) = parse_string_attribute(attr) {
+                    docs_match_arms.push(quote! {
+                        Self::#variant_name { .. } => ::std::option::Option::Some(#url.to_string())
+                    });
+                    docs_url = Some(url);
+                }
+            } else if attr.path.is_ident("error_code") {
+                match parse_string_attribute(attr) {
+                    Some(value) => code = Some(value),
+                    None => {
+                        return syn::Error::new_spanned(attr, "expected #[error_code(\"CODE\")]")
+                            .to_compile_error()
+                    }
+                }
             }
         }
 
+        // `#[error_code(...)]` is returned from `code()`, which registers
+        // it in the global `ErrorRegistry` the first time
+        if let Some(code) = &code {
+            let display = match has_display {
+                true => quote! { ::std::option::Option::Some(#display_format) },
+                false => quote! { ::std::option::Option::None },
+            };
+            let docs = variant
+                .attrs
+                .iter()
+                .filter_map(|attr| match attr.parse_meta() {
+                    Ok(syn::Meta::NameValue(meta)) if meta.path.is_ident("doc") => Some(meta.lit),
+                    _ => None,
+                });
+            let docs_url = match &docs_url {
+                Some(url) => quote! { ::std::option::Option::Some(#url) },
+                None => quote! { ::std::option::Option::None },
+            };
+            let register = quote! {
+                {
+                    static REGISTERED: ::std::sync::Once = ::std::sync::Once::new();
+                    REGISTERED.call_once(|| {
+                        ::error_forge::macros::register_variant_code(
+                            #code,
+                            #display,
+                            &[#(#docs),*],
+                            #caption,
+                            #docs_url,
+                            #retryable,
+                        )
+                    });
+                }
+            };
+            code_match_arms.push(quote! {
+                Self::#variant_name { .. } => {
+                    #register
+                    ::std::option::Option::Some(#code)
+                }
+            });
+            code_registrations.push(register);
+        }
+
+        // `#[error_from]` on the only field of a variant, besides an
+        // optional backtrace, converts the field's type into the variant
+        let backtrace_field = find_backtrace_field(&variant.fields);
+        let is_from = |field: &syn::Field| {
+            field
+                .attrs
+                .iter()
+                .any(|attr| has_flag_attribute(attr, "error_from"))
+        };
+        if let Some(field) = variant.fields.iter().find(|field| is_from(field)) {
+            if variant.fields.len() - usize::from(backtrace_field.is_some()) != 1 {
+                return syn::Error::new_spanned(
+                    variant,
+                    "#[error_from] requires a variant with exactly one field besides an #[error_backtrace] field",
+                )
+                .to_compile_error();
+            }
+            let ty = &field.ty;
+            let values = variant.fields.iter().map(|field| match is_from(field) {
+                true => quote! { source },
+                false => backtrace_capture(&field.ty),
+            });
+            let construct = match &variant.fields {
+                Fields::Named(_) => {
+                    let names = variant.fields.iter().map(|field| &field.ident);
+                    quote! { Self::#variant_name { #(#names: #values),* } }
+                }
+                _ => quote! { Self::#variant_name(#(#values),*) },
+            };
+            from_impls.push(quote! {
+                impl #impl_generics ::std::convert::From<#ty> for #name #ty_generics #where_clause {
+                    fn from(source: #ty) -> Self {
+                        #construct
+                    }
+                }
+            });
+        }
+
+        let transparent = variant
+            .attrs
+            .iter()
+            .any(|attr| has_flag_attribute(attr, "error_transparent"));
+        if generate_constructors {
+            match constructor_name(variant) {
+                Ok(Some(name)) => constructors.push(constructor(variant, name, transparent)),
+                Ok(None) => {}
+                Err(err) => return err.to_compile_error(),
+            }
+        }
+
+        // `#[error_transparent]` forwards everything to the only field,
+        // through its `ForgeError` impl when it has one
+        if transparent {
+            let field = match variant.fields.iter().next() {
+                Some(field) if variant.fields.len() == 1 => field,
+                _ => {
+                    return syn::Error::new_spanned(
+                        variant,
+                        "#[error_transparent] requires a variant with exactly one field",
+                    )
+                    .to_compile_error()
+                }
+            };
+            let member = match &field.ident {
+                Some(ident) => quote! { #ident },
+                None => quote! { 0 },
+            };
+            let pattern = quote! { Self::#variant_name { #member: __inner } };
+            let inner = quote! {
+                {
+                    #[allow(unused_imports)]
+                    use ::error_forge::__private::{ForgeProbeError as _, ForgeProbeFallback as _};
+                    (&::error_forge::__private::ForgeProbe(__inner)).__forge_error()
+                }
+            };
+            let forge = quote! { ::error_forge::error::ForgeError };
+
+            display_match_arms.push(quote! {
+                #pattern => ::std::string::ToString::to_string(__inner)
+            });
+            source_match_arms.push(quote! {
+                #pattern => ::std::error::Error::source((*__inner).as_dyn_error())
+            });
+            kind_match_arms.push(quote! {
+                #pattern => #inner.map_or(#kind_name, #forge::kind)
+            });
+            caption_match_arms.push(quote! {
+                #pattern => #inner.map_or(#caption, #forge::caption)
+            });
+            retryable_match_arms.push(quote! {
+                #pattern => #inner.map_or(#retryable, #forge::is_retryable)
+            });
+            fatal_match_arms.push(quote! {
+                #pattern => #inner.map_or(#fatal, #forge::is_fatal)
+            });
+            status_code_match_arms.push(quote! {
+                #pattern => #inner.map_or(#status_code, #forge::status_code)
+            });
+            exit_code_match_arms.push(quote! {
+                #pattern => #inner.map_or(#exit_code, #forge::exit_code)
+            });
+            severity_match_arms.push(quote! {
+                #pattern => #inner.map_or_else(
+                    || ::error_forge::macros::ErrorLevel::from_flags(
+                        #forge::is_fatal(self),
+                        #forge::is_retryable(self),
+                    ),
+                    #forge::severity,
+                )
+            });
+            class_match_arms.push(quote! {
+                #pattern => #inner.map_or_else(
+                    || ::error_forge::error::ErrorClass::from_retryable(#forge::is_retryable(self)),
+                    #forge::class,
+                )
+            });
+            docs_match_arms.push(quote! {
+                #pattern => #inner.and_then(#forge::docs_url)
+            });
+            user_message_match_arms.push(quote! {
+                #pattern => #inner.map_or_else(
+                    || ::std::string::ToString::to_string(__inner),
+                    #forge::user_message,
+                )
+            });
+            code_match_arms.push(quote! {
+                #pattern => #inner.and_then(#forge::code)
+            });
+            backtrace_match_arms.push(quote! {
+                #pattern => #inner.and_then(#forge::backtrace)
+            });
+            continue;
+        }
+
+        // Wire `backtrace()` to the variant's backtrace field, if any
+        if let Some((index, field)) = backtrace_field {
+            let binding = format_ident!("__backtrace");
+            let pattern = match &field.ident {
+                Some(ident) => quote! { Self::#variant_name { #ident: #binding, .. } },
+                None => {
+                    let index = syn::Index::from(index);
+                    quote! { Self::#variant_name { #index: #binding, .. } }
+                }
+            };
+            let expression = backtrace_expression(quote! { (*#binding) }, &field.ty);
+            backtrace_match_arms.push(quote! {
+                #pattern => #expression
+            });
+        }
+
+        // Wire `source()` to the variant's source field, if any
+        if let Some((index, field)) = find_source_field(&variant.fields) {
+            let binding = format_ident!("__source");
+            let pattern = match &field.ident {
+                Some(ident) => quote! { Self::#variant_name { #ident: #binding, .. } },
+                None => {
+                    let index = syn::Index::from(index);
+                    quote! { Self::#variant_name { #index: #binding, .. } }
+                }
+            };
+            let expression = source_expression(quote! { (*#binding) }, &field.ty);
+            source_match_arms.push(quote! {
+                #pattern => #expression
+            });
+        }
+
+        // `user_message()` from `#[error_user_message(...)]`, with the
+        // fields bound as for `#[error_display(...)]`
+        let user_message = variant
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("error_user_message"))
+            .find_map(parse_string_attribute);
+        if let Some(format) = user_message {
+            user_message_match_arms.push(format_fields(
+                quote! { Self::#variant_name },
+                &variant.fields,
+                &format,
+            ));
+        }
+
         // Generate pattern matching based on the variant's fields
         match &variant.fields {
             Fields::Named(fields) => {
@@ -198,8 +1215,14 @@ fn implement_for_enum(input: &DeriveInput, error_prefix: &str) -> proc_macro2::T
                     Self::#variant_name { .. } => #caption
                 });
 
+                // Only pass the fields the format string names, so fields
+                // such as a `source` can stay out of the message
+                let display_fields: Vec<_> = field_names
+                    .iter()
+                    .filter(|name| format_uses_field(&display_format, &name.to_string()))
+                    .collect();
                 display_match_arms.push(quote! {
-                    Self::#variant_name { #(#field_names),* } => format!(#display_format, #(#field_names = #field_names),*)
+                    Self::#variant_name { #(#display_fields,)* .. } => format!(#display_format #(, #display_fields = #display_fields)*)
                 });
 
                 retryable_match_arms.push(quote! {
@@ -219,7 +1242,12 @@ fn implement_for_enum(input: &DeriveInput, error_prefix: &str) -> proc_macro2::T
                 });
             }
             Fields::Unnamed(fields) => {
-                let field_count = fields.unnamed.len();
+                // Positional arguments must all be used, so only pass as
+                // many fields as the format string consumes
+                let field_count = fields
+                    .unnamed
+                    .len()
+                    .min(format_positional_count(&display_format));
                 let field_names: Vec<_> =
                     (0..field_count).map(|i| format_ident!("_{}", i)).collect();
 
@@ -234,7 +1262,7 @@ fn implement_for_enum(input: &DeriveInput, error_prefix: &str) -> proc_macro2::T
 
                 let field_pattern_list = field_names.iter().map(|name| quote! { #name, });
                 display_match_arms.push(quote! {
-                    Self::#variant_name(#(#field_pattern_list)*) => format!(#display_format #(, #field_names)*)
+                    Self::#variant_name(#(#field_pattern_list)* ..) => format!(#display_format #(, #field_names)*)
                 });
 
                 retryable_match_arms.push(quote! {
@@ -264,7 +1292,7 @@ fn implement_for_enum(input: &DeriveInput, error_prefix: &str) -> proc_macro2::T
                 });
 
                 display_match_arms.push(quote! {
-                    Self::#variant_name => #display_format.to_string()
+                    Self::#variant_name => format!(#display_format)
                 });
 
                 retryable_match_arms.push(quote! {
@@ -286,9 +1314,20 @@ fn implement_for_enum(input: &DeriveInput, error_prefix: &str) -> proc_macro2::T
         }
     }
 
+    let register_codes = (!code_registrations.is_empty()).then(|| {
+        quote! {
+            /// Registers the `#[error_code]` of every variant in the
+            /// global `ErrorRegistry`, for catalogs that should list
+            /// codes before any error is raised.
+            pub fn register_error_codes() {
+                #(#code_registrations)*
+            }
+        }
+    });
+
     // Generate implementation
     quote! {
-        impl ::std::fmt::Display for #name {
+        impl #impl_generics ::std::fmt::Display for #name #ty_generics #where_clause {
             fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
                 let msg = match self {
                     #(#display_match_arms,)*
@@ -297,7 +1336,7 @@ fn implement_for_enum(input: &DeriveInput, error_prefix: &str) -> proc_macro2::T
             }
         }
 
-        impl ::error_forge::error::ForgeError for #name {
+        impl #impl_generics ::error_forge::error::ForgeError for #name #ty_generics #forge_where {
             fn kind(&self) -> &'static str {
                 match self {
                     #(#kind_match_arms,)*
@@ -333,44 +1372,488 @@ fn implement_for_enum(input: &DeriveInput, error_prefix: &str) -> proc_macro2::T
                     #(#exit_code_match_arms,)*
                 }
             }
+
+            #[allow(unreachable_patterns)]
+            fn severity(&self) -> ::error_forge::macros::ErrorLevel {
+                match self {
+                    #(#severity_match_arms,)*
+                    _ => ::error_forge::macros::ErrorLevel::from_flags(
+                        ::error_forge::error::ForgeError::is_fatal(self),
+                        ::error_forge::error::ForgeError::is_retryable(self),
+                    ),
+                }
+            }
+
+            #[allow(unreachable_patterns)]
+            fn class(&self) -> ::error_forge::error::ErrorClass {
+                match self {
+                    #(#class_match_arms,)*
+                    _ => ::error_forge::error::ErrorClass::from_retryable(
+                        ::error_forge::error::ForgeError::is_retryable(self),
+                    ),
+                }
+            }
+
+            #[allow(unreachable_patterns)]
+            fn docs_url(&self) -> ::std::option::Option<::std::string::String> {
+                match self {
+                    #(#docs_match_arms,)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+
+            #[allow(unreachable_patterns)]
+            fn user_message(&self) -> ::std::string::String {
+                match self {
+                    #(#user_message_match_arms,)*
+                    _ => ::std::string::ToString::to_string(self),
+                }
+            }
+
+            #[allow(unreachable_patterns)]
+            fn code(&self) -> ::std::option::Option<&str> {
+                match self {
+                    #(#code_match_arms,)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+
+            #[allow(unreachable_patterns)]
+            fn backtrace(&self) -> ::std::option::Option<&::std::backtrace::Backtrace> {
+                match self {
+                    #(#backtrace_match_arms,)*
+                    _ => ::std::option::Option::None,
+                }
+            }
         }
 
-        impl ::std::error::Error for #name {
+        impl #impl_generics ::std::error::Error for #name #ty_generics #error_where {
+            #[allow(unreachable_patterns)]
             fn source(&self) -> Option<&(dyn ::std::error::Error + 'static)> {
-                None
+                #[allow(unused_imports)]
+                use ::error_forge::__private::AsDynError as _;
+                match self {
+                    #(#source_match_arms,)*
+                    _ => ::std::option::Option::None,
+                }
             }
         }
+
+        impl #impl_generics #name #ty_generics #forge_where {
+            #(#constructors)*
+
+            #register_codes
+        }
+
+        #(#from_impls)*
     }
 }
 
 // Implement ModError for a struct
 fn implement_for_struct(input: &DeriveInput, error_prefix: &str) -> proc_macro2::TokenStream {
     let name = &input.ident;
-    let name_str = name.to_string();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let error_where = where_self(&input.generics, quote! { ::std::fmt::Debug });
+    let forge_where = where_self(
+        &input.generics,
+        quote! { ::std::fmt::Debug + ::std::marker::Send + ::std::marker::Sync + 'static },
+    );
+
+    let (retryable, fatal) = match parse_flags(&input.attrs, false, false) {
+        Ok(flags) => flags,
+        Err(err) => return err.to_compile_error(),
+    };
+
+    // The struct's own attributes, as on an enum variant; methods
+    // without one keep the trait default
+    let mut kind_name = name.to_string();
+    let mut caption = format!("{}: Error", error_prefix);
+    let mut extra_methods = Vec::new();
+    let mut docs_url = None;
+    let mut code = None;
+    for attr in &input.attrs {
+        if attr.path.is_ident("error_kind") {
+            match parse_word_attribute(attr) {
+                Ok(Some(value)) => kind_name = value,
+                Ok(None) => {}
+                Err(err) => return err.to_compile_error(),
+            }
+        } else if attr.path.is_ident("error_caption") {
+            if let Some(value) = parse_string_attribute(attr) {
+                caption = value;
+            }
+        } else if attr.path.is_ident("error_http_status") {
+            if let Some(value) = parse_int_attribute::<u16>(attr) {
+                extra_methods.push(quote! {
+                    fn status_code(&self) -> u16 {
+                        #value
+                    }
+                });
+            }
+        } else if attr.path.is_ident("error_exit_code") {
+            if let Some(value) = parse_int_attribute::<i32>(attr) {
+                extra_methods.push(quote! {
+                    fn exit_code(&self) -> i32 {
+                        #value
+                    }
+                });
+            }
+        } else if attr.path.is_ident("error_severity") {
+            let level = match parse_severity_attribute(attr) {
+                Ok(level) => level,
+                Err(err) => return err.to_compile_error(),
+            };
+            extra_methods.push(quote! {
+                fn severity(&self) -> ::error_forge::macros::ErrorLevel {
+                    ::error_forge::macros::ErrorLevel::#level
+                }
+            });
+        } else if attr.path.is_ident("error_class") {
+            let class = match parse_class_attribute(attr) {
+                Ok(class) => class,
+                Err(err) => return err.to_compile_error(),
+            };
+            extra_methods.push(quote! {
+                fn class(&self) -> ::error_forge::error::ErrorClass {
+                    ::error_forge::error::ErrorClass::#class
+                }
+            });
+        } else if attr.path.is_ident("error_docs") {
+            if let Some(url) = parse_string_attribute(attr) {
+                extra_methods.push(quote! {
+                    fn docs_url(&self) -> ::std::option::Option<::std::string::String> {
+                        ::std::option::Option::Some(#url.to_string())
+                    }
+                });
+                docs_url = Some(url);
+            }
+        } else if attr.path.is_ident("error_code") {
+            match parse_string_attribute(attr) {
+                Some(value) => code = Some(value),
+                None => {
+                    return syn::Error::new_spanned(attr, "expected #[error_code(\"CODE\")]")
+                        .to_compile_error()
+                }
+            }
+        }
+    }
+
+    let source = match &input.data {
+        Data::Struct(data) => find_source_field(&data.fields).map(|(index, field)| {
+            let member = match &field.ident {
+                Some(ident) => quote! { #ident },
+                None => {
+                    let index = syn::Index::from(index);
+                    quote! { #index }
+                }
+            };
+            source_expression(quote! { self.#member }, &field.ty)
+        }),
+        _ => None,
+    };
+    let backtrace = match &input.data {
+        Data::Struct(data) => find_backtrace_field(&data.fields).map(|(index, field)| {
+            let member = match &field.ident {
+                Some(ident) => quote! { #ident },
+                None => {
+                    let index = syn::Index::from(index);
+                    quote! { #index }
+                }
+            };
+            let expression = backtrace_expression(quote! { self.#member }, &field.ty);
+            quote! {
+                fn backtrace(&self) -> ::std::option::Option<&::std::backtrace::Backtrace> {
+                    #expression
+                }
+            }
+        }),
+        _ => None,
+    };
+
+    let source = source.map_or_else(
+        || quote! { ::std::option::Option::None },
+        |expression| {
+            quote! {
+                use ::error_forge::__private::AsDynError as _;
+                #expression
+            }
+        },
+    );
+
+    // `#[error_display(...)]` on the struct, with its fields bound as
+    // for enum variants
+    let display_format = input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("error_display"))
+        .find_map(parse_string_attribute);
+    let display = match (&input.data, &display_format) {
+        (Data::Struct(data), Some(display_format)) => match &data.fields {
+            Fields::Named(fields) => {
+                let display_fields: Vec<_> = fields
+                    .named
+                    .iter()
+                    .filter_map(|f| f.ident.as_ref())
+                    .filter(|name| format_uses_field(display_format, &name.to_string()))
+                    .collect();
+                quote! {
+                    let Self { #(#display_fields,)* .. } = self;
+                    write!(f, #display_format #(, #display_fields = #display_fields)*)
+                }
+            }
+            Fields::Unnamed(fields) => {
+                let field_count = fields
+                    .unnamed
+                    .len()
+                    .min(format_positional_count(display_format));
+                let indexes = (0..field_count).map(syn::Index::from);
+                quote! {
+                    write!(f, #display_format #(, self.#indexes)*)
+                }
+            }
+            Fields::Unit => quote! { write!(f, #display_format) },
+        },
+        _ => quote! { write!(f, "{}: Error", #error_prefix) },
+    };
+
+    // `#[error_code(...)]` registers the code the first time `code()`
+    // is called, as for enum variants
+    if let Some(code) = &code {
+        let display = match &display_format {
+            Some(format) => quote! { ::std::option::Option::Some(#format) },
+            None => quote! { ::std::option::Option::None },
+        };
+        let docs = input
+            .attrs
+            .iter()
+            .filter_map(|attr| match attr.parse_meta() {
+                Ok(syn::Meta::NameValue(meta)) if meta.path.is_ident("doc") => Some(meta.lit),
+                _ => None,
+            });
+        let docs_url = match &docs_url {
+            Some(url) => quote! { ::std::option::Option::Some(#url) },
+            None => quote! { ::std::option::Option::None },
+        };
+        extra_methods.push(quote! {
+            fn code(&self) -> ::std::option::Option<&str> {
+                static REGISTERED: ::std::sync::Once = ::std::sync::Once::new();
+                REGISTERED.call_once(|| {
+                    ::error_forge::macros::register_variant_code(
+                        #code,
+                        #display,
+                        &[#(#docs),*],
+                        #caption,
+                        #docs_url,
+                        #retryable,
+                    )
+                });
+                ::std::option::Option::Some(#code)
+            }
+        });
+    }
+
+    // `#[error_from]` on the only field, besides an optional backtrace,
+    // converts the field's type into the struct
+    let from_impl = match &input.data {
+        Data::Struct(data) => {
+            let is_from = |field: &syn::Field| {
+                field
+                    .attrs
+                    .iter()
+                    .any(|attr| has_flag_attribute(attr, "error_from"))
+            };
+            match data.fields.iter().find(|field| is_from(field)) {
+                Some(field) => {
+                    let backtrace_field = find_backtrace_field(&data.fields);
+                    if data.fields.len() - usize::from(backtrace_field.is_some()) != 1 {
+                        return syn::Error::new_spanned(
+                            &input.ident,
+                            "#[error_from] requires a struct with exactly one field besides an #[error_backtrace] field",
+                        )
+                        .to_compile_error();
+                    }
+                    let ty = &field.ty;
+                    let values = data.fields.iter().map(|field| match is_from(field) {
+                        true => quote! { source },
+                        false => backtrace_capture(&field.ty),
+                    });
+                    let construct = match &data.fields {
+                        Fields::Named(_) => {
+                            let names = data.fields.iter().map(|field| &field.ident);
+                            quote! { Self { #(#names: #values),* } }
+                        }
+                        _ => quote! { Self(#(#values),*) },
+                    };
+                    Some(quote! {
+                        impl #impl_generics ::std::convert::From<#ty> for #name #ty_generics #where_clause {
+                            fn from(source: #ty) -> Self {
+                                #construct
+                            }
+                        }
+                    })
+                }
+                None => None,
+            }
+        }
+        _ => None,
+    };
+
+    let user_message = input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("error_user_message"))
+        .find_map(parse_string_attribute);
+    let user_message = match (&input.data, user_message) {
+        (Data::Struct(data), Some(format)) => {
+            let arm = format_fields(quote! { Self }, &data.fields, &format);
+            Some(quote! {
+                fn user_message(&self) -> ::std::string::String {
+                    match self {
+                        #arm,
+                    }
+                }
+            })
+        }
+        _ => None,
+    };
 
     quote! {
-        impl ::std::fmt::Display for #name {
+        impl #impl_generics ::std::fmt::Display for #name #ty_generics #where_clause {
             fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
-                write!(f, "{}: Error", #error_prefix)
+                #display
             }
         }
 
-        impl ::error_forge::error::ForgeError for #name {
+        impl #impl_generics ::error_forge::error::ForgeError for #name #ty_generics #forge_where {
             fn kind(&self) -> &'static str {
-                #name_str
+                #kind_name
             }
 
             fn caption(&self) -> &'static str {
-                concat!(#error_prefix, ": Error")
+                #caption
             }
+
+            fn is_retryable(&self) -> bool {
+                #retryable
+            }
+
+            fn is_fatal(&self) -> bool {
+                #fatal
+            }
+
+            #user_message
+
+            #(#extra_methods)*
+
+            #backtrace
         }
 
-        impl ::std::error::Error for #name {
+        impl #impl_generics ::std::error::Error for #name #ty_generics #error_where {
             fn source(&self) -> Option<&(dyn ::std::error::Error + 'static)> {
-                None
+                #source
             }
         }
+
+        #from_impl
     }
 }
 
 // Note: The implementation now handles formatting directly in the match arms instead of using a helper function
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The message and the (line, column) it points at.
+    fn diagnostic(src: &str) -> (String, (usize, usize)) {
+        let input: DeriveInput = syn::parse_str(src).unwrap();
+        let err = check_attributes(&input).unwrap_err();
+        let start = err.span().start();
+        (err.to_string(), (start.line, start.column))
+    }
+
+    #[test]
+    fn test_misplaced_attribute() {
+        let src = "enum LoadError {\n    #[error_from]\n    Io(std::io::Error),\n}";
+        assert_eq!(
+            diagnostic(src),
+            (
+                "#[error_from] cannot be used on a variant".to_string(),
+                (2, 4)
+            )
+        );
+
+        let src = "enum ApiError {\n    #[error_prefix(\"Api\")]\n    NotFound,\n}";
+        assert_eq!(
+            diagnostic(src),
+            (
+                "#[error_prefix] cannot be used on a variant".to_string(),
+                (2, 4)
+            )
+        );
+    }
+
+    #[test]
+    fn test_duplicate_attribute() {
+        let src = "#[error_display(\"first\")]\n#[error_display(\"second\")]\nstruct Twice;";
+        assert_eq!(
+            diagnostic(src),
+            ("duplicate #[error_display]".to_string(), (2, 0))
+        );
+    }
+
+    #[test]
+    fn test_wrongly_typed_attribute() {
+        let src = "enum ApiError {\n    #[error_http_status(\"404\")]\n    NotFound,\n}";
+        assert_eq!(
+            diagnostic(src),
+            (
+                "expected an HTTP status code, as in #[error_http_status(404)]".to_string(),
+                (2, 4)
+            )
+        );
+
+        let src = "#[error_retryable(maybe)]\nstruct Flaky;";
+        assert_eq!(
+            diagnostic(src),
+            ("expected `true` or `false`".to_string(), (1, 0))
+        );
+    }
+
+    #[test]
+    fn test_main_signature() {
+        let check = |src: &str| {
+            let function: syn::ItemFn = syn::parse_str(src).unwrap();
+            let err = check_main(&function).unwrap_err();
+            let start = err.span().start();
+            (err.to_string(), (start.line, start.column))
+        };
+        assert_eq!(
+            check("fn main(args: Vec<String>) -> Result<(), E> {\n    Ok(())\n}"),
+            (
+                "#[error_forge::main] functions take no parameters; read arguments with std::env::args"
+                    .to_string(),
+                (1, 8)
+            )
+        );
+        assert_eq!(
+            check("async fn main() -> Result<(), E> {\n    Ok(())\n}"),
+            (
+                "#[error_forge::main] does not support async functions".to_string(),
+                (1, 6)
+            )
+        );
+        let function: syn::ItemFn =
+            syn::parse_str("fn main() -> Result<(), E> { Ok(()) }").unwrap();
+        assert!(check_main(&function).is_ok());
+    }
+
+    #[test]
+    fn test_union_rejected() {
+        let src = "#[error_display(\"bits\")]\nunion Bits {\n    int: u32,\n    float: f32,\n}";
+        assert_eq!(
+            diagnostic(src),
+            ("ModError cannot be derived for unions".to_string(), (2, 0))
+        );
+    }
+}
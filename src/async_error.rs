@@ -113,6 +113,7 @@ pub trait AsyncForgeError: StdError + Send + Sync + 'static {
     }
 
     /// Registers the error with the central error hook (if any).
+    #[track_caller]
     fn register(&self) {
         crate::macros::call_error_hook(
             self.caption(),
@@ -0,0 +1,115 @@
+//! Source errors captured as text, for errors that cross processes.
+//!
+//! A `define_errors!` enum derives `Serialize` with the `serde`
+//! feature, and derives `Deserialize` too when it asks for it with
+//! `#[cfg_attr(feature = "serde", derive(serde::Deserialize))]`. Fields
+//! holding foreign errors such as `io::Error` or
+//! `Box<dyn Error + Send + Sync>` cannot be deserialized. Declare them as
+//! [`CapturedError`] instead: it keeps the message of the error and of
+//! each of its causes, still answers [`source`](Error::source), and
+//! round-trips through any serde format.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::captured::CapturedError;
+//! use std::error::Error as _;
+//!
+//! let io = std::io::Error::new(std::io::ErrorKind::NotFound, "spool.db missing");
+//! let captured = CapturedError::from(io);
+//! assert_eq!(captured.to_string(), "spool.db missing");
+//! assert!(captured.source().is_none());
+//! ```
+
+use std::error::Error;
+use std::fmt;
+
+/// The messages of an error and its causes, kept as strings.
+///
+/// Compares equal when every message matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CapturedError {
+    message: String,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    source: Option<Box<CapturedError>>,
+}
+
+impl CapturedError {
+    /// An error with `message` and no cause.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Capture `err` and every error in its source chain.
+    pub fn capture(err: &(dyn Error + 'static)) -> Self {
+        Self {
+            message: err.to_string(),
+            source: err.source().map(|source| Box::new(Self::capture(source))),
+        }
+    }
+
+    /// The captured message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for CapturedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for CapturedError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn Error + 'static))
+    }
+}
+
+impl From<std::io::Error> for CapturedError {
+    fn from(err: std::io::Error) -> Self {
+        Self::capture(&err)
+    }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for CapturedError {
+    fn from(err: Box<dyn Error + Send + Sync>) -> Self {
+        Self::capture(err.as_ref())
+    }
+}
+
+impl From<String> for CapturedError {
+    fn from(message: String) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<&str> for CapturedError {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppError;
+
+    #[test]
+    fn test_capture_chain() {
+        let err = AppError::other("sync failed").with_source(std::io::Error::other("disk full"));
+        let captured = CapturedError::capture(&err);
+        assert_eq!(captured.message(), err.to_string());
+        assert_eq!(captured.source().unwrap().to_string(), "disk full");
+        assert_eq!(captured.clone(), captured);
+    }
+}
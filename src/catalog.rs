@@ -0,0 +1,446 @@
+//! Error-code catalog tooling.
+//!
+//! Library entry points for developer tooling built on top of the
+//! [`ErrorRegistry`]: listing and documenting registered codes,
+//! explaining a single code, and loading / validating an
+//! `errors.toml` catalog file. A companion binary (or a project's
+//! own `xtask`) only has to wire these functions to command-line
+//! arguments.
+//!
+//! # Catalog format
+//!
+//! [`parse_catalog`] accepts the array-of-tables subset of TOML that
+//! error catalogs need — one `[[error]]` table per code, with string
+//! and boolean values:
+//!
+//! ```toml
+//! [[error]]
+//! code = "AUTH-001"
+//! description = "Authentication failed due to invalid credentials"
+//! documentation_url = "https://docs.example.com/errors/auth-001"
+//! retryable = false
+//! ```
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::catalog::{parse_catalog, validate_catalog};
+//!
+//! let source = r#"
+//! [[error]]
+//! code = "AUTH-001"
+//! description = "Authentication failed"
+//! "#;
+//!
+//! let entries = parse_catalog(source).expect("catalog parses");
+//! let report = validate_catalog(&entries);
+//! assert!(report.is_ok());
+//! ```
+
+use crate::registry::{ErrorCodeInfo, ErrorRegistry};
+use std::collections::HashSet;
+use std::fmt;
+
+/// A syntax error encountered while parsing a catalog file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogParseError {
+    /// 1-based line number of the offending line
+    pub line: usize,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for CatalogParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for CatalogParseError {}
+
+/// The category of a problem found by [`validate_catalog`].
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// checks without breaking callers that `match` on the kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CatalogIssueKind {
+    /// The code is empty
+    EmptyCode,
+    /// The code contains characters outside `A-Z`, `0-9`, `-` and `_`
+    InvalidCode,
+    /// The same code appears more than once
+    DuplicateCode,
+    /// The description is empty
+    MissingDescription,
+    /// The documentation URL is not an `http://` or `https://` URL
+    InvalidDocumentationUrl,
+}
+
+impl CatalogIssueKind {
+    /// Short, stable identifier for the issue kind, suitable for
+    /// machine-readable output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::EmptyCode => "empty-code",
+            Self::InvalidCode => "invalid-code",
+            Self::DuplicateCode => "duplicate-code",
+            Self::MissingDescription => "missing-description",
+            Self::InvalidDocumentationUrl => "invalid-documentation-url",
+        }
+    }
+}
+
+/// A single problem found by [`validate_catalog`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogIssue {
+    /// Zero-based index of the offending entry in the validated slice
+    pub index: usize,
+    /// The code of the offending entry (may be empty)
+    pub code: String,
+    /// What is wrong with the entry
+    pub kind: CatalogIssueKind,
+}
+
+impl fmt::Display for CatalogIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self.kind {
+            CatalogIssueKind::EmptyCode => "entry has an empty code",
+            CatalogIssueKind::InvalidCode => {
+                "code may only contain uppercase letters, digits, '-' and '_'"
+            }
+            CatalogIssueKind::DuplicateCode => "code is defined more than once",
+            CatalogIssueKind::MissingDescription => "description is empty",
+            CatalogIssueKind::InvalidDocumentationUrl => {
+                "documentation_url must start with http:// or https://"
+            }
+        };
+        write!(
+            f,
+            "entry #{} [{}] {}: {}",
+            self.index + 1,
+            self.code,
+            self.kind.as_str(),
+            message
+        )
+    }
+}
+
+/// Structured result of [`validate_catalog`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CatalogReport {
+    /// Number of entries that were checked
+    pub checked: usize,
+    /// Every problem found, in entry order
+    pub issues: Vec<CatalogIssue>,
+}
+
+impl CatalogReport {
+    /// Returns `true` if no issues were found.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for CatalogReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.issues.is_empty() {
+            return write!(f, "{} entries checked, no issues", self.checked);
+        }
+        writeln!(
+            f,
+            "{} entries checked, {} issues:",
+            self.checked,
+            self.issues.len()
+        )?;
+        for issue in &self.issues {
+            writeln!(f, "  {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse an `errors.toml` catalog into registry entries.
+///
+/// Only the subset described in the [module docs](self) is
+/// accepted: `[[error]]` table headers, `key = "string"` and
+/// `key = true|false` pairs, blank lines and `#` comments. Unknown
+/// keys are rejected so typos surface as parse errors rather than
+/// silently dropped metadata.
+pub fn parse_catalog(source: &str) -> Result<Vec<ErrorCodeInfo>, CatalogParseError> {
+    let mut entries = Vec::new();
+    let mut current: Option<ErrorCodeInfo> = None;
+
+    for (index, raw) in source.lines().enumerate() {
+        let line_no = index + 1;
+        let line = strip_comment(raw).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line == "[[error]]" {
+            if let Some(entry) = current.take() {
+                entries.push(entry);
+            }
+            current = Some(ErrorCodeInfo::new(
+                String::new(),
+                String::new(),
+                None,
+                false,
+            ));
+            continue;
+        }
+
+        let error = |message: String| CatalogParseError {
+            line: line_no,
+            message,
+        };
+
+        if line.starts_with('[') {
+            return Err(error(format!("unsupported table header `{line}`")));
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| error(format!("expected `key = value`, found `{line}`")))?;
+        let key = key.trim();
+        let value = value.trim();
+
+        let entry = current
+            .as_mut()
+            .ok_or_else(|| error(format!("`{key}` appears before any [[error]] table")))?;
+
+        match key {
+            "code" => entry.code = parse_string(value).map_err(error)?,
+            "description" => entry.description = parse_string(value).map_err(error)?,
+            "documentation_url" => {
+                entry.documentation_url = Some(parse_string(value).map_err(error)?)
+            }
+            "retryable" => {
+                entry.retryable = match value {
+                    "true" => true,
+                    "false" => false,
+                    other => return Err(error(format!("expected a boolean, found `{other}`"))),
+                }
+            }
+            other => return Err(error(format!("unknown key `{other}`"))),
+        }
+    }
+
+    if let Some(entry) = current {
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+/// Remove a trailing `#` comment, ignoring `#` inside quoted strings.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '\\' if in_string => escaped = !escaped,
+            '"' if !escaped => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => escaped = false,
+        }
+    }
+    line
+}
+
+/// Parse a basic TOML string literal (`"..."` with `\"`, `\\`, `\n`
+/// and `\t` escapes).
+fn parse_string(value: &str) -> Result<String, String> {
+    let inner = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or_else(|| format!("expected a quoted string, found `{value}`"))?;
+
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('"') => out.push('"'),
+            Some('\\') => out.push('\\'),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => return Err(format!("unsupported escape `\\{other}`")),
+            None => return Err("unterminated escape sequence".to_string()),
+        }
+    }
+    Ok(out)
+}
+
+/// Check catalog entries for common mistakes.
+///
+/// Every entry is checked independently and all problems are
+/// reported — validation does not stop at the first issue.
+pub fn validate_catalog(entries: &[ErrorCodeInfo]) -> CatalogReport {
+    let mut issues = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        let mut push = |kind| {
+            issues.push(CatalogIssue {
+                index,
+                code: entry.code.clone(),
+                kind,
+            })
+        };
+
+        if entry.code.is_empty() {
+            push(CatalogIssueKind::EmptyCode);
+        } else if !entry
+            .code
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        {
+            push(CatalogIssueKind::InvalidCode);
+        }
+
+        if !entry.code.is_empty() && !seen.insert(entry.code.as_str()) {
+            push(CatalogIssueKind::DuplicateCode);
+        }
+
+        if entry.description.trim().is_empty() {
+            push(CatalogIssueKind::MissingDescription);
+        }
+
+        if let Some(url) = &entry.documentation_url {
+            if !(url.starts_with("https://") || url.starts_with("http://")) {
+                push(CatalogIssueKind::InvalidDocumentationUrl);
+            }
+        }
+    }
+
+    CatalogReport {
+        checked: entries.len(),
+        issues,
+    }
+}
+
+/// Register every catalog entry in `registry`.
+///
+/// Returns the registration failures (typically already-registered
+/// codes); entries that register successfully are not reported.
+pub fn load_catalog(registry: &ErrorRegistry, entries: Vec<ErrorCodeInfo>) -> Vec<String> {
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            registry
+                .register_code(
+                    entry.code,
+                    entry.description,
+                    entry.documentation_url,
+                    entry.retryable,
+                )
+                .err()
+        })
+        .collect()
+}
+
+/// Render every code registered in `registry` as a Markdown table,
+/// sorted by code.
+pub fn docgen(registry: &ErrorRegistry) -> String {
+    use std::fmt::Write as _;
+
+    let codes = registry.codes();
+    let mut out = String::from("| Code | Description | Retryable | Documentation |\n");
+    out.push_str("|------|-------------|-----------|---------------|\n");
+    for info in &codes {
+        let _ = writeln!(
+            out,
+            "| `{}` | {} | {} | {} |",
+            info.code,
+            info.description.replace('|', "\\|"),
+            if info.retryable { "yes" } else { "no" },
+            info.documentation_url.as_deref().unwrap_or("")
+        );
+    }
+    out
+}
+
+/// Describe a single code registered in `registry` in a
+/// human-readable block, or `None` if the code is not registered.
+pub fn explain(registry: &ErrorRegistry, code: &str) -> Option<String> {
+    let info = registry.get_code_info(code)?;
+    let mut out = format!("{}\n\n{}\n\nRetryable: ", info.code, info.description);
+    out.push_str(if info.retryable { "yes" } else { "no" });
+    if let Some(url) = info.documentation_url {
+        out.push_str("\nDocumentation: ");
+        out.push_str(&url);
+    }
+    Some(out)
+}
+
+/// [`explain`] against the global registry, where `define_errors!`
+/// and `#[derive(ModError)]` register their codes.
+pub fn explain_global(code: &str) -> Option<String> {
+    explain(ErrorRegistry::global(), code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_validate_catalog() {
+        let source = r#"
+            # Authentication errors
+            [[error]]
+            code = "AUTH-001"
+            description = "Invalid credentials" # trailing comment
+            documentation_url = "https://docs.example.com/auth-001"
+            retryable = false
+
+            [[error]]
+            code = "AUTH-001"
+            description = ""
+            documentation_url = "docs/auth-002"
+        "#;
+
+        let entries = parse_catalog(source).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].description, "Invalid credentials");
+
+        let report = validate_catalog(&entries);
+        let kinds: Vec<_> = report.issues.iter().map(|issue| issue.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                CatalogIssueKind::DuplicateCode,
+                CatalogIssueKind::MissingDescription,
+                CatalogIssueKind::InvalidDocumentationUrl,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_catalog_rejects_unknown_keys() {
+        let err = parse_catalog("[[error]]\ncode = \"A-1\"\ndescripton = \"typo\"\n").unwrap_err();
+        assert_eq!(err.line, 3);
+    }
+
+    #[test]
+    fn test_explain_and_docgen_use_the_given_registry() {
+        let registry = ErrorRegistry::new();
+        let entries = parse_catalog(
+            "[[error]]\ncode = \"PAY-001\"\ndescription = \"Card declined\"\nretryable = true\n",
+        )
+        .unwrap();
+        assert!(load_catalog(&registry, entries).is_empty());
+
+        assert_eq!(
+            explain(&registry, "PAY-001").as_deref(),
+            Some("PAY-001\n\nCard declined\n\nRetryable: yes")
+        );
+        assert!(explain(&registry, "PAY-002").is_none());
+        assert!(explain_global("PAY-001").is_none());
+        assert!(docgen(&registry).contains("| `PAY-001` | Card declined | yes |  |"));
+    }
+}
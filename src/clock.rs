@@ -0,0 +1,226 @@
+//! The time source behind every timestamp and time window.
+//!
+//! Error creation times, circuit breaker timeouts, escalation windows,
+//! and retry journal entries all read the clock through the installed
+//! [`TimeProvider`]. The default is the system clock. Tests install a
+//! [`MockClock`] and advance it by hand instead of sleeping, and hot
+//! paths that create many errors can install a [`CoarseClock`], which a
+//! background thread updates at a fixed resolution so reading it is a
+//! single atomic load.
+//!
+//! Retry delays still sleep in real time, so retry elapsed-time budgets
+//! and [`ErrorId`](crate::instance::ErrorId) seeds keep using the
+//! system clock.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::clock::{self, MockClock};
+//! use error_forge::recovery::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
+//! use std::sync::Arc;
+//! use std::time::{Duration, UNIX_EPOCH};
+//!
+//! let mock = Arc::new(MockClock::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000)));
+//! clock::set_time_provider(Arc::clone(&mock));
+//!
+//! let breaker = CircuitBreaker::with_config("search", CircuitBreakerConfig::new(1, 60_000, 30_000));
+//! let _ = breaker.execute(|| Err::<(), _>(std::io::Error::other("down")));
+//! assert_eq!(breaker.state(), CircuitState::Open);
+//!
+//! // No sleeping: the reset timeout passes when the clock is advanced.
+//! mock.advance(Duration::from_secs(31));
+//! assert!(breaker.execute(|| Ok::<_, std::io::Error>(())).is_ok());
+//! assert_eq!(clock::now(), UNIX_EPOCH + Duration::from_secs(1_700_000_031));
+//! # clock::reset_time_provider();
+//! ```
+
+use parking_lot::{Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant, SystemTime};
+
+/// A source of wall-clock and monotonic time.
+///
+/// Implemented for `Arc<P>`, so a provider can be installed while the
+/// caller keeps a handle to it.
+pub trait TimeProvider: Send + Sync + 'static {
+    /// The current wall-clock time, used for timestamps
+    fn now(&self) -> SystemTime;
+
+    /// The current monotonic time, used for timeouts and windows
+    fn instant(&self) -> Instant;
+}
+
+impl<P: TimeProvider> TimeProvider for Arc<P> {
+    fn now(&self) -> SystemTime {
+        (**self).now()
+    }
+
+    fn instant(&self) -> Instant {
+        (**self).instant()
+    }
+}
+
+/// The operating system's clocks; the default provider.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl TimeProvider for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to, for tests.
+#[derive(Debug)]
+pub struct MockClock {
+    start: SystemTime,
+    base: Instant,
+    elapsed: Mutex<Duration>,
+}
+
+impl MockClock {
+    /// A clock stopped at `start`.
+    pub fn new(start: SystemTime) -> Self {
+        Self {
+            start,
+            base: Instant::now(),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Move both clocks forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        *self.elapsed.lock() += by;
+    }
+}
+
+impl TimeProvider for MockClock {
+    fn now(&self) -> SystemTime {
+        self.start + *self.elapsed.lock()
+    }
+
+    fn instant(&self) -> Instant {
+        self.base + *self.elapsed.lock()
+    }
+}
+
+/// A clock read from a cached value that a background thread refreshes
+/// every `resolution`.
+///
+/// Readings lag the system clock by up to one resolution. The thread
+/// stops once the clock is dropped.
+#[derive(Debug)]
+pub struct CoarseClock {
+    shared: Arc<CoarseShared>,
+}
+
+#[derive(Debug)]
+struct CoarseShared {
+    start: SystemTime,
+    base: Instant,
+    elapsed_nanos: AtomicU64,
+}
+
+impl CoarseShared {
+    fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.elapsed_nanos.load(Ordering::Relaxed))
+    }
+}
+
+impl CoarseClock {
+    /// Start a clock refreshed every `resolution`.
+    pub fn new(resolution: Duration) -> Self {
+        let shared = Arc::new(CoarseShared {
+            start: SystemTime::now(),
+            base: Instant::now(),
+            elapsed_nanos: AtomicU64::new(0),
+        });
+        let weak: Weak<CoarseShared> = Arc::downgrade(&shared);
+        let _ = std::thread::Builder::new()
+            .name("error-forge-clock".into())
+            .spawn(move || {
+                while let Some(shared) = weak.upgrade() {
+                    let nanos = shared.base.elapsed().as_nanos();
+                    shared
+                        .elapsed_nanos
+                        .store(u64::try_from(nanos).unwrap_or(u64::MAX), Ordering::Relaxed);
+                    drop(shared);
+                    std::thread::sleep(resolution);
+                }
+            });
+        Self { shared }
+    }
+}
+
+impl TimeProvider for CoarseClock {
+    fn now(&self) -> SystemTime {
+        self.shared.start + self.shared.elapsed()
+    }
+
+    fn instant(&self) -> Instant {
+        self.shared.base + self.shared.elapsed()
+    }
+}
+
+// The installed provider, and whether there is one, so the default
+// path skips the lock.
+static PROVIDER: RwLock<Option<Box<dyn TimeProvider>>> = RwLock::new(None);
+static CUSTOM: AtomicBool = AtomicBool::new(false);
+
+/// Install `provider` process-wide, replacing any previous one.
+pub fn set_time_provider(provider: impl TimeProvider) {
+    *PROVIDER.write() = Some(Box::new(provider));
+    CUSTOM.store(true, Ordering::Release);
+}
+
+/// Go back to the [`SystemClock`].
+pub fn reset_time_provider() {
+    CUSTOM.store(false, Ordering::Release);
+    *PROVIDER.write() = None;
+}
+
+/// The current wall-clock time from the installed provider.
+pub fn now() -> SystemTime {
+    if CUSTOM.load(Ordering::Acquire) {
+        if let Some(provider) = PROVIDER.read().as_ref() {
+            return provider.now();
+        }
+    }
+    SystemTime::now()
+}
+
+/// The current monotonic time from the installed provider.
+pub fn instant() -> Instant {
+    if CUSTOM.load(Ordering::Acquire) {
+        if let Some(provider) = PROVIDER.read().as_ref() {
+            return provider.instant();
+        }
+    }
+    Instant::now()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn test_mock_and_coarse_clocks() {
+        let mock = MockClock::new(UNIX_EPOCH);
+        let before = mock.instant();
+        mock.advance(Duration::from_millis(1500));
+        assert_eq!(mock.now(), UNIX_EPOCH + Duration::from_millis(1500));
+        assert_eq!(mock.instant() - before, Duration::from_millis(1500));
+
+        let coarse = CoarseClock::new(Duration::from_millis(1));
+        let first = coarse.instant();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(coarse.instant() > first);
+        assert!(coarse.now() <= SystemTime::now());
+    }
+}
@@ -1,18 +1,68 @@
 use crate::error::ForgeError;
 use std::error::Error;
 use std::fmt;
+use std::time::SystemTime;
+
+/// Number of errors itemized by `Display` and
+/// [`summary`](ErrorCollector::summary) unless
+/// [`with_display_limit`](ErrorCollector::with_display_limit) says
+/// otherwise.
+pub const DEFAULT_DISPLAY_LIMIT: usize = 10;
+
+/// How [`ErrorCollector::exit_code`] turns the collected errors into a
+/// process exit code.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// policies without breaking callers that `match` on the enum.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExitCodePolicy {
+    /// The highest [`exit_code`](ForgeError::exit_code) among the
+    /// collected errors
+    #[default]
+    MaxOfErrors,
+    /// A dedicated code for a batch that finished with failures; a
+    /// fatal error still exits with its own code
+    PartialFailure(i32),
+}
 
 /// A collection of errors that can be accumulated and returned as a single result
 #[derive(Debug, Default)]
 pub struct ErrorCollector<E> {
     /// The collected errors
     errors: Vec<E>,
+    /// Maximum number of errors to itemize when rendering
+    display_limit: Option<usize>,
+    /// How the errors map to a process exit code
+    exit_policy: ExitCodePolicy,
 }
 
 impl<E> ErrorCollector<E> {
     /// Create a new empty error collector
     pub fn new() -> Self {
-        Self { errors: Vec::new() }
+        Self {
+            errors: Vec::new(),
+            display_limit: None,
+            exit_policy: ExitCodePolicy::default(),
+        }
+    }
+
+    /// Choose how [`exit_code`](Self::exit_code) is computed.
+    pub fn with_exit_code_policy(mut self, policy: ExitCodePolicy) -> Self {
+        self.exit_policy = policy;
+        self
+    }
+
+    /// Itemize at most `limit` errors when rendering, summarizing the
+    /// rest on one line. Defaults to [`DEFAULT_DISPLAY_LIMIT`]; pass
+    /// `usize::MAX` to list every error.
+    pub fn with_display_limit(mut self, limit: usize) -> Self {
+        self.display_limit = Some(limit);
+        self
+    }
+
+    fn display_limit(&self) -> usize {
+        self.display_limit.unwrap_or(DEFAULT_DISPLAY_LIMIT)
     }
 
     /// Add an error to the collection
@@ -97,10 +147,20 @@ impl<E: fmt::Display> fmt::Display for ErrorCollector<E> {
             write!(f, "1 error: {}", self.errors[0])
         } else {
             writeln!(f, "{} errors:", self.errors.len())?;
-            for (i, err) in self.errors.iter().enumerate() {
+            for (i, err) in self.errors.iter().take(self.display_limit()).enumerate() {
                 writeln!(f, "  {}. {}", i + 1, err)?;
             }
-            Ok(())
+            self.write_remainder(f)
+        }
+    }
+}
+
+impl<E> ErrorCollector<E> {
+    // The "… and N more" line for errors past the display limit.
+    fn write_remainder(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match self.errors.len().checked_sub(self.display_limit()) {
+            Some(hidden) if hidden > 0 => writeln!(f, "  … and {hidden} more"),
+            _ => Ok(()),
         }
     }
 }
@@ -132,7 +192,13 @@ impl<T, E> CollectError<T, E> for Result<T, E> {
 // Special implementation for ForgeError types to provide rich error collection
 impl<E: ForgeError> ErrorCollector<E> {
     /// Return a summary of the collected errors using ForgeError traits
+    ///
+    /// A statistical header — counts, errors per kind, the worst
+    /// status code, and the retryable share — comes before the
+    /// itemized list, which is capped at the display limit.
     pub fn summary(&self) -> String {
+        use std::fmt::Write as _;
+
         if self.errors.is_empty() {
             return "No errors".to_string();
         }
@@ -141,25 +207,68 @@ impl<E: ForgeError> ErrorCollector<E> {
         let fatal_count = self.errors.iter().filter(|e| e.is_fatal()).count();
         let retryable_count = self.errors.iter().filter(|e| e.is_retryable()).count();
 
-        result.push_str(&format!(
-            "{} errors collected ({} fatal, {} retryable):\n",
+        let _ = writeln!(
+            result,
+            "{} errors collected ({} fatal, {} retryable):",
             self.errors.len(),
             fatal_count,
             retryable_count
-        ));
-
-        for (i, err) in self.errors.iter().enumerate() {
-            result.push_str(&format!(
-                "  {}. [{}] {}\n",
+        );
+
+        let kinds = self
+            .kind_counts()
+            .iter()
+            .map(|(kind, count)| format!("{kind} ×{count}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let worst_status = self.errors.iter().map(|e| e.status_code()).max();
+        let _ = writeln!(
+            result,
+            "  kinds: {} | worst status: {} | retryable: {}%",
+            kinds,
+            worst_status.unwrap_or_default(),
+            retryable_count * 100 / self.errors.len()
+        );
+
+        for (i, err) in self.errors.iter().take(self.display_limit()).enumerate() {
+            let _ = writeln!(
+                result,
+                "  {}. [{}] {}",
                 i + 1,
                 err.kind(),
                 err.dev_message()
-            ));
+            );
         }
+        let _ = self.write_remainder(&mut result);
 
         result
     }
 
+    /// Number of collected errors per [`kind`](ForgeError::kind), most
+    /// frequent first (ties in order of first appearance).
+    pub fn kind_counts(&self) -> Vec<(&'static str, usize)> {
+        let mut counts: Vec<(&'static str, usize)> = Vec::new();
+        for err in &self.errors {
+            match counts.iter_mut().find(|(kind, _)| *kind == err.kind()) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((err.kind(), 1)),
+            }
+        }
+        counts.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        counts
+    }
+
+    /// The creation times of the earliest and latest collected errors
+    /// that record a [`timestamp`](ForgeError::timestamp), or `None`
+    /// if none do.
+    pub fn time_range(&self) -> Option<(SystemTime, SystemTime)> {
+        let mut times = self.errors.iter().filter_map(|e| e.timestamp());
+        let first = times.next()?;
+        Some(times.fold((first, first), |(earliest, latest), time| {
+            (earliest.min(time), latest.max(time))
+        }))
+    }
+
     /// Check if any of the collected errors is marked as fatal
     pub fn has_fatal(&self) -> bool {
         self.errors.iter().any(|e| e.is_fatal())
@@ -169,6 +278,66 @@ impl<E: ForgeError> ErrorCollector<E> {
     pub fn all_retryable(&self) -> bool {
         !self.errors.is_empty() && self.errors.iter().all(|e| e.is_retryable())
     }
+
+    /// The process exit code for the batch under the collector's
+    /// [`ExitCodePolicy`]: 0 with no errors, and never 0 otherwise.
+    pub fn exit_code(&self) -> i32 {
+        let max = |errors: &mut dyn Iterator<Item = &E>| {
+            errors
+                .map(|e| e.exit_code())
+                .filter(|code| *code != 0)
+                .max()
+        };
+        if self.errors.is_empty() {
+            return 0;
+        }
+        let code = match self.exit_policy {
+            ExitCodePolicy::MaxOfErrors => max(&mut self.errors.iter()),
+            ExitCodePolicy::PartialFailure(code) => {
+                max(&mut self.errors.iter().filter(|e| e.is_fatal())).or(Some(code))
+            }
+        };
+        code.filter(|code| *code != 0).unwrap_or(1)
+    }
+
+    /// Print the errors to stderr in the console theme, up to the
+    /// display limit, run the fatal handlers for the first fatal error,
+    /// and exit with [`exit_code`](Self::exit_code).
+    ///
+    /// The usual ending for a batch CLI built on the collector.
+    pub fn exit_with_summary(&self) -> ! {
+        eprint!("{}", self.themed_summary(&crate::ConsoleTheme::default()));
+        if let Some(fatal) = self.errors.iter().find(|e| e.is_fatal()) {
+            crate::hooks::run_fatal_handlers(fatal);
+        }
+        std::process::exit(self.exit_code())
+    }
+
+    // The header line, each itemized error in `theme`, and the
+    // remainder line.
+    fn themed_summary(&self, theme: &crate::ConsoleTheme) -> String {
+        use std::fmt::Write as _;
+
+        if self.errors.is_empty() {
+            return format!("{}\n", theme.success("No errors"));
+        }
+        let header = format!(
+            "{} errors collected ({} fatal, {} retryable)",
+            self.errors.len(),
+            self.errors.iter().filter(|e| e.is_fatal()).count(),
+            self.errors.iter().filter(|e| e.is_retryable()).count()
+        );
+        let mut result = format!("{}\n\n", theme.bold(&header));
+        for err in self.errors.iter().take(self.display_limit()) {
+            let _ = writeln!(result, "{}", theme.format_error(err));
+        }
+        let mut remainder = String::new();
+        let _ = self.write_remainder(&mut remainder);
+        if !remainder.is_empty() {
+            let _ = writeln!(result, "{}", theme.dim(remainder.trim()));
+        }
+        result
+    }
 }
 
 #[cfg(test)]
@@ -220,5 +389,52 @@ mod tests {
         assert!(summary.contains("2 errors collected (1 fatal, 1 retryable)"));
         assert!(summary.contains("[Config]"));
         assert!(summary.contains("[Network]"));
+        assert!(
+            summary.contains("kinds: Config ×1, Network ×1 | worst status: 503 | retryable: 50%")
+        );
+    }
+
+    #[test]
+    fn test_display_limit() {
+        let mut collector = ErrorCollector::new().with_display_limit(2);
+        for i in 0..5 {
+            collector.push(AppError::config(format!("bad key {i}")));
+        }
+
+        let display = collector.to_string();
+        assert!(display.contains("2. "));
+        assert!(!display.contains("3. "));
+        assert!(display.ends_with("… and 3 more\n"));
+        assert!(collector.summary().contains("kinds: Config ×5"));
+    }
+
+    #[test]
+    fn test_exit_code_policy() {
+        crate::define_errors! {
+            pub enum ImportError {
+                #[kind(BadRow, exit = 65)]
+                BadRow { line: u64 },
+
+                #[kind(Io, exit = 74, fatal = true)]
+                Disk { path: String },
+            }
+        }
+
+        let collector = ErrorCollector::new()
+            .with(ImportError::badrow(3))
+            .with(ImportError::badrow(9));
+        assert_eq!(collector.exit_code(), 65);
+        assert_eq!(ErrorCollector::<ImportError>::new().exit_code(), 0);
+
+        let partial = collector.with_exit_code_policy(ExitCodePolicy::PartialFailure(3));
+        assert_eq!(partial.exit_code(), 3);
+        let fatal = partial.with(ImportError::disk("/tmp/out".to_string()));
+        assert_eq!(fatal.exit_code(), 74);
+
+        let summary = fatal
+            .with_display_limit(1)
+            .themed_summary(&crate::ConsoleTheme::plain());
+        assert!(summary.starts_with("3 errors collected (1 fatal, 0 retryable)"));
+        assert!(summary.contains("… and 2 more"));
     }
 }
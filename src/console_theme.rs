@@ -5,9 +5,66 @@
 //! capabilities via [`std::io::IsTerminal`] and disables colors when
 //! stderr is not a TTY, when `TERM=dumb`, or when `NO_COLOR` is set
 //! (<https://no-color.org/>).
+//!
+//! Per-kind overrides ([`KindStyle`]) let a CLI give classes of
+//! failure their own visual language:
+//!
+//! ```
+//! use error_forge::console_theme::{ConsoleTheme, KindStyle};
+//! use error_forge::AppError;
+//!
+//! let theme = ConsoleTheme::with_colors()
+//!     .with_kind_style("Security", KindStyle::new().with_color("\x1b[31m").with_bold(true).with_icon("🔒"))
+//!     .with_kind_style("Config", KindStyle::new().with_color("\x1b[33m"));
+//!
+//! let out = theme.format_error(&AppError::config("missing key"));
+//! assert!(out.contains("\x1b[33m"));
+//! ```
 
 use std::io::IsTerminal;
 
+/// Style override applied by [`ConsoleTheme`] to errors of one kind.
+///
+/// Unset parts fall back to the theme: the message color follows the
+/// error's severity and the caption keeps the default icon. Colors
+/// and bold are dropped by [`ConsoleTheme::plain`]; icons are kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KindStyle {
+    color: Option<&'static str>,
+    bold: bool,
+    icon: Option<&'static str>,
+}
+
+impl KindStyle {
+    /// A style with no overrides.
+    pub const fn new() -> Self {
+        Self {
+            color: None,
+            bold: false,
+            icon: None,
+        }
+    }
+
+    /// Color the message with `color`, an ANSI escape such as
+    /// `"\x1b[31m"`, instead of the severity color.
+    pub const fn with_color(mut self, color: &'static str) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Render the message in bold.
+    pub const fn with_bold(mut self, bold: bool) -> Self {
+        self.bold = bold;
+        self
+    }
+
+    /// Prefix the caption with `icon` instead of the default `⚠️`.
+    pub const fn with_icon(mut self, icon: &'static str) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+}
+
 /// Color theme for console error output.
 ///
 /// The fields are `&'static str` ANSI escapes — no allocation per
@@ -22,6 +79,7 @@ pub struct ConsoleTheme {
     reset: &'static str,
     bold: &'static str,
     dim: &'static str,
+    kind_styles: Vec<(&'static str, KindStyle)>,
 }
 
 /// Detect if the current terminal supports ANSI colors.
@@ -94,6 +152,7 @@ impl ConsoleTheme {
             reset: "\x1b[0m",
             bold: "\x1b[1m",
             dim: "\x1b[2m",
+            kind_styles: Vec::new(),
         }
     }
 
@@ -109,6 +168,51 @@ impl ConsoleTheme {
             reset: "",
             bold: "",
             dim: "",
+            kind_styles: Vec::new(),
+        }
+    }
+
+    /// Override the style of errors whose
+    /// [`kind`](crate::error::ForgeError::kind) is `kind`, replacing any
+    /// earlier override for the same kind.
+    pub fn with_kind_style(mut self, kind: &'static str, style: KindStyle) -> Self {
+        match self.kind_styles.iter_mut().find(|(k, _)| *k == kind) {
+            Some((_, existing)) => *existing = style,
+            None => self.kind_styles.push((kind, style)),
+        }
+        self
+    }
+
+    /// The style override registered for `kind`, if any.
+    pub fn kind_style(&self, kind: &str) -> Option<&KindStyle> {
+        self.kind_styles
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, style)| style)
+    }
+
+    /// The caption line for an error of `kind`.
+    fn caption_line(&self, kind: &str, caption: &str) -> String {
+        let icon = self
+            .kind_style(kind)
+            .and_then(|style| style.icon)
+            .unwrap_or("⚠️ ");
+        self.caption(&format!("{icon} {caption}"))
+    }
+
+    /// The message line for an error of `kind`: the kind's color and
+    /// weight if overridden, otherwise the severity color.
+    fn message_line(&self, kind: &str, level: crate::macros::ErrorLevel, text: &str) -> String {
+        match self.kind_style(kind) {
+            // Plain themes have an empty reset and drop style escapes.
+            Some(style) if !self.reset.is_empty() && (style.color.is_some() || style.bold) => {
+                let bold = if style.bold { self.bold } else { "" };
+                match style.color {
+                    Some(color) => format!("{bold}{color}{text}{}", self.reset),
+                    None => format!("{bold}{}", self.level(level, text)),
+                }
+            }
+            _ => self.level(level, text),
         }
     }
 
@@ -137,6 +241,19 @@ impl ConsoleTheme {
         format!("{}{}{}", self.caption_color, text, self.reset)
     }
 
+    /// Format text with the color for `level`: the error color for
+    /// `Critical` and `Error`, the warning color for `Warning`, the
+    /// info color for `Info`, and dim for `Debug`.
+    pub fn level(&self, level: crate::macros::ErrorLevel, text: &str) -> String {
+        use crate::macros::ErrorLevel;
+        match level {
+            ErrorLevel::Warning => self.warning(text),
+            ErrorLevel::Info => self.info(text),
+            ErrorLevel::Debug => self.dim(text),
+            _ => self.error(text),
+        }
+    }
+
     /// Format text as bold.
     pub fn bold(&self, text: &str) -> String {
         format!("{}{}{}", self.bold, text, self.reset)
@@ -149,19 +266,49 @@ impl ConsoleTheme {
 
     /// Format an error display in a structured way.
     ///
-    /// Writes the caption, the error's `Display` output, the
-    /// retryability marker, and the optional source chain into a
+    /// Writes the caption, the error's `Display` output (styled by any
+    /// [`KindStyle`] registered for its kind, otherwise colored by its
+    /// [`severity`](crate::error::ForgeError::severity)), the
+    /// retryability marker, the documentation link, the creating source
+    /// location, any attached
+    /// metadata, and the optional
+    /// source chain into a
     /// single `String` buffer. Allocates exactly once.
-    pub fn format_error<E: crate::error::ForgeError>(&self, err: &E) -> String {
+    ///
+    /// Messages longer than the process-wide
+    /// [`max_display_bytes`](crate::limits::PayloadLimits::max_display_bytes)
+    /// are cut at a word boundary.
+    pub fn format_error<E: crate::error::ForgeError + ?Sized>(&self, err: &E) -> String {
+        let max = crate::limits::payload_limits().max_display_bytes;
+        self.format_error_limited(err, max, None)
+    }
+
+    /// [`Self::format_error`] with messages cut to `max` bytes, naming
+    /// `full_text` in the marker as where the rest can be read.
+    pub(crate) fn format_error_limited<E: crate::error::ForgeError + ?Sized>(
+        &self,
+        err: &E,
+        max: usize,
+        full_text: Option<&std::path::Path>,
+    ) -> String {
+        use crate::limits::truncate_words;
         use std::fmt::Write as _;
         let mut buf = String::with_capacity(160);
 
         // Caption — written via the helper formatters so the colour
         // escapes match the rest of the output.
-        let _ = writeln!(buf, "{}", self.caption(&format!("⚠️  {}", err.caption())));
+        let _ = writeln!(buf, "{}", self.caption_line(err.kind(), err.caption()));
 
-        // Error message.
-        let _ = writeln!(buf, "{}", self.error(&err.to_string()));
+        // Error message, colored by kind override or severity.
+        let _ = writeln!(
+            buf,
+            "{}",
+            self.message_line(
+                err.kind(),
+                err.severity(),
+                &truncate_words(&err.to_string(), max, full_text)
+            )
+        );
 
         // Retryable status.
         let marker = if err.is_retryable() {
@@ -171,19 +318,132 @@ impl ConsoleTheme {
         };
         let _ = writeln!(buf, "{}Retryable: {}{}", self.dim, marker, self.reset);
 
+        // Where to read more.
+        if let Some(url) = err.docs_url() {
+            let _ = writeln!(buf, "{}Docs: {}{}", self.dim, url, self.reset);
+        }
+
+        // Where the error was created.
+        if let Some(location) = err.location() {
+            let _ = writeln!(buf, "{}Location: {}{}", self.dim, location, self.reset);
+        }
+
+        // Attached metadata.
+        for (key, value) in err.metadata().iter() {
+            let _ = writeln!(buf, "{}{}: {}{}", self.dim, key, value, self.reset);
+        }
+
         // Source error if available.
         if let Some(source) = err.source() {
             let _ = writeln!(
                 buf,
                 "{}Caused by: {}{}",
                 self.dim,
-                self.error(&source.to_string()),
+                self.error(&truncate_words(&source.to_string(), max, full_text)),
+                self.reset
+            );
+        }
+
+        buf
+    }
+
+    /// Format a previously captured [`ErrorSnapshot`] using the same
+    /// layout as [`Self::format_error`], listing every recorded cause.
+    ///
+    /// [`ErrorSnapshot`]: crate::snapshot::ErrorSnapshot
+    pub fn format_snapshot(&self, snapshot: &crate::snapshot::ErrorSnapshot) -> String {
+        use std::fmt::Write as _;
+        let mut buf = String::with_capacity(160);
+
+        let _ = writeln!(
+            buf,
+            "{}",
+            self.caption_line(&snapshot.kind, &snapshot.caption)
+        );
+        let _ = writeln!(
+            buf,
+            "{}",
+            self.message_line(&snapshot.kind, snapshot.severity, &snapshot.message)
+        );
+
+        let marker = if snapshot.retryable {
+            self.success("Yes")
+        } else {
+            self.error("No")
+        };
+        let _ = writeln!(buf, "{}Retryable: {}{}", self.dim, marker, self.reset);
+
+        if let Some(url) = &snapshot.docs_url {
+            let _ = writeln!(buf, "{}Docs: {}{}", self.dim, url, self.reset);
+        }
+
+        for (key, value) in snapshot.metadata.iter() {
+            let _ = writeln!(buf, "{}{}: {}{}", self.dim, key, value, self.reset);
+        }
+
+        for cause in &snapshot.causes {
+            let _ = writeln!(
+                buf,
+                "{}Caused by: {}{}",
+                self.dim,
+                self.error(cause),
                 self.reset
             );
         }
 
         buf
     }
+
+    /// Format a panic as [`install_panic_hook`] prints it.
+    pub fn format_panic(&self, panic_info: &std::panic::PanicHookInfo<'_>) -> String {
+        use std::fmt::Write as _;
+        let mut buf = String::with_capacity(160);
+        let message = crate::panic::payload_message(panic_info.payload());
+
+        let location = if let Some(location) = panic_info.location() {
+            format!("at {}:{}", location.file(), location.line())
+        } else {
+            "at unknown location".to_string()
+        };
+
+        let _ = writeln!(buf, "{}", self.caption("💥 PANIC"));
+        if let Some(panic) = panic_info
+            .payload()
+            .downcast_ref::<crate::panic::ForgePanic>()
+        {
+            let error = panic.error();
+            let _ = writeln!(buf, "{}", self.dim(&location));
+            if let Some(code) = error.code() {
+                let _ = writeln!(buf, "{}", self.dim(&format!("Code: {code}")));
+            }
+            buf.push_str(&self.format_snapshot(&crate::snapshot::ErrorSnapshot::capture(error)));
+            return buf;
+        }
+        let _ = writeln!(
+            buf,
+            "{}",
+            self.error(&format!("{} {}", message, self.dim(&location)))
+        );
+        buf
+    }
+
+    /// Format a [`ForgeWarning`] as a caption line naming its kind,
+    /// then its message colored by its level (or the kind's
+    /// [`KindStyle`]).
+    ///
+    /// [`ForgeWarning`]: crate::warning::ForgeWarning
+    pub fn format_warning(&self, warning: &crate::warning::ForgeWarning) -> String {
+        format!(
+            "{}\n{}\n",
+            self.caption_line(warning.kind(), &format!("{} Warning", warning.kind())),
+            self.message_line(warning.kind(), warning.level(), &warning.to_string())
+        )
+    }
+}
+
+/// Pretty-print a warning to stderr with the default theme.
+pub fn print_warning(warning: &crate::warning::ForgeWarning) {
+    eprintln!("{}", ConsoleTheme::default().format_warning(warning));
 }
 
 /// Pretty-print an error to stderr with the default theme.
@@ -191,34 +451,19 @@ impl ConsoleTheme {
 /// The default theme is cached process-wide via `OnceLock` — the
 /// terminal-capability check runs at most once regardless of how
 /// many errors are printed.
-pub fn print_error<E: crate::error::ForgeError>(err: &E) {
+pub fn print_error<E: crate::error::ForgeError + ?Sized>(err: &E) {
     static DEFAULT_THEME: std::sync::OnceLock<ConsoleTheme> = std::sync::OnceLock::new();
     let theme = DEFAULT_THEME.get_or_init(ConsoleTheme::default);
     eprintln!("{}", theme.format_error(err));
 }
 
 /// Install a panic hook that formats panics using the ConsoleTheme
+///
+/// A [`ForgePanic`](crate::panic::ForgePanic) payload is rendered as
+/// the full error, with its code and every cause.
 pub fn install_panic_hook() {
     let theme = ConsoleTheme::default();
     std::panic::set_hook(Box::new(move |panic_info| {
-        let message = match panic_info.payload().downcast_ref::<&str>() {
-            Some(s) => *s,
-            None => match panic_info.payload().downcast_ref::<String>() {
-                Some(s) => s.as_str(),
-                None => "Unknown panic",
-            },
-        };
-
-        let location = if let Some(location) = panic_info.location() {
-            format!("at {}:{}", location.file(), location.line())
-        } else {
-            "at unknown location".to_string()
-        };
-
-        eprintln!("{}", theme.caption("💥 PANIC"));
-        eprintln!(
-            "{}",
-            theme.error(&format!("{} {}", message, theme.dim(&location)))
-        );
+        eprint!("{}", theme.format_panic(panic_info));
     }));
 }
@@ -1,5 +1,12 @@
-use crate::error::ForgeError;
+use crate::error::{AppError, CausedError, ForgeError};
+use crate::metadata::{MetaValue, Metadata};
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
+use std::task::{Context, Poll};
 
 /// A wrapper error type that attaches contextual information to
 /// an error.
@@ -16,12 +23,55 @@ pub struct ContextError<E, C> {
     pub error: E,
     /// The context attached to the error
     pub context: C,
+    /// Fields added with [`context_kv`](ContextError::context_kv),
+    /// boxed so errors without any stay small
+    fields: Option<Box<Fields>>,
+    /// Where this layer was added, set by [`context!`](crate::context!)
+    location: Option<&'static std::panic::Location<'static>>,
+}
+
+/// Key/value pairs attached to one [`ContextError`] layer.
+#[derive(Debug)]
+struct Fields {
+    /// The pairs attached at this layer
+    own: Metadata,
+    /// The original error's metadata plus `own`
+    merged: Metadata,
 }
 
 impl<E, C> ContextError<E, C> {
     /// Create a new context error wrapping the original error
     pub fn new(error: E, context: C) -> Self {
-        Self { error, context }
+        Self {
+            error,
+            context,
+            fields: None,
+            location: None,
+        }
+    }
+
+    /// Record where this layer of context was added.
+    ///
+    /// The location is shown in [`ForgeError::dev_message`];
+    /// [`ForgeError::location`] still reports where the original error
+    /// was constructed.
+    pub fn with_location(mut self, location: &'static std::panic::Location<'static>) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    /// Where this layer of context was added, if recorded.
+    pub fn context_location(&self) -> Option<&'static std::panic::Location<'static>> {
+        self.location
+    }
+
+    /// The key/value pairs attached to this layer with
+    /// [`context_kv`](ContextError::context_kv).
+    pub fn fields(&self) -> &Metadata {
+        match &self.fields {
+            Some(fields) => &fields.own,
+            None => Metadata::empty(),
+        }
     }
 
     /// Extract the original error, discarding the context
@@ -37,6 +87,8 @@ impl<E, C> ContextError<E, C> {
         ContextError {
             error: self.error,
             context: f(self.context),
+            fields: self.fields,
+            location: self.location,
         }
     }
 
@@ -49,6 +101,82 @@ impl<E, C> ContextError<E, C> {
     }
 }
 
+impl<E: ForgeError, C> ContextError<E, C> {
+    /// Attach a typed key/value pair to this layer of context.
+    ///
+    /// The pair joins the original error's fields in
+    /// [`ForgeError::metadata`], so it reaches snapshots, JSON output,
+    /// and the logging adapters, and it is listed after the context in
+    /// [`ForgeError::user_message`].
+    ///
+    /// ```
+    /// use error_forge::{AppError, ForgeError};
+    ///
+    /// let err = AppError::network("billing", None)
+    ///     .context("charging card")
+    ///     .context_kv("request_id", "req-7")
+    ///     .context_kv("attempt", 2);
+    ///
+    /// assert_eq!(err.metadata().len(), 2);
+    /// assert!(err.user_message().starts_with("charging card (request_id=req-7 attempt=2): "));
+    /// ```
+    pub fn context_kv(mut self, key: impl Into<String>, value: impl Into<MetaValue>) -> Self {
+        let (key, value) = (key.into(), value.into());
+        let fields = self.fields.get_or_insert_with(|| {
+            Box::new(Fields {
+                own: Metadata::new(),
+                merged: self.error.metadata().clone(),
+            })
+        });
+        fields.merged.insert(key.clone(), value.clone());
+        fields.own.insert(key, value);
+        self
+    }
+}
+
+impl<E: ForgeError, C: fmt::Display + fmt::Debug + Send + Sync + 'static> ContextError<E, C> {
+    /// Every context layer, innermost first, ending with this one.
+    ///
+    /// Layers added by nested `ContextError`s (including through
+    /// metadata, code, and shared wrappers) are included; the search
+    /// stops at the first error that is not a context layer.
+    ///
+    /// ```
+    /// use error_forge::AppError;
+    ///
+    /// let err = AppError::config("missing port")
+    ///     .context("reading settings")
+    ///     .context("starting server");
+    ///
+    /// let layers: Vec<String> = err.contexts().map(|c| c.to_string()).collect();
+    /// assert_eq!(layers, ["reading settings", "starting server"]);
+    /// assert_eq!(err.depth(), 2);
+    /// ```
+    pub fn contexts(&self) -> impl Iterator<Item = &dyn fmt::Display> {
+        // Walk down from the outermost layer for each item rather than
+        // collecting the layers, so iterating does not allocate; chains
+        // are only a few layers deep.
+        (0..self.depth()).rev().filter_map(move |outer| {
+            let mut current: &dyn ForgeError = self;
+            for _ in 0..outer {
+                current = current.context_layer()?.1;
+            }
+            current.context_layer().map(|(context, _)| context)
+        })
+    }
+
+    /// The number of context layers, counting this one.
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut current: &dyn ForgeError = self;
+        while let Some((_, inner)) = current.context_layer() {
+            depth += 1;
+            current = inner;
+        }
+        depth
+    }
+}
+
 impl<E: fmt::Display, C: fmt::Display> fmt::Display for ContextError<E, C> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}: {}", self.context, self.error)
@@ -72,6 +200,87 @@ pub trait ResultExt<T, E> {
     fn with_context<C, F>(self, f: F) -> Result<T, ContextError<E, C>>
     where
         F: FnOnce() -> C;
+
+    /// Adds a string literal as context without allocating.
+    ///
+    /// The context is a `Cow<'static, str>`, so functions that add a
+    /// literal on one path and a formatted string on another
+    /// (`with_context(|| Cow::Owned(format!(...)))`) return the same
+    /// error type.
+    ///
+    /// ```
+    /// use error_forge::{AppError, ResultExt};
+    ///
+    /// let err = Err::<(), _>(AppError::config("missing port"))
+    ///     .context_static("reading settings")
+    ///     .unwrap_err();
+    /// assert!(err.to_string().starts_with("reading settings: "));
+    /// ```
+    fn context_static(self, context: &'static str) -> Result<T, ContextError<E, Cow<'static, str>>>
+    where
+        Self: Sized,
+    {
+        self.context(Cow::Borrowed(context))
+    }
+
+    /// Adds a [`LazyContext`] that runs `f` only if the error is
+    /// displayed, logged, or serialized.
+    fn lazy_context<D, F>(self, f: F) -> Result<T, ContextError<E, LazyContext<F>>>
+    where
+        Self: Sized,
+        F: Fn() -> D,
+        D: fmt::Display,
+    {
+        self.context(LazyContext::new(f))
+    }
+
+    /// Converts the error into an [`AppError::Other`] with `message`,
+    /// keeping the original error as its `source()` through a
+    /// [`CausedError`].
+    ///
+    /// `?` converts the result further into any type implementing
+    /// `From<CausedError<AppError>>`, such as a boxed error, so this
+    /// replaces `map_err(|e| AppError::other(e.to_string()))` without
+    /// flattening the cause into a string.
+    ///
+    /// ```
+    /// use error_forge::{AppError, CausedError, ForgeError, ResultExt};
+    ///
+    /// fn port(raw: &str) -> Result<u16, CausedError<AppError>> {
+    ///     raw.parse().wrap_err(format!("invalid port {raw:?}"))
+    /// }
+    ///
+    /// let err = port("http").unwrap_err();
+    /// assert!(err.is_caused_by::<std::num::ParseIntError>());
+    /// ```
+    #[track_caller]
+    fn wrap_err<M>(self, message: M) -> Result<T, CausedError<AppError>>
+    where
+        Self: Sized,
+        E: std::error::Error + Send + Sync + 'static,
+        M: Into<String>,
+    {
+        match self.context(()) {
+            Ok(value) => Ok(value),
+            Err(error) => Err(AppError::other(message).with_source(error.into_error())),
+        }
+    }
+
+    /// Like [`wrap_err`](Self::wrap_err), building the message only on
+    /// error.
+    #[track_caller]
+    fn wrap_err_with<M, F>(self, f: F) -> Result<T, CausedError<AppError>>
+    where
+        Self: Sized,
+        E: std::error::Error + Send + Sync + 'static,
+        M: Into<String>,
+        F: FnOnce() -> M,
+    {
+        match self.context(()) {
+            Ok(value) => Ok(value),
+            Err(error) => Err(AppError::other(f()).with_source(error.into_error())),
+        }
+    }
 }
 
 impl<T, E> ResultExt<T, E> for Result<T, E> {
@@ -87,6 +296,260 @@ impl<T, E> ResultExt<T, E> for Result<T, E> {
     }
 }
 
+/// Context computed on first use.
+///
+/// [`ResultExt::with_context`] runs its closure as soon as the error
+/// happens. `LazyContext` defers that until the context is displayed
+/// (including by `user_message`, logging, and snapshots) and caches
+/// the result, so an expensive description — a serialized request, a
+/// dump of some state — costs nothing for errors that are handled
+/// without being reported.
+///
+/// ```
+/// use error_forge::{AppError, ResultExt};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static RENDERED: AtomicUsize = AtomicUsize::new(0);
+///
+/// let err = Err::<(), _>(AppError::config("bad"))
+///     .lazy_context(|| {
+///         RENDERED.fetch_add(1, Ordering::SeqCst);
+///         "while loading settings"
+///     })
+///     .unwrap_err();
+/// assert_eq!(RENDERED.load(Ordering::SeqCst), 0);
+///
+/// assert!(err.to_string().starts_with("while loading settings: "));
+/// let _ = err.to_string();
+/// assert_eq!(RENDERED.load(Ordering::SeqCst), 1);
+/// ```
+pub struct LazyContext<F> {
+    render: F,
+    rendered: OnceLock<String>,
+}
+
+impl<F> LazyContext<F> {
+    /// Wrap `render`, which is called at most once.
+    pub fn new(render: F) -> Self {
+        Self {
+            render,
+            rendered: OnceLock::new(),
+        }
+    }
+
+    /// Returns true once the context has been rendered.
+    pub fn is_rendered(&self) -> bool {
+        self.rendered.get().is_some()
+    }
+}
+
+impl<D: fmt::Display, F: Fn() -> D> LazyContext<F> {
+    /// The rendered context, computing it on first call.
+    pub fn get(&self) -> &str {
+        self.rendered.get_or_init(|| (self.render)().to_string())
+    }
+}
+
+impl<D: fmt::Display, F: Fn() -> D> fmt::Display for LazyContext<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.get())
+    }
+}
+
+impl<F> fmt::Debug for LazyContext<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.rendered.get() {
+            Some(rendered) => f.debug_tuple("LazyContext").field(rendered).finish(),
+            None => f.write_str("LazyContext(<not rendered>)"),
+        }
+    }
+}
+
+/// Extension trait turning a missing `Option` value into an error
+///
+/// ```
+/// use error_forge::{AppError, OptionExt};
+///
+/// fn find(id: u32) -> Result<&'static str, AppError> {
+///     let user = [(1, "ada")].into_iter().find(|(key, _)| *key == id);
+///     let (_, name) = user.context(format!("user {id} not found"))?;
+///     Ok(name)
+/// }
+///
+/// assert_eq!(find(1).unwrap(), "ada");
+/// assert!(find(2).unwrap_err().to_string().contains("user 2 not found"));
+/// ```
+pub trait OptionExt<T> {
+    /// Returns the value, or an [`AppError::Other`] whose message is
+    /// `context` if there is none.
+    fn context<C: fmt::Display>(self, context: C) -> Result<T, AppError>;
+
+    /// Like [`context`](Self::context), building the message only when
+    /// the value is missing.
+    fn with_context<C, F>(self, f: F) -> Result<T, AppError>
+    where
+        C: fmt::Display,
+        F: FnOnce() -> C;
+}
+
+impl<T> OptionExt<T> for Option<T> {
+    #[track_caller]
+    fn context<C: fmt::Display>(self, context: C) -> Result<T, AppError> {
+        match self {
+            Some(value) => Ok(value),
+            None => Err(AppError::other(context.to_string())),
+        }
+    }
+
+    #[track_caller]
+    fn with_context<C, F>(self, f: F) -> Result<T, AppError>
+    where
+        C: fmt::Display,
+        F: FnOnce() -> C,
+    {
+        match self {
+            Some(value) => Ok(value),
+            None => Err(AppError::other(f().to_string())),
+        }
+    }
+}
+
+thread_local! {
+    /// The ambient scope for code running on this thread.
+    static SCOPE: RefCell<Vec<Arc<str>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A snapshot of the ambient context labels, outermost first.
+///
+/// Labels are pushed with [`scope`] (synchronous code) or
+/// [`task_scope`] (futures). Attach the current scope to an error with
+/// [`ResultExt::with_context`]:
+///
+/// ```
+/// use error_forge::context::{self, Scope};
+/// use error_forge::{AppError, ResultExt};
+///
+/// let err = context::scope("import", || {
+///     context::scope("row 42", || {
+///         Err::<(), _>(AppError::config("bad date")).with_context(Scope::current)
+///     })
+/// })
+/// .unwrap_err();
+/// assert!(err.to_string().starts_with("import > row 42: "));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scope {
+    frames: Vec<Arc<str>>,
+}
+
+impl Scope {
+    /// The scope active on the current thread or task.
+    pub fn current() -> Self {
+        Self {
+            frames: SCOPE.with(|scope| scope.borrow().clone()),
+        }
+    }
+
+    /// The labels in this scope, outermost first.
+    pub fn frames(&self) -> impl Iterator<Item = &str> {
+        self.frames.iter().map(|frame| &**frame)
+    }
+
+    /// Returns true if no scope was active.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    fn with(mut self, label: Arc<str>) -> Self {
+        self.frames.push(label);
+        self
+    }
+
+    /// Install these frames as the thread's scope until the guard drops.
+    fn enter(&self) -> ScopeGuard {
+        let previous = SCOPE.with(|scope| scope.replace(self.frames.clone()));
+        ScopeGuard { previous }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, frame) in self.frames.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" > ")?;
+            }
+            f.write_str(frame)?;
+        }
+        Ok(())
+    }
+}
+
+/// Restores the previous scope on drop, including during unwinding.
+struct ScopeGuard {
+    previous: Vec<Arc<str>>,
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        let previous = std::mem::take(&mut self.previous);
+        SCOPE.with(|scope| *scope.borrow_mut() = previous);
+    }
+}
+
+/// Run `f` with `label` added to the ambient scope.
+pub fn scope<R>(label: impl Into<Arc<str>>, f: impl FnOnce() -> R) -> R {
+    let _guard = Scope::current().with(label.into()).enter();
+    f()
+}
+
+/// Wrap `future` so it runs with the current scope plus `label`,
+/// across every `.await` and on whichever thread polls it.
+///
+/// The scope is captured when `task_scope` is called, so wrapping a
+/// future before handing it to `spawn` is how a subtask inherits its
+/// parent's scope.
+pub fn task_scope<F: Future>(label: impl Into<Arc<str>>, future: F) -> Scoped<F> {
+    Scoped {
+        scope: Scope::current().with(label.into()),
+        future: Box::pin(future),
+    }
+}
+
+/// Wrap `future` so it runs with the current scope, unchanged.
+///
+/// Use when spawning a subtask that should report errors under its
+/// parent's scope without adding a label of its own.
+pub fn inherit<F: Future>(future: F) -> Scoped<F> {
+    Scoped {
+        scope: Scope::current(),
+        future: Box::pin(future),
+    }
+}
+
+/// A future that installs a captured [`Scope`] each time it is
+/// polled. Returned by [`task_scope`] and [`inherit`].
+pub struct Scoped<F> {
+    scope: Scope,
+    future: Pin<Box<F>>,
+}
+
+impl<F> fmt::Debug for Scoped<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scoped")
+            .field("scope", &self.scope)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F: Future> Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let _guard = self.scope.enter();
+        self.future.as_mut().poll(cx)
+    }
+}
+
 // Implement ForgeError for ContextError when the inner error implements ForgeError
 impl<E: ForgeError, C: fmt::Display + fmt::Debug + Send + Sync + 'static> ForgeError
     for ContextError<E, C>
@@ -115,23 +578,82 @@ impl<E: ForgeError, C: fmt::Display + fmt::Debug + Send + Sync + 'static> ForgeE
         self.error.exit_code()
     }
 
+    fn severity(&self) -> crate::macros::ErrorLevel {
+        self.error.severity()
+    }
+
+    fn class(&self) -> crate::error::ErrorClass {
+        self.error.class()
+    }
+
     fn user_message(&self) -> String {
-        format!("{}: {}", self.context, self.error.user_message())
+        if self.fields().is_empty() {
+            format!("{}: {}", self.context, self.error.user_message())
+        } else {
+            format!(
+                "{} ({}): {}",
+                self.context,
+                self.fields(),
+                self.error.user_message()
+            )
+        }
     }
 
     fn dev_message(&self) -> String {
-        format!("{}: {}", self.context, self.error.dev_message())
+        match self.location {
+            Some(location) => format!(
+                "{} (at {}): {}",
+                self.context,
+                location,
+                self.error.dev_message()
+            ),
+            None => format!("{}: {}", self.context, self.error.dev_message()),
+        }
     }
 
     fn backtrace(&self) -> Option<&std::backtrace::Backtrace> {
         self.error.backtrace()
     }
+
+    fn code(&self) -> Option<&str> {
+        self.error.code()
+    }
+
+    fn docs_url(&self) -> Option<String> {
+        self.error.docs_url()
+    }
+
+    fn retry_after(&self) -> Option<std::time::Duration> {
+        self.error.retry_after()
+    }
+
+    fn error_id(&self) -> Option<crate::instance::ErrorId> {
+        self.error.error_id()
+    }
+
+    fn timestamp(&self) -> Option<std::time::SystemTime> {
+        self.error.timestamp()
+    }
+
+    fn location(&self) -> Option<&'static std::panic::Location<'static>> {
+        self.error.location()
+    }
+
+    fn context_layer(&self) -> Option<(&dyn fmt::Display, &dyn ForgeError)> {
+        Some((&self.context, &self.error))
+    }
+
+    fn metadata(&self) -> &Metadata {
+        match &self.fields {
+            Some(fields) => &fields.merged,
+            None => self.error.metadata(),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::AppError;
 
     #[test]
     fn test_context_error() {
@@ -158,4 +680,181 @@ mod tests {
             "Failed to load settings: ⚙️ Configuration Error: Invalid config"
         );
     }
+
+    #[test]
+    fn test_context_kv() {
+        let err = ContextError::new(
+            AppError::config("bad").with_meta("tenant", "acme"),
+            "loading",
+        )
+        .context_kv("request_id", 42)
+        .context("startup");
+
+        assert!(err
+            .user_message()
+            .starts_with("startup: loading (request_id=42): "));
+        let snapshot = crate::ErrorSnapshot::capture(&err);
+        assert_eq!(snapshot.metadata.len(), 2);
+        assert_eq!(err.error.fields().len(), 1);
+    }
+
+    #[test]
+    fn test_contexts() {
+        use crate::metadata::WithMetadata;
+
+        let parsing = AppError::config("bad").context("parsing");
+        let err = ContextError::new(parsing.with_meta("file", "app.toml"), 7);
+
+        let layers: Vec<String> = err.contexts().map(ToString::to_string).collect();
+        assert_eq!(layers, ["parsing", "7"]);
+        assert_eq!(err.depth(), 2);
+        assert_eq!(ContextError::new(AppError::config("bad"), "x").depth(), 1);
+    }
+
+    // Counts allocations made on the current thread, so the test below
+    // is not disturbed by tests running in parallel.
+    struct CountingAlloc;
+
+    thread_local! {
+        static ALLOCATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    unsafe impl std::alloc::GlobalAlloc for CountingAlloc {
+        unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            std::alloc::System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+            std::alloc::System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAlloc = CountingAlloc;
+
+    #[test]
+    fn test_context_static_does_not_allocate() {
+        let result: Result<(), AppError> = Err(AppError::config("missing port"));
+        let before = ALLOCATIONS.with(|count| count.get());
+
+        let err = result
+            .context_static("reading settings")
+            .context_static("starting server")
+            .unwrap_err();
+        let mut layers = err.contexts();
+        assert!(layers.next().is_some() && layers.next().is_some());
+        assert!(layers.next().is_none());
+        assert_eq!(err.depth(), 2);
+        assert!(matches!(err.context, Cow::Borrowed("starting server")));
+
+        assert_eq!(ALLOCATIONS.with(|count| count.get()), before);
+    }
+
+    #[test]
+    fn test_context_macro() {
+        let path = "app.toml";
+        let result: Result<(), AppError> = Err(AppError::config("bad"));
+        let (result, line) = (crate::context!(result, "loading {path}"), line!());
+        let err = result.unwrap_err();
+
+        assert_eq!(err.context, "loading app.toml");
+        assert_eq!(err.context_location().unwrap().line(), line);
+        let at = format!("loading app.toml (at {}:{line}:", file!());
+        assert!(err.dev_message().starts_with(&at));
+        assert!(err.to_string().starts_with("loading app.toml: "));
+    }
+
+    #[test]
+    fn test_lazy_context() {
+        let err = Err::<(), _>(AppError::config("bad"))
+            .lazy_context(|| format!("row {}", 42))
+            .unwrap_err();
+        assert!(!err.context.is_rendered());
+        assert_eq!(format!("{:?}", err.context), "LazyContext(<not rendered>)");
+
+        // Reporting through the `ForgeError` surface renders it once.
+        assert!(err.user_message().starts_with("row 42: "));
+        assert!(err.context.is_rendered());
+        assert_eq!(err.context.get(), "row 42");
+    }
+
+    /// An implementor written against 1.x, which knew only `context`
+    /// and `with_context`.
+    struct Lookup(Result<u8, AppError>);
+
+    impl ResultExt<u8, AppError> for Lookup {
+        fn context<C>(self, context: C) -> Result<u8, ContextError<AppError, C>> {
+            self.0.context(context)
+        }
+
+        fn with_context<C, F>(self, f: F) -> Result<u8, ContextError<AppError, C>>
+        where
+            F: FnOnce() -> C,
+        {
+            self.0.with_context(f)
+        }
+    }
+
+    #[test]
+    fn test_result_ext_defaults() {
+        let err = Lookup(Err(AppError::other("missing")))
+            .lazy_context(|| "looking up")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "looking up: 🚨 Error: missing");
+
+        let err = Lookup(Err(AppError::other("missing")))
+            .wrap_err("looking up")
+            .unwrap_err();
+        assert!(err.is_caused_by::<AppError>());
+        let ok = Lookup(Ok(1)).wrap_err_with(|| -> String { unreachable!() });
+        assert_eq!(ok.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_wrap_err() {
+        let err = "x".parse::<u8>().wrap_err("bad count").unwrap_err();
+        assert_eq!(err.kind(), "Other");
+        assert!(err.is_caused_by::<std::num::ParseIntError>());
+
+        assert_eq!(err.into_error().to_string(), "🚨 Error: bad count");
+
+        let ok: Result<u8, CausedError<AppError>> = "7"
+            .parse::<u8>()
+            .wrap_err_with(|| -> String { unreachable!() });
+        assert_eq!(ok.unwrap(), 7);
+    }
+
+    #[test]
+    fn test_option_context() {
+        let err = None::<u8>.context("no value").unwrap_err();
+        assert_eq!(err.kind(), "Other");
+        assert!(err.to_string().contains("no value"));
+
+        assert_eq!(
+            Some(1)
+                .with_context(|| -> String { unreachable!() })
+                .unwrap(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_task_scope() {
+        async fn load() -> Result<(), ContextError<AppError, Scope>> {
+            tokio::task::yield_now().await;
+            Err(AppError::config("missing")).with_context(Scope::current)
+        }
+
+        let spawned = task_scope("request 7", async {
+            tokio::spawn(inherit(task_scope("db", load())))
+                .await
+                .unwrap()
+        })
+        .await;
+
+        let scope = spawned.unwrap_err().context;
+        assert_eq!(scope.to_string(), "request 7 > db");
+        assert!(Scope::current().is_empty());
+    }
 }
@@ -0,0 +1,132 @@
+//! Logging an error and carrying on with a fallback.
+//!
+//! A cache that cannot be reached, or an optional setting that fails to
+//! parse, is often better handled by continuing without it. Writing
+//! `.unwrap_or_default()` hides the failure entirely. The
+//! [`DegradeExt`] methods replace the error with a fallback value but
+//! still log it, one [severity](ErrorLevel) below its own, and count it
+//! in `error_forge_suppressed_errors_total` in
+//! [`render_prometheus`](crate::recovery::metrics::render_prometheus).
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::degrade::DegradeExt;
+//! use error_forge::AppError;
+//!
+//! fn cached_greeting() -> Result<String, AppError> {
+//!     Err(AppError::network("cache.internal", None))
+//! }
+//!
+//! fn load_config() -> Result<u32, AppError> {
+//!     Err(AppError::config("missing port"))
+//! }
+//!
+//! assert_eq!(cached_greeting().unwrap_or_default_logged(), "");
+//! assert_eq!(load_config().unwrap_or_else_logged(|_| 8080), 8080);
+//!
+//! // Only network failures are skipped; others are still returned.
+//! assert_eq!(cached_greeting().ignore_kind("Network").unwrap(), None);
+//! assert!(load_config().ignore_kind("Network").is_err());
+//! ```
+
+use crate::error::ForgeError;
+use crate::macros::ErrorLevel;
+
+/// Extension methods for a `Result` whose error is logged rather than
+/// returned.
+pub trait DegradeExt<T, E> {
+    /// The value, or `T::default()` after logging the error.
+    fn unwrap_or_default_logged(self) -> T
+    where
+        T: Default;
+
+    /// The value, or the result of `fallback` on the error after
+    /// logging it.
+    fn unwrap_or_else_logged<F>(self, fallback: F) -> T
+    where
+        F: FnOnce(E) -> T;
+
+    /// `Ok(None)` after logging the error if its
+    /// [`kind`](ForgeError::kind) is `kind`, the error otherwise, and
+    /// `Ok(Some(value))` on success.
+    fn ignore_kind(self, kind: &str) -> Result<Option<T>, E>;
+}
+
+impl<T, E: ForgeError> DegradeExt<T, E> for Result<T, E> {
+    fn unwrap_or_default_logged(self) -> T
+    where
+        T: Default,
+    {
+        self.unwrap_or_else_logged(|_| T::default())
+    }
+
+    fn unwrap_or_else_logged<F>(self, fallback: F) -> T
+    where
+        F: FnOnce(E) -> T,
+    {
+        self.unwrap_or_else(|error| {
+            suppress(&error);
+            fallback(error)
+        })
+    }
+
+    fn ignore_kind(self, kind: &str) -> Result<Option<T>, E> {
+        match self {
+            Ok(value) => Ok(Some(value)),
+            Err(error) if error.kind() == kind => {
+                suppress(&error);
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
+
+// Log `error` one level below its severity and count it.
+fn suppress<E: ForgeError>(error: &E) {
+    let level = match error.severity() {
+        ErrorLevel::Critical => ErrorLevel::Error,
+        ErrorLevel::Error => ErrorLevel::Warning,
+        ErrorLevel::Warning => ErrorLevel::Info,
+        _ => ErrorLevel::Debug,
+    };
+    crate::logging::log_with_level(error, level);
+    crate::recovery::metrics::record_suppressed(error.kind());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppError;
+
+    #[test]
+    fn test_suppressed_errors_are_counted() {
+        crate::define_errors! {
+            pub enum CacheError {
+                #[kind(CacheMiss)]
+                Miss { key: String },
+            }
+        }
+
+        let counted = || {
+            crate::recovery::metrics::render_prometheus()
+                .lines()
+                .find_map(|line| {
+                    line.strip_prefix("error_forge_suppressed_errors_total{kind=\"CacheMiss\"} ")
+                })
+                .map_or(0, |count| count.parse::<u64>().unwrap())
+        };
+
+        let miss = || Err::<u8, _>(CacheError::miss("user:1".to_string()));
+        assert_eq!(miss().unwrap_or_default_logged(), 0);
+        assert_eq!(
+            miss().unwrap_or_else_logged(|err| err.kind().len() as u8),
+            9
+        );
+        assert_eq!(miss().ignore_kind("CacheMiss").unwrap(), None);
+        assert!(miss().ignore_kind("Config").is_err());
+        assert_eq!(Ok::<_, AppError>(3).ignore_kind("Config").unwrap(), Some(3));
+        assert_eq!(counted(), 3);
+    }
+}
@@ -1,8 +1,11 @@
+use crate::instance::Instance;
+use std::any::Any;
 use std::backtrace::Backtrace;
 use std::error::Error as StdError;
 use std::fmt;
 use std::io;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[cfg(feature = "serde")]
 use serde::Serialize;
@@ -28,8 +31,111 @@ pub type AppResult<T> = std::result::Result<T, crate::error::AppError>;
 )]
 pub type Result<T> = AppResult<T>;
 
+/// A type-erased [`ForgeError`], for library boundaries that return a
+/// single error type.
+///
+/// Any `ForgeError` converts into it with `?` or `.into()`, and the
+/// boxed error still answers [`kind`](ForgeError::kind),
+/// [`status_code`](ForgeError::status_code),
+/// [`is_retryable`](ForgeError::is_retryable), and the rest of the
+/// trait. Recover the concrete type with
+/// [`downcast_ref`](trait.ForgeError.html#method.downcast_ref) or
+/// [`downcast`](trait.ForgeError.html#method.downcast).
+///
+/// ```
+/// use error_forge::{AppError, BoxedForgeError, ForgeError};
+///
+/// fn load() -> Result<(), BoxedForgeError> {
+///     Err(AppError::network("db", None))?
+/// }
+///
+/// let err = load().unwrap_err();
+/// assert!(err.is_retryable());
+/// assert!(err.is::<AppError>());
+/// ```
+pub type BoxedForgeError = Box<dyn ForgeError>;
+
+/// How an error relates to retrying the operation that produced it.
+///
+/// Returned by [`ForgeError::class`]. Finer-grained than
+/// [`is_retryable`](ForgeError::is_retryable): retry and recovery code
+/// can, for example, back off longer on `RateLimited` and stop at once
+/// on `Cancelled`.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// classes without breaking existing `match` statements.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum ErrorClass {
+    /// A temporary failure; the same operation may succeed later
+    Transient,
+    /// The operation will keep failing until something changes
+    Permanent,
+    /// Refused because of a quota or rate limit; retry after backing off
+    RateLimited,
+    /// The operation was cancelled and should not be retried
+    Cancelled,
+    /// Nothing is known about whether a retry could succeed
+    Unknown,
+}
+
+impl ErrorClass {
+    /// The default class for an error with the given retryable flag:
+    /// `Transient` if retryable, `Permanent` otherwise.
+    ///
+    /// This is what [`ForgeError::class`] returns unless an error type
+    /// declares its own class.
+    pub const fn from_retryable(is_retryable: bool) -> Self {
+        if is_retryable {
+            Self::Transient
+        } else {
+            Self::Permanent
+        }
+    }
+
+    /// Returns true for the classes worth retrying: `Transient` and
+    /// `RateLimited`.
+    pub const fn is_retryable(&self) -> bool {
+        matches!(self, Self::Transient | Self::RateLimited)
+    }
+
+    /// The snake_case name of the class (`"transient"`, `"rate_limited"`, ...).
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Transient => "transient",
+            Self::Permanent => "permanent",
+            Self::RateLimited => "rate_limited",
+            Self::Cancelled => "cancelled",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ErrorClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "transient" => Ok(Self::Transient),
+            "permanent" => Ok(Self::Permanent),
+            "rate_limited" | "ratelimited" => Ok(Self::RateLimited),
+            "cancelled" | "canceled" => Ok(Self::Cancelled),
+            "unknown" => Ok(Self::Unknown),
+            other => Err(format!("unknown error class '{other}'")),
+        }
+    }
+}
+
 /// Base trait for all custom error variants.
-pub trait ForgeError: std::error::Error + Send + Sync + 'static {
+pub trait ForgeError: std::error::Error + Send + Sync + AsAny + 'static {
     /// Returns the kind of error, typically matching the enum variant
     fn kind(&self) -> &'static str;
 
@@ -56,6 +162,25 @@ pub trait ForgeError: std::error::Error + Send + Sync + 'static {
         1
     }
 
+    /// Returns how severe the error is. Consumed by the error hook,
+    /// [`log_error`](crate::logging::log_error), and console output.
+    ///
+    /// Defaults to [`ErrorLevel::from_flags`](crate::macros::ErrorLevel::from_flags):
+    /// fatal errors are `Critical`, non-retryable errors are `Error`,
+    /// and retryable errors are `Warning`.
+    fn severity(&self) -> crate::macros::ErrorLevel {
+        crate::macros::ErrorLevel::from_flags(self.is_fatal(), self.is_retryable())
+    }
+
+    /// Returns how the error relates to retrying. Consumed by
+    /// [`RetryPolicy::forge_executor`](crate::recovery::RetryPolicy::forge_executor).
+    ///
+    /// Defaults to [`ErrorClass::from_retryable`]: retryable errors are
+    /// `Transient` and the rest are `Permanent`.
+    fn class(&self) -> ErrorClass {
+        ErrorClass::from_retryable(self.is_retryable())
+    }
+
     /// Returns a user-facing message that can be shown to end users
     fn user_message(&self) -> String {
         self.to_string()
@@ -63,7 +188,7 @@ pub trait ForgeError: std::error::Error + Send + Sync + 'static {
 
     /// Returns a detailed technical message for developers/logs
     fn dev_message(&self) -> String {
-        format!("[{}] {}", self.kind(), self)
+        default_dev_message(self)
     }
 
     /// Returns a backtrace if available
@@ -71,15 +196,353 @@ pub trait ForgeError: std::error::Error + Send + Sync + 'static {
         None
     }
 
+    /// Returns the stable error code attached to this error, if any
+    /// (see [`CodedError`](crate::registry::CodedError))
+    fn code(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the structured metadata attached to this error (empty
+    /// unless the error was wrapped with
+    /// [`with_meta`](crate::metadata::WithMetadata::with_meta))
+    fn metadata(&self) -> &crate::metadata::Metadata {
+        crate::metadata::Metadata::empty()
+    }
+
+    /// Returns the unique ID of this error instance, if it carries one.
+    /// Errors built by `define_errors!` constructors get a fresh
+    /// [`ErrorId`](crate::instance::ErrorId) each time.
+    fn error_id(&self) -> Option<crate::instance::ErrorId> {
+        None
+    }
+
+    /// Returns when this error was created, if it records it. Errors
+    /// built by `define_errors!` constructors do.
+    fn timestamp(&self) -> Option<std::time::SystemTime> {
+        None
+    }
+
+    /// Returns a documentation link for this error, if one is known.
+    ///
+    /// The default looks up the error's [`code`](Self::code) in the
+    /// global [`ErrorRegistry`](crate::registry::ErrorRegistry).
+    /// `define_errors!` variants set one with a `docs = "..."` tag and
+    /// `#[derive(ModError)]` variants with `#[error_docs("...")]`.
+    fn docs_url(&self) -> Option<String> {
+        let code = self.code()?;
+        crate::registry::ErrorRegistry::global()
+            .get_code_info(code)?
+            .documentation_url
+    }
+
+    /// How long the caller should wait before retrying, if the error
+    /// knows. A [`CircuitOpenError`](crate::recovery::CircuitOpenError)
+    /// reports the time until its breaker half-opens.
+    fn retry_after(&self) -> Option<std::time::Duration> {
+        None
+    }
+
+    /// The HTTP headers an error response should carry: `Retry-After`,
+    /// `X-Error-Code`, `X-Error-Id`, and `Cache-Control: no-store`,
+    /// adjusted by the [header hook](crate::headers::set_header_hook).
+    fn response_headers(&self) -> crate::headers::ResponseHeaders
+    where
+        Self: Sized,
+    {
+        crate::headers::ResponseHeaders::for_error(self)
+    }
+
+    /// Returns the source location that created this error, if it
+    /// records one. `define_errors!` and `AppError` constructors are
+    /// `#[track_caller]`, so this is the line that called them.
+    fn location(&self) -> Option<&'static std::panic::Location<'static>> {
+        None
+    }
+
+    /// The context this error adds and the error it wraps, if it is a
+    /// [`ContextError`](crate::context::ContextError) (or a transparent
+    /// wrapper around one). Used to walk context layers without
+    /// flattening them into one string.
+    fn context_layer(&self) -> Option<(&dyn fmt::Display, &dyn ForgeError)> {
+        None
+    }
+
     /// Registers the error with the central error registry
+    #[track_caller]
     fn register(&self) {
-        crate::macros::call_error_hook(
+        crate::macros::call_error_hook_with_level(
+            self.severity(),
             self.caption(),
             self.kind(),
             self.is_fatal(),
             self.is_retryable(),
         );
     }
+
+    /// Returns an iterator over this error and its source chain,
+    /// outermost first.
+    ///
+    /// ```
+    /// use error_forge::{AppError, ForgeError};
+    ///
+    /// let err = AppError::filesystem("config.toml", std::io::Error::other("denied"));
+    /// let messages: Vec<String> = err.chain().map(|e| e.to_string()).collect();
+    /// assert_eq!(messages.len(), 2);
+    /// assert_eq!(messages[1], "denied");
+    /// ```
+    fn chain(&self) -> Chain<'_>
+    where
+        Self: Sized,
+    {
+        Chain::new(self)
+    }
+
+    /// Returns the innermost error in the source chain — the last
+    /// error yielded by [`chain`](Self::chain). An error without a
+    /// source is its own root cause.
+    fn root_cause(&self) -> &(dyn StdError + 'static)
+    where
+        Self: Sized,
+    {
+        self.chain().last().unwrap_or(self)
+    }
+
+    /// Returns true if this error, or any error in its source chain,
+    /// is a `T`.
+    ///
+    /// ```
+    /// use error_forge::{AppError, ForgeError};
+    ///
+    /// let err = AppError::filesystem("config.toml", std::io::Error::other("denied"));
+    /// assert!(err.is_caused_by::<std::io::Error>());
+    /// ```
+    fn is_caused_by<T: StdError + 'static>(&self) -> bool
+    where
+        Self: Sized,
+    {
+        self.find_in_chain::<T>().is_some()
+    }
+
+    /// Returns the first error of type `T` found by walking this error
+    /// and then its source chain, outermost first.
+    fn find_in_chain<T: StdError + 'static>(&self) -> Option<&T>
+    where
+        Self: Sized,
+    {
+        self.chain().find_map(|err| err.downcast_ref::<T>())
+    }
+
+    /// Returns true if this error, one of its
+    /// [context layers](Self::context_layer), or a `ForgeError` cause
+    /// in its source chain has the given [`kind`](Self::kind).
+    ///
+    /// Causes are type-erased once they enter the source chain, so
+    /// there only [`AppError`] and [`BoxedForgeError`] causes can be
+    /// recognised. Box other error types with `BoxedForgeError::from`
+    /// before using them as a cause to make their kind visible.
+    ///
+    /// ```
+    /// use error_forge::{define_errors, AppError, BoxedForgeError, ForgeError};
+    ///
+    /// define_errors! {
+    ///     pub enum StoreError {
+    ///         #[kind(Store, retryable = true)]
+    ///         Unavailable { shard: u16 },
+    ///     }
+    /// }
+    ///
+    /// let cause = BoxedForgeError::from(StoreError::unavailable(3));
+    /// let err = AppError::network("orders", Some(Box::new(cause) as _));
+    /// assert!(err.chain_contains_kind("Store"));
+    /// assert!(!err.chain_contains_kind("Config"));
+    /// ```
+    fn chain_contains_kind(&self, kind: &str) -> bool {
+        // This error and every context layer below it
+        fn layers_contain(mut err: &dyn ForgeError, kind: &str) -> bool {
+            loop {
+                if err.kind() == kind {
+                    return true;
+                }
+                match err.context_layer() {
+                    Some((_, inner)) => err = inner,
+                    None => return false,
+                }
+            }
+        }
+
+        if self.kind() == kind
+            || self
+                .context_layer()
+                .is_some_and(|(_, inner)| layers_contain(inner, kind))
+        {
+            return true;
+        }
+        self.source().is_some_and(|source| {
+            Chain::new(source).any(|err| {
+                let forge = match err.downcast_ref::<AppError>() {
+                    Some(app) => Some(app as &dyn ForgeError),
+                    None => err.downcast_ref::<BoxedForgeError>().map(|boxed| &**boxed),
+                };
+                forge.is_some_and(|forge| layers_contain(forge, kind))
+            })
+        })
+    }
+}
+
+/// The default [`ForgeError::dev_message`], callable from impls that
+/// override it for only some variants.
+#[doc(hidden)]
+pub fn default_dev_message<E: ForgeError + ?Sized>(err: &E) -> String {
+    match (err.error_id(), err.location()) {
+        (Some(id), Some(location)) => {
+            format!("[{}] {} (id: {}, at {})", err.kind(), err, id, location)
+        }
+        (Some(id), None) => format!("[{}] {} (id: {})", err.kind(), err, id),
+        (None, Some(location)) => format!("[{}] {} (at {})", err.kind(), err, location),
+        (None, None) => format!("[{}] {}", err.kind(), err),
+    }
+}
+
+/// Iterator over an error and its source chain, outermost first.
+///
+/// Returned by [`ForgeError::chain`]. Yields the starting error itself, then each successive
+/// [`source`](std::error::Error::source), until the chain ends.
+///
+/// Iteration also stops after [`max_chain_depth`] errors, or when an
+/// error would be yielded a second time (a wrapper that indirectly
+/// sources itself); [`is_truncated`](Self::is_truncated) reports
+/// whether either guard fired.
+#[derive(Debug, Clone)]
+pub struct Chain<'a> {
+    next: Option<&'a (dyn StdError + 'static)>,
+    seen: Vec<&'a (dyn StdError + 'static)>,
+    max_depth: usize,
+    truncated: bool,
+}
+
+impl<'a> Chain<'a> {
+    /// Start a chain at `err`.
+    pub fn new(err: &'a (dyn StdError + 'static)) -> Self {
+        Self {
+            next: Some(err),
+            seen: Vec::new(),
+            max_depth: max_chain_depth(),
+            truncated: false,
+        }
+    }
+
+    /// Returns true if iteration stopped at the depth limit or at a
+    /// cycle rather than at the end of the chain.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl<'a> Iterator for Chain<'a> {
+    type Item = &'a (dyn StdError + 'static);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        if self.seen.len() >= self.max_depth
+            || self.seen.iter().any(|seen| std::ptr::eq(*seen, current))
+        {
+            self.truncated = true;
+            return None;
+        }
+        self.seen.push(current);
+        self.next = current.source();
+        Some(current)
+    }
+}
+
+/// Default for [`max_chain_depth`].
+pub const DEFAULT_MAX_CHAIN_DEPTH: usize = 32;
+
+static MAX_CHAIN_DEPTH: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_CHAIN_DEPTH);
+
+/// The most errors a [`Chain`] yields — and so the most causes
+/// snapshots, console output, and JSON record — before truncating.
+pub fn max_chain_depth() -> usize {
+    MAX_CHAIN_DEPTH.load(Ordering::Relaxed)
+}
+
+/// Set the depth limit returned by [`max_chain_depth`]. A limit of
+/// `0` is treated as `1`, so the starting error is always yielded.
+pub fn set_max_chain_depth(depth: usize) {
+    MAX_CHAIN_DEPTH.store(depth.max(1), Ordering::Relaxed);
+}
+
+/// Upcast to [`Any`] for downcasting through `dyn ForgeError`.
+///
+/// Implemented for every sized `'static` type; not meant to be named
+/// directly.
+#[doc(hidden)]
+pub trait AsAny {
+    fn __forge_as_any(&self) -> &dyn Any;
+    fn __forge_as_any_mut(&mut self) -> &mut dyn Any;
+    fn __forge_into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<T: Any> AsAny for T {
+    fn __forge_as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn __forge_as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn __forge_into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+impl dyn ForgeError {
+    /// The HTTP headers an error response should carry; see
+    /// [`ForgeError::response_headers`].
+    pub fn response_headers(&self) -> crate::headers::ResponseHeaders {
+        crate::headers::ResponseHeaders::for_error(self)
+    }
+
+    /// Returns true if the erased error is a `T`.
+    pub fn is<T: ForgeError>(&self) -> bool {
+        self.__forge_as_any().is::<T>()
+    }
+
+    /// Returns the erased error as a `T`, if it is one.
+    pub fn downcast_ref<T: ForgeError>(&self) -> Option<&T> {
+        self.__forge_as_any().downcast_ref()
+    }
+
+    /// Returns the erased error as a mutable `T`, if it is one.
+    pub fn downcast_mut<T: ForgeError>(&mut self) -> Option<&mut T> {
+        self.__forge_as_any_mut().downcast_mut()
+    }
+
+    /// Unboxes the erased error as a `T`, handing the box back if it
+    /// is not one.
+    pub fn downcast<T: ForgeError>(self: Box<Self>) -> std::result::Result<Box<T>, Box<Self>> {
+        if self.is::<T>() {
+            Ok(self
+                .__forge_into_any()
+                .downcast()
+                .expect("type checked by `is`"))
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<E: ForgeError> From<E> for BoxedForgeError {
+    fn from(err: E) -> Self {
+        Box::new(err)
+    }
+}
+
+impl StdError for BoxedForgeError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        (**self).source()
+    }
 }
 
 /// Example error enum that can be replaced by the define_errors! macro.
@@ -92,6 +555,9 @@ pub enum AppError {
         retryable: bool,
         fatal: bool,
         status: u16,
+        #[doc(hidden)]
+        #[cfg_attr(feature = "serde", serde(flatten))]
+        __forge: Instance,
     },
 
     /// Filesystem-related errors with optional path and source error
@@ -102,6 +568,9 @@ pub enum AppError {
         retryable: bool,
         fatal: bool,
         status: u16,
+        #[doc(hidden)]
+        #[cfg_attr(feature = "serde", serde(flatten))]
+        __forge: Instance,
     },
 
     /// Network-related errors
@@ -112,6 +581,9 @@ pub enum AppError {
         retryable: bool,
         fatal: bool,
         status: u16,
+        #[doc(hidden)]
+        #[cfg_attr(feature = "serde", serde(flatten))]
+        __forge: Instance,
     },
 
     /// Generic errors for anything not covered by specific variants
@@ -120,6 +592,9 @@ pub enum AppError {
         retryable: bool,
         fatal: bool,
         status: u16,
+        #[doc(hidden)]
+        #[cfg_attr(feature = "serde", serde(flatten))]
+        __forge: Instance,
     },
 }
 
@@ -161,6 +636,7 @@ impl std::error::Error for AppError {
 }
 
 impl From<io::Error> for AppError {
+    #[track_caller]
     fn from(e: io::Error) -> Self {
         AppError::Filesystem {
             path: None,
@@ -168,6 +644,7 @@ impl From<io::Error> for AppError {
             retryable: false,
             fatal: true,
             status: 500,
+            __forge: Instance::new(),
         }
     }
 }
@@ -217,28 +694,46 @@ impl ForgeError for AppError {
             Self::Other { status, .. } => *status,
         }
     }
+
+    fn backtrace(&self) -> Option<&Backtrace> {
+        self.instance().backtrace()
+    }
+
+    fn error_id(&self) -> Option<crate::instance::ErrorId> {
+        Some(self.instance().id())
+    }
+
+    fn timestamp(&self) -> Option<std::time::SystemTime> {
+        Some(self.instance().timestamp())
+    }
+
+    fn location(&self) -> Option<&'static std::panic::Location<'static>> {
+        Some(self.instance().location())
+    }
+
+    fn metadata(&self) -> &crate::metadata::Metadata {
+        self.instance().metadata()
+    }
 }
 
 /// Constructor methods for AppError
 impl AppError {
     /// Create a new Config error
+    #[track_caller]
     pub fn config(message: impl Into<String>) -> Self {
         let instance = Self::Config {
             message: message.into(),
             retryable: false,
             fatal: false,
             status: 500,
+            __forge: Instance::new(),
         };
-        crate::macros::call_error_hook(
-            instance.caption(),
-            instance.kind(),
-            instance.is_fatal(),
-            instance.is_retryable(),
-        );
+        instance.register();
         instance
     }
 
     /// Create a new Filesystem error
+    #[track_caller]
     pub fn filesystem(path: impl Into<String>, source: impl Into<Option<io::Error>>) -> Self {
         // Convert the source parameter
         let source = match source.into() {
@@ -252,17 +747,14 @@ impl AppError {
             retryable: false,
             fatal: false,
             status: 500,
+            __forge: Instance::new(),
         };
-        crate::macros::call_error_hook(
-            instance.caption(),
-            instance.kind(),
-            instance.is_fatal(),
-            instance.is_retryable(),
-        );
+        instance.register();
         instance
     }
 
     /// Create a filesystem error with specific source error
+    #[track_caller]
     pub fn filesystem_with_source(path: impl Into<PathBuf>, source: io::Error) -> Self {
         let instance = Self::Filesystem {
             path: Some(path.into()),
@@ -270,17 +762,14 @@ impl AppError {
             retryable: false,
             fatal: false,
             status: 500,
+            __forge: Instance::new(),
         };
-        crate::macros::call_error_hook(
-            instance.caption(),
-            instance.kind(),
-            instance.is_fatal(),
-            instance.is_retryable(),
-        );
+        instance.register();
         instance
     }
 
     /// Create a new Network error
+    #[track_caller]
     pub fn network(
         endpoint: impl Into<String>,
         source: impl Into<Option<Box<dyn StdError + Send + Sync>>>,
@@ -294,17 +783,14 @@ impl AppError {
             retryable: true,
             fatal: false,
             status: 503,
+            __forge: Instance::new(),
         };
-        crate::macros::call_error_hook(
-            instance.caption(),
-            instance.kind(),
-            instance.is_fatal(),
-            instance.is_retryable(),
-        );
+        instance.register();
         instance
     }
 
     /// Create a network error with specific source error
+    #[track_caller]
     pub fn network_with_source(
         endpoint: impl Into<String>,
         source: Option<Box<dyn StdError + Send + Sync>>,
@@ -315,33 +801,35 @@ impl AppError {
             retryable: true,
             fatal: false,
             status: 503,
+            __forge: Instance::new(),
         };
-        crate::macros::call_error_hook(
-            instance.caption(),
-            instance.kind(),
-            instance.is_fatal(),
-            instance.is_retryable(),
-        );
+        instance.register();
         instance
     }
 
     /// Create a new generic error
+    #[track_caller]
     pub fn other(message: impl Into<String>) -> Self {
         let instance = Self::Other {
             message: message.into(),
             retryable: false,
             fatal: false,
             status: 500,
+            __forge: Instance::new(),
         };
-        crate::macros::call_error_hook(
-            instance.caption(),
-            instance.kind(),
-            instance.is_fatal(),
-            instance.is_retryable(),
-        );
+        instance.register();
         instance
     }
 
+    fn instance(&self) -> &Instance {
+        match self {
+            Self::Config { __forge, .. }
+            | Self::Filesystem { __forge, .. }
+            | Self::Network { __forge, .. }
+            | Self::Other { __forge, .. } => __forge,
+        }
+    }
+
     /// Set whether this error is retryable
     pub fn with_retryable(mut self, retryable: bool) -> Self {
         match &mut self {
@@ -375,11 +863,41 @@ impl AppError {
         self
     }
 
+    /// Attach a source error, so it appears in
+    /// [`source()`](std::error::Error::source), [`chain`](ForgeError::chain),
+    /// and snapshots instead of being flattened into the message.
+    ///
+    /// The cause replaces any source the error carries itself.
+    ///
+    /// ```
+    /// use error_forge::{AppError, ForgeError};
+    ///
+    /// let parse = "x".parse::<u32>().unwrap_err();
+    /// let err = AppError::other("bad port").with_source(parse);
+    /// assert!(err.is_caused_by::<std::num::ParseIntError>());
+    /// assert_eq!(err.chain().count(), 2);
+    /// ```
+    pub fn with_source(
+        self,
+        source: impl Into<Box<dyn StdError + Send + Sync>>,
+    ) -> CausedError<Self> {
+        CausedError::new(self, source)
+    }
+
     /// Add a code to this error
     pub fn with_code(self, code: impl Into<String>) -> crate::registry::CodedError<Self> {
         crate::registry::CodedError::new(self, code.into())
     }
 
+    /// Attach a metadata field to this error
+    pub fn with_meta(
+        self,
+        key: impl Into<String>,
+        value: impl Into<crate::metadata::MetaValue>,
+    ) -> crate::metadata::MetadataError<Self> {
+        crate::metadata::MetadataError::new(self).with_meta(key, value)
+    }
+
     /// Add context to this error
     pub fn context<C: std::fmt::Display + std::fmt::Debug + Send + Sync + 'static>(
         self,
@@ -388,3 +906,124 @@ impl AppError {
         crate::context::ContextError::new(self, context)
     }
 }
+
+/// An error with a cause attached.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// fields without breaking callers. Build one with
+/// [`CausedError::new`] or [`AppError::with_source`]. Display and every
+/// [`ForgeError`] method delegate to the wrapped error;
+/// [`source()`](std::error::Error::source) returns the attached cause.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct CausedError<E> {
+    /// The original error
+    pub error: E,
+    /// The attached cause
+    pub source: Box<dyn StdError + Send + Sync>,
+}
+
+impl<E> CausedError<E> {
+    /// Wrap `error` with `source` as its cause.
+    pub fn new(error: E, source: impl Into<Box<dyn StdError + Send + Sync>>) -> Self {
+        Self {
+            error,
+            source: source.into(),
+        }
+    }
+
+    /// Extract the original error, discarding the cause
+    pub fn into_error(self) -> E {
+        self.error
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for CausedError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<E: StdError> StdError for CausedError<E> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+impl<E: ForgeError> ForgeError for CausedError<E> {
+    fn kind(&self) -> &'static str {
+        self.error.kind()
+    }
+
+    fn caption(&self) -> &'static str {
+        self.error.caption()
+    }
+
+    fn is_retryable(&self) -> bool {
+        self.error.is_retryable()
+    }
+
+    fn is_fatal(&self) -> bool {
+        self.error.is_fatal()
+    }
+
+    fn status_code(&self) -> u16 {
+        self.error.status_code()
+    }
+
+    fn exit_code(&self) -> i32 {
+        self.error.exit_code()
+    }
+
+    fn severity(&self) -> crate::macros::ErrorLevel {
+        self.error.severity()
+    }
+
+    fn class(&self) -> ErrorClass {
+        self.error.class()
+    }
+
+    fn user_message(&self) -> String {
+        self.error.user_message()
+    }
+
+    fn dev_message(&self) -> String {
+        self.error.dev_message()
+    }
+
+    fn backtrace(&self) -> Option<&Backtrace> {
+        self.error.backtrace()
+    }
+
+    fn code(&self) -> Option<&str> {
+        self.error.code()
+    }
+
+    fn docs_url(&self) -> Option<String> {
+        self.error.docs_url()
+    }
+
+    fn retry_after(&self) -> Option<std::time::Duration> {
+        self.error.retry_after()
+    }
+
+    fn error_id(&self) -> Option<crate::instance::ErrorId> {
+        self.error.error_id()
+    }
+
+    fn timestamp(&self) -> Option<std::time::SystemTime> {
+        self.error.timestamp()
+    }
+
+    fn location(&self) -> Option<&'static std::panic::Location<'static>> {
+        self.error.location()
+    }
+
+    fn context_layer(&self) -> Option<(&dyn fmt::Display, &dyn ForgeError)> {
+        self.error.context_layer()
+    }
+
+    fn metadata(&self) -> &crate::metadata::Metadata {
+        self.error.metadata()
+    }
+}
@@ -0,0 +1,379 @@
+//! Turning bursts of errors into one higher-severity event.
+//!
+//! A single `NET-001` timeout is noise; twenty in five minutes means a
+//! dependency is down. An [`Escalator`] holds [`EscalationRule`]s that
+//! count matching errors in a sliding window. When a rule's threshold
+//! is reached it synthesizes a [`SystemDegraded`] error, fires it
+//! through the error hook and observers (so alerting sees one
+//! meaningful event), and returns it. The rule then stays quiet until
+//! the rate falls back below the threshold.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::escalation::{EscalationRule, Escalator};
+//! use error_forge::{AppError, ErrorMatcher, ForgeError};
+//! use std::time::Duration;
+//!
+//! let escalator = Escalator::new().with_rule(EscalationRule::new(
+//!     "billing-degraded",
+//!     ErrorMatcher::any().code("NET-001"),
+//!     3,
+//!     Duration::from_secs(300),
+//! ));
+//!
+//! let mut raised = Vec::new();
+//! for _ in 0..5 {
+//!     let err = AppError::network("billing", None).with_code("NET-001");
+//!     raised.extend(escalator.observe(&err));
+//! }
+//!
+//! // Raised once, on the third occurrence.
+//! assert_eq!(raised.len(), 1);
+//! assert_eq!(raised[0].kind(), "SystemDegraded");
+//! ```
+
+use crate::error::ForgeError;
+use crate::macros::ErrorLevel;
+use crate::matcher::ErrorMatcher;
+use crate::metadata::Metadata;
+use crate::request;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// The kind reported by [`SystemDegraded`].
+pub const SYSTEM_DEGRADED_KIND: &str = "SystemDegraded";
+
+/// Escalate when `threshold` errors matching `matcher` occur within
+/// `window`.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// fields without breaking callers. Build one with
+/// [`EscalationRule::new`] and the `with_*` methods.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct EscalationRule {
+    /// Name of the rule, reported by the synthesized error
+    pub name: String,
+    /// Which errors count towards the threshold
+    pub matcher: ErrorMatcher,
+    /// How many matching errors trigger the rule
+    pub threshold: usize,
+    /// The sliding window the errors must fall within
+    pub window: Duration,
+    /// Severity of the synthesized error
+    pub severity: ErrorLevel,
+    /// Count each tenant separately, so one noisy tenant raises an
+    /// error naming it without pushing the others over the threshold
+    pub per_tenant: bool,
+}
+
+impl EscalationRule {
+    /// A rule raising a `Critical` error. A `threshold` of zero is
+    /// treated as one.
+    pub fn new(
+        name: impl Into<String>,
+        matcher: ErrorMatcher,
+        threshold: usize,
+        window: Duration,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            matcher,
+            threshold: threshold.max(1),
+            window,
+            severity: ErrorLevel::Critical,
+            per_tenant: false,
+        }
+    }
+
+    /// Set the severity of the synthesized error.
+    pub fn with_severity(mut self, severity: ErrorLevel) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Count errors per tenant, keyed on their
+    /// [`tenant_id`](crate::request::tenant_id). Errors without a tenant
+    /// are counted together.
+    pub fn per_tenant(mut self) -> Self {
+        self.per_tenant = true;
+        self
+    }
+}
+
+/// A rule and the recent occurrences it has counted, per tenant when
+/// the rule asks for it.
+#[derive(Debug)]
+struct RuleState {
+    rule: EscalationRule,
+    windows: HashMap<Option<String>, Window>,
+    /// When idle windows were last dropped
+    pruned: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+struct Window {
+    /// The most recent matches, at most `threshold` of them
+    seen: VecDeque<Instant>,
+    /// Raised during the current episode
+    fired: bool,
+}
+
+impl RuleState {
+    fn observe(&mut self, now: Instant, tenant: Option<&str>) -> Option<SystemDegraded> {
+        let tenant = tenant.filter(|_| self.rule.per_tenant);
+        self.prune(now);
+        let state = self.windows.entry(tenant.map(String::from)).or_default();
+        let window = self.rule.window;
+        while state
+            .seen
+            .front()
+            .is_some_and(|&at| now.duration_since(at) > window)
+        {
+            state.seen.pop_front();
+        }
+        if state.seen.len() == self.rule.threshold {
+            state.seen.pop_front();
+        }
+        state.seen.push_back(now);
+
+        if state.seen.len() < self.rule.threshold {
+            state.fired = false;
+            return None;
+        }
+        if std::mem::replace(&mut state.fired, true) {
+            return None;
+        }
+        Some(SystemDegraded::new(&self.rule, tenant))
+    }
+
+    /// Drop the windows of tenants with no match inside the rule's
+    /// window, at most once per window. They hold nothing a fresh
+    /// window would not, and tenant IDs come from requests, so keeping
+    /// them would grow the map without bound.
+    fn prune(&mut self, now: Instant) {
+        let window = self.rule.window;
+        let pruned = *self.pruned.get_or_insert(now);
+        if now.saturating_duration_since(pruned) < window {
+            return;
+        }
+        self.pruned = Some(now);
+        self.windows.retain(|_, state| {
+            state
+                .seen
+                .back()
+                .is_some_and(|&at| now.saturating_duration_since(at) <= window)
+        });
+    }
+}
+
+/// A set of [`EscalationRule`]s fed with errors as they happen.
+///
+/// Shareable across threads; feed it from wherever errors are logged
+/// or returned.
+#[derive(Debug, Default)]
+pub struct Escalator {
+    rules: Mutex<Vec<RuleState>>,
+}
+
+impl Escalator {
+    /// An escalator with no rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule.
+    pub fn with_rule(self, rule: EscalationRule) -> Self {
+        self.rules.lock().push(RuleState {
+            rule,
+            windows: HashMap::new(),
+            pruned: None,
+        });
+        self
+    }
+
+    /// Count `err` against every rule it matches.
+    ///
+    /// Each rule whose threshold is reached for the first time in the
+    /// current episode synthesizes a [`SystemDegraded`] error, fires it
+    /// through the error hook, and returns it.
+    pub fn observe<E: ForgeError + ?Sized>(&self, err: &E) -> Vec<SystemDegraded> {
+        self.observe_at(err, crate::clock::instant())
+    }
+
+    fn observe_at<E: ForgeError + ?Sized>(&self, err: &E, now: Instant) -> Vec<SystemDegraded> {
+        let raised: Vec<_> = self
+            .rules
+            .lock()
+            .iter_mut()
+            .filter(|state| state.rule.matcher.matches(err))
+            .filter_map(|state| state.observe(now, request::tenant_id(err)))
+            .collect();
+        // Outside the lock, so hooks may feed this escalator again.
+        for degraded in &raised {
+            degraded.register();
+        }
+        raised
+    }
+
+    /// Forget all counted occurrences and re-arm every rule.
+    pub fn reset(&self) {
+        for state in self.rules.lock().iter_mut() {
+            state.windows.clear();
+        }
+    }
+}
+
+/// Raised by an [`Escalator`] when a rule's threshold is reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SystemDegraded {
+    /// The rule that fired
+    pub rule: String,
+    /// How many matching errors were counted
+    pub occurrences: usize,
+    /// The window they fell within
+    pub window: Duration,
+    /// The rule's severity
+    pub severity: ErrorLevel,
+    /// The tenant the errors came from, for per-tenant rules
+    pub tenant: Option<String>,
+    metadata: Metadata,
+}
+
+impl SystemDegraded {
+    fn new(rule: &EscalationRule, tenant: Option<&str>) -> Self {
+        let mut metadata = Metadata::new();
+        metadata.insert("escalation.rule", rule.name.clone());
+        metadata.insert("escalation.occurrences", rule.threshold);
+        metadata.insert("escalation.window_ms", rule.window.as_millis() as u64);
+        if let Some(tenant) = tenant {
+            metadata.insert(request::TENANT_ID_KEY, tenant);
+        }
+        Self {
+            rule: rule.name.clone(),
+            occurrences: rule.threshold,
+            window: rule.window,
+            severity: rule.severity,
+            tenant: tenant.map(String::from),
+            metadata,
+        }
+    }
+}
+
+impl fmt::Display for SystemDegraded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.rule)?;
+        if let Some(tenant) = &self.tenant {
+            write!(f, " (tenant {tenant})")?;
+        }
+        write!(
+            f,
+            ": {} matching errors within {:?}",
+            self.occurrences, self.window
+        )
+    }
+}
+
+impl std::error::Error for SystemDegraded {}
+
+impl ForgeError for SystemDegraded {
+    fn kind(&self) -> &'static str {
+        SYSTEM_DEGRADED_KIND
+    }
+
+    fn caption(&self) -> &'static str {
+        "System degraded"
+    }
+
+    fn status_code(&self) -> u16 {
+        503
+    }
+
+    fn severity(&self) -> ErrorLevel {
+        self.severity
+    }
+
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppError;
+
+    #[test]
+    fn test_escalation_window() {
+        let escalator = Escalator::new().with_rule(
+            EscalationRule::new(
+                "net",
+                ErrorMatcher::any().code("NET-001"),
+                3,
+                Duration::from_secs(60),
+            )
+            .with_severity(ErrorLevel::Error),
+        );
+        let err = AppError::network("api", None).with_code("NET-001");
+        let other = AppError::network("api", None).with_code("NET-002");
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        // Spread too thin to trigger.
+        for secs in [0, 40, 80, 120] {
+            assert!(escalator.observe_at(&err, at(secs)).is_empty());
+        }
+        assert!(escalator.observe_at(&other, at(121)).is_empty());
+
+        let raised = escalator.observe_at(&err, at(130));
+        assert_eq!(raised.len(), 1);
+        assert_eq!(raised[0].severity(), ErrorLevel::Error);
+        assert_eq!(raised[0].metadata().len(), 3);
+        // Still degraded: no repeat.
+        assert!(escalator.observe_at(&err, at(131)).is_empty());
+
+        // The rate falls below the threshold, then climbs again.
+        assert!(escalator.observe_at(&err, at(300)).is_empty());
+        assert!(escalator.observe_at(&err, at(301)).is_empty());
+        assert_eq!(escalator.observe_at(&err, at(302)).len(), 1);
+    }
+
+    #[test]
+    fn test_escalation_per_tenant() {
+        use crate::request::RequestContext;
+
+        let escalator = Escalator::new().with_rule(
+            EscalationRule::new(
+                "db",
+                ErrorMatcher::kind("Network"),
+                2,
+                Duration::from_secs(60),
+            )
+            .per_tenant(),
+        );
+        let from = |tenant: &str| {
+            RequestContext::new()
+                .with_tenant_id(tenant)
+                .scope(|| AppError::network("db", None))
+        };
+        let start = Instant::now();
+
+        assert!(escalator.observe_at(&from("acme"), start).is_empty());
+        assert!(escalator.observe_at(&from("globex"), start).is_empty());
+        let raised = escalator.observe_at(&from("acme"), start);
+        assert_eq!(raised.len(), 1);
+        assert_eq!(raised[0].tenant.as_deref(), Some("acme"));
+        assert_eq!(crate::request::tenant_id(&raised[0]), Some("acme"));
+        assert!(raised[0].to_string().starts_with("db (tenant acme): 2"));
+
+        // Quiet tenants' windows are dropped once they expire.
+        let later = start + Duration::from_secs(120);
+        assert!(escalator.observe_at(&from("initech"), later).is_empty());
+        let tenants: Vec<_> = escalator.rules.lock()[0].windows.keys().cloned().collect();
+        assert_eq!(tenants, [Some("initech".to_string())]);
+    }
+}
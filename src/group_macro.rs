@@ -7,8 +7,12 @@ use std::error::Error as StdError;
 /// Macro for composing multi-error enums with automatic
 /// `From<OtherError>` conversions and full [`ForgeError`] delegation.
 ///
-/// Every variant must wrap exactly one source type that itself
-/// implements [`ForgeError`]. The macro generates:
+/// A variant either wraps exactly one source type that itself
+/// implements [`ForgeError`], or is a unit or named-field variant of
+/// its own described with `define_errors!`-style tags:
+/// `#[error(display = "...")]` (optional; the variant name otherwise)
+/// and `#[kind(Kind, status = 504, retryable = true, ...)]`, in that
+/// order and after any doc comment. The macro generates:
 ///
 /// - the enum declaration,
 /// - `Display` and `Error` implementations that forward to the
@@ -16,7 +20,15 @@ use std::error::Error as StdError;
 /// - `From<T>` for each wrapped type,
 /// - a [`ForgeError`] impl whose methods delegate directly to the
 ///   wrapped source's `ForgeError` methods (no type-erased
-///   downcast, no fallback values).
+///   downcast, no fallback values),
+/// - with this crate's `serde` feature, a `Serialize` impl writing a
+///   single-entry map from the variant name to the wrapped error, or
+///   to its `Display` string when the wrapped type is not `Serialize`
+///   (variants of the group's own serialize their message),
+/// - an `as_<variant>()` accessor for each wrapped variant, named in
+///   snake case (`Db(DbError)` gets `as_db()`), and `into_inner::<T>()`,
+///   which hands back the wrapped error if it is a `T` and returns the
+///   group unchanged otherwise.
 ///
 /// # Example
 ///
@@ -36,7 +48,148 @@ use std::error::Error as StdError;
 ///
 /// // `From<AppError>` is generated, so `?` works against any
 /// // function that returns an `AppError`.
-/// let _err: ServiceError = AppError::config("missing").into();
+/// let err: ServiceError = AppError::config("missing").into();
+/// assert!(err.as_app().is_some());
+/// let _app: AppError = err.into_inner::<AppError>().unwrap();
+/// ```
+///
+/// A wrapped type that is itself a group can list the types it
+/// converts from after `=>`, as in `Infra(InfraError => std::io::Error)`.
+/// The outer group then converts each of them in one step, through
+/// the inner group, so `?` works across layers:
+///
+/// ```
+/// use error_forge::{define_errors, group, ForgeError};
+///
+/// define_errors! {
+///     pub enum DiskError {
+///         #[kind(Disk, status = 507)]
+///         Full { path: String },
+///     }
+/// }
+///
+/// group! {
+///     #[derive(Debug)]
+///     pub enum InfraError {
+///         Disk(DiskError),
+///     }
+/// }
+///
+/// group! {
+///     #[derive(Debug)]
+///     pub enum ServiceError {
+///         Infra(InfraError => DiskError),
+///     }
+/// }
+///
+/// fn write() -> Result<(), DiskError> {
+///     Err(DiskError::full("/var/log".to_string()))
+/// }
+///
+/// fn handle() -> Result<(), ServiceError> {
+///     write()?;
+///     Ok(())
+/// }
+///
+/// let err = handle().unwrap_err();
+/// assert!(matches!(err, ServiceError::Infra(InfraError::Disk(_))));
+/// assert_eq!(err.status_code(), 507);
+/// ```
+///
+/// Variants of the group's own sit alongside the wrapped ones:
+///
+/// ```
+/// use error_forge::{group, AppError, ForgeError};
+///
+/// group! {
+///     #[derive(Debug)]
+///     pub enum JobError {
+///         App(AppError),
+///         #[error(display = "timed out after {seconds}s")]
+///         #[kind(Timeout, status = 504, retryable = true)]
+///         Timeout { seconds: u64 },
+///     }
+/// }
+///
+/// let err = JobError::Timeout { seconds: 30 };
+/// assert_eq!(err.to_string(), "timed out after 30s");
+/// assert_eq!(err.status_code(), 504);
+/// ```
+///
+/// A wrapped variant can override what the inner error reports at
+/// the group boundary with `#[display("...")]` (also used as the user
+/// message), `#[caption("...")]`, and `#[status(503)]`, in that order
+/// and after any doc comment. This is useful when re-exposing a
+/// library's errors at a service API edge. Everything else, including
+/// the `source()` chain, still comes from the inner error:
+///
+/// ```
+/// use error_forge::{group, AppError, ForgeError};
+///
+/// group! {
+///     #[derive(Debug)]
+///     pub enum ApiError {
+///         /// Storage failures, shown without their internals
+///         #[display("storage unavailable")]
+///         #[caption("💾 Storage")]
+///         #[status(503)]
+///         Storage(AppError),
+///     }
+/// }
+///
+/// let err = ApiError::from(AppError::filesystem("/data/db", None));
+/// assert_eq!(err.to_string(), "storage unavailable");
+/// assert_eq!((err.caption(), err.status_code()), ("💾 Storage", 503));
+/// assert_eq!(err.kind(), "Filesystem");
+/// ```
+///
+/// `#[group(prefix = "Svc")]`, after any doc comment and before the
+/// other attributes, namespaces every kind the group reports, as in
+/// `"Svc::Config"`. This keeps kinds from colliding when several groups
+/// feed the same registry, metrics, or logging pipeline. Prefixed kinds
+/// of wrapped errors are interned: each distinct one is allocated once.
+///
+/// ```
+/// use error_forge::{group, AppError, ForgeError};
+///
+/// group! {
+///     #[group(prefix = "Svc")]
+///     #[derive(Debug)]
+///     pub enum ServiceError {
+///         App(AppError),
+///     }
+/// }
+///
+/// let err = ServiceError::from(AppError::config("missing"));
+/// assert_eq!(err.kind(), "Svc::Config");
+/// ```
+///
+/// A variant marked `#[other]`, written without a payload, holds a
+/// `Box<dyn Error + Send + Sync>` for errors no other variant covers.
+/// It gets `From<Box<dyn Error + Send + Sync>>` and an `other`
+/// constructor taking anything that converts into that box, and
+/// reports kind `"Other"`, status 500, and class `Unknown`. At most
+/// one variant may be marked.
+///
+/// ```
+/// use error_forge::{group, AppError, ErrorClass, ForgeError};
+///
+/// group! {
+///     #[derive(Debug)]
+///     pub enum ServiceError {
+///         App(AppError),
+///         #[other]
+///         Unexpected,
+///     }
+/// }
+///
+/// fn parse(input: &str) -> Result<u16, ServiceError> {
+///     input.parse::<u16>().map_err(ServiceError::other)
+/// }
+///
+/// let err = parse("port").unwrap_err();
+/// assert_eq!(err.to_string(), "invalid digit found in string");
+/// assert_eq!((err.kind(), err.class()), ("Other", ErrorClass::Unknown));
 /// ```
 ///
 /// # `ForgeError` requirement
@@ -57,28 +210,178 @@ use std::error::Error as StdError;
 #[macro_export]
 macro_rules! group {
     (
+        $(#[doc = $doc:literal])*
+        #[group(prefix = $prefix:literal)]
         $(#[$meta:meta])*
         $vis:vis enum $name:ident {
-            $(
-                $(#[$vmeta:meta])*
-                $variant:ident($source_type:ty)
-            ),* $(,)?
+            $($body:tt)*
+        }
+    ) => {
+        $crate::group!(@parse [$(#[doc = $doc])* $(#[$meta])*] [$vis] $name [$prefix] [] [] [] [] $($body)*);
+    };
+
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($body:tt)*
         }
     ) => {
-        $(#[$meta])*
+        $crate::group!(@parse [$(#[$meta])*] [$vis] $name [] [] [] [] [] $($body)*);
+    };
+
+    // Each variant is moved onto two lists: its declaration, and the
+    // wrapped variants, the variants with their own tags, or the
+    // catch-all variant.
+    (@parse $meta:tt $vis:tt $name:ident $prefix:tt $decls:tt $wraps:tt $extras:tt $other:tt) => {
+        $crate::group!(@build $meta $vis $name $prefix $decls $wraps $extras $other);
+    };
+
+    (@parse $meta:tt $vis:tt $name:ident $prefix:tt [$($decl:tt)*] $wraps:tt $extras:tt []
+        $(#[doc = $doc:literal])*
+        #[other]
+        $(#[$vmeta:meta])*
+        $variant:ident
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::group!(@parse $meta $vis $name $prefix
+            [$($decl)* $(#[doc = $doc])* $(#[$vmeta])*
+                $variant(::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Send + ::std::marker::Sync>),]
+            $wraps
+            $extras
+            [($variant)]
+            $($($rest)*)?
+        );
+    };
+
+    (@parse $meta:tt $vis:tt $name:ident $prefix:tt [$($decl:tt)*] $wraps:tt [$($extra:tt)*] $other:tt
+        $(#[doc = $doc:literal])*
+        $(#[error(display = $display:literal)])?
+        #[kind($kind:ident $(, $($tag:tt)*)?)]
+        $(#[$vmeta:meta])*
+        $variant:ident { $($field:ident : $field_type:ty),* $(,)? }
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::group!(@parse $meta $vis $name $prefix
+            [$($decl)* $(#[doc = $doc])* $(#[$vmeta])* $variant { $($field: $field_type),* },]
+            $wraps
+            [$($extra)* ($variant [{ $($field),* }] [$($display)?] [kind = $kind $(, $($tag)*)?])]
+            $other
+            $($($rest)*)?
+        );
+    };
+
+    (@parse $meta:tt $vis:tt $name:ident $prefix:tt [$($decl:tt)*] $wraps:tt [$($extra:tt)*] $other:tt
+        $(#[doc = $doc:literal])*
+        $(#[error(display = $display:literal)])?
+        #[kind($kind:ident $(, $($tag:tt)*)?)]
+        $(#[$vmeta:meta])*
+        $variant:ident
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::group!(@parse $meta $vis $name $prefix
+            [$($decl)* $(#[doc = $doc])* $(#[$vmeta])* $variant,]
+            $wraps
+            [$($extra)* ($variant [] [$($display)?] [kind = $kind $(, $($tag)*)?])]
+            $other
+            $($($rest)*)?
+        );
+    };
+
+    // A wrapped variant with overrides takes only doc comments, since
+    // a general attribute list would be ambiguous with the overrides.
+    (@parse $meta:tt $vis:tt $name:ident $prefix:tt [$($decl:tt)*] [$($wrap:tt)*] $extras:tt $other:tt
+        $(#[doc = $doc:literal])*
+        $(#[display($display:literal)])?
+        $(#[caption($caption:literal)])?
+        $(#[status($status:literal)])?
+        $variant:ident($source_type:ty $(=> $($via:ty),+ $(,)?)?)
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::group!(@parse $meta $vis $name $prefix
+            [$($decl)* $(#[doc = $doc])* $variant($source_type),]
+            [$($wrap)* ($variant $source_type [$($($via),+)?] [$($display)?] [$($caption)?] [$($status)?])]
+            $extras
+            $other
+            $($($rest)*)?
+        );
+    };
+
+    (@parse $meta:tt $vis:tt $name:ident $prefix:tt [$($decl:tt)*] [$($wrap:tt)*] $extras:tt $other:tt
+        $(#[$vmeta:meta])*
+        $variant:ident($source_type:ty $(=> $($via:ty),+ $(,)?)?)
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::group!(@parse $meta $vis $name $prefix
+            [$($decl)* $(#[$vmeta])* $variant($source_type),]
+            [$($wrap)* ($variant $source_type [$($($via),+)?] [] [] [])]
+            $extras
+            $other
+            $($($rest)*)?
+        );
+    };
+
+    // A variant's own message: its `display` string, else its name
+    (@display $f:ident, $variant:ident) => {
+        $f.write_str(stringify!($variant))
+    };
+
+    (@display $f:ident, $variant:ident, $display:literal) => {
+        write!($f, $display)
+    };
+
+    // A kind, namespaced by the group's prefix if it has one
+    (@kind [] $kind:expr) => {
+        $kind
+    };
+
+    (@kind [$prefix:literal] $kind:expr) => {
+        $crate::macros::prefixed_kind($prefix, $kind)
+    };
+
+    (@static_kind [] $kind:expr) => {
+        $kind
+    };
+
+    (@static_kind [$prefix:literal] $kind:expr) => {
+        concat!($prefix, "::", $kind)
+    };
+
+    // A wrapped variant's override if it has one, else the default
+    (@or [] $default:expr) => {
+        $default
+    };
+
+    (@or [$value:expr] $default:expr) => {
+        $value
+    };
+
+    (@build [$($meta:tt)*] [$vis:vis] $name:ident $prefix:tt [$($decl:tt)*]
+        [$(($wv:ident $wt:ty [$($wvia:ty),*] [$($wdisplay:literal)?] [$($wcaption:literal)?] [$($wstatus:literal)?]))*]
+        [$(($ev:ident [$($epat:tt)*] [$($edisplay:literal)?] [kind = $ek:ident $(, $($etag:tt)*)?]))*]
+        [$(($ov:ident))?]
+    ) => {
+        $($meta)*
         $vis enum $name {
-            $(
-                $(#[$vmeta])*
-                $variant($source_type),
-            )*
+            $($decl)*
         }
 
         impl ::std::fmt::Display for $name {
             fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
                 match self {
                     $(
-                        Self::$variant(source) => ::std::fmt::Display::fmt(source, f),
+                        #[allow(unused_variables)]
+                        Self::$wv(source) => $crate::group!(@or
+                            [$(f.write_str($wdisplay))?]
+                            ::std::fmt::Display::fmt(source, f)
+                        ),
                     )*
+                    $(
+                        #[allow(unused_variables)]
+                        Self::$ev $($epat)* => $crate::group!(@display f, $ev $(, $edisplay)?),
+                    )*
+                    $(
+                        Self::$ov(source) => ::std::fmt::Display::fmt(source, f),
+                    )?
                 }
             }
         }
@@ -87,27 +390,121 @@ macro_rules! group {
             fn source(&self) -> ::std::option::Option<&(dyn ::std::error::Error + 'static)> {
                 match self {
                     $(
-                        Self::$variant(source) => {
+                        Self::$wv(source) => {
                             ::std::option::Option::Some(source as &(dyn ::std::error::Error + 'static))
                         }
                     )*
+                    $(
+                        Self::$ev { .. } => ::std::option::Option::None,
+                    )*
+                    $(
+                        Self::$ov(source) => {
+                            ::std::option::Option::Some(&**source as &(dyn ::std::error::Error + 'static))
+                        }
+                    )?
+                }
+            }
+        }
+
+        impl $name {
+            $(
+                $crate::__private::pastey::paste! {
+                    #[doc = concat!(
+                        "The wrapped error if this is [`Self::", stringify!($wv), "`]."
+                    )]
+                    #[allow(dead_code)]
+                    $vis fn [<as_ $wv:snake>](&self) -> ::std::option::Option<&$wt> {
+                        match self {
+                            Self::$wv(source) => ::std::option::Option::Some(source),
+                            #[allow(unreachable_patterns)]
+                            _ => ::std::option::Option::None,
+                        }
+                    }
+                }
+            )*
+
+            /// Take back the wrapped error if it is a `T`, or return
+            /// `self` unchanged.
+            #[allow(dead_code, clippy::result_large_err)]
+            $vis fn into_inner<T: 'static>(self) -> ::std::result::Result<T, Self> {
+                match self {
+                    $(
+                        Self::$wv(source) => {
+                            let mut slot = ::std::option::Option::Some(source);
+                            let any = &mut slot as &mut dyn ::std::any::Any;
+                            if let ::std::option::Option::Some(inner) =
+                                any.downcast_mut::<::std::option::Option<T>>().and_then(::std::option::Option::take)
+                            {
+                                return ::std::result::Result::Ok(inner);
+                            }
+                            match slot {
+                                ::std::option::Option::Some(source) => ::std::result::Result::Err(Self::$wv(source)),
+                                ::std::option::Option::None => ::std::unreachable!(),
+                            }
+                        }
+                    )*
+                    #[allow(unreachable_patterns)]
+                    unmatched => ::std::result::Result::Err(unmatched),
                 }
             }
         }
 
         $(
-            impl ::std::convert::From<$source_type> for $name {
-                fn from(source: $source_type) -> Self {
-                    Self::$variant(source)
+            impl $name {
+                /// Wrap any error in the catch-all variant.
+                #[allow(dead_code)]
+                $vis fn other<E>(error: E) -> Self
+                where
+                    E: ::std::convert::Into<
+                        ::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Send + ::std::marker::Sync>,
+                    >,
+                {
+                    Self::$ov(error.into())
+                }
+            }
+
+            impl ::std::convert::From<::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Send + ::std::marker::Sync>>
+                for $name
+            {
+                fn from(
+                    source: ::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Send + ::std::marker::Sync>,
+                ) -> Self {
+                    Self::$ov(source)
                 }
             }
+        )?
+
+        $(
+            impl ::std::convert::From<$wt> for $name {
+                fn from(source: $wt) -> Self {
+                    Self::$wv(source)
+                }
+            }
+
+            $(
+                impl ::std::convert::From<$wvia> for $name {
+                    fn from(source: $wvia) -> Self {
+                        Self::$wv(<$wt as ::std::convert::From<$wvia>>::from(source))
+                    }
+                }
+            )*
         )*
 
         impl $crate::error::ForgeError for $name {
             fn kind(&self) -> &'static str {
                 match self {
                     $(
-                        Self::$variant(source) => $crate::error::ForgeError::kind(source),
+                        Self::$wv(source) => {
+                            $crate::group!(@kind $prefix $crate::error::ForgeError::kind(source))
+                        }
+                    )*
+                    $(
+                        Self::$ov(..) => $crate::group!(@static_kind $prefix "Other"),
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            $crate::group!(@static_kind $prefix stringify!($ek))
+                        }
                     )*
                 }
             }
@@ -115,7 +512,19 @@ macro_rules! group {
             fn caption(&self) -> &'static str {
                 match self {
                     $(
-                        Self::$variant(source) => $crate::error::ForgeError::caption(source),
+                        #[allow(unused_variables)]
+                        Self::$wv(source) => $crate::group!(@or
+                            [$($wcaption)?]
+                            $crate::error::ForgeError::caption(source)
+                        ),
+                    )*
+                    $(
+                        Self::$ov(..) => "🚨 Error",
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            $crate::define_errors!(@get_caption $ek $(, $($etag)*)?)
+                        }
                     )*
                 }
             }
@@ -123,7 +532,15 @@ macro_rules! group {
             fn is_retryable(&self) -> bool {
                 match self {
                     $(
-                        Self::$variant(source) => $crate::error::ForgeError::is_retryable(source),
+                        Self::$wv(source) => $crate::error::ForgeError::is_retryable(source),
+                    )*
+                    $(
+                        Self::$ov(..) => false,
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            $crate::define_errors!(@get_tag retryable, false, kind = $ek $(, $($etag)*)?)
+                        }
                     )*
                 }
             }
@@ -131,7 +548,15 @@ macro_rules! group {
             fn is_fatal(&self) -> bool {
                 match self {
                     $(
-                        Self::$variant(source) => $crate::error::ForgeError::is_fatal(source),
+                        Self::$wv(source) => $crate::error::ForgeError::is_fatal(source),
+                    )*
+                    $(
+                        Self::$ov(..) => false,
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            $crate::define_errors!(@get_tag fatal, false, kind = $ek $(, $($etag)*)?)
+                        }
                     )*
                 }
             }
@@ -139,7 +564,19 @@ macro_rules! group {
             fn status_code(&self) -> u16 {
                 match self {
                     $(
-                        Self::$variant(source) => $crate::error::ForgeError::status_code(source),
+                        #[allow(unused_variables)]
+                        Self::$wv(source) => $crate::group!(@or
+                            [$($wstatus)?]
+                            $crate::error::ForgeError::status_code(source)
+                        ),
+                    )*
+                    $(
+                        Self::$ov(..) => 500,
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            $crate::define_errors!(@get_tag status, 500, kind = $ek $(, $($etag)*)?)
+                        }
                     )*
                 }
             }
@@ -147,7 +584,54 @@ macro_rules! group {
             fn exit_code(&self) -> i32 {
                 match self {
                     $(
-                        Self::$variant(source) => $crate::error::ForgeError::exit_code(source),
+                        Self::$wv(source) => $crate::error::ForgeError::exit_code(source),
+                    )*
+                    $(
+                        Self::$ov(..) => 1,
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            $crate::define_errors!(@get_tag exit, 1, kind = $ek $(, $($etag)*)?)
+                        }
+                    )*
+                }
+            }
+
+            fn severity(&self) -> $crate::macros::ErrorLevel {
+                match self {
+                    $(
+                        Self::$wv(source) => $crate::error::ForgeError::severity(source),
+                    )*
+                    $(
+                        Self::$ov(..) => $crate::macros::ErrorLevel::Error,
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            $crate::define_errors!(@get_severity
+                                $crate::error::ForgeError::is_fatal(self),
+                                $crate::error::ForgeError::is_retryable(self)
+                                $(, $($etag)*)?
+                            )
+                        }
+                    )*
+                }
+            }
+
+            fn class(&self) -> $crate::error::ErrorClass {
+                match self {
+                    $(
+                        Self::$wv(source) => $crate::error::ForgeError::class(source),
+                    )*
+                    $(
+                        Self::$ov(..) => $crate::error::ErrorClass::Unknown,
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            $crate::define_errors!(@get_class
+                                $crate::error::ForgeError::is_retryable(self),
+                                kind = $ek $(, $($etag)*)?
+                            )
+                        }
                     )*
                 }
             }
@@ -155,7 +639,19 @@ macro_rules! group {
             fn user_message(&self) -> ::std::string::String {
                 match self {
                     $(
-                        Self::$variant(source) => $crate::error::ForgeError::user_message(source),
+                        #[allow(unused_variables)]
+                        Self::$wv(source) => $crate::group!(@or
+                            [$(::std::string::String::from($wdisplay))?]
+                            $crate::error::ForgeError::user_message(source)
+                        ),
+                    )*
+                    $(
+                        Self::$ov(..) => ::std::string::ToString::to_string(self),
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            ::std::string::ToString::to_string(self)
+                        }
                     )*
                 }
             }
@@ -163,10 +659,426 @@ macro_rules! group {
             fn dev_message(&self) -> ::std::string::String {
                 match self {
                     $(
-                        Self::$variant(source) => $crate::error::ForgeError::dev_message(source),
+                        Self::$wv(source) => $crate::error::ForgeError::dev_message(source),
+                    )*
+                    $(
+                        Self::$ov(..) => $crate::error::default_dev_message(self),
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            $crate::error::default_dev_message(self)
+                        }
                     )*
                 }
             }
+
+            fn code(&self) -> ::std::option::Option<&str> {
+                match self {
+                    $(
+                        Self::$wv(source) => $crate::error::ForgeError::code(source),
+                    )*
+                    $(
+                        Self::$ov(..) => ::std::option::Option::None,
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            $crate::define_errors!(@get_tag code, None::<&str>, kind = $ek $(, $($etag)*)?)
+                        }
+                    )*
+                }
+            }
+
+            fn docs_url(&self) -> ::std::option::Option<::std::string::String> {
+                match self {
+                    $(
+                        Self::$wv(source) => $crate::error::ForgeError::docs_url(source),
+                    )*
+                    $(
+                        Self::$ov(..) => ::std::option::Option::None,
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            $crate::define_errors!(@get_tag docs, None::<&str>, kind = $ek $(, $($etag)*)?)
+                                .map(::std::string::String::from)
+                        }
+                    )*
+                }
+            }
+
+            fn retry_after(&self) -> ::std::option::Option<::std::time::Duration> {
+                match self {
+                    $(
+                        Self::$wv(source) => $crate::error::ForgeError::retry_after(source),
+                    )*
+                    $(
+                        Self::$ov(..) => ::std::option::Option::None,
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            ::std::option::Option::None
+                        }
+                    )*
+                }
+            }
+
+            fn backtrace(&self) -> ::std::option::Option<&::std::backtrace::Backtrace> {
+                match self {
+                    $(
+                        Self::$wv(source) => $crate::error::ForgeError::backtrace(source),
+                    )*
+                    $(
+                        Self::$ov(..) => ::std::option::Option::None,
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            ::std::option::Option::None
+                        }
+                    )*
+                }
+            }
+
+            fn error_id(&self) -> ::std::option::Option<$crate::instance::ErrorId> {
+                match self {
+                    $(
+                        Self::$wv(source) => $crate::error::ForgeError::error_id(source),
+                    )*
+                    $(
+                        Self::$ov(..) => ::std::option::Option::None,
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            ::std::option::Option::None
+                        }
+                    )*
+                }
+            }
+
+            fn timestamp(&self) -> ::std::option::Option<::std::time::SystemTime> {
+                match self {
+                    $(
+                        Self::$wv(source) => $crate::error::ForgeError::timestamp(source),
+                    )*
+                    $(
+                        Self::$ov(..) => ::std::option::Option::None,
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            ::std::option::Option::None
+                        }
+                    )*
+                }
+            }
+
+            fn location(&self) -> ::std::option::Option<&'static ::std::panic::Location<'static>> {
+                match self {
+                    $(
+                        Self::$wv(source) => $crate::error::ForgeError::location(source),
+                    )*
+                    $(
+                        Self::$ov(..) => ::std::option::Option::None,
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            ::std::option::Option::None
+                        }
+                    )*
+                }
+            }
+
+            fn metadata(&self) -> &$crate::metadata::Metadata {
+                match self {
+                    $(
+                        Self::$wv(source) => $crate::error::ForgeError::metadata(source),
+                    )*
+                    $(
+                        Self::$ov(..) => $crate::metadata::Metadata::empty(),
+                    )?
+                    $(
+                        Self::$ev { .. } => {
+                            $crate::metadata::Metadata::empty()
+                        }
+                    )*
+                }
+            }
+
+            fn context_layer(
+                &self,
+            ) -> ::std::option::Option<(&dyn ::std::fmt::Display, &dyn $crate::error::ForgeError)> {
+                match self {
+                    $(
+                        Self::$wv(source) => $crate::error::ForgeError::context_layer(source),
+                    )*
+                    $(
+                        Self::$ov(..) => ::std::option::Option::None,
+                    )?
+                    $(
+                        Self::$ev { .. } => ::std::option::Option::None,
+                    )*
+                }
+            }
+        }
+
+        $crate::__group_serialize!($name [$($wv)*] [$($ev)*] [$($ov)?]);
+    };
+}
+
+/// Generates `Serialize` for a `group!` enum when the `serde` feature
+/// of this crate is enabled.
+///
+/// Each error serializes as a single-entry map from its variant name to
+/// the wrapped error, or to its `Display` string when the wrapped type
+/// is not `Serialize`. Variants of the group's own, and the `#[other]`
+/// variant, serialize their message.
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __group_serialize {
+    ($name:ident [$($wv:ident)*] [$($ev:ident)*] [$($ov:ident)?]) => {
+        impl $crate::__private::serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+            where
+                S: $crate::__private::serde::Serializer,
+            {
+                use $crate::__private::serde::ser::SerializeMap as _;
+                #[allow(unused_imports)]
+                use $crate::__private::{SerializeProbeDisplay as _, SerializeProbeValue as _};
+
+                let mut map = serializer.serialize_map(::std::option::Option::Some(1))?;
+                match self {
+                    $(
+                        Self::$wv(source) => map.serialize_entry(
+                            stringify!($wv),
+                            &(&$crate::__private::SerializeProbe(source)).__forge_serializable(),
+                        )?,
+                    )*
+                    $(
+                        Self::$ev { .. } => {
+                            map.serialize_entry(stringify!($ev), &::std::string::ToString::to_string(self))?
+                        }
+                    )*
+                    $(
+                        Self::$ov(source) => {
+                            map.serialize_entry(stringify!($ov), &::std::string::ToString::to_string(source))?
+                        }
+                    )?
+                }
+                map.end()
+            }
         }
     };
 }
+
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __group_serialize {
+    ($($tokens:tt)*) => {};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::ContextError;
+    use crate::error::ForgeError;
+    use crate::metadata::MetadataError;
+    use crate::AppError;
+
+    #[test]
+    fn test_group_delegates_to_wrapped_errors() {
+        crate::define_errors! {
+            #[tracked]
+            pub enum StoreError {
+                #[kind(Store, status = 503, retryable = true, code = "TEST-STORE-001")]
+                Unavailable { shard: u16 },
+            }
+        }
+
+        crate::group! {
+            #[derive(Debug)]
+            pub enum ServiceError {
+                Store(StoreError),
+                App(ContextError<MetadataError<AppError>, &'static str>),
+            }
+        }
+
+        let err = ServiceError::from(StoreError::unavailable(7));
+        assert_eq!(err.kind(), "Store");
+        assert_eq!(err.status_code(), 503);
+        assert!(err.is_retryable());
+        assert_eq!(err.code(), Some("TEST-STORE-001"));
+        assert!(err.error_id().is_some());
+
+        let inner = AppError::config("bad port").with_meta("tenant", "acme");
+        let err = ServiceError::from(ContextError::new(inner, "loading settings"));
+        assert_eq!(err.kind(), "Config");
+        assert_eq!(err.metadata().to_string(), "tenant=acme");
+        let (context, _) = err.context_layer().expect("context delegated");
+        assert_eq!(context.to_string(), "loading settings");
+    }
+
+    #[test]
+    fn test_group_with_own_variants() {
+        use crate::error::ErrorClass;
+        use std::error::Error as _;
+
+        crate::group! {
+            #[derive(Debug)]
+            pub enum JobError {
+                App(AppError),
+                /// The job ran past its deadline
+                #[error(display = "timed out after {seconds}s")]
+                #[kind(Timeout, status = 504, retryable = true, code = "TEST-JOB-001")]
+                Timeout { seconds: u64 },
+                #[kind(Cancelled, class = Cancelled)]
+                Cancelled,
+            }
+        }
+
+        let err = JobError::Timeout { seconds: 30 };
+        assert_eq!(err.to_string(), "timed out after 30s");
+        assert_eq!((err.kind(), err.status_code()), ("Timeout", 504));
+        assert!(err.is_retryable() && err.source().is_none());
+        assert_eq!(err.code(), Some("TEST-JOB-001"));
+
+        assert_eq!(JobError::Cancelled.to_string(), "Cancelled");
+        assert_eq!(JobError::Cancelled.class(), ErrorClass::Cancelled);
+        assert_eq!(JobError::from(AppError::config("bad")).kind(), "Config");
+    }
+
+    #[test]
+    fn test_group_accessors() {
+        crate::group! {
+            #[derive(Debug)]
+            pub enum LoadError {
+                App(AppError),
+                AppContext(ContextError<AppError, &'static str>),
+                #[kind(Empty)]
+                Empty,
+            }
+        }
+
+        let err = LoadError::from(AppError::config("bad"));
+        assert_eq!(err.as_app().map(ForgeError::kind), Some("Config"));
+        assert!(err.as_app_context().is_none());
+
+        let err = err
+            .into_inner::<ContextError<AppError, &'static str>>()
+            .unwrap_err();
+        let app = err.into_inner::<AppError>().expect("wrapped AppError");
+        assert_eq!(app.kind(), "Config");
+
+        assert!(LoadError::Empty.as_app().is_none());
+        assert!(matches!(
+            LoadError::Empty.into_inner::<AppError>(),
+            Err(LoadError::Empty)
+        ));
+    }
+
+    #[test]
+    fn test_group_variant_overrides() {
+        use std::error::Error as _;
+
+        crate::group! {
+            #[derive(Debug)]
+            pub enum EdgeError {
+                #[display("upstream unavailable")]
+                #[status(502)]
+                Upstream(AppError),
+                #[caption("📦 Config")]
+                Settings(ContextError<AppError, &'static str>),
+            }
+        }
+
+        let err = EdgeError::Upstream(AppError::network("billing", None));
+        assert_eq!(err.to_string(), "upstream unavailable");
+        assert_eq!(err.user_message(), "upstream unavailable");
+        assert_eq!((err.status_code(), err.kind()), (502, "Network"));
+        assert_eq!(err.caption(), "🌐 Network");
+        assert!(err.source().is_some());
+
+        let err = EdgeError::from(ContextError::new(AppError::config("bad"), "loading"));
+        assert_eq!(err.caption(), "📦 Config");
+        assert_eq!(err.status_code(), 500);
+        assert!(err.to_string().contains("bad"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_group_serializes_variant_and_inner_error() {
+        crate::group! {
+            #[derive(Debug)]
+            pub enum ReportError {
+                App(AppError),
+                Context(ContextError<AppError, &'static str>),
+                #[error(display = "gave up after {attempts} attempts")]
+                #[kind(Exhausted)]
+                Exhausted { attempts: u32 },
+            }
+        }
+
+        let json = serde_json::to_value(ReportError::from(AppError::config("bad port"))).unwrap();
+        assert_eq!(json["App"]["Config"]["message"], "bad port");
+
+        let err = ReportError::from(ContextError::new(AppError::config("bad port"), "loading"));
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["Context"], err.to_string());
+
+        let json = serde_json::to_value(ReportError::Exhausted { attempts: 3 }).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "Exhausted": "gave up after 3 attempts" })
+        );
+    }
+
+    #[test]
+    fn test_group_kind_prefix() {
+        crate::group! {
+            /// Errors from the billing service
+            #[group(prefix = "Billing")]
+            #[derive(Debug)]
+            pub enum BillingError {
+                App(AppError),
+                #[kind(Declined, status = 402)]
+                Declined,
+                #[other]
+                Unexpected,
+            }
+        }
+
+        let err = BillingError::from(AppError::config("bad"));
+        assert_eq!(err.kind(), "Billing::Config");
+        assert!(std::ptr::eq(err.kind(), err.kind()));
+        assert_eq!(BillingError::Declined.kind(), "Billing::Declined");
+        assert_eq!(BillingError::other("boom").kind(), "Billing::Other");
+        assert_eq!(BillingError::Declined.status_code(), 402);
+    }
+
+    #[test]
+    fn test_group_other_variant() {
+        use std::error::Error as _;
+
+        crate::group! {
+            #[derive(Debug)]
+            pub enum SyncError {
+                App(AppError),
+                /// Anything the other variants do not cover
+                #[other]
+                Unexpected,
+            }
+        }
+
+        let io = std::io::Error::other("disk on fire");
+        let err = SyncError::other(io);
+        assert!(matches!(err, SyncError::Unexpected(_)));
+        assert_eq!(err.to_string(), "disk on fire");
+        assert_eq!((err.kind(), err.status_code()), ("Other", 500));
+        assert!(!err.is_retryable());
+        assert_eq!(
+            err.source().expect("boxed source").to_string(),
+            "disk on fire"
+        );
+
+        let boxed: Box<dyn std::error::Error + Send + Sync> = "no route".into();
+        assert_eq!(SyncError::from(boxed).user_message(), "no route");
+        assert_eq!(SyncError::from(AppError::config("bad")).kind(), "Config");
+    }
+}
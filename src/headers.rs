@@ -0,0 +1,188 @@
+//! HTTP response headers derived from an error.
+//!
+//! Every web integration needs the same few headers on an error
+//! response: `Retry-After` when the client may try again later, the
+//! error code and instance ID so support can find the failure, and
+//! `Cache-Control: no-store` so proxies never serve an error twice.
+//! [`ForgeError::response_headers`] produces them as a
+//! [`ResponseHeaders`] list that any framework adapter can copy onto
+//! its response.
+//!
+//! An application that needs more (a `Link` to the error's
+//! documentation, say) or less (no instance IDs on public endpoints)
+//! installs a hook with [`set_header_hook`], which sees every header
+//! set before it is returned.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::headers::{self, ResponseHeaders};
+//! use error_forge::{AppError, ForgeError};
+//!
+//! let err = AppError::network("billing", None).with_code("NET-001");
+//! let headers = err.response_headers();
+//! assert_eq!(headers.get(headers::X_ERROR_CODE), Some("NET-001"));
+//! assert_eq!(headers.get("cache-control"), Some("no-store"));
+//!
+//! headers::set_header_hook(|_err, headers: &mut ResponseHeaders| {
+//!     headers.remove(headers::X_ERROR_ID);
+//! });
+//! assert!(err.response_headers().get(headers::X_ERROR_ID).is_none());
+//! # headers::clear_header_hook();
+//! ```
+
+use crate::error::ForgeError;
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// How long the client should wait before retrying, in whole seconds.
+pub const RETRY_AFTER: &str = "Retry-After";
+/// The error's stable [`code`](ForgeError::code).
+pub const X_ERROR_CODE: &str = "X-Error-Code";
+/// The error's [`ErrorId`](crate::ErrorId).
+pub const X_ERROR_ID: &str = "X-Error-Id";
+/// Always `no-store` on error responses.
+pub const CACHE_CONTROL: &str = "Cache-Control";
+
+type HeaderHook = Arc<dyn Fn(&dyn ForgeError, &mut ResponseHeaders) + Send + Sync>;
+
+static HOOK: RwLock<Option<HeaderHook>> = RwLock::new(None);
+
+/// Install `hook` process-wide, replacing any previous one. It runs
+/// after the standard headers are set and may add, change, or remove
+/// any of them.
+pub fn set_header_hook(
+    hook: impl Fn(&dyn ForgeError, &mut ResponseHeaders) + Send + Sync + 'static,
+) {
+    *HOOK.write() = Some(Arc::new(hook));
+}
+
+/// Remove the installed hook.
+pub fn clear_header_hook() {
+    *HOOK.write() = None;
+}
+
+/// An ordered list of header names and values.
+///
+/// Names are matched case-insensitively, and each name appears at
+/// most once.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponseHeaders {
+    entries: Vec<(String, String)>,
+}
+
+impl ResponseHeaders {
+    /// An empty list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The standard headers for `err`, passed through the installed
+    /// hook. [`ForgeError::response_headers`] calls this; call it
+    /// directly for a `dyn ForgeError`.
+    pub fn for_error(err: &dyn ForgeError) -> Self {
+        let mut headers = Self::new();
+        if let Some(after) = err.retry_after() {
+            // Round up: retrying early would only fail again.
+            let secs = after.as_secs() + u64::from(after.subsec_nanos() > 0);
+            headers.insert(RETRY_AFTER, secs.to_string());
+        }
+        if let Some(code) = err.code() {
+            headers.insert(X_ERROR_CODE, code);
+        }
+        if let Some(id) = err.error_id() {
+            headers.insert(X_ERROR_ID, id.to_string());
+        }
+        headers.insert(CACHE_CONTROL, "no-store");
+
+        // Cloned so the hook may itself install or clear hooks.
+        let hook = HOOK.read().clone();
+        if let Some(hook) = hook {
+            hook(err, &mut headers);
+        }
+        headers
+    }
+
+    /// Set `name` to `value`, replacing any existing value.
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let (name, value) = (name.into(), value.into());
+        match self.position(&name) {
+            Some(index) => self.entries[index].1 = value,
+            None => self.entries.push((name, value)),
+        }
+    }
+
+    /// Remove `name`, returning its value if it was set.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        let index = self.position(name)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    /// The value of `name`, if set.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.position(name)
+            .map(|index| self.entries[index].1.as_str())
+    }
+
+    /// Each name and value, in the order they were first set.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// The number of headers.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no headers are set.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|(existing, _)| existing.eq_ignore_ascii_case(name))
+    }
+}
+
+impl IntoIterator for ResponseHeaders {
+    type Item = (String, String);
+    type IntoIter = std::vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recovery::{CircuitBreaker, CircuitBreakerConfig, CircuitOpenError};
+
+    #[test]
+    fn test_standard_headers() {
+        let config = CircuitBreakerConfig::default()
+            .with_failure_threshold(1)
+            .with_reset_timeout_ms(10_000);
+        let breaker = CircuitBreaker::with_config("payments", config);
+        let _ = breaker.execute(|| Err::<(), _>(crate::AppError::other("down")));
+        let err = breaker
+            .execute(|| Ok::<_, crate::AppError>(()))
+            .unwrap_err();
+        let open = err.downcast_ref::<CircuitOpenError>().unwrap();
+        let headers = open.response_headers();
+
+        // 9.99... seconds remain, rounded up.
+        assert_eq!(headers.get("retry-after"), Some("10"));
+        assert_eq!(headers.get(CACHE_CONTROL), Some("no-store"));
+        assert_eq!(headers.get(X_ERROR_CODE), None);
+
+        let mut headers = headers;
+        headers.insert("RETRY-AFTER", "5");
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers.remove(RETRY_AFTER).as_deref(), Some("5"));
+    }
+}
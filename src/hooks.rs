@@ -0,0 +1,724 @@
+//! Deferred delivery of error-hook and logger events, and handlers
+//! run before a fatal error ends the process.
+//!
+//! Speculative code often builds errors it then handles itself — a
+//! cache probe that falls back to the database, a parser trying
+//! several formats. [`deferred`] buffers the error hook and
+//! [`log_error`](crate::logging::log_error) events raised on the
+//! current thread while a section runs, then drops them if the
+//! section succeeds or delivers them in order if it fails.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::{hooks, AppError};
+//!
+//! // Nothing reaches the hook or logger: the fallback succeeded.
+//! let port: Result<u16, AppError> = hooks::deferred(|| {
+//!     std::env::var("PORT")
+//!         .map_err(|_| AppError::config("PORT not set"))
+//!         .and_then(|raw| raw.parse().map_err(|_| AppError::config("PORT not a number")))
+//!         .or_else(|_| Ok(8080))
+//! });
+//! assert_eq!(port.unwrap(), 8080);
+//! ```
+//!
+//! Sections nest: events flushed by a failing inner section join the
+//! enclosing section's buffer. Buffering is per thread, so errors
+//! raised on other threads are delivered immediately. Logger events
+//! are replayed from an owned copy of the error, which keeps its
+//! messages, flags, class, code, metadata, timestamp, and location but not its source
+//! chain.
+//!
+//! # Fatal handlers
+//!
+//! [`register_fatal_handler`] adds a callback run when a fatal error
+//! reaches [`output::report`](crate::output::report) — and so
+//! [`output::exit_with`](crate::output::exit_with) and the
+//! `#[error_forge::main]` wrapper — before the process exits. Use it
+//! to flush telemetry, write crash reports, or notify a supervisor.
+//! Handlers run one at a time in registration order on a helper
+//! thread, and the exit path waits for them for at most
+//! [`set_fatal_handler_timeout`] (five seconds by default); handlers
+//! still running after that are abandoned.
+//!
+//! ```
+//! use error_forge::hooks;
+//!
+//! hooks::register_fatal_handler(|snapshot| {
+//!     eprintln!("crash report: {} ({})", snapshot.message, snapshot.kind);
+//! });
+//! ```
+//!
+//! # Observers
+//!
+//! The error hook is set once per process. Plugins and subsystems that
+//! come and go should [`subscribe`] an [`ErrorObserver`] instead: every
+//! observer sees each error-hook event after the hook itself.
+//! [`subscribe_weak`] holds only a weak reference, so the subscription
+//! ends on its own when the observer is dropped — nobody has to
+//! remember to call [`unsubscribe`]. At most [`max_observers`] live
+//! subscriptions are allowed (64 by default); [`observer_stats`]
+//! reports what is registered when the cap is hit.
+//!
+//! ```
+//! use error_forge::hooks::{self, ErrorObserver};
+//! use error_forge::macros::ErrorContext;
+//! use std::sync::Arc;
+//!
+//! struct Plugin;
+//!
+//! impl ErrorObserver for Plugin {
+//!     fn on_error(&self, ctx: &ErrorContext<'_>) {
+//!         println!("plugin saw {}", ctx.kind);
+//!     }
+//! }
+//!
+//! let plugin = Arc::new(Plugin);
+//! hooks::subscribe_weak(&plugin).unwrap();
+//! drop(plugin); // the subscription goes with it
+//! ```
+
+use crate::error::{ErrorClass, ForgeError};
+use crate::instance::ErrorId;
+use crate::macros::{ErrorContext, ErrorLevel};
+use crate::metadata::Metadata;
+use crate::snapshot::ErrorSnapshot;
+use parking_lot::Mutex;
+use std::cell::RefCell;
+use std::fmt;
+use std::panic::Location;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, OnceLock, Weak};
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// A buffered event.
+enum Event {
+    Hook {
+        level: ErrorLevel,
+        caption: String,
+        kind: String,
+        is_fatal: bool,
+        is_retryable: bool,
+    },
+    Log(Replayed),
+    Message {
+        message: String,
+        level: ErrorLevel,
+    },
+}
+
+thread_local! {
+    /// One buffer per open [`deferred`] section, innermost last.
+    static SECTIONS: RefCell<Vec<Vec<Event>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Run `f`, holding back error-hook and logger events raised on this
+/// thread until it returns.
+///
+/// If `f` returns `Ok` the buffered events are dropped; if it returns
+/// `Err` (or panics) they are delivered in the order they were raised.
+pub fn deferred<T, E>(f: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+    let mut section = Section::open();
+    let result = f();
+    section.failed = result.is_err();
+    result
+}
+
+/// Returns true if the current thread is inside a [`deferred`]
+/// section.
+pub fn is_deferring() -> bool {
+    SECTIONS.with(|sections| !sections.borrow().is_empty())
+}
+
+/// Opens a section on creation and closes it on drop, flushing when
+/// the section failed or unwound.
+struct Section {
+    failed: bool,
+}
+
+impl Section {
+    fn open() -> Self {
+        SECTIONS.with(|sections| sections.borrow_mut().push(Vec::new()));
+        Self { failed: true }
+    }
+}
+
+impl Drop for Section {
+    fn drop(&mut self) {
+        let events = SECTIONS.with(|sections| sections.borrow_mut().pop().unwrap_or_default());
+        if self.failed || std::thread::panicking() {
+            for event in events {
+                event.deliver();
+            }
+        }
+    }
+}
+
+impl Event {
+    fn deliver(self) {
+        match self {
+            Self::Hook {
+                level,
+                caption,
+                kind,
+                is_fatal,
+                is_retryable,
+            } => crate::macros::dispatch_error_hook(level, &caption, &kind, is_fatal, is_retryable),
+            Self::Log(error) => crate::logging::log_error(&error),
+            Self::Message { message, level } => crate::logging::log_message(&message, level),
+        }
+    }
+}
+
+/// Buffer a hook event if a section is open. Returns false (and
+/// buffers nothing) otherwise.
+pub(crate) fn defer_hook(
+    level: ErrorLevel,
+    caption: &str,
+    kind: &str,
+    is_fatal: bool,
+    is_retryable: bool,
+) -> bool {
+    push(|| Event::Hook {
+        level,
+        caption: caption.to_string(),
+        kind: kind.to_string(),
+        is_fatal,
+        is_retryable,
+    })
+}
+
+/// Buffer a logger event if a section is open. Returns false (and
+/// buffers nothing) otherwise.
+pub(crate) fn defer_log(error: &dyn ForgeError, level: ErrorLevel) -> bool {
+    push(|| {
+        let mut error = Replayed::capture(error);
+        error.severity = level;
+        Event::Log(error)
+    })
+}
+
+/// Buffer a logger message if a section is open. Returns false (and
+/// buffers nothing) otherwise.
+pub(crate) fn defer_message(message: &str, level: ErrorLevel) -> bool {
+    push(|| Event::Message {
+        message: message.to_string(),
+        level,
+    })
+}
+
+fn push(event: impl FnOnce() -> Event) -> bool {
+    SECTIONS.with(|sections| match sections.borrow_mut().last_mut() {
+        Some(buffer) => {
+            buffer.push(event());
+            true
+        }
+        None => false,
+    })
+}
+
+/// Fatal handler callback type. Handlers receive an owned snapshot so
+/// they can run on the helper thread that enforces the timeout.
+type FatalHandler = Arc<dyn Fn(&ErrorSnapshot) + Send + Sync + 'static>;
+
+struct FatalHandlers {
+    handlers: Vec<FatalHandler>,
+    timeout: Duration,
+}
+
+fn fatal_handlers() -> &'static Mutex<FatalHandlers> {
+    static HANDLERS: OnceLock<Mutex<FatalHandlers>> = OnceLock::new();
+    HANDLERS.get_or_init(|| {
+        Mutex::new(FatalHandlers {
+            handlers: Vec::new(),
+            timeout: Duration::from_secs(5),
+        })
+    })
+}
+
+/// Register a handler to run before a fatal error exits the process.
+///
+/// Handlers run in registration order. See the
+/// [module documentation](self#fatal-handlers) for when they run.
+pub fn register_fatal_handler<F>(handler: F)
+where
+    F: Fn(&ErrorSnapshot) + Send + Sync + 'static,
+{
+    fatal_handlers().lock().handlers.push(Arc::new(handler));
+}
+
+/// Set how long the exit path waits for all fatal handlers together.
+pub fn set_fatal_handler_timeout(timeout: Duration) {
+    fatal_handlers().lock().timeout = timeout;
+}
+
+/// Run the fatal handlers for `err`, waiting at most the configured
+/// timeout. Returns false if the handlers did not finish in time.
+/// Nothing runs while [side effects](crate::side_effects) are disabled.
+pub(crate) fn run_fatal_handlers<E: ForgeError + ?Sized>(err: &E) -> bool {
+    if !crate::side_effects::enabled() {
+        return true;
+    }
+    let (handlers, timeout) = {
+        let registry = fatal_handlers().lock();
+        (registry.handlers.clone(), registry.timeout)
+    };
+    if handlers.is_empty() {
+        return true;
+    }
+
+    let snapshot = ErrorSnapshot::capture(err);
+    let (done, finished) = mpsc::channel();
+    let spawned = std::thread::Builder::new()
+        .name("error-forge-fatal".into())
+        .spawn(move || {
+            for handler in &handlers {
+                handler(&snapshot);
+            }
+            let _ = done.send(());
+        });
+    spawned.is_ok() && finished.recv_timeout(timeout).is_ok()
+}
+
+/// Receives every error-hook event while subscribed.
+///
+/// Implemented for closures taking `&ErrorContext`.
+pub trait ErrorObserver: Send + Sync + 'static {
+    /// Called after the error hook for each error raised.
+    fn on_error(&self, ctx: &ErrorContext<'_>);
+}
+
+impl<F> ErrorObserver for F
+where
+    F: Fn(&ErrorContext<'_>) + Send + Sync + 'static,
+{
+    fn on_error(&self, ctx: &ErrorContext<'_>) {
+        self(ctx)
+    }
+}
+
+/// Default for [`set_max_observers`].
+pub const DEFAULT_MAX_OBSERVERS: usize = 64;
+
+/// Identifies a subscription for [`unsubscribe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+/// Counts of registered observers, from [`observer_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct ObserverStats {
+    /// Subscriptions that keep their observer alive
+    pub strong: usize,
+    /// Weak subscriptions whose observer is still alive
+    pub weak: usize,
+    /// Weak subscriptions removed because their observer was dropped
+    pub reclaimed: u64,
+    /// The configured cap
+    pub limit: usize,
+}
+
+impl fmt::Display for ObserverStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} strong, {} weak of {} allowed ({} reclaimed)",
+            self.strong, self.weak, self.limit, self.reclaimed
+        )
+    }
+}
+
+/// Returned by [`subscribe`] and [`subscribe_weak`] when the observer
+/// cap is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ObserverLimitError {
+    /// What was registered when the subscription was refused
+    pub stats: ObserverStats,
+}
+
+impl fmt::Display for ObserverLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "error observer limit reached: {}; unsubscribe unused observers or prefer subscribe_weak",
+            self.stats
+        )
+    }
+}
+
+impl std::error::Error for ObserverLimitError {}
+
+enum Handle {
+    Strong(Arc<dyn ErrorObserver>),
+    Weak(Weak<dyn ErrorObserver>),
+}
+
+struct Observers {
+    entries: Vec<(SubscriptionId, Handle)>,
+    limit: usize,
+    reclaimed: u64,
+}
+
+impl Observers {
+    /// Drop weak entries whose observer is gone.
+    fn prune(&mut self) {
+        let before = self.entries.len();
+        self.entries.retain(|(_, handle)| match handle {
+            Handle::Strong(_) => true,
+            Handle::Weak(weak) => weak.strong_count() > 0,
+        });
+        self.reclaimed += (before - self.entries.len()) as u64;
+    }
+
+    fn stats(&self) -> ObserverStats {
+        let strong = self
+            .entries
+            .iter()
+            .filter(|(_, handle)| matches!(handle, Handle::Strong(_)))
+            .count();
+        ObserverStats {
+            strong,
+            weak: self.entries.len() - strong,
+            reclaimed: self.reclaimed,
+            limit: self.limit,
+        }
+    }
+}
+
+fn observers() -> &'static Mutex<Observers> {
+    static OBSERVERS: OnceLock<Mutex<Observers>> = OnceLock::new();
+    OBSERVERS.get_or_init(|| {
+        Mutex::new(Observers {
+            entries: Vec::new(),
+            limit: DEFAULT_MAX_OBSERVERS,
+            reclaimed: 0,
+        })
+    })
+}
+
+fn add_observer(handle: Handle) -> Result<SubscriptionId, ObserverLimitError> {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+    let mut registry = observers().lock();
+    registry.prune();
+    if registry.entries.len() >= registry.limit {
+        let err = ObserverLimitError {
+            stats: registry.stats(),
+        };
+        drop(registry);
+        if let Some(logger) = crate::logging::logger() {
+            logger.log_message(&err.to_string(), ErrorLevel::Warning);
+        }
+        return Err(err);
+    }
+    let id = SubscriptionId(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    registry.entries.push((id, handle));
+    Ok(id)
+}
+
+/// Subscribe `observer` until [`unsubscribe`] is called with the
+/// returned id.
+///
+/// Fails with [`ObserverLimitError`] (also sent to the registered
+/// logger as a warning) when [`max_observers`] subscriptions are live.
+pub fn subscribe(observer: impl ErrorObserver) -> Result<SubscriptionId, ObserverLimitError> {
+    add_observer(Handle::Strong(Arc::new(observer)))
+}
+
+/// Subscribe `observer` without keeping it alive.
+///
+/// The subscription is removed once every `Arc` to the observer is
+/// dropped. Fails like [`subscribe`] when the cap is reached.
+pub fn subscribe_weak<O: ErrorObserver>(
+    observer: &Arc<O>,
+) -> Result<SubscriptionId, ObserverLimitError> {
+    let observer: Arc<dyn ErrorObserver> = observer.clone();
+    add_observer(Handle::Weak(Arc::downgrade(&observer)))
+}
+
+/// Remove a subscription. Returns false if it was already gone.
+pub fn unsubscribe(id: SubscriptionId) -> bool {
+    let mut registry = observers().lock();
+    let before = registry.entries.len();
+    registry.entries.retain(|(entry, _)| *entry != id);
+    registry.entries.len() != before
+}
+
+/// Set how many subscriptions may be live at once. Existing
+/// subscriptions above a lowered cap are kept.
+pub fn set_max_observers(limit: usize) {
+    observers().lock().limit = limit;
+}
+
+/// The cap set with [`set_max_observers`].
+pub fn max_observers() -> usize {
+    observers().lock().limit
+}
+
+/// Counts of live subscriptions, after removing dropped weak ones.
+pub fn observer_stats() -> ObserverStats {
+    let mut registry = observers().lock();
+    registry.prune();
+    registry.stats()
+}
+
+/// Deliver an error-hook event to every live observer. Observers run
+/// outside the registry lock, so they may subscribe or unsubscribe.
+pub(crate) fn notify_observers(ctx: &ErrorContext<'_>) {
+    let live: Vec<Arc<dyn ErrorObserver>> = {
+        let mut registry = observers().lock();
+        if registry.entries.is_empty() {
+            return;
+        }
+        registry.prune();
+        registry
+            .entries
+            .iter()
+            .filter_map(|(_, handle)| match handle {
+                Handle::Strong(observer) => Some(Arc::clone(observer)),
+                Handle::Weak(weak) => weak.upgrade(),
+            })
+            .collect()
+    };
+    for observer in live {
+        observer.on_error(ctx);
+    }
+}
+
+/// Owned copy of an error, replayed to the logger on flush.
+#[derive(Debug)]
+struct Replayed {
+    kind: &'static str,
+    caption: &'static str,
+    message: String,
+    user_message: String,
+    dev_message: String,
+    code: Option<String>,
+    docs_url: Option<String>,
+    retry_after: Option<Duration>,
+    status_code: u16,
+    exit_code: i32,
+    is_retryable: bool,
+    is_fatal: bool,
+    severity: ErrorLevel,
+    class: ErrorClass,
+    metadata: Metadata,
+    error_id: Option<ErrorId>,
+    timestamp: Option<SystemTime>,
+    location: Option<&'static Location<'static>>,
+}
+
+impl Replayed {
+    fn capture(error: &dyn ForgeError) -> Self {
+        Self {
+            kind: error.kind(),
+            caption: error.caption(),
+            message: error.to_string(),
+            user_message: error.user_message(),
+            dev_message: error.dev_message(),
+            code: error.code().map(str::to_string),
+            docs_url: error.docs_url(),
+            retry_after: error.retry_after(),
+            status_code: error.status_code(),
+            exit_code: error.exit_code(),
+            is_retryable: error.is_retryable(),
+            is_fatal: error.is_fatal(),
+            severity: error.severity(),
+            class: error.class(),
+            metadata: error.metadata().clone(),
+            error_id: error.error_id(),
+            timestamp: error.timestamp(),
+            location: error.location(),
+        }
+    }
+}
+
+impl fmt::Display for Replayed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Replayed {}
+
+impl ForgeError for Replayed {
+    fn kind(&self) -> &'static str {
+        self.kind
+    }
+
+    fn caption(&self) -> &'static str {
+        self.caption
+    }
+
+    fn is_retryable(&self) -> bool {
+        self.is_retryable
+    }
+
+    fn is_fatal(&self) -> bool {
+        self.is_fatal
+    }
+
+    fn status_code(&self) -> u16 {
+        self.status_code
+    }
+
+    fn exit_code(&self) -> i32 {
+        self.exit_code
+    }
+
+    fn severity(&self) -> ErrorLevel {
+        self.severity
+    }
+
+    fn class(&self) -> ErrorClass {
+        self.class
+    }
+
+    fn user_message(&self) -> String {
+        self.user_message.clone()
+    }
+
+    fn dev_message(&self) -> String {
+        self.dev_message.clone()
+    }
+
+    fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    fn docs_url(&self) -> Option<String> {
+        self.docs_url.clone()
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        self.retry_after
+    }
+
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn error_id(&self) -> Option<ErrorId> {
+        self.error_id
+    }
+
+    fn timestamp(&self) -> Option<SystemTime> {
+        self.timestamp
+    }
+
+    fn location(&self) -> Option<&'static Location<'static>> {
+        self.location
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppError;
+
+    fn buffered() -> usize {
+        SECTIONS.with(|sections| sections.borrow().last().map_or(0, Vec::len))
+    }
+
+    #[test]
+    fn test_deferred_sections() {
+        assert!(!is_deferring());
+
+        let outer: Result<(), AppError> = deferred(|| {
+            assert!(is_deferring());
+            defer_hook(ErrorLevel::Error, "Config", "Config", false, false);
+
+            // A succeeding inner section drops its events...
+            let _ = deferred(|| {
+                defer_hook(ErrorLevel::Error, "Config", "Config", false, false);
+                Ok::<_, AppError>(())
+            });
+            assert_eq!(buffered(), 1);
+
+            // ...a failing one hands them to the enclosing section.
+            // (the constructor's hook event plus the log event).
+            let _ = deferred(|| {
+                let err = AppError::config("inner");
+                defer_log(&err, ErrorLevel::Error);
+                Err::<(), _>(err)
+            });
+            assert_eq!(buffered(), 3);
+            Ok(())
+        });
+
+        assert!(outer.is_ok());
+        assert!(!is_deferring());
+        assert!(!defer_hook(ErrorLevel::Error, "", "", false, false));
+    }
+
+    #[test]
+    fn test_fatal_handlers_run_in_order() {
+        static ORDER: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+        register_fatal_handler(|_| ORDER.lock().push("first"));
+        register_fatal_handler(|snapshot| {
+            assert!(snapshot.fatal);
+            ORDER.lock().push("second");
+        });
+
+        assert!(run_fatal_handlers(
+            &AppError::config("boom").with_fatal(true)
+        ));
+        let order = ORDER.lock();
+        assert_eq!(order[..2], ["first", "second"]);
+    }
+
+    #[test]
+    fn test_weak_observers() {
+        use std::sync::atomic::AtomicUsize;
+
+        struct Counter(AtomicUsize);
+
+        impl ErrorObserver for Counter {
+            fn on_error(&self, ctx: &ErrorContext<'_>) {
+                if ctx.kind == "ObserverTest" {
+                    self.0.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        }
+
+        let counter = Arc::new(Counter(AtomicUsize::new(0)));
+        let id = subscribe_weak(&counter).unwrap();
+        let raise = || crate::macros::call_error_hook("x", "ObserverTest", false, false);
+
+        raise();
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+
+        // Dropping the last `Arc` ends the subscription.
+        let weak = Arc::downgrade(&counter);
+        drop(counter);
+        raise();
+        assert!(weak.upgrade().is_none());
+        assert!(!unsubscribe(id));
+        assert!(observer_stats().reclaimed >= 1);
+    }
+
+    #[test]
+    fn test_observer_limit() {
+        let mut registry = Observers {
+            entries: Vec::new(),
+            limit: 1,
+            reclaimed: 0,
+        };
+        let observer: Arc<dyn ErrorObserver> = Arc::new(|_: &ErrorContext<'_>| {});
+        registry
+            .entries
+            .push((SubscriptionId(0), Handle::Weak(Arc::downgrade(&observer))));
+        assert_eq!(registry.stats().weak, 1);
+
+        drop(observer);
+        registry.prune();
+        let stats = registry.stats();
+        assert_eq!((stats.weak, stats.reclaimed), (0, 1));
+        assert!(ObserverLimitError { stats }
+            .to_string()
+            .contains("0 strong, 0 weak of 1 allowed"));
+    }
+}
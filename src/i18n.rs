@@ -0,0 +1,111 @@
+//! Localized user messages.
+//!
+//! A `define_errors!` variant tagged `msg_key = "errors.config.missing"`
+//! resolves its [`user_message`](ForgeError::user_message) through the
+//! installed [`MessageCatalog`], which can be backed by Fluent, gettext,
+//! or a plain map. When no catalog is installed, or the catalog has no
+//! entry for the key, the English display is used. `Display` and
+//! [`dev_message`](ForgeError::dev_message) are never translated, so
+//! logs stay searchable.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::{define_errors, i18n, ForgeError};
+//!
+//! define_errors! {
+//!     pub enum SetupError {
+//!         #[error(display = "missing setting {name}", name)]
+//!         #[kind(Config, msg_key = "errors.config.missing")]
+//!         Missing { name: String },
+//!     }
+//! }
+//!
+//! i18n::set_message_catalog(|key: &str, _err: &dyn ForgeError| match key {
+//!     "errors.config.missing" => Some("Paramètre manquant".to_string()),
+//!     _ => None,
+//! });
+//!
+//! let err = SetupError::missing("DATABASE_URL".to_string());
+//! assert_eq!(err.user_message(), "Paramètre manquant");
+//! assert_eq!(err.to_string(), "missing setting DATABASE_URL");
+//! # i18n::clear_message_catalog();
+//! ```
+
+use crate::error::ForgeError;
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// A source of translated user messages, looked up by message key.
+///
+/// Implemented for closures taking the key and the error.
+pub trait MessageCatalog: Send + Sync + 'static {
+    /// The message for `key`, or `None` to fall back to the error's
+    /// display. `error` gives access to its code and metadata, for
+    /// messages with placeholders.
+    fn message(&self, key: &str, error: &dyn ForgeError) -> Option<String>;
+}
+
+impl<F> MessageCatalog for F
+where
+    F: Fn(&str, &dyn ForgeError) -> Option<String> + Send + Sync + 'static,
+{
+    fn message(&self, key: &str, error: &dyn ForgeError) -> Option<String> {
+        self(key, error)
+    }
+}
+
+static CATALOG: RwLock<Option<Arc<dyn MessageCatalog>>> = RwLock::new(None);
+
+/// Install `catalog` process-wide, replacing any previous one.
+pub fn set_message_catalog(catalog: impl MessageCatalog) {
+    *CATALOG.write() = Some(Arc::new(catalog));
+}
+
+/// Remove the installed catalog, so user messages are the display again.
+pub fn clear_message_catalog() {
+    *CATALOG.write() = None;
+}
+
+/// The user message for `error` under `key`: the installed catalog's
+/// entry, else the error's display. Used by `define_errors!`.
+pub fn localize<E: ForgeError>(error: &E, key: Option<&str>) -> String {
+    let catalog = key.and_then(|_| CATALOG.read().clone());
+    match (key, catalog) {
+        (Some(key), Some(catalog)) => catalog
+            .message(key, error)
+            .unwrap_or_else(|| error.to_string()),
+        _ => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(dead_code)]
+    fn test_localize_falls_back() {
+        crate::define_errors! {
+            pub enum LocaleError {
+                #[kind(Quota, msg_key = "test.i18n.quota")]
+                Quota { limit: u32 },
+
+                #[kind(Other)]
+                Plain { detail: String },
+            }
+        }
+
+        let quota = LocaleError::quota(5);
+        let plain = LocaleError::plain("x".to_string());
+        assert_eq!(quota.user_message(), quota.to_string());
+
+        set_message_catalog(|key: &str, err: &dyn ForgeError| {
+            (key == "test.i18n.quota").then(|| format!("Límite alcanzado ({})", err.kind()))
+        });
+        assert_eq!(quota.user_message(), "Límite alcanzado (Quota)");
+        assert_eq!(plain.user_message(), plain.to_string());
+        assert!(!quota.dev_message().contains("Límite"));
+        clear_message_catalog();
+    }
+}
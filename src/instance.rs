@@ -0,0 +1,268 @@
+//! Per-instance error state.
+//!
+//! Errors built by `define_errors!` constructors carry a hidden
+//! [`Instance`] that records facts about that particular occurrence,
+//! starting with a unique [`ErrorId`] so support tickets can be
+//! correlated with log lines, the time and source location at which
+//! the error was created, and a backtrace when the `backtrace` feature is enabled and
+//! `RUST_BACKTRACE` (or `RUST_LIB_BACKTRACE`) asks for one. The
+//! [`RequestContext`] active at that point, if any, is kept too.
+//!
+//! [`RequestContext`]: crate::request::RequestContext
+
+use crate::metadata::Metadata;
+use crate::request::RequestContext;
+use std::backtrace::Backtrace;
+use std::fmt;
+use std::panic::Location;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A process-unique, roughly time-ordered error instance identifier.
+///
+/// IDs are snowflake-style 64-bit values: the high bits hold the
+/// millisecond timestamp at which the process produced its first ID
+/// mixed with the process id, and the low bits count upward, so IDs
+/// never repeat within a process and rarely collide across
+/// processes. They render as 16 lowercase hex digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ErrorId(u64);
+
+impl ErrorId {
+    /// Generate the next ID.
+    pub fn next() -> Self {
+        static SEED: OnceLock<u64> = OnceLock::new();
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let seed = *SEED.get_or_init(|| {
+            let millis = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_millis() as u64);
+            let pid = u64::from(std::process::id()) & 0x3FF;
+            (millis << 22) | (pid << 12)
+        });
+        Self(seed.wrapping_add(COUNTER.fetch_add(1, Ordering::Relaxed)))
+    }
+
+    /// Build an ID from its raw value (for example one read back from
+    /// a log line).
+    pub const fn from_u64(value: u64) -> Self {
+        Self(value)
+    }
+
+    /// The raw 64-bit value.
+    pub const fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for ErrorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl std::str::FromStr for ErrorId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(s, 16).map(Self)
+    }
+}
+
+/// Hidden per-instance state stored in the struct variants of a
+/// `#[tracked]` enum generated by `define_errors!`.
+///
+/// Not part of the public API: it is `pub` only so macro expansions in
+/// user crates can name it.
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct Instance {
+    id: ErrorId,
+    /// Nanoseconds since the Unix epoch; half the size of a
+    /// `SystemTime`, which keeps errors small enough to return by value.
+    timestamp: u64,
+    location: &'static Location<'static>,
+    backtrace: Option<Arc<Backtrace>>,
+    request: Option<Arc<RequestContext>>,
+    overrides: Overrides,
+}
+
+/// Per-instance overrides of a variant's `retryable`, `fatal`, and
+/// `status` tags, set by the generated `with_*` builders.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Overrides {
+    pub retryable: Option<bool>,
+    pub fatal: Option<bool>,
+    pub status: Option<u16>,
+}
+
+impl Instance {
+    #[doc(hidden)]
+    #[track_caller]
+    pub fn new() -> Self {
+        Self {
+            id: ErrorId::next(),
+            timestamp: crate::clock::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos() as u64),
+            location: Location::caller(),
+            backtrace: capture_backtrace(),
+            request: RequestContext::current(),
+            overrides: Overrides::default(),
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn id(&self) -> ErrorId {
+        self.id
+    }
+
+    #[doc(hidden)]
+    pub fn timestamp(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_nanos(self.timestamp)
+    }
+
+    #[doc(hidden)]
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    #[doc(hidden)]
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.backtrace.as_deref()
+    }
+
+    #[doc(hidden)]
+    pub fn overrides(&self) -> &Overrides {
+        &self.overrides
+    }
+
+    #[doc(hidden)]
+    pub fn overrides_mut(&mut self) -> &mut Overrides {
+        &mut self.overrides
+    }
+
+    /// The fields of the request active when the error was created.
+    #[doc(hidden)]
+    pub fn metadata(&self) -> &Metadata {
+        match &self.request {
+            Some(request) => request.metadata(),
+            None => Metadata::empty(),
+        }
+    }
+}
+
+impl Default for Instance {
+    #[track_caller]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Only the ID, so a derived `Debug` of the error shows its own fields
+/// rather than a backtrace and request context.
+impl fmt::Debug for Instance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Instance").field(&self.id).finish()
+    }
+}
+
+/// Capture a backtrace if the environment enables them. Disabled and
+/// unsupported captures are not kept.
+#[cfg(feature = "backtrace")]
+fn capture_backtrace() -> Option<Arc<Backtrace>> {
+    use std::backtrace::BacktraceStatus;
+    let backtrace = Backtrace::capture();
+    (backtrace.status() == BacktraceStatus::Captured).then(|| Arc::new(backtrace))
+}
+
+#[cfg(not(feature = "backtrace"))]
+fn capture_backtrace() -> Option<Arc<Backtrace>> {
+    None
+}
+
+/// Milliseconds since the Unix epoch, the form timestamps take in
+/// snapshots and serialized errors. Times before the epoch map to 0.
+pub fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_millis() as u64
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Instance {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("error_id", &self.id.to_string())?;
+        map.serialize_entry("timestamp", &unix_millis(self.timestamp()))?;
+        map.end()
+    }
+}
+
+/// Restores the id and timestamp written by `Serialize`; other keys are
+/// ignored. The location, backtrace, and request are not serialized, so
+/// a deserialized error has no backtrace or request fields and its
+/// location points here.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Instance {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct InstanceVisitor(Instance);
+
+        impl<'de> serde::de::Visitor<'de> for InstanceVisitor {
+            type Value = Instance;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an error_id and timestamp")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut access: A,
+            ) -> Result<Instance, A::Error> {
+                let mut instance = self.0;
+                while let Some(key) = access.next_key::<String>()? {
+                    match key.as_str() {
+                        "error_id" => {
+                            let id = access.next_value::<String>()?;
+                            instance.id = id.parse().map_err(serde::de::Error::custom)?;
+                        }
+                        "timestamp" => {
+                            let millis = access.next_value::<u64>()?;
+                            instance.timestamp = millis.saturating_mul(1_000_000);
+                        }
+                        _ => {
+                            access.next_value::<serde::de::IgnoredAny>()?;
+                        }
+                    }
+                }
+                Ok(instance)
+            }
+        }
+
+        let instance = Instance {
+            id: ErrorId::next(),
+            timestamp: 0,
+            location: Location::caller(),
+            backtrace: None,
+            request: None,
+            overrides: Overrides::default(),
+        };
+        deserializer.deserialize_map(InstanceVisitor(instance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backtrace_follows_environment() {
+        let expected = cfg!(feature = "backtrace")
+            && Backtrace::capture().status() == std::backtrace::BacktraceStatus::Captured;
+        assert_eq!(Instance::new().backtrace().is_some(), expected);
+    }
+}
@@ -12,6 +12,16 @@
 //! - [`group!`] for coarse-grained composition
 //! - optional derive support with `#[derive(ModError)]`
 //! - context wrapping, error codes, collectors, logging hooks, and console formatting
+//! - `Arc`-backed errors for handing one failure to several consumers ([`SharedError`])
+//! - deferred hook and logger delivery for speculative code, and weak
+//!   error observers that unsubscribe when dropped ([`hooks`])
+//! - structured key/value metadata on any error ([`metadata`]) and
+//!   declarative matching over it ([`ErrorMatcher`])
+//! - negotiated human / JSON error output ([`output`])
+//! - catalog tooling ([`catalog`]) and owned error snapshots ([`ErrorSnapshot`])
+//! - size limits for serialized and logged errors ([`limits`])
+//! - metadata consistency checks for error enums ([`lint`]) and
+//!   per-build bans on constructing particular kinds ([`policy`])
 //! - synchronous retry and circuit-breaker helpers in [`recovery`]
 //!
 //! ## Quick Start
@@ -21,11 +31,11 @@
 //!
 //! define_errors! {
 //!     pub enum ServiceError {
-//!         #[error(display = "Configuration error: {message}", message)]
+//!         #[error(display = "Configuration error: {message}")]
 //!         #[kind(Config, status = 500)]
 //!         Config { message: String },
 //!
-//!         #[error(display = "Request to {endpoint} failed", endpoint)]
+//!         #[error(display = "Request to {endpoint} failed")]
 //!         #[kind(Network, retryable = true, status = 503)]
 //!         Network { endpoint: String },
 //!     }
@@ -43,15 +53,43 @@
 //! let error = AppError::config("Database connection failed");
 //! print_error(&error);
 //! ```
+pub mod captured;
+pub mod catalog;
+pub mod clock;
 pub mod collector;
 pub mod console_theme;
 pub mod context;
+pub mod degrade;
 pub mod error;
+pub mod escalation;
 pub mod group_macro;
+pub mod headers;
+pub mod hooks;
+pub mod i18n;
+pub mod instance;
+pub mod limits;
+pub mod lint;
 pub mod logging;
 pub mod macros;
+pub mod matcher;
+pub mod metadata;
+pub mod normalizer;
+pub mod output;
+pub mod panic;
+pub mod policy;
 pub mod recovery;
 pub mod registry;
+pub mod request;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod severity;
+pub mod shared;
+pub mod side_effects;
+pub mod snapshot;
+pub mod spi;
+pub mod testing;
+pub mod upstream;
+pub mod warning;
 
 #[cfg(feature = "async")]
 pub mod async_error;
@@ -59,8 +97,10 @@ pub mod async_error;
 pub mod async_error_impl;
 
 // Re-export core types and traits
-pub use crate::console_theme::{install_panic_hook, print_error, ConsoleTheme};
-pub use crate::error::{AppError, AppResult, ForgeError};
+pub use crate::console_theme::{install_panic_hook, print_error, ConsoleTheme, KindStyle};
+pub use crate::error::{
+    AppError, AppResult, BoxedForgeError, CausedError, Chain, ErrorClass, ForgeError,
+};
 
 // Historical re-export. `Result` shadows `std::result::Result` in
 // glob imports; deprecated in favour of `AppResult`. Kept for
@@ -69,19 +109,37 @@ pub use crate::error::{AppError, AppResult, ForgeError};
 pub use crate::error::Result;
 
 // Re-export context module
-pub use crate::context::{ContextError, ResultExt};
+pub use crate::context::{ContextError, LazyContext, OptionExt, ResultExt};
 
 // Re-export registry module
 pub use crate::registry::{
     register_error_code, CodedError, ErrorCodeInfo, ErrorRegistry, WithErrorCode,
 };
 
+// Re-export matcher module
+pub use crate::matcher::ErrorMatcher;
+
+// Re-export per-instance error IDs
+pub use crate::instance::ErrorId;
+
+// Re-export metadata module
+pub use crate::metadata::{MetaValue, Metadata, MetadataError, WithMetadata};
+
+// Re-export shared errors
+pub use crate::shared::SharedError;
+
+// Re-export snapshot module
+pub use crate::snapshot::ErrorSnapshot;
+
 // Re-export collector module
-pub use crate::collector::{CollectError, ErrorCollector};
+pub use crate::collector::{CollectError, ErrorCollector, ExitCodePolicy};
 
 // Re-export logging module
 pub use crate::logging::{log_error, logger, register_logger, ErrorLogger};
 
+// Re-export the side-effect switch
+pub use crate::side_effects::{set_side_effects, SideEffects};
+
 // Re-export async module (when enabled)
 #[cfg(feature = "async")]
 pub use crate::async_error::{AsyncForgeError, AsyncResult};
@@ -93,12 +151,227 @@ pub use crate::async_error::{AsyncForgeError, AsyncResult};
 #[allow(deprecated)]
 pub use crate::macros::{
     register_error_hook, try_register_error_hook, ErrorContext, ErrorLevel, ErrorSource,
+    KindProfile, LogLevel,
 };
 
 // Optional re-export of the proc macro
 #[cfg(feature = "derive")]
 pub use error_forge_derive::*;
 
+/// `#[derive(ModError)]` rejects malformed attributes at compile time.
+///
+/// A value of the wrong type:
+///
+/// ```compile_fail
+/// #[derive(Debug, error_forge::ModError)]
+/// enum ApiError {
+///     #[error_http_status("404")]
+///     NotFound,
+/// }
+/// ```
+///
+/// A repeated attribute:
+///
+/// ```compile_fail
+/// #[derive(Debug, error_forge::ModError)]
+/// #[error_display("first")]
+/// #[error_display("second")]
+/// struct Twice;
+/// ```
+///
+/// A field attribute on a variant:
+///
+/// ```compile_fail
+/// #[derive(Debug, error_forge::ModError)]
+/// enum LoadError {
+///     #[error_from]
+///     Io(std::io::Error),
+/// }
+/// ```
+///
+/// A union:
+///
+/// ```compile_fail
+/// #[derive(error_forge::ModError)]
+/// #[error_display("bits")]
+/// union Bits {
+///     int: u32,
+///     float: f32,
+/// }
+/// ```
+#[cfg(all(doctest, feature = "derive"))]
+pub struct DeriveDiagnostics;
+
+/// `#[error_forge::main]` wraps a fallible `main`.
+///
+/// Success exits with code 0:
+///
+/// ```
+/// use error_forge::AppError;
+///
+/// #[error_forge::main]
+/// fn main() -> Result<(), AppError> {
+///     let port: u16 = "8080".parse().map_err(|_| AppError::config("bad port"))?;
+///     assert_eq!(port, 8080);
+///     Ok(())
+/// }
+/// ```
+///
+/// An error is printed and becomes the exit code:
+///
+/// ```no_run
+/// use error_forge::define_errors;
+///
+/// define_errors! {
+///     pub enum CliError {
+///         #[kind(Usage, exit = 64)]
+///         Usage { flag: String },
+///     }
+/// }
+///
+/// #[error_forge::main]
+/// fn main() -> Result<(), CliError> {
+///     Err(CliError::usage("--colour".to_string()))
+/// }
+/// ```
+///
+/// Async functions, attribute arguments, and parameters are rejected:
+///
+/// ```compile_fail
+/// #[error_forge::main]
+/// async fn main() -> Result<(), error_forge::AppError> {
+///     Ok(())
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[error_forge::main(quiet)]
+/// fn main() -> Result<(), error_forge::AppError> {
+///     Ok(())
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[error_forge::main]
+/// fn main(args: Vec<String>) -> Result<(), error_forge::AppError> {
+///     Ok(())
+/// }
+/// ```
+#[cfg(all(doctest, feature = "derive"))]
+pub struct MainAttribute;
+
+/// `define_errors!` generates `PartialEq`, so every field that is not
+/// `#[compare(skip)]` must be `PartialEq`:
+///
+/// ```compile_fail
+/// error_forge::define_errors! {
+///     pub enum LoadError {
+///         #[kind(Io)]
+///         Io { cause: std::io::Error },
+///     }
+/// }
+/// ```
+///
+/// ```
+/// use std::sync::atomic::AtomicU32;
+///
+/// error_forge::define_errors! {
+///     pub enum LimitError {
+///         #[kind(Throttled, status = 429)]
+///         Throttled { key: String, #[compare(skip)] hits: AtomicU32 },
+///     }
+/// }
+///
+/// let err = |key: &str, hits| LimitError::throttled(key.into(), AtomicU32::new(hits));
+/// assert_eq!(err("a", 1), err("a", 2));
+/// assert_ne!(err("a", 1), err("b", 1));
+/// ```
+///
+/// An enum tagged `#[compare(skip)]` gets no `PartialEq`, and can
+/// derive its own, but deriving it without the tag conflicts:
+///
+/// ```compile_fail
+/// error_forge::define_errors! {
+///     #[derive(PartialEq)]
+///     pub enum LookupError {
+///         #[kind(NotFound, status = 404)]
+///         Missing { key: String },
+///     }
+/// }
+/// ```
+#[cfg(doctest)]
+pub struct DefineErrorsCompare;
+
+/// `with_retryable`, `with_fatal`, and `with_status` store their
+/// override in the per-instance state of a `#[tracked]` enum:
+///
+/// ```
+/// error_forge::define_errors! {
+///     #[tracked]
+///     pub enum FetchError {
+///         #[kind(Fetch, status = 502)]
+///         Failed { url: String },
+///     }
+/// }
+///
+/// let err = FetchError::failed("/a".into()).with_status(504);
+/// assert_eq!(err.status_code(), 504);
+/// ```
+///
+/// Other enums have nowhere to keep it, so the call does not compile:
+///
+/// ```compile_fail
+/// error_forge::define_errors! {
+///     pub enum FetchError {
+///         #[kind(Fetch, status = 502)]
+///         Failed { url: String },
+///     }
+/// }
+///
+/// let err = FetchError::failed("/a".into()).with_status(504);
+/// ```
+#[cfg(doctest)]
+pub struct DefineErrorsOverrides;
+
+/// `define_errors!` display strings name fields directly, but not
+/// values nested inside them:
+///
+/// ```compile_fail
+/// error_forge::define_errors! {
+///     pub enum FetchError {
+///         #[error(display = "cannot reach {endpoint.0}")]
+///         #[kind(Fetch)]
+///         Failed { endpoint: (String, u16) },
+///     }
+/// }
+/// ```
+///
+/// Pass them as named or positional arguments instead:
+///
+/// ```
+/// error_forge::define_errors! {
+///     pub enum FetchError {
+///         #[error(display = "cannot reach {host}", host = endpoint.0)]
+///         #[kind(Fetch)]
+///         Failed { endpoint: (String, u16) },
+///
+///         #[error(display = "{0}:{1} refused", endpoint.0, endpoint.1)]
+///         #[kind(Fetch)]
+///         Refused { endpoint: (String, u16) },
+///     }
+/// }
+///
+/// let endpoint = ("db".to_string(), 5432);
+/// assert_eq!(FetchError::refused(endpoint).to_string(), "db:5432 refused");
+/// ```
+#[cfg(doctest)]
+pub struct DefineErrorsNestedDisplay;
+
+// Lets the crate's own tests use `#[derive(ModError)]`, whose expansion
+// refers to `::error_forge`.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as error_forge;
+
 /// Internal re-exports for use by macros expanded in user crates.
 ///
 /// This module is not part of the public API. Items here may
@@ -109,7 +382,15 @@ pub use error_forge_derive::*;
 /// `Cargo.toml`.
 #[doc(hidden)]
 pub mod __private {
+    pub use crate::macros::{
+        AsDynError, ForgeProbe, ForgeProbeError, ForgeProbeFallback, SourceProbe, SourceProbeError,
+        SourceProbeFallback,
+    };
+    #[cfg(feature = "serde")]
+    pub use crate::macros::{SerializeProbe, SerializeProbeDisplay, SerializeProbeValue};
     pub use pastey;
+    #[cfg(feature = "serde")]
+    pub use serde;
 }
 
 // Extension methods are implemented in error.rs
@@ -129,4 +410,1234 @@ mod tests {
         let err = crate::error::AppError::config("Test error");
         assert_eq!(err.kind(), "Config");
     }
+
+    #[test]
+    fn test_cause_analysis() {
+        let io = std::io::Error::new(std::io::ErrorKind::TimedOut, "timed out");
+        let err = crate::error::AppError::filesystem("data.db", io).context("loading state");
+
+        assert!(err.is_caused_by::<std::io::Error>());
+        assert!(!err.is_caused_by::<std::fmt::Error>());
+        assert_eq!(
+            err.find_in_chain::<std::io::Error>().map(|io| io.kind()),
+            Some(std::io::ErrorKind::TimedOut)
+        );
+        assert!(err.chain_contains_kind("Filesystem"));
+        assert_eq!(err.chain().count(), 3);
+        assert_eq!(err.root_cause().to_string(), "timed out");
+
+        let wrapped = crate::error::AppError::network("api", Some(Box::new(err.into_error()) as _));
+        assert!(wrapped.chain_contains_kind("Filesystem"));
+        assert!(!wrapped.chain_contains_kind("Config"));
+
+        // A boxed cause is recognised through its context layers.
+        let cause = crate::BoxedForgeError::from(crate::AppError::config("bad").context("parsing"));
+        let wrapped = crate::error::AppError::network("api", Some(Box::new(cause) as _));
+        assert!(wrapped.chain_contains_kind("Config"));
+    }
+
+    #[test]
+    fn test_bail_and_ensure() {
+        use crate::ContextError;
+
+        crate::define_errors! {
+            #[tracked]
+            pub enum PortError {
+                #[error(display = "{message}")]
+                #[kind(Config)]
+                Invalid { message: String },
+            }
+        }
+
+        // Each error's context is the line it was built on.
+        fn check(port: u16) -> Result<u16, ContextError<PortError, u32>> {
+            crate::forge_ensure!(
+                port != 0,
+                ContextError::new(PortError::invalid("zero".into()), line!())
+            );
+            if port < 1024 {
+                crate::forge_bail!(
+                    |message| ContextError::new(PortError::invalid(message), line!()),
+                    "port {port} is privileged"
+                );
+            }
+            Ok(port)
+        }
+
+        let err = check(0).unwrap_err();
+        assert_eq!(err.location().unwrap().line(), err.context);
+        assert!(check(80)
+            .unwrap_err()
+            .to_string()
+            .contains("port 80 is privileged"));
+        assert_eq!(check(8080).unwrap(), 8080);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn test_forge_err() {
+        crate::define_errors! {
+            pub enum ParseError {
+                #[kind(Parse, status = 400)]
+                Syntax { message: String },
+            }
+        }
+
+        let line = 3;
+        let err: ParseError = crate::forge_err!(ParseError::syntax, "bad token at line {line}");
+        assert_eq!(err.status_code(), 400);
+        assert!(err.to_string().contains("bad token at line 3"));
+
+        let err = crate::forge_err!("{} + {} failed", 1, 2);
+        assert!(matches!(err, crate::AppError::Other { .. }));
+        assert!(err.to_string().contains("1 + 2 failed"));
+    }
+
+    #[test]
+    fn test_expect_forge() {
+        use crate::{AppError, ContextError};
+
+        crate::define_errors! {
+            #[tracked]
+            pub enum ValueError {
+                #[error(display = "{message}")]
+                #[kind(Config)]
+                Missing { message: String },
+            }
+        }
+
+        // The error's context is the line it was built on.
+        fn first_even(values: &[u32]) -> Result<u32, ContextError<ValueError, u32>> {
+            crate::expect_forge!(
+                !values.is_empty(),
+                ContextError::new(ValueError::missing("empty".into()), line!())
+            );
+            let value = crate::expect_forge!(
+                values.iter().find(|value| *value % 2 == 0),
+                ContextError::new(ValueError::missing("no even value".into()), line!())
+            );
+            Ok(*value)
+        }
+
+        assert_eq!(first_even(&[1, 4]).unwrap(), 4);
+        let err = first_even(&[]).unwrap_err();
+        assert_eq!(err.location().unwrap().line(), err.context);
+        let err = first_even(&[1]).unwrap_err();
+        assert_eq!(err.location().unwrap().line(), err.context);
+
+        let none: Option<u8> = None;
+        assert!(crate::ok_or_forge!(none, AppError::config("missing")).is_err());
+    }
+
+    #[test]
+    fn test_chain_guards() {
+        use std::error::Error;
+
+        // An error that is its own source.
+        #[derive(Debug)]
+        struct Loop;
+
+        impl std::fmt::Display for Loop {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("loop")
+            }
+        }
+
+        impl Error for Loop {
+            fn source(&self) -> Option<&(dyn Error + 'static)> {
+                Some(self)
+            }
+        }
+
+        let mut chain = crate::Chain::new(&Loop);
+        assert_eq!(chain.by_ref().count(), 1);
+        assert!(chain.is_truncated());
+
+        let err = crate::AppError::other("outer").with_source(Loop);
+        assert_eq!(err.root_cause().to_string(), "loop");
+        // Boxing may hand out a second `dyn Error` view of the same
+        // value, so the cycle can take one extra lap to be noticed.
+        let causes = crate::ErrorSnapshot::capture(&err).causes;
+        assert!(causes.len() <= 3);
+        assert_eq!(causes.last().unwrap(), crate::snapshot::TRUNCATED_CAUSES);
+
+        let mut deep: Box<dyn Error + Send + Sync> = Box::new(crate::AppError::other("0"));
+        for i in 1..39 {
+            deep = Box::new(crate::AppError::other(i.to_string()).with_source(deep));
+        }
+        let deep = crate::AppError::other("39").with_source(deep);
+        let mut chain = deep.chain();
+        assert_eq!(
+            chain.by_ref().count(),
+            crate::error::DEFAULT_MAX_CHAIN_DEPTH
+        );
+        assert!(chain.is_truncated());
+        assert!(!crate::AppError::other("x").chain().is_truncated());
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn test_const_sentinels() {
+        crate::define_errors! {
+            pub enum PoolError {
+                #[error(display = "pool exhausted")]
+                #[kind(Pool, retryable = true)]
+                PoolExhausted,
+
+                #[kind(Pool, severity = Info, docs = "https://docs.example.com/pool-closed")]
+                Closed { reason: String },
+            }
+        }
+
+        const EXHAUSTED: PoolError = PoolError::POOL_EXHAUSTED;
+        static SHARED: PoolError = PoolError::POOL_EXHAUSTED;
+
+        assert!(matches!(EXHAUSTED, PoolError::PoolExhausted));
+        assert!(SHARED.is_retryable());
+        assert_eq!(SHARED.to_string(), "pool exhausted");
+        SHARED.register();
+
+        let closed = PoolError::closed("shutdown".to_string());
+        assert_eq!(closed.kind(), "Pool");
+        assert_eq!(closed.severity(), crate::ErrorLevel::Info);
+        assert_eq!(
+            closed.docs_url().as_deref(),
+            Some("https://docs.example.com/pool-closed")
+        );
+        assert_eq!(SHARED.docs_url(), None);
+        assert_eq!(SHARED.severity(), crate::ErrorLevel::Warning);
+        assert_eq!(
+            crate::ErrorSnapshot::capture(&closed).severity,
+            crate::ErrorLevel::Info
+        );
+    }
+
+    #[test]
+    fn test_app_error_instance() {
+        let line = line!() + 1;
+        let err = crate::AppError::config("bad").with_status(400);
+        let other = crate::AppError::config("bad");
+        assert_eq!(err.location().unwrap().file(), file!());
+        assert_eq!(err.location().unwrap().line(), line);
+        assert!(err.timestamp().is_some());
+        assert_ne!(err.error_id(), other.error_id());
+        assert_eq!(err.status_code(), 400);
+
+        let crate::AppError::Config {
+            message, status, ..
+        } = &err
+        else {
+            unreachable!()
+        };
+        assert_eq!((message.as_str(), *status), ("bad", 400));
+    }
+
+    #[test]
+    fn test_error_ids() {
+        crate::define_errors! {
+            #[tracked]
+            pub enum JobError {
+                #[kind(Job)]
+                Stalled { job: String },
+
+                #[kind(Job)]
+                Cancelled,
+            }
+        }
+
+        let (first, line) = (JobError::stalled("a".to_string()), line!());
+        let second = JobError::stalled("a".to_string());
+        let id = first.error_id().expect("struct variants carry an id");
+        assert_ne!(Some(id), second.error_id());
+        assert_eq!(JobError::cancelled().error_id(), None);
+        assert_eq!(JobError::CANCELLED.error_id(), None);
+        assert!(first.timestamp().unwrap() <= second.timestamp().unwrap());
+        assert_eq!(JobError::CANCELLED.timestamp(), None);
+
+        let collector = crate::ErrorCollector::new()
+            .with(JobError::cancelled())
+            .with(second);
+        assert!(collector.time_range().is_some());
+        assert_eq!(id.to_string().parse::<crate::ErrorId>(), Ok(id));
+
+        let location = first.location().expect("constructors record their caller");
+        assert_eq!(location.file(), file!());
+        assert_eq!(location.line(), line);
+        assert!(first
+            .dev_message()
+            .ends_with(&format!("(id: {id}, at {location})")));
+        let wrapped = crate::ContextError::new(first, "polling");
+        assert_eq!(wrapped.error_id(), Some(id));
+        assert_eq!(
+            crate::ErrorSnapshot::capture(&wrapped).error_id,
+            Some(id.to_string())
+        );
+
+        crate::define_errors! {
+            pub enum PlainError {
+                #[kind(Job)]
+                Stalled { job: String },
+            }
+        }
+
+        let plain = PlainError::stalled("a".to_string());
+        assert_eq!(plain.error_id(), None);
+        assert_eq!(plain.location(), None);
+        assert_eq!(
+            std::mem::size_of::<PlainError>(),
+            std::mem::size_of::<String>()
+        );
+        let PlainError::Stalled { job } = plain;
+        assert_eq!(job, "a");
+        assert!(format!("{:?}", JobError::stalled("b".into())).contains("Instance("));
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn test_define_errors_eq() {
+        // Everything a field needs except PartialEq.
+        #[derive(Debug)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+        pub struct Handle(u32);
+
+        impl std::fmt::Display for Handle {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "#{}", self.0)
+            }
+        }
+
+        crate::define_errors! {
+            pub enum StoreError {
+                #[kind(Store)]
+                Missing { key: String },
+
+                #[kind(Store)]
+                Read { path: String, #[compare(skip)] handle: Handle },
+
+                #[kind(Store)]
+                Closed,
+            }
+        }
+
+        let missing = StoreError::missing("a".to_string());
+        assert_eq!(missing, StoreError::missing("a".to_string()));
+        assert_ne!(missing, StoreError::missing("b".to_string()));
+        assert_ne!(missing, StoreError::CLOSED);
+        assert_eq!(StoreError::CLOSED, StoreError::CLOSED);
+
+        let read = |path: &str| StoreError::read(path.to_string(), Handle(1));
+        assert_eq!(read("x"), read("x"));
+        assert_ne!(read("x"), read("y"));
+
+        let closed = StoreError::closed();
+        assert_eq!(closed, closed);
+        assert!(missing.matches_kind(&StoreError::missing("b".to_string())));
+        assert!(!missing.matches_kind(&StoreError::CLOSED));
+
+        // Opting out leaves the enum free to derive it.
+        crate::define_errors! {
+            #[compare(skip)]
+            #[derive(PartialEq)]
+            pub enum CacheError {
+                #[kind(Cache)]
+                Stale { key: String },
+            }
+        }
+
+        let stale = CacheError::stale("a".to_string());
+        assert_eq!(stale, CacheError::stale("a".to_string()));
+        assert_ne!(stale, CacheError::stale("b".to_string()));
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn test_define_errors_delegate() {
+        use std::error::Error as _;
+
+        crate::define_errors! {
+            pub enum DbError {
+                #[error(display = "lost connection to {host}", host)]
+                #[kind(Database, retryable = true, status = 503)]
+                Disconnected { host: String },
+            }
+
+            pub enum ServiceError {
+                Database(#[delegate] DbError),
+
+                #[kind(Auth, status = 401)]
+                Unauthorized,
+            }
+        }
+
+        let inner = DbError::disconnected("db1".to_string());
+        let id = inner.error_id();
+        let err: ServiceError = inner.into();
+
+        assert_eq!(err.kind(), "Database");
+        assert_eq!(err.status_code(), 503);
+        assert!(err.is_retryable());
+        assert_eq!(err.error_id(), id);
+        assert_eq!(err.to_string(), "lost connection to db1");
+        assert!(err.source().unwrap().is::<DbError>());
+        assert_eq!(
+            err,
+            ServiceError::Database(DbError::disconnected("db1".to_string()))
+        );
+
+        assert_eq!(ServiceError::UNAUTHORIZED.kind(), "Auth");
+        assert!(ServiceError::UNAUTHORIZED.source().is_none());
+    }
+
+    // Foreign source types are not `Serialize`.
+    #[cfg(not(feature = "serde"))]
+    #[test]
+    #[allow(dead_code)]
+    fn test_define_errors_from() {
+        use std::error::Error as _;
+
+        crate::define_errors! {
+            #[tracked]
+            pub enum LoadError {
+                #[kind(Io)]
+                Io { #[compare(skip)] #[from] cause: std::io::Error },
+
+                #[kind(Parse)]
+                Parse { #[compare(skip)] #[from] source: std::num::ParseIntError },
+            }
+        }
+
+        fn read(raw: &str) -> Result<u32, LoadError> {
+            if raw.is_empty() {
+                Err(std::io::Error::other("empty input"))?;
+            }
+            Ok(raw.parse()?)
+        }
+
+        let (err, line) = (read("").unwrap_err(), line!() - 5);
+        assert_eq!(err.kind(), "Io");
+        assert_eq!(err.location().unwrap().line(), line);
+        assert!(err.source().unwrap().is::<std::io::Error>());
+
+        let err = read("x").unwrap_err();
+        assert_eq!(err.kind(), "Parse");
+        assert!(err.source().unwrap().is::<std::num::ParseIntError>());
+        assert_eq!(err, LoadError::from("y".parse::<u32>().unwrap_err()));
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn test_define_errors_doc_display() {
+        crate::define_errors! {
+            pub enum CacheError {
+                /// The cache is full
+                /// and rejected the write.
+                ///
+                /// Raised by `insert` once `capacity` entries are stored.
+                #[kind(Cache, retryable = true)]
+                Full { capacity: usize },
+
+                /// Overridden by the explicit display
+                #[error(display = "key {key} expired", key)]
+                #[kind(Cache)]
+                Expired { key: String },
+
+                #[kind(Cache)]
+                Corrupt,
+            }
+        }
+
+        assert_eq!(
+            CacheError::full(8).to_string(),
+            "The cache is full and rejected the write."
+        );
+        assert_eq!(CacheError::expired("a".into()).to_string(), "key a expired");
+        assert_eq!(CacheError::CORRUPT.to_string(), "Cache: Corrupt");
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn test_define_errors_format_spec() {
+        #[derive(Debug, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+        pub struct Endpoint {
+            host: String,
+            port: u16,
+        }
+
+        crate::define_errors! {
+            pub enum IoError {
+                #[error(display = "cannot open {path:?} ({count:>3} tries)")]
+                #[kind(Io)]
+                Open { path: String, count: u32 },
+
+                #[error(display = "{host}:{port} refused", host = endpoint.host, port = endpoint.port)]
+                #[kind(Io)]
+                Refused { endpoint: Endpoint },
+
+                #[error(display = "{0} timed out after {secs}s", endpoint.host)]
+                #[kind(Io)]
+                Timeout { endpoint: Endpoint, secs: u64 },
+            }
+        }
+
+        assert_eq!(
+            IoError::open("a.txt".into(), 7).to_string(),
+            "cannot open \"a.txt\" (  7 tries)"
+        );
+        let endpoint = Endpoint {
+            host: "db".into(),
+            port: 5432,
+        };
+        assert_eq!(IoError::refused(endpoint).to_string(), "db:5432 refused");
+        let endpoint = Endpoint {
+            host: "cache".into(),
+            port: 6379,
+        };
+        assert_eq!(
+            IoError::timeout(endpoint, 3).to_string(),
+            "cache timed out after 3s"
+        );
+    }
+
+    // Foreign source types are not `Serialize`.
+    #[cfg(not(feature = "serde"))]
+    #[test]
+    #[allow(dead_code)]
+    fn test_define_errors_source() {
+        use std::error::Error as _;
+
+        crate::define_errors! {
+            pub enum JobError {
+                #[kind(Upstream)]
+                Upstream { name: String, #[compare(skip)] #[source] cause: Box<dyn std::error::Error + Send + Sync> },
+
+                #[kind(Format)]
+                Format { line: u32, inner: std::fmt::Error },
+
+                #[kind(Invalid)]
+                Invalid { reason: String },
+            }
+        }
+
+        let err = JobError::upstream("search".into(), "timed out".into());
+        assert_eq!(err.source().unwrap().to_string(), "timed out");
+
+        let err = JobError::format(3, std::fmt::Error);
+        assert!(err.source().unwrap().is::<std::fmt::Error>());
+        assert_eq!(err.chain().count(), 2);
+
+        assert!(JobError::invalid("empty".into()).source().is_none());
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn test_define_errors_generic() {
+        use std::error::Error as _;
+
+        crate::define_errors! {
+            pub enum RepoError<K: std::fmt::Display + Clone, E>
+            where
+                E: std::error::Error + 'static,
+            {
+                #[error(display = "no record {key}", key)]
+                #[kind(NotFound, status = 404)]
+                Missing { key: K },
+
+                #[kind(Storage, retryable = true)]
+                Storage { key: K, #[source] cause: E },
+            }
+        }
+
+        let err = RepoError::<u32, std::fmt::Error>::missing(7);
+        assert_eq!(err.to_string(), "no record 7");
+        assert_eq!(err.status_code(), 404);
+
+        let err = RepoError::storage("orders", std::fmt::Error);
+        assert!(err.source().unwrap().is::<std::fmt::Error>());
+        let boxed: Box<dyn ForgeError> = Box::new(err);
+        assert!(boxed.is_retryable());
+    }
+
+    #[test]
+    fn test_define_errors_code_registration() {
+        use crate::registry::ErrorRegistry;
+
+        crate::define_errors! {
+            pub enum BillingError {
+                #[error(display = "card {last4} declined")]
+                #[kind(Declined, code = "BILL-001", docs = "https://errors.example.com/BILL-001")]
+                Declined { last4: String },
+
+                /// The payment provider is unreachable.
+                #[kind(Provider, code = "BILL-002", retryable = true)]
+                Provider,
+            }
+        }
+
+        let registry = ErrorRegistry::global();
+        assert!(!registry.is_registered("BILL-001"));
+        let err = BillingError::declined("4242".into());
+        assert_eq!(err.code(), Some("BILL-001"));
+
+        let info = registry.get_code_info("BILL-001").unwrap();
+        assert_eq!(info.description, "card {last4} declined");
+        assert_eq!(
+            info.documentation_url.as_deref(),
+            Some("https://errors.example.com/BILL-001")
+        );
+        assert!(!info.retryable);
+
+        // Sentinels register once they are registered themselves.
+        let provider = BillingError::PROVIDER;
+        assert!(!registry.is_registered("BILL-002"));
+        provider.register();
+        let info = registry.get_code_info("BILL-002").unwrap();
+        assert_eq!(info.description, "The payment provider is unreachable.");
+        assert!(info.retryable);
+        assert_eq!(BillingError::provider(), provider);
+    }
+
+    #[test]
+    fn test_define_errors_constructor_tags() {
+        crate::define_errors! {
+            pub enum ParseError {
+                #[kind(Parse, constructor = false, status = 400)]
+                Syntax { line: u32 },
+
+                #[kind(Parse, retryable = false, constructor = pub(crate) new_token, status = 422)]
+                Token { token: String },
+
+                #[kind(Parse, constructor = eof)]
+                Eof,
+            }
+        }
+
+        impl ParseError {
+            // Would collide with a generated `syntax` constructor.
+            pub fn syntax(line: u32) -> Self {
+                Self::Syntax { line: line + 1 }
+            }
+        }
+
+        assert_eq!(
+            ParseError::syntax(0).to_string(),
+            "Parse: Syntax | line = 1"
+        );
+        assert_eq!(ParseError::syntax(0).status_code(), 400);
+        let token = ParseError::new_token("}".into());
+        assert_eq!(token.status_code(), 422);
+        assert_eq!(ParseError::eof(), ParseError::EOF);
+    }
+
+    #[test]
+    fn test_define_errors_kind_profile() {
+        use crate::lint::ErrorTable;
+
+        const UNAVAILABLE: crate::KindProfile = crate::KindProfile::new()
+            .with_status(503)
+            .with_retryable(true)
+            .with_exit(75);
+
+        crate::define_errors! {
+            pub enum FeedError {
+                #[kind(Unavailable, profile = UNAVAILABLE)]
+                Down { feed: String },
+
+                #[kind(Unavailable, status = 504, profile = UNAVAILABLE, retryable = false)]
+                Timeout,
+            }
+        }
+
+        let down = FeedError::down("prices".into());
+        assert_eq!(down.status_code(), 503);
+        assert_eq!(down.exit_code(), 75);
+        assert!(down.is_retryable());
+        assert_eq!(down.severity(), crate::ErrorLevel::Warning);
+        assert_eq!(FeedError::timeout().status_code(), 504);
+        assert!(!FeedError::TIMEOUT.is_retryable());
+        assert_eq!(FeedError::variants()[1].exit_code, 75);
+    }
+
+    #[test]
+    fn test_define_errors_overrides() {
+        crate::define_errors! {
+            #[tracked]
+            pub enum JobError {
+                #[kind(Timeout, retryable = true, status = 504)]
+                Timeout { job: String },
+
+                #[kind(Cancelled)]
+                Cancelled,
+            }
+        }
+
+        let err = JobError::timeout("sync".into())
+            .with_retryable(false)
+            .with_status(408);
+        assert!(!err.is_retryable());
+        assert_eq!(err.status_code(), 408);
+        assert_eq!(err.severity(), crate::macros::ErrorLevel::Error);
+
+        let err = err.with_fatal(true);
+        assert!(err.is_fatal());
+        assert_eq!(err.severity(), crate::macros::ErrorLevel::Critical);
+
+        let status = std::panic::catch_unwind(|| JobError::cancelled().with_status(499));
+        let fatal = std::panic::catch_unwind(|| JobError::CANCELLED.with_fatal(true));
+        assert!(status.is_err() && fatal.is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_define_errors_deserialize() {
+        use crate::captured::CapturedError;
+        use std::error::Error as _;
+
+        crate::define_errors! {
+            #[tracked]
+            #[derive(serde::Deserialize)]
+            pub enum QueueError {
+                #[kind(Publish, retryable = true)]
+                Publish { topic: String, #[source] cause: CapturedError },
+
+                #[kind(Closed)]
+                Closed,
+            }
+        }
+
+        let io = std::io::Error::other("broker gone");
+        let err = QueueError::publish("orders".into(), io.into());
+        let json = serde_json::to_string(&err).unwrap();
+        let back: QueueError = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, err);
+        assert_eq!(back.error_id(), err.error_id());
+        assert_eq!(back.source().unwrap().to_string(), "broker gone");
+        assert_eq!(
+            crate::instance::unix_millis(back.timestamp().unwrap()),
+            crate::instance::unix_millis(err.timestamp().unwrap())
+        );
+
+        let json = serde_json::to_string(&QueueError::CLOSED).unwrap();
+        let back: QueueError = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, QueueError::closed());
+    }
+
+    #[test]
+    fn test_define_errors_cfg() {
+        use crate::lint::ErrorTable;
+
+        crate::define_errors! {
+            #[non_exhaustive]
+            pub enum GatedError {
+                #[kind(Io)]
+                Io { path: String },
+
+                /// Compiled out, as a disabled feature would be.
+                #[cfg(any())]
+                #[kind(Cloud)]
+                Cloud { bucket: String },
+
+                #[doc(hidden)]
+                #[kind(Internal)]
+                Internal,
+            }
+        }
+
+        let kinds: Vec<_> = GatedError::variants().iter().map(|v| v.kind).collect();
+        assert_eq!(kinds, ["Io", "Internal"]);
+        assert_eq!(
+            GatedError::io("a".into()).to_string(),
+            "Io: Io | path = \"a\""
+        );
+        assert_eq!(GatedError::internal(), GatedError::INTERNAL);
+    }
+
+    #[test]
+    fn test_boxed_forge_error() {
+        use crate::{AppError, BoxedForgeError, ContextError};
+
+        let mut boxed: BoxedForgeError = AppError::network("api", None).into();
+        assert_eq!(boxed.kind(), "Network");
+        assert!(boxed.is_retryable());
+        assert!(boxed.is::<AppError>());
+        assert!(boxed.downcast_mut::<AppError>().is_some());
+        assert!(boxed
+            .downcast_ref::<ContextError<AppError, String>>()
+            .is_none());
+
+        let boxed = boxed
+            .downcast::<ContextError<AppError, String>>()
+            .unwrap_err();
+        let app = boxed.downcast::<AppError>().expect("boxed an AppError");
+        assert_eq!(app.status_code(), 503);
+
+        let dynamic: Box<dyn std::error::Error> = Box::new(BoxedForgeError::from(*app));
+        assert!(dynamic.to_string().contains("api"));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_error_source() {
+        use crate::ModError;
+        use std::error::Error as _;
+
+        #[derive(Debug, ModError)]
+        enum StoreError {
+            #[error_display("read of {path} failed")]
+            Read {
+                path: String,
+                source: std::io::Error,
+            },
+            #[error_display("upstream failed")]
+            Upstream(#[error_source] Option<Box<dyn std::error::Error + Send + Sync>>),
+            #[error_display("missing")]
+            #[error_severity(Info)]
+            #[error_class(Unknown)]
+            Missing,
+        }
+
+        let read = StoreError::Read {
+            path: "a.db".to_string(),
+            source: std::io::Error::other("disk"),
+        };
+        assert_eq!(read.to_string(), "read of a.db failed");
+        assert_eq!(read.source().map(|s| s.to_string()), Some("disk".into()));
+        assert!(read.is_caused_by::<std::io::Error>());
+
+        let upstream = StoreError::Upstream(Some("boom".into()));
+        assert_eq!(
+            upstream.source().map(|s| s.to_string()),
+            Some("boom".into())
+        );
+        assert!(StoreError::Upstream(None).source().is_none());
+        assert!(StoreError::Missing.source().is_none());
+        assert_eq!(StoreError::Missing.severity(), crate::ErrorLevel::Info);
+        assert_eq!(read.severity(), crate::ErrorLevel::Error);
+        assert_eq!(StoreError::Missing.class(), crate::ErrorClass::Unknown);
+        assert_eq!(read.class(), crate::ErrorClass::Permanent);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_display_fields() {
+        use crate::ModError;
+
+        #[derive(Debug, ModError)]
+        enum QueryError {
+            #[error_display("query failed: {reason} ({{reason}} {attempts:>2})")]
+            Failed { reason: String, attempts: u32 },
+
+            #[error_display("no {{shard}} left")]
+            Exhausted,
+        }
+
+        #[derive(Debug, ModError)]
+        #[error_prefix("Quota")]
+        #[error_display("{used} of {limit} requests used")]
+        struct QuotaError {
+            used: u32,
+            limit: u32,
+        }
+
+        #[derive(Debug, ModError)]
+        #[error_display("shard {0} offline")]
+        struct ShardError(u16);
+
+        let err = QueryError::Failed {
+            reason: "timeout".into(),
+            attempts: 3,
+        };
+        assert_eq!(err.to_string(), "query failed: timeout ({reason}  3)");
+        assert_eq!(QueryError::Exhausted.to_string(), "no {shard} left");
+        let quota = QuotaError {
+            used: 100,
+            limit: 100,
+        };
+        assert_eq!(quota.to_string(), "100 of 100 requests used");
+        assert_eq!(quota.caption(), "Quota: Error");
+        assert_eq!(ShardError(4).to_string(), "shard 4 offline");
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_error_from() {
+        use crate::ModError;
+        use std::error::Error as _;
+
+        #[derive(Debug, ModError)]
+        enum LoadError {
+            #[error_display("read failed")]
+            Io(#[error_from] std::io::Error),
+            #[error_display("bad number: {source}")]
+            Parse {
+                #[error_from]
+                source: std::num::ParseIntError,
+            },
+        }
+
+        fn load(text: &str) -> Result<u32, LoadError> {
+            if text.is_empty() {
+                Err(std::io::Error::other("empty"))?;
+            }
+            Ok(text.parse()?)
+        }
+
+        assert_eq!(load("7").unwrap(), 7);
+        let err = load("").unwrap_err();
+        assert!(matches!(err, LoadError::Io(_)));
+        assert_eq!(err.source().unwrap().to_string(), "empty");
+        let err = load("x").unwrap_err();
+        assert!(err.to_string().starts_with("bad number: "));
+        assert!(err.is_caused_by::<std::num::ParseIntError>());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_kind_and_caption() {
+        use crate::ModError;
+
+        #[derive(Debug, ModError)]
+        #[error_prefix("Cache")]
+        enum CacheError {
+            #[error_kind(Network)]
+            #[error_caption("Cache: Unreachable")]
+            Unreachable,
+            #[error_kind("Storage")]
+            Full,
+            Stale,
+        }
+
+        #[derive(Debug, ModError)]
+        #[error_prefix("Quota")]
+        #[error_kind(RateLimit)]
+        #[error_caption("Quota exceeded")]
+        struct QuotaError;
+
+        assert_eq!(CacheError::Unreachable.kind(), "Network");
+        assert_eq!(CacheError::Unreachable.caption(), "Cache: Unreachable");
+        assert_eq!(CacheError::Full.kind(), "Storage");
+        assert_eq!(CacheError::Stale.caption(), "Cache: Error");
+        assert_eq!(
+            (QuotaError.kind(), QuotaError.caption()),
+            ("RateLimit", "Quota exceeded")
+        );
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_user_message() {
+        use crate::ModError;
+
+        #[derive(Debug, ModError)]
+        #[error_prefix("Payment")]
+        enum PaymentError {
+            #[error_display("gateway {gateway} returned {code}")]
+            #[error_user_message("Payment failed, please retry ({code})")]
+            Declined { gateway: String, code: u16 },
+            #[error_display("ledger write failed: {0}")]
+            Ledger(String),
+            #[error_user_message("Card on hold, see {{account}}")]
+            Held,
+        }
+
+        #[derive(Debug, ModError)]
+        #[error_display("token {0} expired at {1}")]
+        #[error_user_message("Your session expired")]
+        struct SessionExpired(String, u64);
+
+        let declined = PaymentError::Declined {
+            gateway: "stripe-eu".to_string(),
+            code: 402,
+        };
+        assert_eq!(declined.to_string(), "gateway stripe-eu returned 402");
+        assert_eq!(
+            declined.user_message(),
+            "Payment failed, please retry (402)"
+        );
+        let ledger = PaymentError::Ledger("disk full".to_string());
+        assert_eq!(ledger.user_message(), ledger.to_string());
+        assert_eq!(
+            PaymentError::Held.user_message(),
+            "Card on hold, see {account}"
+        );
+        assert_eq!(
+            SessionExpired("t-1".to_string(), 9).user_message(),
+            "Your session expired"
+        );
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_struct_fields() {
+        use crate::ModError;
+        use std::error::Error as _;
+
+        /// The manifest could not be read
+        #[derive(Debug, ModError)]
+        #[error_kind(Manifest)]
+        #[error_display("cannot read {path}")]
+        #[error_http_status(422)]
+        #[error_exit_code(65)]
+        #[error_class(Permanent)]
+        #[error_code("TEST-MANIFEST-001")]
+        struct ManifestError {
+            path: String,
+            source: std::io::Error,
+        }
+
+        #[derive(Debug, ModError)]
+        #[error_display("bad port")]
+        #[error_severity(Warning)]
+        struct PortError(#[error_from] std::num::ParseIntError);
+
+        let err = ManifestError {
+            path: "Cargo.toml".to_string(),
+            source: std::io::Error::other("denied"),
+        };
+        assert_eq!(err.to_string(), "cannot read Cargo.toml");
+        assert_eq!(err.source().unwrap().to_string(), "denied");
+        assert_eq!((err.status_code(), err.exit_code()), (422, 65));
+        assert_eq!(err.class(), crate::ErrorClass::Permanent);
+        assert_eq!(err.code(), Some("TEST-MANIFEST-001"));
+        let info = crate::ErrorRegistry::global()
+            .get_code_info("TEST-MANIFEST-001")
+            .expect("registered on first use");
+        assert_eq!(info.description, "cannot read {path}");
+
+        let port: Result<u16, PortError> = "x".parse::<u16>().map_err(PortError::from);
+        let err = port.unwrap_err();
+        assert!(err.source().is_some());
+        assert_eq!(err.severity(), crate::macros::ErrorLevel::Warning);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_fatal_flags() {
+        use crate::ModError;
+
+        #[derive(Debug, ModError)]
+        #[error_prefix("Startup")]
+        #[error_fatal]
+        enum StartupError {
+            MissingConfig,
+            #[error_fatal(false)]
+            #[error_retryable]
+            PortBusy,
+            #[error_retryable = false]
+            Disabled,
+        }
+
+        #[derive(Debug, ModError)]
+        #[error_fatal(true)]
+        struct Corrupted;
+
+        assert!(StartupError::MissingConfig.is_fatal());
+        assert!(!StartupError::PortBusy.is_fatal());
+        assert!(StartupError::PortBusy.is_retryable());
+        assert!(StartupError::Disabled.is_fatal() && !StartupError::Disabled.is_retryable());
+        assert!(Corrupted.is_fatal() && !Corrupted.is_retryable());
+        assert_eq!(Corrupted.severity(), crate::macros::ErrorLevel::Critical);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_constructors() {
+        use crate::ModError;
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Debug, ModError)]
+        #[error_prefix("Database")]
+        #[error_constructors]
+        enum DbError {
+            #[error_display("connection to {0} failed")]
+            #[error_retryable]
+            ConnectionFailed(String, #[error_backtrace] std::backtrace::Backtrace),
+            #[error_display("query failed: {reason}")]
+            #[error_constructor(query_failed)]
+            QueryFailed {
+                reason: String,
+            },
+            #[error_constructor(false)]
+            Busy,
+            Type,
+            #[error_transparent]
+            App(crate::AppError),
+        }
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let id = crate::hooks::subscribe(move |ctx: &crate::macros::ErrorContext<'_>| {
+            if ctx.caption == "Database: Error" {
+                sink.lock()
+                    .unwrap()
+                    .push((ctx.kind.to_string(), ctx.is_retryable));
+            }
+        })
+        .unwrap();
+
+        let err = DbError::connectionfailed("db.internal".to_string());
+        assert_eq!(err.to_string(), "connection to db.internal failed");
+        let err = DbError::query_failed("timeout".to_string());
+        assert_eq!(err.kind(), "QueryFailed");
+        assert_eq!(DbError::r#type().kind(), "Type");
+        let app = DbError::app(crate::AppError::config("x"));
+        assert_eq!(app.kind(), "Config");
+        crate::hooks::unsubscribe(id);
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(
+            *seen,
+            [
+                ("ConnectionFailed".to_string(), true),
+                ("QueryFailed".to_string(), false),
+                ("Type".to_string(), false),
+            ]
+        );
+        assert!(matches!(DbError::Busy, DbError::Busy));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_error_code() {
+        use crate::ModError;
+
+        #[derive(Debug, ModError)]
+        #[error_prefix("Database")]
+        enum DbError {
+            /// The replica set has no primary.
+            #[error_code("TEST-DB-003")]
+            #[error_retryable]
+            NoPrimary,
+            #[error_display("deadlock on {0}")]
+            #[error_code("TEST-DB-004")]
+            Deadlock(String),
+            Other,
+        }
+
+        let registry = crate::ErrorRegistry::global();
+        assert!(registry.get_code_info("TEST-DB-003").is_none());
+        assert_eq!(DbError::NoPrimary.code(), Some("TEST-DB-003"));
+        let info = registry.get_code_info("TEST-DB-003").unwrap();
+        assert_eq!(info.description, "The replica set has no primary.");
+        assert!(info.retryable);
+        assert_eq!(DbError::Other.code(), None);
+
+        DbError::register_error_codes();
+        let info = registry.get_code_info("TEST-DB-004").unwrap();
+        assert_eq!(info.description, "deadlock on {0}");
+        let _ = DbError::Deadlock(String::new());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_generics() {
+        use crate::ModError;
+        use std::fmt::Display;
+
+        #[derive(Debug, ModError)]
+        #[error_prefix("Database")]
+        enum DbError<T: Display> {
+            #[error_display("no row with key {0}")]
+            Missing(T),
+            #[error_display("query failed")]
+            #[error_retryable]
+            Query {
+                #[error_from]
+                source: std::io::Error,
+            },
+        }
+
+        #[derive(Debug, ModError)]
+        #[error_display("bad value {value}")]
+        struct ValueError<'a, T>
+        where
+            T: Display,
+        {
+            value: &'a T,
+        }
+
+        let err = DbError::Missing(42u32);
+        assert_eq!(err.to_string(), "no row with key 42");
+        assert_eq!(err.kind(), "Missing");
+        let err: DbError<String> = std::io::Error::other("reset").into();
+        assert!(err.is_retryable());
+
+        let value = String::from("x");
+        assert_eq!(ValueError { value: &value }.to_string(), "bad value x");
+        assert_eq!(ValueError::<u8> { value: &1 }.kind(), "ValueError");
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_backtrace() {
+        use crate::ModError;
+        use std::backtrace::{Backtrace, BacktraceStatus};
+
+        #[derive(Debug, ModError)]
+        enum LoadError {
+            #[error_display("read failed")]
+            Io(#[error_from] std::io::Error, #[error_backtrace] Backtrace),
+            #[error_display("bad number")]
+            Parse {
+                #[error_from]
+                source: std::num::ParseIntError,
+                #[error_backtrace]
+                trace: Option<Backtrace>,
+            },
+        }
+
+        #[derive(Debug, ModError)]
+        #[error_display("timed out")]
+        struct TimeoutError {
+            #[error_backtrace]
+            trace: Backtrace,
+        }
+
+        let captured = Backtrace::capture().status() == BacktraceStatus::Captured;
+        let err = LoadError::from(std::io::Error::other("eof"));
+        assert!(matches!(err, LoadError::Io(_, _)));
+        assert_eq!(err.backtrace().is_some(), captured);
+        let err = LoadError::from("x".parse::<u8>().unwrap_err());
+        assert_eq!(err.backtrace().is_some(), captured);
+
+        let err = TimeoutError {
+            trace: Backtrace::force_capture(),
+        };
+        assert!(err.backtrace().is_some());
+        let err = TimeoutError {
+            trace: Backtrace::disabled(),
+        };
+        assert!(err.backtrace().is_none());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_transparent() {
+        use crate::ModError;
+        use std::error::Error as _;
+
+        #[derive(Debug, ModError)]
+        enum ServiceError {
+            #[error_transparent]
+            App(#[error_from] crate::CodedError<crate::AppError>),
+            #[error_transparent]
+            #[error_http_status(502)]
+            Io { inner: std::io::Error },
+        }
+
+        let app = crate::AppError::filesystem("data.db", std::io::Error::other("disk full"))
+            .with_code("FS-1");
+        let expected = (app.to_string(), app.kind(), app.status_code());
+        let err = ServiceError::from(app);
+        assert_eq!((err.to_string(), err.kind(), err.status_code()), expected);
+        assert_eq!(err.code(), Some("FS-1"));
+        assert!(err.is_caused_by::<std::io::Error>());
+
+        let err = ServiceError::Io {
+            inner: std::io::Error::other("reset"),
+        };
+        assert_eq!(err.to_string(), "reset");
+        assert_eq!((err.kind(), err.status_code()), ("Io", 502));
+        assert!(err.source().is_none());
+    }
 }
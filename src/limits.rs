@@ -0,0 +1,279 @@
+//! Size limits applied when errors are serialized or logged.
+//!
+//! An error that embeds a whole response body, or a metadata set built
+//! in a loop, can produce megabytes of output per log line. The
+//! process-wide [`PayloadLimits`] cap message length, metadata field
+//! count, and source-chain depth in [`ErrorSnapshot`] (and so JSON
+//! output) and in the `log` and `tracing` adapters. Anything cut off is
+//! replaced by an explicit marker that says how much was omitted.
+//!
+//! Console output and log lines are also held to
+//! [`max_display_bytes`](PayloadLimits::max_display_bytes), cut at a
+//! word boundary, so a giant SQL statement or base64 blob in a message
+//! cannot flood a terminal. An
+//! [`ErrorPrinter`](crate::output::ErrorPrinter) can override the cap and
+//! save the full text to a report file named in the marker.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::limits::{self, PayloadLimits};
+//! use error_forge::{AppError, ErrorSnapshot};
+//!
+//! limits::set_payload_limits(PayloadLimits::new().with_max_message_bytes(16));
+//!
+//! let body = "x".repeat(10_000);
+//! let snapshot = ErrorSnapshot::capture(&AppError::other(body));
+//! assert!(snapshot.message.ends_with("bytes omitted)"));
+//! assert!(snapshot.message.len() < 64);
+//! # limits::set_payload_limits(PayloadLimits::new());
+//! ```
+//!
+//! [`ErrorSnapshot`]: crate::snapshot::ErrorSnapshot
+
+use crate::metadata::{MetaValue, Metadata};
+use parking_lot::Mutex;
+use std::borrow::Cow;
+
+/// Appended to text cut off at [`PayloadLimits::max_message_bytes`].
+pub const TRUNCATED_MARKER: &str = "…truncated";
+
+/// Metadata key recording how many fields were dropped past
+/// [`PayloadLimits::max_fields`].
+pub const TRUNCATED_FIELDS_KEY: &str = "…truncated_fields";
+
+/// Caps on the size of a serialized or logged error.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// limits without breaking callers. Start from [`PayloadLimits::new`]
+/// (the defaults) and adjust with the `with_*` methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PayloadLimits {
+    /// Longest message, cause, or string metadata value kept, in bytes
+    pub max_message_bytes: usize,
+    /// Most metadata fields kept
+    pub max_fields: usize,
+    /// Most source-chain entries kept
+    pub max_causes: usize,
+    /// Longest message shown in console output and log lines, in bytes
+    pub max_display_bytes: usize,
+}
+
+impl PayloadLimits {
+    /// The defaults: 16 KiB per message, 64 metadata fields, 32
+    /// causes, and 4 KiB of displayed message.
+    pub const fn new() -> Self {
+        Self {
+            max_message_bytes: 16 * 1024,
+            max_fields: 64,
+            max_causes: 32,
+            max_display_bytes: 4 * 1024,
+        }
+    }
+
+    /// No limits at all.
+    pub const fn unlimited() -> Self {
+        Self {
+            max_message_bytes: usize::MAX,
+            max_fields: usize::MAX,
+            max_causes: usize::MAX,
+            max_display_bytes: usize::MAX,
+        }
+    }
+
+    /// Set the longest message kept, in bytes.
+    pub fn with_max_message_bytes(mut self, max: usize) -> Self {
+        self.max_message_bytes = max;
+        self
+    }
+
+    /// Set the most metadata fields kept.
+    pub fn with_max_fields(mut self, max: usize) -> Self {
+        self.max_fields = max;
+        self
+    }
+
+    /// Set the most source-chain entries kept.
+    pub fn with_max_causes(mut self, max: usize) -> Self {
+        self.max_causes = max;
+        self
+    }
+
+    /// Set the longest message shown in console output and log lines.
+    pub fn with_max_display_bytes(mut self, max: usize) -> Self {
+        self.max_display_bytes = max;
+        self
+    }
+
+    /// Cut `text` to [`max_message_bytes`](Self::max_message_bytes),
+    /// on a character boundary, and append the marker with the number
+    /// of bytes omitted.
+    pub fn truncate<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if text.len() <= self.max_message_bytes {
+            return Cow::Borrowed(text);
+        }
+        let mut end = self.max_message_bytes;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        Cow::Owned(format!(
+            "{}{TRUNCATED_MARKER} ({} bytes omitted)",
+            &text[..end],
+            text.len() - end
+        ))
+    }
+
+    /// Cut `text` for display to the smaller of
+    /// [`max_display_bytes`](Self::max_display_bytes) and
+    /// [`max_message_bytes`](Self::max_message_bytes), at the last word
+    /// boundary before the limit, and append the marker with the number
+    /// of bytes omitted.
+    pub fn truncate_display<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        truncate_words(
+            text,
+            self.max_display_bytes.min(self.max_message_bytes),
+            None,
+        )
+    }
+
+    /// Apply [`truncate`](Self::truncate) to string values and keep at
+    /// most [`max_fields`](Self::max_fields) fields, recording the
+    /// number dropped under [`TRUNCATED_FIELDS_KEY`].
+    pub fn limit_metadata<'a>(&self, metadata: &'a Metadata) -> Cow<'a, Metadata> {
+        let oversized = |value: &MetaValue| matches!(value, MetaValue::String(s) if s.len() > self.max_message_bytes);
+        if metadata.len() <= self.max_fields && !metadata.iter().any(|(_, v)| oversized(v)) {
+            return Cow::Borrowed(metadata);
+        }
+
+        let mut limited = Metadata::new();
+        for (key, value) in metadata.iter().take(self.max_fields) {
+            match value {
+                MetaValue::String(s) => limited.insert(key, self.truncate(s).into_owned()),
+                other => limited.insert(key, other.clone()),
+            }
+        }
+        if metadata.len() > self.max_fields {
+            limited.insert(TRUNCATED_FIELDS_KEY, metadata.len() - self.max_fields);
+        }
+        Cow::Owned(limited)
+    }
+}
+
+impl Default for PayloadLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cut `text` to at most `max` bytes, backing up to the last whitespace
+/// unless that would drop more than half the text kept, and append the
+/// marker, naming `full_text` as where the rest can be read.
+pub(crate) fn truncate_words<'a>(
+    text: &'a str,
+    max: usize,
+    full_text: Option<&std::path::Path>,
+) -> Cow<'a, str> {
+    if text.len() <= max {
+        return Cow::Borrowed(text);
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    if let Some(space) = text[..end].rfind(char::is_whitespace) {
+        if space >= end / 2 {
+            end = space;
+        }
+    }
+    let head = text[..end].trim_end();
+    let omitted = text.len() - head.len();
+    Cow::Owned(match full_text {
+        Some(path) => format!(
+            "{head} {TRUNCATED_MARKER} ({omitted} bytes omitted; full text in {})",
+            path.display()
+        ),
+        None => format!("{head} {TRUNCATED_MARKER} ({omitted} bytes omitted)"),
+    })
+}
+
+static LIMITS: Mutex<PayloadLimits> = Mutex::new(PayloadLimits::new());
+
+/// The limits currently applied to snapshots and log output.
+pub fn payload_limits() -> PayloadLimits {
+    *LIMITS.lock()
+}
+
+/// Replace the process-wide limits.
+pub fn set_payload_limits(limits: PayloadLimits) {
+    *LIMITS.lock() = limits;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate() {
+        let limits = PayloadLimits::new().with_max_message_bytes(4);
+        assert_eq!(limits.truncate("abcd"), "abcd");
+        assert_eq!(
+            limits.truncate("abcdef"),
+            "abcd…truncated (2 bytes omitted)"
+        );
+        // "é" is two bytes; the cut moves back to the boundary.
+        assert_eq!(limits.truncate("abcé"), "abc…truncated (2 bytes omitted)");
+
+        let limits = PayloadLimits::new().with_max_display_bytes(12);
+        assert_eq!(
+            limits.truncate_display("SELECT id FROM users"),
+            "SELECT id …truncated (11 bytes omitted)"
+        );
+        assert_eq!(
+            truncate_words("aGVsbG8gd29ybGQ=", 8, Some("err.log".as_ref())),
+            "aGVsbG8g …truncated (8 bytes omitted; full text in err.log)"
+        );
+    }
+
+    #[test]
+    fn test_limit_metadata() {
+        let mut metadata = Metadata::new();
+        metadata.insert("body", "x".repeat(10));
+        metadata.insert("a", 1);
+        metadata.insert("b", 2);
+
+        let limits = PayloadLimits::new()
+            .with_max_message_bytes(2)
+            .with_max_fields(2);
+        let limited = limits.limit_metadata(&metadata);
+        assert_eq!(limited.len(), 3);
+        assert_eq!(
+            limited.get("body").unwrap().to_string(),
+            "xx…truncated (8 bytes omitted)"
+        );
+        assert_eq!(limited.get(TRUNCATED_FIELDS_KEY), Some(&MetaValue::Int(1)));
+        assert!(matches!(
+            PayloadLimits::unlimited().limit_metadata(&metadata),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_snapshot_limits() {
+        use crate::snapshot::{ErrorSnapshot, TRUNCATED_CAUSES};
+        use crate::{AppError, ResultExt};
+
+        let err = Err::<(), _>(AppError::other("root"))
+            .context("middle")
+            .context("x".repeat(100))
+            .unwrap_err();
+        let limits = PayloadLimits::new()
+            .with_max_message_bytes(8)
+            .with_max_causes(1);
+        let snapshot = ErrorSnapshot::capture_with_limits(&err, &limits);
+
+        assert!(snapshot.message.starts_with("xxxxxxxx…truncated"));
+        assert_eq!(snapshot.causes.len(), 2);
+        assert_eq!(snapshot.causes[1], TRUNCATED_CAUSES);
+    }
+}
@@ -0,0 +1,298 @@
+//! Consistency checks over an error enum's declared metadata.
+//!
+//! `define_errors!` records each variant's tags in a table exposed
+//! through [`ErrorTable`]. [`check_enum_metadata`] walks that table and
+//! reports combinations that are almost always mistakes, so a project
+//! can fail CI from an ordinary test:
+//!
+//! ```
+//! use error_forge::{define_errors, lint};
+//!
+//! define_errors! {
+//!     pub enum ApiError {
+//!         #[kind(Auth, status = 401, code = "API-401", docs = "https://docs.example.com/401")]
+//!         Unauthorized,
+//!
+//!         #[kind(Upstream, retryable = true, status = 503, code = "API-503", docs = "https://docs.example.com/503")]
+//!         Unavailable { service: String },
+//!     }
+//! }
+//!
+//! let issues = lint::check_enum_metadata::<ApiError>();
+//! assert!(issues.is_empty(), "{issues}");
+//! ```
+//!
+//! Variants that wrap a `#[delegate]` payload have no metadata of their
+//! own and are not listed; check the payload's type separately.
+
+use crate::collector::ErrorCollector;
+use crate::error::ErrorClass;
+use crate::macros::ErrorLevel;
+use std::collections::HashSet;
+use std::fmt;
+
+/// The declared metadata of one variant.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// fields without breaking callers. Build one with
+/// [`VariantInfo::new`] and the `with_*` methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct VariantInfo {
+    /// The variant name
+    pub name: &'static str,
+    /// The error kind
+    pub kind: &'static str,
+    /// The error caption
+    pub caption: &'static str,
+    /// The error code, if one is declared
+    pub code: Option<&'static str>,
+    /// HTTP status code
+    pub status_code: u16,
+    /// Process exit code
+    pub exit_code: i32,
+    /// Whether the variant is retryable
+    pub retryable: bool,
+    /// Whether the variant is fatal
+    pub fatal: bool,
+    /// The variant's retry class
+    pub class: ErrorClass,
+    /// The variant's severity
+    pub severity: ErrorLevel,
+    /// Documentation link, the variant's help text
+    pub docs_url: Option<&'static str>,
+    /// The `#[error(display = ...)]` format string, if one is declared
+    pub message: Option<&'static str>,
+    /// The name and Rust type of each field, in declaration order
+    pub fields: &'static [(&'static str, &'static str)],
+}
+
+impl VariantInfo {
+    /// Metadata for `name` with the `define_errors!` defaults: status
+    /// 500, exit code 1, neither retryable nor fatal.
+    pub fn new(name: &'static str, kind: &'static str) -> Self {
+        Self {
+            name,
+            kind,
+            caption: kind,
+            code: None,
+            status_code: 500,
+            exit_code: 1,
+            retryable: false,
+            fatal: false,
+            class: ErrorClass::from_retryable(false),
+            severity: ErrorLevel::from_flags(false, false),
+            docs_url: None,
+            message: None,
+            fields: &[],
+        }
+    }
+
+    /// Set the caption.
+    pub fn with_caption(mut self, caption: &'static str) -> Self {
+        self.caption = caption;
+        self
+    }
+
+    /// Set the error code.
+    pub fn with_code(mut self, code: Option<&'static str>) -> Self {
+        self.code = code;
+        self
+    }
+
+    /// Set the HTTP status code.
+    pub fn with_status_code(mut self, status_code: u16) -> Self {
+        self.status_code = status_code;
+        self
+    }
+
+    /// Set the process exit code.
+    pub fn with_exit_code(mut self, exit_code: i32) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+
+    /// Set the retryable and fatal flags.
+    pub fn with_flags(mut self, retryable: bool, fatal: bool) -> Self {
+        self.retryable = retryable;
+        self.fatal = fatal;
+        self
+    }
+
+    /// Set the retry class.
+    pub fn with_class(mut self, class: ErrorClass) -> Self {
+        self.class = class;
+        self
+    }
+
+    /// Set the severity.
+    pub fn with_severity(mut self, severity: ErrorLevel) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Set the documentation link.
+    pub fn with_docs_url(mut self, docs_url: Option<&'static str>) -> Self {
+        self.docs_url = docs_url;
+        self
+    }
+
+    /// Set the display format string.
+    pub fn with_message(mut self, message: Option<&'static str>) -> Self {
+        self.message = message;
+        self
+    }
+
+    /// Set the `(name, type)` of each field.
+    pub fn with_fields(mut self, fields: &'static [(&'static str, &'static str)]) -> Self {
+        self.fields = fields;
+        self
+    }
+}
+
+/// An error type whose variants' metadata can be listed without
+/// building a value. Implemented by `define_errors!`.
+pub trait ErrorTable {
+    /// The metadata of every variant, in declaration order.
+    fn variants() -> Vec<VariantInfo>;
+}
+
+/// The category of a problem found by [`check_enum_metadata`].
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// checks without breaking callers that `match` on the kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LintIssueKind {
+    /// The variant is both retryable and fatal
+    RetryableFatal,
+    /// The variant is retryable but has a 4xx status other than 408
+    /// or 429, so retrying the same request cannot succeed
+    RetryableClientError,
+    /// Another variant declares the same code
+    DuplicateCode,
+    /// The variant has no `docs` link
+    MissingDocs,
+}
+
+impl LintIssueKind {
+    /// Short, stable identifier for the issue kind, suitable for
+    /// machine-readable output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RetryableFatal => "retryable-fatal",
+            Self::RetryableClientError => "retryable-client-error",
+            Self::DuplicateCode => "duplicate-code",
+            Self::MissingDocs => "missing-docs",
+        }
+    }
+}
+
+/// A single problem found by [`check_enum_metadata`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    /// The offending variant
+    pub variant: &'static str,
+    /// What is wrong with it
+    pub kind: LintIssueKind,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self.kind {
+            LintIssueKind::RetryableFatal => "variant is both retryable and fatal",
+            LintIssueKind::RetryableClientError => {
+                "variant is retryable but has a 4xx status other than 408 or 429"
+            }
+            LintIssueKind::DuplicateCode => "code is declared by an earlier variant",
+            LintIssueKind::MissingDocs => "variant has no docs link",
+        };
+        write!(f, "{} {}: {}", self.variant, self.kind.as_str(), message)
+    }
+}
+
+impl std::error::Error for LintIssue {}
+
+/// Check the declared metadata of every variant of `E`.
+///
+/// Returns the problems found, in variant order; an empty collector
+/// means the enum passed. Use
+/// [`into_result`](ErrorCollector::into_result) to fail a test.
+pub fn check_enum_metadata<E: ErrorTable>() -> ErrorCollector<LintIssue> {
+    check_variants(&E::variants())
+}
+
+/// Check a list of variants directly, for types not built with
+/// `define_errors!`.
+pub fn check_variants(variants: &[VariantInfo]) -> ErrorCollector<LintIssue> {
+    let mut issues = ErrorCollector::new();
+    let mut codes = HashSet::new();
+    for info in variants {
+        let mut issue = |kind| {
+            issues.push(LintIssue {
+                variant: info.name,
+                kind,
+            })
+        };
+        if info.retryable && info.fatal {
+            issue(LintIssueKind::RetryableFatal);
+        }
+        if info.retryable
+            && (400..500).contains(&info.status_code)
+            && !matches!(info.status_code, 408 | 429)
+        {
+            issue(LintIssueKind::RetryableClientError);
+        }
+        if let Some(code) = info.code {
+            if !codes.insert(code) {
+                issue(LintIssueKind::DuplicateCode);
+            }
+        }
+        if info.docs_url.is_none() {
+            issue(LintIssueKind::MissingDocs);
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(dead_code)]
+    fn test_check_enum_metadata() {
+        crate::define_errors! {
+            pub enum BadError {
+                #[kind(Quota, retryable = true, fatal = true, code = "E1", docs = "https://x")]
+                Quota,
+
+                #[kind(Input, retryable = true, status = 422, code = "E1")]
+                Input { field: String },
+
+                #[kind(Limited, retryable = true, status = 429, docs = "https://x")]
+                Limited,
+            }
+        }
+
+        let variants = BadError::variants();
+        assert_eq!(variants.len(), 3);
+        assert_eq!(variants[1].code, Some("E1"));
+        assert_eq!(variants[2].class, ErrorClass::Transient);
+
+        let kinds: Vec<_> = check_enum_metadata::<BadError>()
+            .into_errors()
+            .into_iter()
+            .map(|issue| (issue.variant, issue.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("Quota", LintIssueKind::RetryableFatal),
+                ("Input", LintIssueKind::RetryableClientError),
+                ("Input", LintIssueKind::DuplicateCode),
+                ("Input", LintIssueKind::MissingDocs),
+            ]
+        );
+    }
+}
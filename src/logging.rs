@@ -1,5 +1,7 @@
-use crate::error::ForgeError;
+use crate::collector::ErrorCollector;
+use crate::error::{Chain, ForgeError};
 use crate::macros::ErrorLevel;
+use std::error::Error;
 use std::sync::OnceLock;
 
 /// Trait for error logging adapters
@@ -37,26 +39,64 @@ pub fn logger() -> Option<&'static dyn ErrorLogger> {
     ERROR_LOGGER.get().map(|boxed| boxed.as_ref())
 }
 
-/// Log an error with the appropriate level
+/// Log an error at its [`severity`](ForgeError::severity)
+///
+/// Inside a [`deferred`](crate::hooks::deferred) section the event is
+/// held back until the section ends.
 pub fn log_error(error: &dyn ForgeError) {
-    if let Some(logger) = logger() {
-        let level = if error.is_fatal() {
-            ErrorLevel::Critical
-        } else if !error.is_retryable() {
-            ErrorLevel::Error
-        } else {
-            ErrorLevel::Warning
-        };
+    log_with_level(error, error.severity());
+}
 
+/// Log an error at `level` instead of its own severity
+///
+/// Useful for errors that are expected in one place but not another,
+/// such as a `NotFound` that is only worth a debug line.
+pub fn log_with_level(error: &dyn ForgeError, level: ErrorLevel) {
+    if !crate::side_effects::enabled() || crate::hooks::defer_log(error, level) {
+        return;
+    }
+    if let Some(logger) = logger() {
         logger.log_error(error, level);
     }
+    crate::spi::export(error, level);
+}
+
+/// Log any error, such as one from another crate, at `Error` level
+///
+/// The error has no kind or metadata, so it reaches the logger's
+/// [`log_message`](ErrorLogger::log_message) as its message followed
+/// by each cause: `"outer: caused by: inner"`.
+pub fn log_any(error: &(dyn Error + 'static)) {
+    let message = Chain::new(error)
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(": caused by: ");
+    log_message(&message, ErrorLevel::Error);
+}
+
+/// Log each error in a collector, in the order they were collected
+pub fn log_collector<E: ForgeError>(collector: &ErrorCollector<E>) {
+    for error in collector.errors() {
+        log_error(error);
+    }
+}
+
+/// Log a plain message at `level`, held back like errors inside a
+/// [`deferred`](crate::hooks::deferred) section
+pub fn log_message(message: &str, level: ErrorLevel) {
+    if !crate::side_effects::enabled() || crate::hooks::defer_message(message, level) {
+        return;
+    }
+    if let Some(logger) = logger() {
+        logger.log_message(message, level);
+    }
 }
 
 /// Standard logging implementation for common logging crates
 #[cfg(feature = "log")]
 pub mod log_impl {
     use super::*;
-    use log::{debug, error, info, warn};
+    use log::{error, log};
 
     /// A logger that uses the `log` crate
     pub struct LogAdapter;
@@ -64,27 +104,23 @@ pub mod log_impl {
     impl ErrorLogger for LogAdapter {
         fn log_error(&self, error: &dyn ForgeError, level: ErrorLevel) {
             let kind = error.kind();
-            let message = error.dev_message();
-            match level {
-                ErrorLevel::Critical => {
-                    error!(target: "error-forge", "[CRITICAL] [{kind}] {message}")
-                }
-                ErrorLevel::Error => error!(target: "error-forge", "[ERROR] [{kind}] {message}"),
-                ErrorLevel::Warning => warn!(target: "error-forge", "[WARNING] [{kind}] {message}"),
-                ErrorLevel::Info => info!(target: "error-forge", "[INFO] [{kind}] {message}"),
-                ErrorLevel::Debug => debug!(target: "error-forge", "[DEBUG] [{kind}] {message}"),
-            }
+            let limits = crate::limits::payload_limits();
+            let dev_message = error.dev_message();
+            let dev_message = limits.truncate_display(&dev_message);
+            let message = if error.metadata().is_empty() {
+                dev_message.into_owned()
+            } else {
+                format!(
+                    "{dev_message} {{{}}}",
+                    limits.limit_metadata(error.metadata())
+                )
+            };
+            let severity = level.as_str().to_ascii_uppercase();
+            log!(target: "error-forge", level.to_log_level(), "[{severity}] [{kind}] {message}");
         }
 
         fn log_message(&self, message: &str, level: ErrorLevel) {
-            match level {
-                ErrorLevel::Critical | ErrorLevel::Error => {
-                    error!(target: "error-forge", "{message}")
-                }
-                ErrorLevel::Warning => warn!(target: "error-forge", "{message}"),
-                ErrorLevel::Info => info!(target: "error-forge", "{message}"),
-                ErrorLevel::Debug => debug!(target: "error-forge", "{message}"),
-            }
+            log!(target: "error-forge", level.to_log_level(), "{message}");
         }
 
         fn log_panic(&self, info: &std::panic::PanicHookInfo) {
@@ -101,40 +137,53 @@ pub mod log_impl {
 #[cfg(feature = "tracing")]
 pub mod tracing_impl {
     use super::*;
-    use tracing::{debug, error, info, warn};
+    use crate::macros::LogLevel;
+    use tracing::{debug, error, info, trace, warn};
 
     /// A logger that uses the `tracing` crate
     pub struct TracingAdapter;
 
     impl ErrorLogger for TracingAdapter {
         fn log_error(&self, error: &dyn ForgeError, level: ErrorLevel) {
-            match level {
-                ErrorLevel::Critical => {
-                    error!(target: "error-forge", kind = %error.kind(), message = %error.dev_message(), "Critical error")
+            let limits = crate::limits::payload_limits();
+            let dev_message = error.dev_message();
+            let message = limits.truncate_display(&dev_message);
+            let metadata = limits.limit_metadata(error.metadata());
+            let kind = error.kind();
+            let label = match level {
+                ErrorLevel::Critical => "Critical error",
+                ErrorLevel::Error => "Error",
+                ErrorLevel::Warning => "Warning",
+                ErrorLevel::Info => "Info",
+                ErrorLevel::Debug => "Debug",
+            };
+            // `tracing` levels must be constant at each call site.
+            match level.log_level() {
+                LogLevel::Error => {
+                    error!(target: "error-forge", kind = %kind, message = %message, metadata = %metadata, "{label}")
                 }
-                ErrorLevel::Error => {
-                    error!(target: "error-forge", kind = %error.kind(), message = %error.dev_message(), "Error")
+                LogLevel::Warn => {
+                    warn!(target: "error-forge", kind = %kind, message = %message, metadata = %metadata, "{label}")
                 }
-                ErrorLevel::Warning => {
-                    warn!(target: "error-forge", kind = %error.kind(), message = %error.dev_message(), "Warning")
+                LogLevel::Info => {
+                    info!(target: "error-forge", kind = %kind, message = %message, metadata = %metadata, "{label}")
                 }
-                ErrorLevel::Info => {
-                    info!(target: "error-forge", kind = %error.kind(), message = %error.dev_message(), "Info")
+                LogLevel::Debug => {
+                    debug!(target: "error-forge", kind = %kind, message = %message, metadata = %metadata, "{label}")
                 }
-                ErrorLevel::Debug => {
-                    debug!(target: "error-forge", kind = %error.kind(), message = %error.dev_message(), "Debug")
+                LogLevel::Trace => {
+                    trace!(target: "error-forge", kind = %kind, message = %message, metadata = %metadata, "{label}")
                 }
             }
         }
 
         fn log_message(&self, message: &str, level: ErrorLevel) {
-            match level {
-                ErrorLevel::Critical | ErrorLevel::Error => {
-                    error!(target: "error-forge", "{message}")
-                }
-                ErrorLevel::Warning => warn!(target: "error-forge", "{message}"),
-                ErrorLevel::Info => info!(target: "error-forge", "{message}"),
-                ErrorLevel::Debug => debug!(target: "error-forge", "{message}"),
+            match level.log_level() {
+                LogLevel::Error => error!(target: "error-forge", "{message}"),
+                LogLevel::Warn => warn!(target: "error-forge", "{message}"),
+                LogLevel::Info => info!(target: "error-forge", "{message}"),
+                LogLevel::Debug => debug!(target: "error-forge", "{message}"),
+                LogLevel::Trace => trace!(target: "error-forge", "{message}"),
             }
         }
 
@@ -244,53 +293,113 @@ pub mod custom {
 mod tests {
     use super::*;
     use crate::AppError;
-    use std::sync::Arc;
-    use std::sync::Mutex;
-
-    #[test]
-    fn test_custom_logger() {
-        // A simple test logger that captures logs in a Vec
-        struct TestLogger {
-            logs: Arc<Mutex<Vec<String>>>,
-        }
+    use std::sync::{Mutex, OnceLock};
 
-        impl ErrorLogger for TestLogger {
-            fn log_error(&self, error: &dyn ForgeError, level: ErrorLevel) {
-                let kind = error.kind();
-                let message = error.dev_message();
-                let log = format!("{level:?}: [{kind}] {message}");
-                self.logs.lock().unwrap().push(log);
-            }
+    // A simple test logger that captures logs in a Vec
+    struct TestLogger {
+        logs: &'static Mutex<Vec<String>>,
+    }
 
-            fn log_message(&self, message: &str, level: ErrorLevel) {
-                let log = format!("{level:?}: {message}");
-                self.logs.lock().unwrap().push(log);
-            }
+    impl ErrorLogger for TestLogger {
+        fn log_error(&self, error: &dyn ForgeError, level: ErrorLevel) {
+            let kind = error.kind();
+            let message = error.dev_message();
+            let log = format!("{level:?}: [{kind}] {message}");
+            self.logs.lock().unwrap().push(log);
+        }
 
-            fn log_panic(&self, info: &std::panic::PanicHookInfo) {
-                let log = format!("PANIC: {info}");
-                self.logs.lock().unwrap().push(log);
-            }
+        fn log_message(&self, message: &str, level: ErrorLevel) {
+            let log = format!("{level:?}: {message}");
+            self.logs.lock().unwrap().push(log);
         }
 
-        // Create and register the logger
-        let logs = Arc::new(Mutex::new(Vec::new()));
-        let logger = TestLogger {
-            logs: Arc::clone(&logs),
-        };
+        fn log_panic(&self, info: &std::panic::PanicHookInfo) {
+            let log = format!("PANIC: {info}");
+            self.logs.lock().unwrap().push(log);
+        }
+    }
 
-        // We need to make sure we have a fresh state for this test
-        // In a real app, you'd only register once at startup
-        let _ = register_logger(logger);
+    // Only one logger can be registered per process, so the tests share
+    // it and look for their own messages.
+    fn logs_containing(text: &str) -> Vec<String> {
+        static LOGS: OnceLock<&'static Mutex<Vec<String>>> = OnceLock::new();
+        let logs = LOGS.get_or_init(|| {
+            let logs = Box::leak(Box::new(Mutex::new(Vec::new())));
+            let _ = register_logger(TestLogger { logs });
+            logs
+        });
+        logs.lock()
+            .unwrap()
+            .iter()
+            .filter(|log| log.contains(text))
+            .cloned()
+            .collect()
+    }
 
-        // Log an error
-        let error = AppError::config("Test error");
+    #[test]
+    fn test_custom_logger() {
+        logs_containing("");
+        let error = AppError::config("custom logger test");
         log_error(&error);
 
-        // Check that the log was captured
-        let captured_logs = logs.lock().unwrap();
-        assert!(!captured_logs.is_empty());
-        assert!(captured_logs[0].contains("[Config]"));
-        assert!(captured_logs[0].contains("Test error"));
+        // Logged at the error's own severity
+        let logs = logs_containing("custom logger test");
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].starts_with(&format!("{:?}: [Config]", error.severity())));
+    }
+
+    #[test]
+    fn test_log_with_level() {
+        logs_containing("");
+        log_with_level(&AppError::network("level test", None), ErrorLevel::Debug);
+
+        let logs = logs_containing("level test");
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].starts_with("Debug: [Network]"));
+    }
+
+    #[test]
+    fn test_log_any() {
+        logs_containing("");
+        let io = std::io::Error::other("log_any inner");
+        log_any(&AppError::other("log_any outer").with_source(io));
+
+        assert_eq!(
+            logs_containing("log_any"),
+            ["Error: 🚨 Error: log_any outer: caused by: log_any inner"]
+        );
+    }
+
+    #[test]
+    fn test_log_collector() {
+        logs_containing("");
+        let collector = crate::ErrorCollector::new()
+            .with(AppError::config("collected first"))
+            .with(AppError::network("collected second", None));
+        log_collector(&collector);
+
+        let logs = logs_containing("collected");
+        assert_eq!(logs.len(), 2);
+        assert!(logs[0].contains("[Config]") && logs[0].contains("collected first"));
+        assert!(logs[1].contains("[Network]") && logs[1].contains("collected second"));
+    }
+
+    #[test]
+    fn test_level_mapping() {
+        use crate::macros::{set_log_level_mapping, LogLevel};
+
+        assert_eq!(ErrorLevel::Critical.log_level(), LogLevel::Error);
+        assert_eq!(ErrorLevel::Warning.log_level(), LogLevel::Warn);
+        assert_eq!(ErrorLevel::from(LogLevel::Trace), ErrorLevel::Debug);
+
+        set_log_level_mapping(ErrorLevel::Debug, Some(LogLevel::Trace));
+        assert_eq!(ErrorLevel::Debug.log_level(), LogLevel::Trace);
+        #[cfg(feature = "log")]
+        assert_eq!(log::Level::from(ErrorLevel::Debug), log::Level::Trace);
+        #[cfg(feature = "tracing")]
+        assert_eq!(ErrorLevel::Debug.to_tracing_level(), tracing::Level::TRACE);
+
+        set_log_level_mapping(ErrorLevel::Debug, None);
+        assert_eq!(ErrorLevel::Debug.log_level(), LogLevel::Debug);
     }
 }
@@ -3,7 +3,9 @@
 /// Marked `#[non_exhaustive]` so future minor releases can add new
 /// severity variants (e.g. `Notice`, `Trace`) without breaking
 /// existing `match` statements.
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 #[non_exhaustive]
 pub enum ErrorLevel {
     /// Debug-level errors (for detailed debugging)
@@ -18,6 +20,240 @@ pub enum ErrorLevel {
     Critical,
 }
 
+impl ErrorLevel {
+    /// The default severity for an error with the given flags: fatal
+    /// errors are `Critical`, non-retryable errors are `Error`, and
+    /// retryable errors are `Warning`.
+    ///
+    /// This is what [`ForgeError::severity`](crate::error::ForgeError::severity)
+    /// returns unless an error type declares its own severity.
+    pub const fn from_flags(is_fatal: bool, is_retryable: bool) -> Self {
+        if is_fatal {
+            Self::Critical
+        } else if !is_retryable {
+            Self::Error
+        } else {
+            Self::Warning
+        }
+    }
+
+    /// The lowercase name of the level (`"warning"`, `"critical"`, ...).
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ErrorLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "debug" => Ok(Self::Debug),
+            "info" => Ok(Self::Info),
+            "warning" | "warn" => Ok(Self::Warning),
+            "error" => Ok(Self::Error),
+            "critical" | "fatal" => Ok(Self::Critical),
+            other => Err(format!("unknown error level '{other}'")),
+        }
+    }
+}
+
+/// The five levels shared by the `log` and `tracing` crates.
+///
+/// [`ErrorLevel::log_level`] maps severities onto these; both logging
+/// adapters go through it, so an override set with
+/// [`set_log_level_mapping`] applies to either backend.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum LogLevel {
+    /// The most severe level
+    Error,
+    /// Hazardous situations
+    Warn,
+    /// Useful information
+    Info,
+    /// Lower-priority information
+    Debug,
+    /// Very verbose output
+    Trace,
+}
+
+impl LogLevel {
+    const ALL: [Self; 5] = [
+        Self::Error,
+        Self::Warn,
+        Self::Info,
+        Self::Debug,
+        Self::Trace,
+    ];
+}
+
+impl ErrorLevel {
+    /// The index of this level in the mapping table.
+    const fn index(self) -> usize {
+        match self {
+            Self::Debug => 0,
+            Self::Info => 1,
+            Self::Warning => 2,
+            Self::Error => 3,
+            Self::Critical => 4,
+        }
+    }
+
+    /// The built-in mapping: `Critical` and `Error` log as `Error`,
+    /// `Warning` as `Warn`, and the rest as their namesakes.
+    pub const fn default_log_level(self) -> LogLevel {
+        match self {
+            Self::Debug => LogLevel::Debug,
+            Self::Info => LogLevel::Info,
+            Self::Warning => LogLevel::Warn,
+            Self::Error | Self::Critical => LogLevel::Error,
+        }
+    }
+
+    /// The level this severity is logged at, honouring any override
+    /// set with [`set_log_level_mapping`].
+    pub fn log_level(self) -> LogLevel {
+        match LOG_LEVEL_MAP[self.index()].load(Ordering::Relaxed) {
+            0 => self.default_log_level(),
+            mapped => LogLevel::ALL[usize::from(mapped) - 1],
+        }
+    }
+
+    /// The `log` crate level for this severity.
+    #[cfg(feature = "log")]
+    pub fn to_log_level(self) -> log::Level {
+        self.log_level().into()
+    }
+
+    /// The `tracing` crate level for this severity.
+    #[cfg(feature = "tracing")]
+    pub fn to_tracing_level(self) -> tracing::Level {
+        self.log_level().into()
+    }
+}
+
+impl From<LogLevel> for ErrorLevel {
+    /// `Trace` has no counterpart and becomes `Debug`.
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => Self::Error,
+            LogLevel::Warn => Self::Warning,
+            LogLevel::Info => Self::Info,
+            LogLevel::Debug | LogLevel::Trace => Self::Debug,
+        }
+    }
+}
+
+impl From<ErrorLevel> for LogLevel {
+    fn from(level: ErrorLevel) -> Self {
+        level.log_level()
+    }
+}
+
+/// Per-severity overrides; 0 means the default, otherwise one more
+/// than the index into [`LogLevel::ALL`].
+static LOG_LEVEL_MAP: [AtomicU8; 5] = [const { AtomicU8::new(0) }; 5];
+
+/// Log errors of `severity` at `level` instead of the default, or
+/// restore the default with `None`. Applies process-wide to
+/// [`ErrorLevel::log_level`] and the logging adapters.
+pub fn set_log_level_mapping(severity: ErrorLevel, level: Option<LogLevel>) {
+    let mapped = level.map_or(0, |level| level as u8 + 1);
+    LOG_LEVEL_MAP[severity.index()].store(mapped, Ordering::Relaxed);
+}
+
+#[cfg(feature = "log")]
+impl From<LogLevel> for log::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => Self::Error,
+            LogLevel::Warn => Self::Warn,
+            LogLevel::Info => Self::Info,
+            LogLevel::Debug => Self::Debug,
+            LogLevel::Trace => Self::Trace,
+        }
+    }
+}
+
+#[cfg(feature = "log")]
+impl From<log::Level> for LogLevel {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => Self::Error,
+            log::Level::Warn => Self::Warn,
+            log::Level::Info => Self::Info,
+            log::Level::Debug => Self::Debug,
+            log::Level::Trace => Self::Trace,
+        }
+    }
+}
+
+#[cfg(feature = "log")]
+impl From<ErrorLevel> for log::Level {
+    fn from(level: ErrorLevel) -> Self {
+        level.to_log_level()
+    }
+}
+
+#[cfg(feature = "log")]
+impl From<log::Level> for ErrorLevel {
+    fn from(level: log::Level) -> Self {
+        LogLevel::from(level).into()
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl From<LogLevel> for tracing::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => Self::ERROR,
+            LogLevel::Warn => Self::WARN,
+            LogLevel::Info => Self::INFO,
+            LogLevel::Debug => Self::DEBUG,
+            LogLevel::Trace => Self::TRACE,
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl From<tracing::Level> for LogLevel {
+    fn from(level: tracing::Level) -> Self {
+        match level {
+            tracing::Level::ERROR => Self::Error,
+            tracing::Level::WARN => Self::Warn,
+            tracing::Level::INFO => Self::Info,
+            tracing::Level::DEBUG => Self::Debug,
+            _ => Self::Trace,
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl From<ErrorLevel> for tracing::Level {
+    fn from(level: ErrorLevel) -> Self {
+        level.to_tracing_level()
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl From<tracing::Level> for ErrorLevel {
+    fn from(level: tracing::Level) -> Self {
+        LogLevel::from(level).into()
+    }
+}
+
 /// Error context passed to registered hooks.
 ///
 /// Marked `#[non_exhaustive]` so future minor releases can add new
@@ -61,6 +297,92 @@ impl<'a> ErrorContext<'a> {
     }
 }
 
+/// Default tags shared by variants of many `define_errors!` enums.
+///
+/// Declare a profile once as a `const` and reference it from a
+/// variant's `#[kind(...)]` with `profile = NAME`. The profile supplies
+/// `status`, `exit`, `retryable`, and `fatal`; tags written on the
+/// variant itself take precedence.
+///
+/// ```
+/// use error_forge::{define_errors, KindProfile};
+///
+/// const NOT_FOUND: KindProfile = KindProfile::new().with_status(404).with_exit(2);
+///
+/// define_errors! {
+///     pub enum UserError {
+///         #[kind(NotFound, profile = NOT_FOUND)]
+///         Missing { id: u64 },
+///     }
+///
+///     pub enum FileError {
+///         #[kind(NotFound, profile = NOT_FOUND, exit = 66)]
+///         Missing { path: String },
+///     }
+/// }
+///
+/// assert_eq!(UserError::missing(7).status_code(), 404);
+/// assert_eq!(FileError::missing("a.txt".into()).exit_code(), 66);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct KindProfile {
+    /// HTTP status code
+    pub status: u16,
+    /// Process exit code
+    pub exit: i32,
+    /// Whether errors of the profile are retryable
+    pub retryable: bool,
+    /// Whether errors of the profile are fatal
+    pub fatal: bool,
+}
+
+impl KindProfile {
+    /// A profile with the `define_errors!` defaults: status 500, exit
+    /// code 1, neither retryable nor fatal.
+    pub const fn new() -> Self {
+        Self {
+            status: 500,
+            exit: 1,
+            retryable: false,
+            fatal: false,
+        }
+    }
+
+    /// Set the HTTP status code.
+    pub const fn with_status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Set the process exit code.
+    pub const fn with_exit(mut self, exit: i32) -> Self {
+        self.exit = exit;
+        self
+    }
+
+    /// Set whether errors are retryable.
+    pub const fn with_retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+
+    /// Set whether errors are fatal.
+    pub const fn with_fatal(mut self, fatal: bool) -> Self {
+        self.fatal = fatal;
+        self
+    }
+}
+
+impl Default for KindProfile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::OnceLock;
 
 /// Hook callback type.
@@ -118,6 +440,222 @@ impl ErrorSource for Option<Box<dyn std::error::Error>> {
     }
 }
 
+/// Writes the first paragraph of a variant's doc comment, one line per
+/// `#[doc]` attribute, joined with single spaces.
+#[doc(hidden)]
+pub fn write_doc_display(f: &mut std::fmt::Formatter<'_>, lines: &[&str]) -> std::fmt::Result {
+    for (i, line) in doc_paragraph(lines).enumerate() {
+        if i > 0 {
+            f.write_str(" ")?;
+        }
+        f.write_str(line)?;
+    }
+    Ok(())
+}
+
+fn doc_paragraph<'a>(lines: &'a [&'a str]) -> impl Iterator<Item = &'a str> {
+    lines
+        .iter()
+        .map(|line| line.trim())
+        .skip_while(|line| line.is_empty())
+        .take_while(|line| !line.is_empty())
+}
+
+/// Registers a variant's `code` tag in the global
+/// [`ErrorRegistry`](crate::registry::ErrorRegistry), described by its
+/// display format, its doc comment, or else its caption. A code that is
+/// already registered keeps its existing entry.
+#[doc(hidden)]
+pub fn register_variant_code(
+    code: &str,
+    display: Option<&str>,
+    doc: &[&str],
+    caption: &str,
+    documentation_url: Option<&str>,
+    retryable: bool,
+) {
+    let description = match display {
+        Some(display) => display.to_string(),
+        None => doc_paragraph(doc).collect::<Vec<_>>().join(" "),
+    };
+    let description = match description.is_empty() {
+        true => caption.to_string(),
+        false => description,
+    };
+    let _ = crate::registry::ErrorRegistry::global().register_code(
+        code.to_string(),
+        description,
+        documentation_url.map(String::from),
+        retryable,
+    );
+}
+
+/// Returns `"{prefix}::{kind}"` as a `&'static str`, for generated
+/// `group!` implementations whose prefixed kind depends on the wrapped
+/// error. Each distinct pair is allocated once and kept for the rest of
+/// the process, so the cost is bounded by the number of kinds.
+#[doc(hidden)]
+pub fn prefixed_kind(prefix: &'static str, kind: &'static str) -> &'static str {
+    type Kinds = RwLock<HashMap<(&'static str, &'static str), &'static str>>;
+    static KINDS: OnceLock<Kinds> = OnceLock::new();
+
+    let kinds = KINDS.get_or_init(Default::default);
+    if let Some(prefixed) = kinds.read().get(&(prefix, kind)) {
+        return prefixed;
+    }
+    kinds
+        .write()
+        .entry((prefix, kind))
+        .or_insert_with(|| Box::leak(format!("{prefix}::{kind}").into_boxed_str()))
+}
+
+/// Never implemented. The `with_*` builders of a `define_errors!` enum
+/// that is not `#[tracked]` require it, so calling one is a compile
+/// error instead of an override that is silently dropped.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "this `define_errors!` enum is not `#[tracked]`, so it has nowhere to store a `with_*` override",
+    label = "needs a `#[tracked]` enum",
+    note = "add `#[tracked]` above the enum, or set the tag on the variant's `#[kind(...)]`"
+)]
+pub trait TrackedOverride {}
+
+/// A field probed by generated `source()` implementations.
+///
+/// Generated code calls `(&SourceProbe(field)).__forge_source()`:
+/// fields whose type implements `Error` resolve to
+/// [`SourceProbeError`] and are returned as the source, and any other
+/// field falls back to [`SourceProbeFallback`] after autoref, which
+/// returns `None`.
+#[doc(hidden)]
+pub struct SourceProbe<'a, T: ?Sized>(pub &'a T);
+
+#[doc(hidden)]
+pub trait SourceProbeError<'a> {
+    fn __forge_source(&self) -> Option<&'a (dyn std::error::Error + 'static)>;
+}
+
+impl<'a, T: std::error::Error + 'static> SourceProbeError<'a> for SourceProbe<'a, T> {
+    fn __forge_source(&self) -> Option<&'a (dyn std::error::Error + 'static)> {
+        Some(self.0)
+    }
+}
+
+#[doc(hidden)]
+pub trait SourceProbeFallback<'a> {
+    fn __forge_source(&self) -> Option<&'a (dyn std::error::Error + 'static)>;
+}
+
+impl<'a, T: ?Sized> SourceProbeFallback<'a> for &SourceProbe<'a, T> {
+    fn __forge_source(&self) -> Option<&'a (dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+/// The field of a transparent variant, probed by generated
+/// [`ForgeError`](crate::error::ForgeError) implementations.
+///
+/// Generated code calls `(&ForgeProbe(field)).__forge_error()`: fields
+/// whose type implements `ForgeError` resolve to [`ForgeProbeError`]
+/// and are returned for delegation, and any other field falls back to
+/// [`ForgeProbeFallback`] after autoref, which returns `None`.
+#[doc(hidden)]
+pub struct ForgeProbe<'a, T: ?Sized>(pub &'a T);
+
+#[doc(hidden)]
+pub trait ForgeProbeError<'a> {
+    fn __forge_error(&self) -> Option<&'a dyn crate::error::ForgeError>;
+}
+
+impl<'a, T: crate::error::ForgeError> ForgeProbeError<'a> for ForgeProbe<'a, T> {
+    fn __forge_error(&self) -> Option<&'a dyn crate::error::ForgeError> {
+        Some(self.0)
+    }
+}
+
+#[doc(hidden)]
+pub trait ForgeProbeFallback<'a> {
+    fn __forge_error(&self) -> Option<&'a dyn crate::error::ForgeError>;
+}
+
+impl<'a, T: ?Sized> ForgeProbeFallback<'a> for &ForgeProbe<'a, T> {
+    fn __forge_error(&self) -> Option<&'a dyn crate::error::ForgeError> {
+        None
+    }
+}
+
+/// A wrapped error serialized by generated `group!` implementations.
+///
+/// Generated code calls `(&SerializeProbe(error)).__forge_serializable()`:
+/// errors whose type implements `Serialize` resolve to
+/// [`SerializeProbeValue`] and serialize as themselves, and any other
+/// error falls back to [`SerializeProbeDisplay`] after autoref, which
+/// serializes its `Display` string.
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub struct SerializeProbe<'a, T: ?Sized>(pub &'a T);
+
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub trait SerializeProbeValue<'a, T: ?Sized> {
+    fn __forge_serializable(&self) -> &'a T;
+}
+
+#[cfg(feature = "serde")]
+impl<'a, T: serde::Serialize + ?Sized> SerializeProbeValue<'a, T> for SerializeProbe<'a, T> {
+    fn __forge_serializable(&self) -> &'a T {
+        self.0
+    }
+}
+
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub trait SerializeProbeDisplay {
+    fn __forge_serializable(&self) -> String;
+}
+
+#[cfg(feature = "serde")]
+impl<T: std::fmt::Display + ?Sized> SerializeProbeDisplay for &SerializeProbe<'_, T> {
+    fn __forge_serializable(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// Borrows any error value — sized or `dyn` — as a `'static` error
+/// trait object.
+///
+/// Generated `source()` implementations call this with method syntax,
+/// so auto-deref reaches through `Box<dyn Error + ...>` while concrete
+/// error types match the blanket impl directly.
+#[doc(hidden)]
+pub trait AsDynError<'a> {
+    fn as_dyn_error(&self) -> &(dyn std::error::Error + 'a);
+}
+
+impl<'a, T: std::error::Error + 'a> AsDynError<'a> for T {
+    fn as_dyn_error(&self) -> &(dyn std::error::Error + 'a) {
+        self
+    }
+}
+
+impl<'a> AsDynError<'a> for dyn std::error::Error + 'a {
+    fn as_dyn_error(&self) -> &(dyn std::error::Error + 'a) {
+        self
+    }
+}
+
+impl<'a> AsDynError<'a> for dyn std::error::Error + Send + 'a {
+    fn as_dyn_error(&self) -> &(dyn std::error::Error + 'a) {
+        self
+    }
+}
+
+impl<'a> AsDynError<'a> for dyn std::error::Error + Send + Sync + 'a {
+    fn as_dyn_error(&self) -> &(dyn std::error::Error + 'a) {
+        self
+    }
+}
+
 /// Register a callback to be called when errors are created.
 ///
 /// **Deprecated since `1.0.0`.** This variant silently discards
@@ -189,172 +727,989 @@ where
 }
 
 /// Call the registered error hook with error context if one is registered
+///
+/// The level is derived from the flags with [`ErrorLevel::from_flags`];
+/// [`ForgeError::register`](crate::error::ForgeError::register) uses
+/// [`call_error_hook_with_level`] to pass the error's own severity.
 #[doc(hidden)]
+#[track_caller]
 pub fn call_error_hook(caption: &str, kind: &str, is_fatal: bool, is_retryable: bool) {
-    if let Some(hook) = ERROR_HOOK.get() {
-        // Determine error level based on error properties
-        let level = if is_fatal {
-            ErrorLevel::Critical
-        } else if !is_retryable {
-            ErrorLevel::Error
-        } else if kind == "Warning" {
-            ErrorLevel::Warning
-        } else if kind == "Debug" {
-            ErrorLevel::Debug
-        } else {
-            ErrorLevel::Info
-        };
+    call_error_hook_with_level(
+        ErrorLevel::from_flags(is_fatal, is_retryable),
+        caption,
+        kind,
+        is_fatal,
+        is_retryable,
+    );
+}
 
-        hook(ErrorContext {
-            caption,
-            kind,
-            level,
-            is_fatal,
-            is_retryable,
-        });
+/// Call the registered error hook, reporting `level` as the severity
+///
+/// The kind is first checked against the
+/// [construction policy](crate::policy).
+#[doc(hidden)]
+#[track_caller]
+pub fn call_error_hook_with_level(
+    level: ErrorLevel,
+    caption: &str,
+    kind: &str,
+    is_fatal: bool,
+    is_retryable: bool,
+) {
+    crate::policy::check(kind);
+    dispatch_error_hook(level, caption, kind, is_fatal, is_retryable);
+}
+
+/// Deliver a hook event (or buffer it in a deferred section) without
+/// the policy check; used when replaying buffered events.
+pub(crate) fn dispatch_error_hook(
+    level: ErrorLevel,
+    caption: &str,
+    kind: &str,
+    is_fatal: bool,
+    is_retryable: bool,
+) {
+    if !crate::side_effects::enabled() {
+        return;
+    }
+    if crate::hooks::defer_hook(level, caption, kind, is_fatal, is_retryable) {
+        return;
+    }
+    let context = || ErrorContext::new(caption, kind, level, is_fatal, is_retryable);
+    if let Some(hook) = ERROR_HOOK.get() {
+        hook(context());
     }
+    crate::hooks::notify_observers(&context());
 }
 
 #[macro_export]
 macro_rules! define_errors {
+    // Type parameters take path bounds (`K: Display + Clone + 'static`);
+    // bounds with generic arguments go in the `where` clause, since a
+    // closing `>>` cannot be split here.
     (
         $(
-            $(#[$meta:meta])* $vis:vis enum $name:ident {
-                $(
-                   $(#[error(display = $display:literal $(, $($display_param:ident),* )?)])?
-                   #[kind($kind:ident $(, $($tag:ident = $val:expr),* )?)]
-                   $variant:ident $( { $($field:ident : $ftype:ty),* $(,)? } )?, )*
-            }
+            $(#[$($meta:tt)*])* $vis:vis enum $name:ident
+            $( < $( $param:ident $( : $($bound:ident)::+ $( + $($bounds:ident)::+ )* $( + $lifetime:lifetime )? )? ),+ $(,)? > )?
+            $( where $( $wty:ty : $($wbound:ident)::+ $( < $($wbarg:ty),* > )? $( + $($wbounds:ident)::+ $( < $($wbargs:ty),* > )? )* $( + $wlifetime:lifetime )? ),+ $(,)? )?
+            { $($body:tt)* }
         )*
     ) => {
         $(
-            $(#[$meta])* #[derive(Debug)]
-            #[cfg_attr(feature = "serde", derive(serde::Serialize))]
-            $vis enum $name {
-                $( $variant $( { $($field : $ftype),* } )?, )*
-            }
-
-            impl $name {
-                $(
-                    $crate::__private::pastey::paste! {
-                        pub fn [<$variant:lower>]($($($field : $ftype),*)?) -> Self {
-                            let instance = Self::$variant $( { $($field),* } )?;
-                            // Call the error hook - no need to directly access ERROR_HOOK here
-                            $crate::macros::call_error_hook(
-                                instance.caption(),
-                                instance.kind(),
-                                instance.is_fatal(),
-                                instance.is_retryable()
-                            );
-                            instance
-                        }
-                    }
-                )*
+            $crate::define_errors!(@options [] [compare] [] [$([$($meta)*])*] [$vis] $name
+                [$($( $param $( : $($bound)::+ $( + $($bounds)::+ )* $( + $lifetime )? )? ),+)?]
+                [$($($param),+)?]
+                [$($( $wty : $($wbound)::+ $( < $($wbarg),* > )? $( + $($wbounds)::+ $( < $($wbargs),* > )? )* $( + $wlifetime )?, )+)?]
+                { $($body)* }
+            );
+        )*
+    };
 
-                pub fn caption(&self) -> &'static str {
-                    match self {
-                        $( Self::$variant { .. } => {
-                            define_errors!(@get_caption $kind $(, $($tag = $val),* )?)
-                        } ),*
-                    }
+    // Enum-level options are taken off the attribute list, and the
+    // other attributes are kept for the enum. `#[tracked]` gives struct
+    // variants a hidden per-instance `Instance`. `PartialEq` is generated
+    // unless the enum is tagged `#[compare(skip)]`; a bare `#[compare]`
+    // is accepted and changes nothing.
+    (@options $tracked:tt $compare:tt [$($kept:tt)*] [[tracked] $($attrs:tt)*] $($rest:tt)*) => {
+        $crate::define_errors!(@options [tracked] $compare [$($kept)*] [$($attrs)*] $($rest)*);
+    };
+
+    (@options $tracked:tt $compare:tt [$($kept:tt)*] [[compare] $($attrs:tt)*] $($rest:tt)*) => {
+        $crate::define_errors!(@options $tracked $compare [$($kept)*] [$($attrs)*] $($rest)*);
+    };
+
+    (@options $tracked:tt $compare:tt [$($kept:tt)*] [[compare(skip)] $($attrs:tt)*] $($rest:tt)*) => {
+        $crate::define_errors!(@options $tracked [] [$($kept)*] [$($attrs)*] $($rest)*);
+    };
+
+    (@options $tracked:tt $compare:tt [$($kept:tt)*] [[$($attr:tt)*] $($attrs:tt)*] $($rest:tt)*) => {
+        $crate::define_errors!(@options $tracked $compare [$($kept)* #[$($attr)*]] [$($attrs)*] $($rest)*);
+    };
+
+    (@options $tracked:tt $compare:tt $kept:tt [] [$vis:vis] $name:ident $g:tt $p:tt $w:tt { $($body:tt)* }) => {
+        $crate::define_errors!(@enum_items $kept $tracked $compare $vis $name $g $p $w [$g $p $w $tracked] { $($body)* });
+    };
+
+    // `$generics` repeats the three lists and the `#[tracked]` option
+    // as one token tree, for helpers expanded once per variant.
+    (
+        @enum_items [$(#[$meta:meta])*] $tracked:tt $compare:tt $vis:vis $name:ident [$($g:tt)*] [$($p:ident),*] [$($w:tt)*] $generics:tt
+        {
+            $(
+               $(#[doc = $doc:literal])*
+               $(#[cfg($($cfg:tt)*)])*
+               $(#[doc($($docattr:tt)*)])*
+               $(#[error(display = $display:literal $(, $($display_arg:tt)*)?)])?
+               $(#[kind($kind:ident $(, $($tag:tt)*)?)])?
+               $variant:ident
+               $( ( #[delegate] $dty:ty ) )?
+               $( { $( $(#[$fattr:ident $(($farg:ident))?])* $field:ident : $ftype:ty),* $(,)? } )?, )*
+        }
+    ) => {
+        $crate::define_errors!(@decl $tracked [$(#[$meta])*] [$vis] $name [$($g)*] [$($w)*] {
+            $( [$(#[doc = $doc])* $(#[cfg($($cfg)*)])* $(#[doc($($docattr)*)])*] $variant $( ($dty) )? $( { $($field : $ftype),* } )?, )*
+        });
+
+        impl<$($g)*> $name<$($p),*> where $($w)* {
+            $( $(#[cfg($($cfg)*)])* $crate::define_errors!(@constructor $tracked $variant [$($dty)?] [$($($($tag)*)?)?] $( { $($field : $ftype),* } )?); )*
+
+            $( $(#[cfg($($cfg)*)])* $crate::define_errors!(@sentinel $variant [$($dty)?] $( { $($field),* } )?); )*
+
+            /// Fires the registered error hook for this error.
+            ///
+            /// Constructor functions do this automatically; `const`
+            /// sentinels such as `Self::TIMEOUT` skip it, so call this
+            /// when a sentinel is actually returned or observed.
+            #[track_caller]
+            pub fn register(&self) {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@register_code [$($display)?] [$($doc),*] $( kind = $kind $(, $($tag)*)? )?)
+                    } ),*
                 }
+                $crate::macros::call_error_hook_with_level(
+                    self.severity(),
+                    self.caption(),
+                    self.kind(),
+                    self.is_fatal(),
+                    self.is_retryable()
+                );
+            }
 
-                pub fn kind(&self) -> &'static str {
-                    match self {
-                        $( Self::$variant { .. } => {
-                            stringify!($kind)
-                        } ),*
-                    }
+            pub fn caption(&self) -> &'static str {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], caption, {
+                            $( $crate::define_errors!(@get_caption $kind $(, $($tag)*)?) )?
+                        })
+                    } ),*
                 }
+            }
 
-                pub fn is_retryable(&self) -> bool {
-                    match self {
-                        $( Self::$variant { .. } => {
-                            define_errors!(@get_tag retryable, false $(, $($tag = $val),* )?)
-                        } ),*
-                    }
+            pub fn kind(&self) -> &'static str {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], kind, {
+                            $( stringify!($kind) )?
+                        })
+                    } ),*
                 }
+            }
 
-                pub fn is_fatal(&self) -> bool {
-                    match self {
-                        $( Self::$variant { .. } => {
-                            define_errors!(@get_tag fatal, false $(, $($tag = $val),* )?)
-                        } ),*
-                    }
+            pub fn is_retryable(&self) -> bool {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], is_retryable, {
+                            $crate::define_errors!(@override self, retryable, {
+                                $( $crate::define_errors!(@get_tag retryable, false, kind = $kind $(, $($tag)*)?) )?
+                            })
+                        })
+                    } ),*
                 }
+            }
 
-                pub fn status_code(&self) -> u16 {
-                    match self {
-                        $( Self::$variant { .. } => {
-                            define_errors!(@get_tag status, 500 $(, $($tag = $val),* )?)
-                        } ),*
-                    }
+            pub fn is_fatal(&self) -> bool {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], is_fatal, {
+                            $crate::define_errors!(@override self, fatal, {
+                                $( $crate::define_errors!(@get_tag fatal, false, kind = $kind $(, $($tag)*)?) )?
+                            })
+                        })
+                    } ),*
                 }
+            }
 
-                pub fn exit_code(&self) -> i32 {
-                    match self {
-                        $( Self::$variant { .. } => {
-                            define_errors!(@get_tag exit, 1 $(, $($tag = $val),* )?)
-                        } ),*
-                    }
+            pub fn status_code(&self) -> u16 {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], status_code, {
+                            $crate::define_errors!(@override self, status, {
+                                $( $crate::define_errors!(@get_tag status, 500, kind = $kind $(, $($tag)*)?) )?
+                            })
+                        })
+                    } ),*
                 }
             }
 
-            impl std::fmt::Display for $name {
-                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                    match self {
-                        $( Self::$variant $( { $($field),* } )? => {
-                            $(
-                                #[allow(unused_variables)]
-                                if let Some(display) = define_errors!(@format_display $display $(, $($display_param),*)?) {
-                                    return write!(f, "{}", display);
-                                }
-                            )?
-                            // If no custom display format is provided, use a default format
-                            write!(f, "{}: ", self.caption())?;
-                            write!(f, stringify!($variant))?;
-                            // Format each field with name=value
-                            $( $(
-                                write!(f, " | {} = ", stringify!($field))?
-                                ;
-                                match stringify!($field) {
-                                    "source" => write!(f, "{}", $field)?,
-                                    _ => write!(f, "{:?}", $field)?,
-                                }
-                            ; )* )?
-                            Ok(())
-                        } ),*
-                    }
+            pub fn exit_code(&self) -> i32 {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], exit_code, {
+                            $( $crate::define_errors!(@get_tag exit, 1, kind = $kind $(, $($tag)*)?) )?
+                        })
+                    } ),*
                 }
             }
 
-            impl std::error::Error for $name {
-                fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-                    match self {
-                        $( Self::$variant $( { $($field),* } )? => {
-                            define_errors!(@find_source $( $($field),* )? )
-                        } ),*
-                    }
+            pub fn severity(&self) -> $crate::macros::ErrorLevel {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], severity, {
+                            $( $crate::define_errors!(@get_severity self.is_fatal(), self.is_retryable(), kind = $kind $(, $($tag)*)?) )?
+                        })
+                    } ),*
                 }
             }
-        )*
-    };
 
-    (@find_source) => {
-        None
-    };
+            /// How the error relates to retrying, set with the
+            /// variant's `class` tag. Defaults to `Transient` for
+            /// retryable variants and `Permanent` otherwise.
+            pub fn class(&self) -> $crate::error::ErrorClass {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], class, {
+                            $( $crate::define_errors!(@get_class self.is_retryable(), kind = $kind $(, $($tag)*)?) )?
+                        })
+                    } ),*
+                }
+            }
 
-    (@find_source $field:ident $(, $rest:ident)*) => {
-        define_errors!(@find_source_match $field, $field $(, $rest)*)
-    };
+            /// The documentation link set with the variant's `docs`
+            /// tag, if any.
+            pub fn docs_url(&self) -> Option<String> {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], docs_url, {
+                            $( $crate::define_errors!(@get_tag docs, None::<&str>, kind = $kind $(, $($tag)*)?).map(String::from) )?
+                        })
+                    } ),*
+                }
+            }
 
-    (@find_source_match source, $source_field:ident $(, $rest:ident)*) => {
-        $crate::macros::ErrorSource::as_source($source_field)
-    };
+            /// The stable error code set with the variant's `code`
+            /// tag, if any.
+            pub fn code(&self) -> Option<&str> {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], code, {
+                            $( $crate::define_errors!(@get_tag code, None::<&str>, kind = $kind $(, $($tag)*)?) )?
+                        })
+                    } ),*
+                }
+            }
 
-    (@find_source_match $field_name:ident, $field:ident $(, $rest:ident)*) => {
-        define_errors!(@find_source $($rest),*)
-    };
+            /// The unique ID of this error instance. `None` unless the
+            /// enum is `#[tracked]`, and for unit variants, which carry
+            /// no per-instance state.
+            pub fn error_id(&self) -> Option<$crate::instance::ErrorId> {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], error_id, {
+                            self.__instance().map($crate::instance::Instance::id)
+                        })
+                    } ),*
+                }
+            }
+
+            /// When this error was created. `None` unless the enum is
+            /// `#[tracked]`, and for unit variants.
+            pub fn timestamp(&self) -> Option<std::time::SystemTime> {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], timestamp, {
+                            self.__instance().map($crate::instance::Instance::timestamp)
+                        })
+                    } ),*
+                }
+            }
+
+            /// The source location that called this error's
+            /// constructor. `None` unless the enum is `#[tracked]`, and
+            /// for unit variants.
+            pub fn location(&self) -> Option<&'static std::panic::Location<'static>> {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], location, {
+                            self.__instance().map($crate::instance::Instance::location)
+                        })
+                    } ),*
+                }
+            }
+
+            /// The backtrace captured when this error was created, if
+            /// the `backtrace` feature is on and `RUST_BACKTRACE`
+            /// enabled capture. `None` unless the enum is `#[tracked]`,
+            /// and for unit variants.
+            pub fn backtrace(&self) -> Option<&std::backtrace::Backtrace> {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], backtrace, {
+                            self.__instance().and_then($crate::instance::Instance::backtrace)
+                        })
+                    } ),*
+                }
+            }
+
+            /// Returns true if both errors are the same variant,
+            /// whatever their field values.
+            #[allow(dead_code)]
+            pub fn matches_kind(&self, other: &Self) -> bool {
+                std::mem::discriminant(self) == std::mem::discriminant(other)
+            }
+
+            $crate::define_errors!(@builders $tracked $name);
+
+            #[doc(hidden)]
+            pub fn __instance(&self) -> Option<&$crate::instance::Instance> {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@instance $tracked self, $variant $( { $($field),* } )?)
+                    } ),*
+                }
+            }
+
+            #[doc(hidden)]
+            #[allow(dead_code)]
+            fn __instance_mut(&mut self) -> Option<&mut $crate::instance::Instance> {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@instance $tracked self, $variant $( { $($field),* } )?)
+                    } ),*
+                }
+            }
+        }
+
+        $( $(#[cfg($($cfg)*)])* $crate::define_errors!(@from_delegate $generics $name, $variant, [$($dty)?]); )*
+
+        $( $(#[cfg($($cfg)*)])* $crate::define_errors!(@from $generics $name, $variant $( { $($field : $ftype [$(#[$fattr $(($farg))?])*]),* } )?); )*
+
+        impl<$($g)*> $crate::error::ForgeError for $name<$($p),*>
+        where
+            $($w)*
+            $($p: std::fmt::Debug + Send + Sync + 'static,)*
+        {
+            fn kind(&self) -> &'static str {
+                $name::kind(self)
+            }
+
+            fn caption(&self) -> &'static str {
+                $name::caption(self)
+            }
+
+            fn is_retryable(&self) -> bool {
+                $name::is_retryable(self)
+            }
+
+            fn is_fatal(&self) -> bool {
+                $name::is_fatal(self)
+            }
+
+            fn status_code(&self) -> u16 {
+                $name::status_code(self)
+            }
+
+            fn exit_code(&self) -> i32 {
+                $name::exit_code(self)
+            }
+
+            fn severity(&self) -> $crate::macros::ErrorLevel {
+                $name::severity(self)
+            }
+
+            fn class(&self) -> $crate::error::ErrorClass {
+                $name::class(self)
+            }
+
+            fn user_message(&self) -> String {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], user_message, {
+                            $crate::i18n::localize(self, None $( .or($crate::define_errors!(@get_tag msg_key, None::<&str>, kind = $kind $(, $($tag)*)?)) )?)
+                        })
+                    } ),*
+                }
+            }
+
+            fn dev_message(&self) -> String {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], dev_message, {
+                            $crate::error::default_dev_message(self)
+                        })
+                    } ),*
+                }
+            }
+
+            fn code(&self) -> Option<&str> {
+                $name::code(self)
+            }
+
+            fn metadata(&self) -> &$crate::metadata::Metadata {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], metadata, {
+                            match self.__instance() {
+                                Some(instance) => instance.metadata(),
+                                None => $crate::metadata::Metadata::empty(),
+                            }
+                        })
+                    } ),*
+                }
+            }
+
+            fn docs_url(&self) -> Option<String> {
+                $name::docs_url(self)
+            }
+
+            fn retry_after(&self) -> Option<std::time::Duration> {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* Self::$variant { .. } => {
+                        $crate::define_errors!(@dispatch self, $variant, [$($dty)?], retry_after, { None })
+                    } ),*
+                }
+            }
+
+            fn error_id(&self) -> Option<$crate::instance::ErrorId> {
+                $name::error_id(self)
+            }
+
+            fn timestamp(&self) -> Option<std::time::SystemTime> {
+                $name::timestamp(self)
+            }
+
+            fn backtrace(&self) -> Option<&std::backtrace::Backtrace> {
+                $name::backtrace(self)
+            }
+
+            fn location(&self) -> Option<&'static std::panic::Location<'static>> {
+                $name::location(self)
+            }
+        }
+
+        impl<$($g)*> $crate::lint::ErrorTable for $name<$($p),*> where $($w)* {
+            fn variants() -> Vec<$crate::lint::VariantInfo> {
+                #[allow(unused_mut)]
+                let mut variants = Vec::new();
+                $( $(#[cfg($($cfg)*)])* {
+                    // Unused by `#[delegate]` variants, which are not listed.
+                    #[allow(unused_variables)]
+                    let fields: &'static [(&'static str, &'static str)] =
+                        &[$($( (stringify!($field), stringify!($ftype)) ),*)?];
+                    #[allow(unused_variables)]
+                    let message: Option<&'static str> = None $( .or(Some($display)) )?;
+                    $(
+                    variants.push($crate::lint::VariantInfo::new(stringify!($variant), stringify!($kind))
+                        .with_message(message)
+                        .with_fields(fields)
+                        .with_caption($crate::define_errors!(@get_caption $kind $(, $($tag)*)?))
+                        .with_code($crate::define_errors!(@get_tag code, None::<&'static str>, kind = $kind $(, $($tag)*)?))
+                        .with_status_code($crate::define_errors!(@get_tag status, 500, kind = $kind $(, $($tag)*)?))
+                        .with_exit_code($crate::define_errors!(@get_tag exit, 1, kind = $kind $(, $($tag)*)?))
+                        .with_flags(
+                            $crate::define_errors!(@get_tag retryable, false, kind = $kind $(, $($tag)*)?),
+                            $crate::define_errors!(@get_tag fatal, false, kind = $kind $(, $($tag)*)?),
+                        )
+                        .with_class($crate::define_errors!(@get_class
+                            $crate::define_errors!(@get_tag retryable, false, kind = $kind $(, $($tag)*)?),
+                            kind = $kind $(, $($tag)*)?))
+                        .with_severity($crate::define_errors!(@get_severity
+                            $crate::define_errors!(@get_tag fatal, false, kind = $kind $(, $($tag)*)?),
+                            $crate::define_errors!(@get_tag retryable, false, kind = $kind $(, $($tag)*)?),
+                            kind = $kind $(, $($tag)*)?))
+                        .with_docs_url($crate::define_errors!(@get_tag docs, None::<&'static str>, kind = $kind $(, $($tag)*)?)));
+                    )?
+                } )*
+                variants
+            }
+        }
+
+        impl<$($g)*> std::fmt::Display for $name<$($p),*>
+        where
+            $($w)*
+            $($p: std::fmt::Debug,)*
+        {
+            // Delegating and documented variants return before the
+            // default format.
+            #[allow(unused_variables, unreachable_code)]
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* $crate::define_errors!(@pattern $variant [$(__delegate $dty)?] $( { $($field),* } )?) => {
+                        $(
+                            return std::fmt::Display::fmt($crate::define_errors!(@first __delegate, $dty), f);
+                        )?
+                        $(
+                            #[allow(unused_variables)]
+                            if let Some(display) = $crate::define_errors!(@format_display $display $(, $($display_arg)*)?) {
+                                return write!(f, "{}", display);
+                            }
+                        )?
+                        $crate::define_errors!(@doc_display f, [$($doc),*]);
+                        // If no custom display format is provided, use a default format
+                        write!(f, "{}: ", self.caption())?;
+                        write!(f, stringify!($variant))?;
+                        // Format each field with name=value
+                        $( $(
+                            write!(f, " | {} = ", stringify!($field))?;
+                            $crate::define_errors!(@default_field f, $field, $field)?;
+                        )* )?
+                        Ok(())
+                    } ),*
+                }
+            }
+        }
+
+        // Unless `#[compare(skip)]`: same variant and equal fields. The hidden
+        // instance (id, timestamp, location) and `#[compare(skip)]`
+        // fields are ignored; every other field must be `PartialEq`.
+        $crate::define_errors!(@if_compare $compare {
+        impl<$($g)*> PartialEq for $name<$($p),*>
+        where
+            $($w)*
+            $($p: PartialEq,)*
+        {
+            fn eq(&self, other: &Self) -> bool {
+                $crate::__private::pastey::paste! {
+                    match (self, other) {
+                        $( $(#[cfg($($cfg)*)])* (
+                            $crate::define_errors!(@pattern $variant [$(__lhs_delegate $dty)?] $( { $($field: [<__lhs_ $field>]),* } )?),
+                            $crate::define_errors!(@pattern $variant [$(__rhs_delegate $dty)?] $( { $($field: [<__rhs_ $field>]),* } )?),
+                        ) => {
+                            true
+                            $( && $crate::define_errors!(@field_eq __lhs_delegate, __rhs_delegate, type $dty) )?
+                            $( $( && $crate::define_errors!(@field_eq [<__lhs_ $field>], [<__rhs_ $field>], [$(#[$fattr $(($farg))?])*]) )* )?
+                        } )*
+                        #[allow(unreachable_patterns)]
+                        _ => false,
+                    }
+                }
+            }
+        }
+        });
+
+        impl<$($g)*> std::error::Error for $name<$($p),*>
+        where
+            $($w)*
+            $($p: std::fmt::Debug + 'static,)*
+        {
+            // Delegating variants return before the field search.
+            #[allow(unused_variables, unreachable_code)]
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                match self {
+                    $( $(#[cfg($($cfg)*)])* $crate::define_errors!(@pattern $variant [$(__delegate $dty)?] $( { $($field),* } )?) => {
+                        $(
+                            return Some($crate::define_errors!(@first __delegate, $dty) as &(dyn std::error::Error + 'static));
+                        )?
+                        $crate::define_errors!(@find_source $( $($field [$(#[$fattr $(($farg))?])*]),* )? )
+                    } ),*
+                }
+            }
+        }
+    };
+
+    // The `constructor` tag picks the constructor: `constructor = false`
+    // skips it, and `constructor = pub(crate) make_config` sets its
+    // visibility and name as a `fn` item would. Without the tag it is
+    // `pub` and named after the variant in lowercase.
+    (@constructor $tracked:tt $variant:ident $dty:tt [constructor = false $(, $($rest:tt)*)?] $($fields:tt)?) => {};
+
+    (@constructor $tracked:tt $variant:ident $dty:tt [constructor = $vis:vis $name:ident $(, $($rest:tt)*)?] $($fields:tt)?) => {
+        $crate::define_errors!(@constructor_fn $tracked $variant [$vis] $name $dty $($fields)?);
+    };
+
+    (@constructor $tracked:tt $variant:ident $dty:tt [$tag:ident = $val:expr $(, $($rest:tt)*)?] $($fields:tt)?) => {
+        $crate::define_errors!(@constructor $tracked $variant $dty [$($($rest)*)?] $($fields)?);
+    };
+
+    (@constructor $tracked:tt $variant:ident $dty:tt [] $($fields:tt)?) => {
+        $crate::__private::pastey::paste! {
+            $crate::define_errors!(@constructor_fn $tracked $variant [pub] [<$variant:lower>] $dty $($fields)?);
+        }
+    };
+
+    // `Variant(#[delegate] Payload)` wraps another `ForgeError`; its
+    // constructor takes the payload, which already ran the error hook.
+    (@constructor_fn $tracked:tt $variant:ident [$vis:vis] $name:ident [$dty:ty]) => {
+        #[doc = concat!("Wraps a payload error in [`Self::", stringify!($variant), "`].")]
+        $vis fn $name(error: $dty) -> Self {
+            Self::$variant(error)
+        }
+    };
+
+    (@constructor_fn $tracked:tt $variant:ident [$vis:vis] $name:ident [] $( { $($field:ident : $ftype:ty),* } )?) => {
+        #[track_caller]
+        $vis fn $name($($($field : $ftype),*)?) -> Self {
+            let instance = $crate::define_errors!(@new $tracked $variant $( { $($field),* } )?);
+            instance.register();
+            instance
+        }
+    };
+
+    // A method body for one variant: delegating variants forward to the
+    // payload's `ForgeError` method, the rest use their own expression.
+    (@dispatch $self:ident, $variant:ident, [$dty:ty], $method:ident, { $($own:tt)* }) => {
+        match $self {
+            Self::$variant(payload) => $crate::error::ForgeError::$method(payload),
+            #[allow(unreachable_patterns)]
+            _ => unreachable!(),
+        }
+    };
+
+    (@dispatch $self:ident, $variant:ident, [], $method:ident, {}) => {
+        compile_error!(concat!(
+            "define_errors!: variant `",
+            stringify!($variant),
+            "` needs a #[kind(...)] attribute or a #[delegate] payload"
+        ))
+    };
+
+    (@dispatch $self:ident, $variant:ident, [], $method:ident, { $($own:tt)+ }) => {
+        $($own)+
+    };
+
+    // Patterns binding a delegating variant's payload, or a struct
+    // variant's fields (optionally renamed).
+    (@pattern $variant:ident [$bind:ident $dty:ty]) => {
+        Self::$variant($bind)
+    };
+
+    (@pattern $variant:ident []) => {
+        Self::$variant
+    };
+
+    (@pattern $variant:ident [] { $($field:ident $(: $bind:ident)?),* }) => {
+        Self::$variant { $($field $(: $bind)?,)* .. }
+    };
+
+    (@first $first:ident, $($rest:tt)*) => {
+        $first
+    };
+
+    // Unit variants get a `const` sentinel named after the variant in
+    // SCREAMING_SNAKE_CASE; it is built without running the error hook.
+    (@sentinel $variant:ident []) => {
+        $crate::__private::pastey::paste! {
+            #[doc = concat!("`const` sentinel for [`Self::", stringify!($variant), "`], usable in `const` and `static` items.")]
+            #[doc = ""]
+            #[doc = "# Error hook"]
+            #[doc = ""]
+            #[doc = "**Using this constant never fires the error hook**, and never"]
+            #[doc = "registers the variant's `code`: not when it is built, returned,"]
+            #[doc = "displayed, or converted. Hook subscribers, loggers, and metrics"]
+            #[doc = "do not see it. Call [`register()`](Self::register) where the"]
+            #[doc = "error is actually returned, or use the variant's constructor,"]
+            #[doc = "which does both."]
+            pub const [<$variant:snake:upper>]: Self = Self::$variant;
+        }
+    };
+
+    (@sentinel $variant:ident [] { $($field:ident),* }) => {};
+
+    (@sentinel $variant:ident [$dty:ty]) => {};
+
+    // The first time a variant with a `code` tag is registered, its code
+    // joins the global registry. Delegating variants have no tags.
+    (@register_code [$($display:literal)?] [$($doc:literal),*]) => {{}};
+
+    (@register_code [$($display:literal)?] [$($doc:literal),*] kind = $kind:ident $(, $($tag:tt)*)?) => {{
+        if let Some(code) = $crate::define_errors!(@get_tag code, None::<&'static str>, kind = $kind $(, $($tag)*)?) {
+            static REGISTERED: std::sync::Once = std::sync::Once::new();
+            REGISTERED.call_once(|| {
+                $crate::macros::register_variant_code(
+                    code,
+                    None $( .or(Some($display)) )?,
+                    &[$($doc),*],
+                    $crate::define_errors!(@get_caption $kind $(, $($tag)*)?),
+                    $crate::define_errors!(@get_tag docs, None::<&'static str>, kind = $kind $(, $($tag)*)?),
+                    $crate::define_errors!(@get_tag retryable, false, kind = $kind $(, $($tag)*)?),
+                )
+            });
+        }
+    }};
+
+    // A tag overridden by a `with_*` builder, or the variant's own.
+    (@override $self:ident, $tag:ident, { $($default:tt)* }) => {
+        match $self.__instance().and_then(|instance| instance.overrides().$tag) {
+            Some(value) => value,
+            None => { $($default)* }
+        }
+    };
+
+    // Struct variants of `#[tracked]` enums carry a hidden `Instance`;
+    // unit variants and other enums have none.
+    (@instance [tracked] $self:ident, $variant:ident { $($field:ident),* }) => {
+        match $self {
+            Self::$variant { __forge, .. } => Some(__forge),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    };
+
+    (@instance $tracked:tt $self:ident, $variant:ident $($fields:tt)?) => {
+        None
+    };
+
+    // A new value of a unit or struct variant, with a fresh `Instance`
+    // if the enum is `#[tracked]`.
+    (@new [tracked] $variant:ident { $($field:ident),* }) => {
+        Self::$variant {
+            $($field,)*
+            __forge: $crate::instance::Instance::new(),
+        }
+    };
+
+    (@new $tracked:tt $variant:ident $({ $($field:ident),* })?) => {
+        Self::$variant $({ $($field),* })?
+    };
+
+    // The enum itself. `#[tracked]` struct variants get the hidden
+    // `Instance` field, declared through a helper that follows this
+    // crate's `serde` feature.
+    (@decl [] [$($meta:tt)*] [$vis:vis] $name:ident [$($g:tt)*] [$($w:tt)*] {
+        $( [$($vattr:tt)*] $variant:ident $( ($dty:ty) )? $( { $($field:ident : $ftype:ty),* } )?, )*
+    }) => {
+        $($meta)* #[derive(Debug)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+        $vis enum $name<$($g)*> where $($w)* {
+            $( $($vattr)* $variant $( ($dty) )? $( { $($field : $ftype),* } )?, )*
+        }
+    };
+
+    (@decl [tracked] $($rest:tt)*) => {
+        $crate::__define_errors_tracked!($($rest)*);
+    };
+
+    (@decl_tracked [$serde:ident] [$($meta:tt)*] [$vis:vis] $name:ident [$($g:tt)*] [$($w:tt)*] {
+        $( [$($vattr:tt)*] $variant:ident $( ($dty:ty) )? $( { $($field:ident : $ftype:ty),* } )?, )*
+    }) => {
+        $($meta)* #[derive(Debug)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+        $vis enum $name<$($g)*> where $($w)* {
+            $( $($vattr)* $variant $( ($dty) )? $( {
+                $($field : $ftype,)*
+                #[doc(hidden)]
+                #[cfg_attr(feature = "serde", serde($serde))]
+                __forge: $crate::instance::Instance
+            } )?, )*
+        }
+    };
+
+    // Builders overriding tags for one instance, stored in the hidden
+    // `Instance` of a `#[tracked]` enum. Other enums get the builders
+    // too, but with a bound that is never met, so calling one is a
+    // compile error rather than a no-op.
+    (@builders [] $name:ident) => {
+        /// Override whether this error is retryable. Needs a
+        /// `#[tracked]` enum; here, calling it does not compile.
+        #[allow(dead_code)]
+        pub fn with_retryable(self, _retryable: bool) -> Self
+        where
+            for<'a> &'a Self: $crate::macros::TrackedOverride,
+        {
+            self
+        }
+
+        /// Override whether this error is fatal. Needs a
+        /// `#[tracked]` enum; here, calling it does not compile.
+        #[allow(dead_code)]
+        pub fn with_fatal(self, _fatal: bool) -> Self
+        where
+            for<'a> &'a Self: $crate::macros::TrackedOverride,
+        {
+            self
+        }
+
+        /// Override the HTTP status code for this error. Needs a
+        /// `#[tracked]` enum; here, calling it does not compile.
+        #[allow(dead_code)]
+        pub fn with_status(self, _status: u16) -> Self
+        where
+            for<'a> &'a Self: $crate::macros::TrackedOverride,
+        {
+            self
+        }
+    };
+
+    (@builders [tracked] $name:ident) => {
+        /// Override whether this error is retryable.
+        ///
+        /// # Panics
+        ///
+        /// On unit and delegating variants, which carry no
+        /// per-instance state to store the override in.
+        #[allow(dead_code)]
+        #[track_caller]
+        pub fn with_retryable(mut self, retryable: bool) -> Self {
+            self.__overrides_mut("with_retryable").retryable = Some(retryable);
+            self
+        }
+
+        /// Override whether this error is fatal.
+        ///
+        /// # Panics
+        ///
+        /// On unit and delegating variants.
+        #[allow(dead_code)]
+        #[track_caller]
+        pub fn with_fatal(mut self, fatal: bool) -> Self {
+            self.__overrides_mut("with_fatal").fatal = Some(fatal);
+            self
+        }
+
+        /// Override the HTTP status code for this error.
+        ///
+        /// # Panics
+        ///
+        /// On unit and delegating variants.
+        #[allow(dead_code)]
+        #[track_caller]
+        pub fn with_status(mut self, status: u16) -> Self {
+            self.__overrides_mut("with_status").status = Some(status);
+            self
+        }
+
+        #[allow(dead_code)]
+        #[track_caller]
+        fn __overrides_mut(&mut self, builder: &str) -> &mut $crate::instance::Overrides {
+            let kind = self.kind();
+            match self.__instance_mut() {
+                Some(instance) => instance.overrides_mut(),
+                None => panic!(
+                    "{}::{builder} called on a unit or delegating variant (kind `{kind}`), which has no per-instance state to store the override",
+                    stringify!($name)
+                ),
+            }
+        }
+    };
+
+    (@if_compare [compare] { $($item:tt)* }) => {
+        $($item)*
+    };
+
+    (@if_compare [] $item:tt) => {};
+
+    (@field_eq $lhs:ident, $rhs:ident) => {
+        $lhs == $rhs
+    };
+
+    // Field attributes: `#[compare(...)]` picks the comparison, any
+    // other attribute is skipped.
+    (@field_eq $lhs:ident, $rhs:ident, []) => {
+        $crate::define_errors!(@field_eq $lhs, $rhs)
+    };
+
+    (@field_eq $lhs:ident, $rhs:ident, [#[compare($cmp:ident)] $($rest:tt)*]) => {
+        $crate::define_errors!(@field_eq $lhs, $rhs, $cmp)
+    };
+
+    (@field_eq $lhs:ident, $rhs:ident, [#[$attr:ident $(($arg:ident))?] $($rest:tt)*]) => {
+        $crate::define_errors!(@field_eq $lhs, $rhs, [$($rest)*])
+    };
+
+    (@field_eq $lhs:ident, $rhs:ident, skip) => {
+        true
+    };
+
+    (@field_eq $lhs:ident, $rhs:ident, type $dty:ty) => {
+        $lhs == $rhs
+    };
+
+    // `source()` is the field marked `#[source]` or `#[from]`, else the
+    // field named `source`, else the first field whose type is an
+    // error, else `None`.
+    (@find_source $($field:ident [$($attr:tt)*]),*) => {
+        $crate::define_errors!(@marked_source [$($field [$($attr)*])*] [$($field),*])
+    };
+
+    (@marked_source [] [$($field:ident),*]) => {
+        $crate::define_errors!(@named_source [$($field),*] $($field),*)
+    };
+
+    (@marked_source [$field:ident [#[from] $($attr:tt)*] $($rest:tt)*] $names:tt) => {
+        $crate::define_errors!(@marked_source [$field [#[source]] $($rest)*] $names)
+    };
+
+    (@marked_source [$field:ident [#[source] $($attr:tt)*] $($rest:tt)*] $names:tt) => {{
+        use $crate::__private::AsDynError as _;
+        Some($field.as_dyn_error())
+    }};
+
+    (@marked_source [$field:ident [#[$attr:ident $(($arg:ident))?] $($attrs:tt)*] $($rest:tt)*] $names:tt) => {
+        $crate::define_errors!(@marked_source [$field [$($attrs)*] $($rest)*] $names)
+    };
+
+    (@marked_source [$field:ident [] $($rest:tt)*] $names:tt) => {
+        $crate::define_errors!(@marked_source [$($rest)*] $names)
+    };
+
+    (@named_source [$($all:ident),*]) => {
+        $crate::define_errors!(@typed_source $($all),*)
+    };
+
+    (@named_source $all:tt $field:ident $(, $rest:ident)*) => {
+        $crate::define_errors!(@named_source_match $all, $field, $field $(, $rest)*)
+    };
+
+    (@named_source_match $all:tt, source, $source_field:ident $(, $rest:ident)*) => {
+        $crate::macros::ErrorSource::as_source($source_field)
+    };
+
+    (@named_source_match $all:tt, $field_name:ident, $field:ident $(, $rest:ident)*) => {
+        $crate::define_errors!(@named_source $all $($rest),*)
+    };
+
+    (@typed_source $($field:ident),*) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::{SourceProbeError as _, SourceProbeFallback as _};
+        None $( .or_else(|| (&$crate::__private::SourceProbe($field)).__forge_source()) )*
+    }};
+
+    // `#[from]` on a variant's only field generates `From<FieldType>`
+    // through the `#[track_caller]` constructor, so `?` records its own
+    // line and fires the error hook.
+    (@from $generics:tt $name:ident, $variant:ident) => {};
+
+    (@from $generics:tt $name:ident, $variant:ident { $field:ident : $ftype:ty [$($attr:tt)*] }) => {
+        $crate::define_errors!(@from_field $generics $name, $variant, $field : $ftype, [$($attr)*]);
+    };
+
+    (@from $generics:tt $name:ident, $variant:ident { $($field:ident : $ftype:ty [$($attr:tt)*]),* }) => {
+        $( $crate::define_errors!(@from_reject $variant, $field, [$($attr)*]); )*
+    };
+
+    (@from_field $generics:tt $name:ident, $variant:ident, $field:ident : $ftype:ty, []) => {};
+
+    (@from_field [[$($g:tt)*] [$($p:ident),*] [$($w:tt)*] $tracked:tt] $name:ident, $variant:ident, $field:ident : $ftype:ty, [#[from] $($rest:tt)*]) => {
+        impl<$($g)*> From<$ftype> for $name<$($p),*> where $($w)* {
+            #[track_caller]
+            fn from($field: $ftype) -> Self {
+                let instance = $crate::define_errors!(@new $tracked $variant { $field });
+                instance.register();
+                instance
+            }
+        }
+    };
+
+    (@from_field $generics:tt $name:ident, $variant:ident, $field:ident : $ftype:ty, [#[$attr:ident $(($arg:ident))?] $($rest:tt)*]) => {
+        $crate::define_errors!(@from_field $generics $name, $variant, $field : $ftype, [$($rest)*]);
+    };
+
+    // A delegating variant converts from its payload.
+    (@from_delegate $generics:tt $name:ident, $variant:ident, []) => {};
+
+    (@from_delegate [[$($g:tt)*] [$($p:ident),*] [$($w:tt)*] $tracked:tt] $name:ident, $variant:ident, [$dty:ty]) => {
+        impl<$($g)*> From<$dty> for $name<$($p),*> where $($w)* {
+            fn from(error: $dty) -> Self {
+                Self::$variant(error)
+            }
+        }
+    };
+
+    (@from_reject $variant:ident, $field:ident, []) => {};
+
+    (@from_reject $variant:ident, $field:ident, [#[from] $($rest:tt)*]) => {
+        compile_error!(concat!(
+            "define_errors!: `#[from]` on `",
+            stringify!($variant),
+            "::",
+            stringify!($field),
+            "` requires it to be the variant's only field"
+        ));
+    };
+
+    (@from_reject $variant:ident, $field:ident, [#[$attr:ident $(($arg:ident))?] $($rest:tt)*]) => {
+        $crate::define_errors!(@from_reject $variant, $field, [$($rest)*]);
+    };
 
     (@get_caption $kind:ident) => {
         stringify!($kind)
@@ -364,14 +1719,60 @@ macro_rules! define_errors {
         $caption
     };
 
+    // `constructor = pub(crate) name` is not an expression; every tag
+    // scan steps over it before trying `$val:expr`.
+    (@get_caption $kind:ident, constructor = $cvis:vis $cname:ident $(, $($rest:tt)*)?) => {
+        $crate::define_errors!(@get_caption $kind $(, $($rest)*)?)
+    };
+
     (@get_caption $kind:ident, $tag:ident = $val:expr $(, $($rest:tt)*)?) => {
-        define_errors!(@get_caption $kind $(, $($rest)*)?)
+        $crate::define_errors!(@get_caption $kind $(, $($rest)*)?)
     };
 
     (@get_tag $target:ident, $default:expr) => {
         $default
     };
 
+    // `severity = Warning` (or any `ErrorLevel` expression); defaults to
+    // `ErrorLevel::from_flags` over the variant's fatal/retryable tags.
+    (@get_severity $fatal:expr, $retryable:expr) => {
+        $crate::macros::ErrorLevel::from_flags($fatal, $retryable)
+    };
+
+    (@get_severity $fatal:expr, $retryable:expr, severity = $val:expr $(, $($rest:tt)*)?) => {{
+        #[allow(unused_imports)]
+        use $crate::macros::ErrorLevel::{self, *};
+        $val
+    }};
+
+    (@get_severity $fatal:expr, $retryable:expr, constructor = $cvis:vis $cname:ident $(, $($rest:tt)*)?) => {
+        $crate::define_errors!(@get_severity $fatal, $retryable $(, $($rest)*)?)
+    };
+
+    (@get_severity $fatal:expr, $retryable:expr, $tag:ident = $val:expr $(, $($rest:tt)*)?) => {
+        $crate::define_errors!(@get_severity $fatal, $retryable $(, $($rest)*)?)
+    };
+
+    // `class = RateLimited` (or any `ErrorClass` expression); defaults
+    // to `ErrorClass::from_retryable` over the variant's retryable tag.
+    (@get_class $retryable:expr) => {
+        $crate::error::ErrorClass::from_retryable($retryable)
+    };
+
+    (@get_class $retryable:expr, class = $val:expr $(, $($rest:tt)*)?) => {{
+        #[allow(unused_imports)]
+        use $crate::error::ErrorClass::{self, *};
+        $val
+    }};
+
+    (@get_class $retryable:expr, constructor = $cvis:vis $cname:ident $(, $($rest:tt)*)?) => {
+        $crate::define_errors!(@get_class $retryable $(, $($rest)*)?)
+    };
+
+    (@get_class $retryable:expr, $tag:ident = $val:expr $(, $($rest:tt)*)?) => {
+        $crate::define_errors!(@get_class $retryable $(, $($rest)*)?)
+    };
+
     (@get_tag retryable, $default:expr, retryable = $val:expr $(, $($rest:tt)*)?) => {
         $val
     };
@@ -388,24 +1789,344 @@ macro_rules! define_errors {
         $val
     };
 
+    (@get_tag docs, $default:expr, docs = $val:expr $(, $($rest:tt)*)?) => {
+        Some($val)
+    };
+
+    (@get_tag code, $default:expr, code = $val:expr $(, $($rest:tt)*)?) => {
+        Some($val)
+    };
+
+    (@get_tag msg_key, $default:expr, msg_key = $val:expr $(, $($rest:tt)*)?) => {
+        Some($val)
+    };
+
+    // Every scan starts at `kind = ...`; a `profile` tag anywhere in
+    // the list replaces the default before the variant's own tags are
+    // searched.
+    (@get_tag $target:ident, $default:expr, kind = $kind:ident $(, $($rest:tt)*)?) => {
+        $crate::define_errors!(@get_tag $target, $crate::define_errors!(@get_profile $target, $default $(, $($rest)*)?) $(, $($rest)*)?)
+    };
+
+    (@get_tag $target:ident, $default:expr, constructor = $cvis:vis $cname:ident $(, $($rest:tt)*)?) => {
+        $crate::define_errors!(@get_tag $target, $default $(, $($rest)*)?)
+    };
+
     (@get_tag $target:ident, $default:expr, $tag:ident = $val:expr $(, $($rest:tt)*)?) => {
-        define_errors!(@get_tag $target, $default $(, $($rest)*)?)
+        $crate::define_errors!(@get_tag $target, $default $(, $($rest)*)?)
+    };
+
+    (@get_profile $target:ident, $default:expr) => {
+        $default
+    };
+
+    (@get_profile status, $default:expr, profile = $profile:expr $(, $($rest:tt)*)?) => {
+        $profile.status
+    };
+
+    (@get_profile exit, $default:expr, profile = $profile:expr $(, $($rest:tt)*)?) => {
+        $profile.exit
+    };
+
+    (@get_profile retryable, $default:expr, profile = $profile:expr $(, $($rest:tt)*)?) => {
+        $profile.retryable
+    };
+
+    (@get_profile fatal, $default:expr, profile = $profile:expr $(, $($rest:tt)*)?) => {
+        $profile.fatal
+    };
+
+    (@get_profile $target:ident, $default:expr, constructor = $cvis:vis $cname:ident $(, $($rest:tt)*)?) => {
+        $crate::define_errors!(@get_profile $target, $default $(, $($rest)*)?)
+    };
+
+    (@get_profile $target:ident, $default:expr, $tag:ident = $val:expr $(, $($rest:tt)*)?) => {
+        $crate::define_errors!(@get_profile $target, $default $(, $($rest)*)?)
+    };
+
+    // Without `#[error(display = ...)]`, the first paragraph of the
+    // variant's doc comment is the message.
+    (@doc_display $f:ident, []) => {};
+
+    (@doc_display $f:ident, [$($doc:literal),+]) => {
+        return $crate::macros::write_doc_display($f, &[$($doc),+])
+    };
+
+    // The default display shows a `source` field with `Display` and
+    // every other field with `Debug`.
+    (@default_field $f:ident, source, $field:ident) => {
+        write!($f, "{}", $field)
+    };
+
+    (@default_field $f:ident, $name:ident, $field:ident) => {
+        write!($f, "{:?}", $field)
+    };
+
+    // The display string captures the variant's fields by name, with
+    // any format spec (`{path:?}`, `{count:>5}`). Listing a field after
+    // the string is accepted for compatibility. Format strings cannot
+    // name nested values, and the compiler rejects `{config.host}`;
+    // `name = expr` arguments (`host = config.host`) or positional
+    // ones (`"{0}", config.host`) reach them instead.
+    (@format_display $display:literal $(,)?) => {
+        Some(format!($display))
+    };
+
+    (@format_display $display:literal, $($args:tt)+) => {
+        $crate::define_errors!(@format_args $display [] $($args)+)
+    };
+
+    (@format_args $display:literal [$($done:tt)*] $name:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::define_errors!(@format_args $display [$($done)* $name = $value,] $($($rest)*)?)
+    };
+
+    (@format_args $display:literal [$($done:tt)*] $name:ident $(, $($rest:tt)*)?) => {
+        $crate::define_errors!(@format_args $display [$($done)* $name = $name,] $($($rest)*)?)
+    };
+
+    (@format_args $display:literal [$($done:tt)*] $value:expr $(, $($rest:tt)*)?) => {
+        $crate::define_errors!(@format_args $display [$($done)* $value,] $($($rest)*)?)
+    };
+
+    (@format_args $display:literal [$($done:tt)*]) => {
+        Some(format!($display, $($done)*))
     };
+}
+
+/// Declare a `#[tracked]` enum for [`define_errors!`].
+///
+/// The hidden `Instance` field is flattened into the serialized error
+/// when this crate's `serde` feature makes it `Serialize`, and skipped
+/// otherwise, whatever features the calling crate has.
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __define_errors_tracked {
+    ($($rest:tt)*) => {
+        $crate::define_errors!(@decl_tracked [flatten] $($rest)*);
+    };
+}
+
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __define_errors_tracked {
+    ($($rest:tt)*) => {
+        $crate::define_errors!(@decl_tracked [skip] $($rest)*);
+    };
+}
+
+/// Build an error from a format string.
+///
+/// `forge_err!("format", args...)` creates an
+/// [`AppError::Other`](crate::AppError::Other) with the formatted
+/// message, like `anyhow!` but typed. `forge_err!(constructor,
+/// "format", args...)` passes the message to any constructor taking a
+/// `String`, such as `AppError::config` or one generated by
+/// `define_errors!`. Those constructors are `#[track_caller]`, so a
+/// `#[tracked]` enum records the `forge_err!` line as its location.
+///
+/// ```
+/// use error_forge::{define_errors, forge_err, AppError, ForgeError};
+///
+/// let path = "app.toml";
+/// let err = forge_err!("failed to parse {path}");
+/// assert_eq!(err.kind(), "Other");
+/// assert!(err.to_string().contains("failed to parse app.toml"));
+///
+/// let err = forge_err!(AppError::config, "missing key {}", "port");
+/// assert_eq!(err.kind(), "Config");
+///
+/// define_errors! {
+///     #[tracked]
+///     pub enum ParseError {
+///         #[error(display = "{message}")]
+///         #[kind(Parse)]
+///         Syntax { message: String },
+///     }
+/// }
+///
+/// let (err, line) = (forge_err!(ParseError::syntax, "bad token"), line!());
+/// assert_eq!(err.location().unwrap().line(), line);
+/// ```
+#[macro_export]
+macro_rules! forge_err {
+    ($fmt:literal $($rest:tt)*) => {
+        $crate::AppError::other(::std::format!($fmt $($rest)*))
+    };
+    ($ctor:expr, $($fmt:tt)+) => {
+        ($ctor)(::std::format!($($fmt)+))
+    };
+}
+
+/// Return early with an error.
+///
+/// `forge_bail!(constructor, "format", args...)` formats the message,
+/// passes it to `constructor`, and returns `Err` with the result
+/// converted by `From`. `forge_bail!(error)` returns an already built
+/// error the same way. Constructors generated by `define_errors!` are
+/// `#[track_caller]`, so a `#[tracked]` enum records the `forge_bail!`
+/// line as its location.
+///
+/// ```
+/// use error_forge::{forge_bail, AppError, ForgeError};
+///
+/// fn lookup(key: &str) -> Result<String, AppError> {
+///     if key.is_empty() {
+///         forge_bail!(AppError::config("empty key"));
+///     }
+///     forge_bail!(AppError::config, "missing {key}")
+/// }
+///
+/// let err = lookup("port").unwrap_err();
+/// assert!(err.to_string().contains("missing port"));
+/// ```
+#[macro_export]
+macro_rules! forge_bail {
+    ($err:expr $(,)?) => {
+        return ::core::result::Result::Err(::core::convert::From::from($err))
+    };
+    ($ctor:expr, $($fmt:tt)+) => {
+        return ::core::result::Result::Err(::core::convert::From::from(
+            $crate::forge_err!($ctor, $($fmt)+)
+        ))
+    };
+}
 
-    (@format_display $display:literal) => {
-        Some($display.to_string())
+/// Return early with an error unless a condition holds.
+///
+/// `forge_ensure!(condition, ...)` is `if !condition { forge_bail!(...) }`;
+/// everything after the condition is passed to [`forge_bail!`].
+///
+/// ```
+/// use error_forge::{forge_ensure, AppError};
+///
+/// fn set_workers(count: usize) -> Result<usize, AppError> {
+///     forge_ensure!(count > 0, AppError::config, "workers must be positive, got {count}");
+///     forge_ensure!(count <= 64, AppError::config("too many workers"));
+///     Ok(count)
+/// }
+///
+/// assert!(set_workers(0).is_err());
+/// assert_eq!(set_workers(8).unwrap(), 8);
+/// ```
+#[macro_export]
+macro_rules! forge_ensure {
+    ($cond:expr, $($rest:tt)+) => {
+        if !$cond {
+            $crate::forge_bail!($($rest)+);
+        }
     };
+}
+
+/// An `Option` or `bool` checked by [`expect_forge!`] and
+/// [`ok_or_forge!`]; `true` counts as `Some(())`.
+#[doc(hidden)]
+pub trait ForgeCheck {
+    type Value;
+    fn into_checked(self) -> Option<Self::Value>;
+}
 
-    (@format_display $display:literal, $($param:ident),+) => {
-        Some(format!($display, $($param = $param),+))
+impl<T> ForgeCheck for Option<T> {
+    type Value = T;
+
+    fn into_checked(self) -> Option<T> {
+        self
+    }
+}
+
+impl ForgeCheck for bool {
+    type Value = ();
+
+    fn into_checked(self) -> Option<()> {
+        self.then_some(())
+    }
+}
+
+/// Turn a missing value or failed check into a typed error.
+///
+/// `ok_or_forge!(option, error)` evaluates to `Ok(value)` or to
+/// `Err(error)`; `ok_or_forge!(flag, error)` does the same for a
+/// `bool`, with `Ok(())` when it is `true`. The error expression only
+/// runs on failure, so its constructor records the macro's line and
+/// fires the error hook only then.
+///
+/// ```
+/// use error_forge::{ok_or_forge, AppError};
+///
+/// let port: Option<u16> = None;
+/// let result = ok_or_forge!(port, AppError::config("PORT is not set"));
+/// assert!(result.is_err());
+/// assert!(ok_or_forge!(1 < 2, AppError::config("unreachable")).is_ok());
+/// ```
+#[macro_export]
+macro_rules! ok_or_forge {
+    ($check:expr, $err:expr $(,)?) => {
+        match $crate::macros::ForgeCheck::into_checked($check) {
+            ::core::option::Option::Some(value) => ::core::result::Result::Ok(value),
+            ::core::option::Option::None => ::core::result::Result::Err($err),
+        }
     };
+}
 
-    // Support for nested field access in error display formatting
-    (@format_display_field $field:ident) => {
-        $field
+/// Unwrap an `Option` (or check a `bool`), returning early with an
+/// error instead of panicking like `expect()`.
+///
+/// `expect_forge!(option, error)` evaluates to the value, or returns
+/// `Err(error.into())` from the enclosing function. As with
+/// [`ok_or_forge!`](crate::ok_or_forge!), the error is only built on failure.
+///
+/// ```
+/// use error_forge::{expect_forge, AppError, ForgeError};
+///
+/// fn database_url(vars: &[(&str, &str)]) -> Result<String, AppError> {
+///     let (_, url) = expect_forge!(
+///         vars.iter().find(|(key, _)| *key == "DATABASE_URL"),
+///         AppError::config("DATABASE_URL is not set")
+///     );
+///     expect_forge!(url.starts_with("postgres://"), AppError::config("unsupported database"));
+///     Ok(url.to_string())
+/// }
+///
+/// let err = database_url(&[]).unwrap_err();
+/// assert!(err.to_string().contains("DATABASE_URL is not set"));
+/// ```
+#[macro_export]
+macro_rules! expect_forge {
+    ($check:expr, $err:expr $(,)?) => {
+        match $crate::macros::ForgeCheck::into_checked($check) {
+            ::core::option::Option::Some(value) => value,
+            ::core::option::Option::None => {
+                return ::core::result::Result::Err(::core::convert::From::from($err))
+            }
+        }
     };
+}
 
-    (@format_display_field $field:ident . $($rest:ident).+) => {
-        $field$(.$rest)+
+/// Add formatted context to a `Result`'s error, recording where it was
+/// added.
+///
+/// `context!(result, "loading {path} for user {uid}")` is
+/// [`with_context`](crate::ResultExt::with_context) with `format!`
+/// arguments plus [`ContextError::with_location`](crate::ContextError::with_location)
+/// at the macro's call site. The message is only formatted on error.
+///
+/// ```
+/// use error_forge::{context, AppError, ForgeError};
+///
+/// let (path, uid) = ("config.toml", 42);
+/// let result: Result<(), AppError> = Err(AppError::config("missing key"));
+/// let err = context!(result, "loading {path} for user {uid}").unwrap_err();
+///
+/// assert_eq!(err.context, "loading config.toml for user 42");
+/// assert_eq!(err.context_location().unwrap().file(), file!());
+/// ```
+#[macro_export]
+macro_rules! context {
+    ($result:expr, $($fmt:tt)+) => {
+        ::core::result::Result::map_err($result, |error| {
+            $crate::ContextError::new(error, ::std::format!($($fmt)+))
+                .with_location(::core::panic::Location::caller())
+        })
     };
 }
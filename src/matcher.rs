@@ -0,0 +1,177 @@
+//! Declarative matching on error metadata.
+//!
+//! [`ErrorMatcher`] is one shared predicate over the [`ForgeError`]
+//! surface — kind, code, status, flags, and attached metadata — so
+//! retry predicates, logger routing, policy rules, and test
+//! assertions can all describe "which errors" the same way.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::{AppError, ErrorMatcher, WithErrorCode};
+//!
+//! let upstream = ErrorMatcher::kind("Network")
+//!     .code_prefix("NET-")
+//!     .status_range(500..=599);
+//!
+//! let err = AppError::network("billing", None).with_code("NET-017");
+//! assert!(upstream.matches(&err));
+//! assert!(!upstream.matches(&AppError::config("missing")));
+//! ```
+//!
+//! As a retry predicate:
+//!
+//! ```
+//! use error_forge::recovery::RetryPolicy;
+//! use error_forge::{AppError, ErrorMatcher};
+//!
+//! let transient = ErrorMatcher::any().retryable(true);
+//! let executor = RetryPolicy::new_fixed(1)
+//!     .executor::<AppError>()
+//!     .with_retry_if(move |err| transient.matches(err));
+//! # let _ = executor;
+//! ```
+
+use crate::error::ForgeError;
+use crate::metadata::MetaValue;
+use std::ops::{Bound, RangeBounds};
+
+/// A reusable predicate over [`ForgeError`] metadata.
+///
+/// Every criterion that has been set must hold for
+/// [`matches`](Self::matches) to return true; an
+/// [`ErrorMatcher::any`] with no criteria matches every error.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorMatcher {
+    kinds: Vec<String>,
+    code: Option<String>,
+    code_prefix: Option<String>,
+    status: Option<(Bound<u16>, Bound<u16>)>,
+    retryable: Option<bool>,
+    fatal: Option<bool>,
+    metadata: Vec<(String, MetaValue)>,
+}
+
+impl ErrorMatcher {
+    /// A matcher with no criteria, matching every error.
+    pub fn any() -> Self {
+        Self::default()
+    }
+
+    /// A matcher for errors whose [`kind`](ForgeError::kind) is `kind`.
+    pub fn kind(kind: impl Into<String>) -> Self {
+        Self::any().or_kind(kind)
+    }
+
+    /// Also accept errors of `kind`. Kinds are alternatives: the error
+    /// must have one of them.
+    pub fn or_kind(mut self, kind: impl Into<String>) -> Self {
+        self.kinds.push(kind.into());
+        self
+    }
+
+    /// Require the error [`code`](ForgeError::code) to equal `code`.
+    pub fn code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// Require the error [`code`](ForgeError::code) to start with
+    /// `prefix`.
+    pub fn code_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.code_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Require the [`status_code`](ForgeError::status_code) to fall in
+    /// `range`.
+    pub fn status_range(mut self, range: impl RangeBounds<u16>) -> Self {
+        self.status = Some((range.start_bound().cloned(), range.end_bound().cloned()));
+        self
+    }
+
+    /// Require the [`status_code`](ForgeError::status_code) to equal
+    /// `status`.
+    pub fn status(self, status: u16) -> Self {
+        self.status_range(status..=status)
+    }
+
+    /// Require [`is_retryable`](ForgeError::is_retryable) to equal
+    /// `retryable`.
+    pub fn retryable(mut self, retryable: bool) -> Self {
+        self.retryable = Some(retryable);
+        self
+    }
+
+    /// Require [`is_fatal`](ForgeError::is_fatal) to equal `fatal`.
+    pub fn fatal(mut self, fatal: bool) -> Self {
+        self.fatal = Some(fatal);
+        self
+    }
+
+    /// Require the metadata field `key` to equal `value`.
+    pub fn meta(mut self, key: impl Into<String>, value: impl Into<MetaValue>) -> Self {
+        self.metadata.push((key.into(), value.into()));
+        self
+    }
+
+    /// Returns true if `err` satisfies every criterion of this matcher.
+    pub fn matches<E: ForgeError + ?Sized>(&self, err: &E) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.iter().any(|kind| kind == err.kind()) {
+            return false;
+        }
+        if let Some(code) = &self.code {
+            if err.code() != Some(code.as_str()) {
+                return false;
+            }
+        }
+        if let Some(prefix) = &self.code_prefix {
+            if !err
+                .code()
+                .is_some_and(|code| code.starts_with(prefix.as_str()))
+            {
+                return false;
+            }
+        }
+        if let Some(range) = self.status {
+            if !range.contains(&err.status_code()) {
+                return false;
+            }
+        }
+        if self
+            .retryable
+            .is_some_and(|retryable| retryable != err.is_retryable())
+        {
+            return false;
+        }
+        if self.fatal.is_some_and(|fatal| fatal != err.is_fatal()) {
+            return false;
+        }
+        self.metadata
+            .iter()
+            .all(|(key, value)| err.metadata().get(key) == Some(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppError;
+
+    #[test]
+    fn test_matcher_criteria() {
+        let err = AppError::filesystem("db", None).with_meta("tenant", "acme");
+
+        assert!(ErrorMatcher::any().matches(&err));
+        assert!(ErrorMatcher::kind("Config")
+            .or_kind("Filesystem")
+            .matches(&err));
+        assert!(ErrorMatcher::any().meta("tenant", "acme").matches(&err));
+        assert!(!ErrorMatcher::any().meta("tenant", "globex").matches(&err));
+        assert!(!ErrorMatcher::any().code_prefix("FS-").matches(&err));
+        assert!(!ErrorMatcher::any().status(503).matches(&err));
+
+        let dynamic: &dyn ForgeError = &err;
+        assert!(ErrorMatcher::any().retryable(false).matches(dynamic));
+    }
+}
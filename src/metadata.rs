@@ -0,0 +1,377 @@
+//! Structured key/value metadata attached to errors.
+//!
+//! Any [`ForgeError`] can carry request ids, tenant ids, and similar
+//! fields without changing its type definition: wrap it with
+//! [`WithMetadata::with_meta`] and read the fields back through
+//! [`ForgeError::metadata`]. The fields flow into the `log` and
+//! `tracing` adapters behind [`log_error`], [`ErrorSnapshot`], and
+//! console output.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::{AppError, ForgeError, WithMetadata};
+//!
+//! let err = AppError::network("billing", None)
+//!     .with_meta("request_id", "req-42")
+//!     .with_meta("attempt", 3);
+//!
+//! assert_eq!(err.metadata().get("request_id").map(|v| v.to_string()), Some("req-42".into()));
+//! assert_eq!(err.kind(), "Network");
+//! ```
+//!
+//! [`log_error`]: crate::logging::log_error
+//! [`ErrorSnapshot`]: crate::snapshot::ErrorSnapshot
+
+use crate::error::ForgeError;
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A single metadata value.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// value types without breaking callers that `match` on the enum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+#[non_exhaustive]
+pub enum MetaValue {
+    /// A boolean flag
+    Bool(bool),
+    /// A signed integer
+    Int(i64),
+    /// An unsigned integer too large for `i64`
+    UInt(u64),
+    /// A string
+    String(String),
+}
+
+impl fmt::Display for MetaValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::UInt(value) => write!(f, "{value}"),
+            Self::String(value) => f.write_str(value),
+        }
+    }
+}
+
+impl From<&str> for MetaValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for MetaValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<bool> for MetaValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+macro_rules! meta_value_from_int {
+    ($($int:ty),*) => {
+        $(
+            impl From<$int> for MetaValue {
+                fn from(value: $int) -> Self {
+                    Self::Int(i64::from(value))
+                }
+            }
+        )*
+    };
+}
+
+meta_value_from_int!(i8, i16, i32, i64, u8, u16, u32);
+
+impl From<u64> for MetaValue {
+    fn from(value: u64) -> Self {
+        i64::try_from(value).map_or(Self::UInt(value), Self::Int)
+    }
+}
+
+impl From<usize> for MetaValue {
+    fn from(value: usize) -> Self {
+        Self::from(value as u64)
+    }
+}
+
+/// An ordered set of metadata fields. Keys are unique; inserting an
+/// existing key replaces its value in place.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata {
+    entries: Vec<(String, MetaValue)>,
+}
+
+impl Metadata {
+    /// Create an empty set.
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Insert a field, replacing any existing value for `key`.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<MetaValue>) {
+        let key = key.into();
+        let value = value.into();
+        match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.entries.push((key, value)),
+        }
+    }
+
+    /// Look up the value for `key`.
+    pub fn get(&self, key: &str) -> Option<&MetaValue> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Iterate over the fields in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &MetaValue)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value))
+    }
+
+    /// Number of fields.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if there are no fields.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The empty set returned by the default [`ForgeError::metadata`].
+    #[doc(hidden)]
+    pub fn empty() -> &'static Self {
+        static EMPTY: Metadata = Metadata::new();
+        &EMPTY
+    }
+}
+
+impl fmt::Display for Metadata {
+    /// Formats the fields as space-separated `key=value` pairs.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (key, value)) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{key}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Metadata {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (key, value) in self.iter() {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Metadata {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MetadataVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for MetadataVisitor {
+            type Value = Metadata;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a map of metadata fields")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut access: A,
+            ) -> Result<Metadata, A::Error> {
+                let mut metadata = Metadata::new();
+                while let Some((key, value)) = access.next_entry::<String, MetaValue>()? {
+                    metadata.insert(key, value);
+                }
+                Ok(metadata)
+            }
+        }
+
+        deserializer.deserialize_map(MetadataVisitor)
+    }
+}
+
+/// An error with structured metadata attached.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// fields without breaking callers. Build one with
+/// [`MetadataError::new`] or the [`WithMetadata::with_meta`]
+/// extension method. Every [`ForgeError`] method other than
+/// [`metadata`](ForgeError::metadata) delegates to the wrapped error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct MetadataError<E> {
+    /// The original error
+    pub error: E,
+    /// The attached fields, including any carried by the original error
+    pub metadata: Metadata,
+}
+
+impl<E: ForgeError> MetadataError<E> {
+    /// Wrap `error`, starting from the metadata it already carries.
+    pub fn new(error: E) -> Self {
+        let metadata = error.metadata().clone();
+        Self { error, metadata }
+    }
+
+    /// Attach another field, replacing any existing value for `key`.
+    pub fn with_meta(mut self, key: impl Into<String>, value: impl Into<MetaValue>) -> Self {
+        self.metadata.insert(key, value);
+        self
+    }
+
+    /// Extract the original error, discarding the metadata
+    pub fn into_error(self) -> E {
+        self.error
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for MetadataError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<E: std::error::Error> std::error::Error for MetadataError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+impl<E: ForgeError> ForgeError for MetadataError<E> {
+    fn kind(&self) -> &'static str {
+        self.error.kind()
+    }
+
+    fn caption(&self) -> &'static str {
+        self.error.caption()
+    }
+
+    fn is_retryable(&self) -> bool {
+        self.error.is_retryable()
+    }
+
+    fn is_fatal(&self) -> bool {
+        self.error.is_fatal()
+    }
+
+    fn status_code(&self) -> u16 {
+        self.error.status_code()
+    }
+
+    fn exit_code(&self) -> i32 {
+        self.error.exit_code()
+    }
+
+    fn severity(&self) -> crate::macros::ErrorLevel {
+        self.error.severity()
+    }
+
+    fn class(&self) -> crate::error::ErrorClass {
+        self.error.class()
+    }
+
+    fn user_message(&self) -> String {
+        self.error.user_message()
+    }
+
+    fn dev_message(&self) -> String {
+        self.error.dev_message()
+    }
+
+    fn backtrace(&self) -> Option<&std::backtrace::Backtrace> {
+        self.error.backtrace()
+    }
+
+    fn code(&self) -> Option<&str> {
+        self.error.code()
+    }
+
+    fn docs_url(&self) -> Option<String> {
+        self.error.docs_url()
+    }
+
+    fn retry_after(&self) -> Option<std::time::Duration> {
+        self.error.retry_after()
+    }
+
+    fn error_id(&self) -> Option<crate::instance::ErrorId> {
+        self.error.error_id()
+    }
+
+    fn timestamp(&self) -> Option<std::time::SystemTime> {
+        self.error.timestamp()
+    }
+
+    fn location(&self) -> Option<&'static std::panic::Location<'static>> {
+        self.error.location()
+    }
+
+    fn context_layer(&self) -> Option<(&dyn fmt::Display, &dyn ForgeError)> {
+        self.error.context_layer()
+    }
+
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+}
+
+/// Extension trait for attaching metadata to any [`ForgeError`]
+pub trait WithMetadata: ForgeError + Sized {
+    /// Attach a metadata field to an error
+    fn with_meta(self, key: impl Into<String>, value: impl Into<MetaValue>) -> MetadataError<Self>;
+}
+
+impl<E: ForgeError> WithMetadata for E {
+    fn with_meta(self, key: impl Into<String>, value: impl Into<MetaValue>) -> MetadataError<Self> {
+        MetadataError::new(self).with_meta(key, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppError;
+
+    #[test]
+    fn test_with_meta() {
+        let err = AppError::config("bad")
+            .with_meta("tenant", "acme")
+            .with_meta("attempt", 2u32)
+            .with_meta("tenant", "globex");
+
+        assert_eq!(err.metadata().len(), 2);
+        assert_eq!(
+            err.metadata().get("tenant"),
+            Some(&MetaValue::from("globex"))
+        );
+        assert_eq!(err.metadata().to_string(), "tenant=globex attempt=2");
+
+        // Wrapping carries the inner metadata forward.
+        let wrapped = crate::ContextError::new(err, "loading").with_meta("request_id", "r-1");
+        assert_eq!(wrapped.metadata().len(), 3);
+    }
+}
@@ -0,0 +1,435 @@
+//! Message normalization: replacing the variable parts of an error
+//! message with placeholders.
+//!
+//! `"user 8812 not found in /srv/data/users.db at 2024-03-01T10:15:00Z"`
+//! and the same message for user `9021` are one problem. A
+//! [`Normalizer`] applies an ordered list of [`Rule`]s (UUIDs,
+//! timestamps, paths, numbers, literal text, or a custom function) so
+//! both become `"user <n> not found in <path> at <timestamp>"`. The
+//! result groups messages for fingerprinting and deduplication, and
+//! with a `[redacted]` replacement it strips identifiers before they
+//! leave the process. Using one normalizer for all of these keeps them
+//! consistent; [`set_normalizer`] installs the process-wide one.
+//!
+//! The rules are hand-written scanners rather than regular
+//! expressions, so the crate takes no regex dependency.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::normalizer::{Normalizer, Rule};
+//!
+//! let normalizer = Normalizer::standard();
+//! assert_eq!(
+//!     normalizer.normalize("user 8812 not found in /srv/users.db at 2024-03-01T10:15:00Z"),
+//!     "user <n> not found in <path> at <timestamp>"
+//! );
+//!
+//! let redactor = Normalizer::new()
+//!     .with_rule(Rule::uuids().with_replacement("[redacted]"))
+//!     .with_rule(Rule::literal("hunter2", "[redacted]"));
+//! assert_eq!(
+//!     redactor.normalize("session 0f8fad5b-d9cb-469f-a165-70867728950e password hunter2"),
+//!     "session [redacted] password [redacted]"
+//! );
+//! ```
+
+use parking_lot::RwLock;
+use std::fmt;
+use std::sync::Arc;
+
+type CustomFn = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+#[derive(Clone)]
+enum Matcher {
+    Uuid,
+    Timestamp,
+    Path,
+    Number,
+    Literal(String),
+    Custom(CustomFn),
+}
+
+/// One transformation applied by a [`Normalizer`].
+///
+/// Built with [`Rule::uuids`], [`Rule::timestamps`], [`Rule::paths`],
+/// [`Rule::numbers`], [`Rule::literal`], or [`Rule::custom`]; the
+/// scanning rules take their placeholder from
+/// [`with_replacement`](Self::with_replacement).
+#[derive(Clone)]
+pub struct Rule {
+    matcher: Matcher,
+    replacement: String,
+}
+
+impl Rule {
+    fn new(matcher: Matcher, replacement: &str) -> Self {
+        Self {
+            matcher,
+            replacement: replacement.to_string(),
+        }
+    }
+
+    /// Hyphenated UUIDs, replaced with `<uuid>`.
+    pub fn uuids() -> Self {
+        Self::new(Matcher::Uuid, "<uuid>")
+    }
+
+    /// ISO 8601 dates and date-times (`2024-03-01`,
+    /// `2024-03-01T10:15:00.123Z`, `2024-03-01 10:15:00+02:00`) and
+    /// bare `HH:MM:SS` times, replaced with `<timestamp>`.
+    pub fn timestamps() -> Self {
+        Self::new(Matcher::Timestamp, "<timestamp>")
+    }
+
+    /// Absolute and relative file paths (`/srv/app.db`, `./x`,
+    /// `~/conf`, `C:\data`), replaced with `<path>`.
+    pub fn paths() -> Self {
+        Self::new(Matcher::Path, "<path>")
+    }
+
+    /// Standalone integers, decimals, and `0x` hex numbers, replaced
+    /// with `<n>`. Digits inside words such as `v2` or `utf8` are kept.
+    pub fn numbers() -> Self {
+        Self::new(Matcher::Number, "<n>")
+    }
+
+    /// Every occurrence of `text`, replaced with `replacement`.
+    pub fn literal(text: impl Into<String>, replacement: impl Into<String>) -> Self {
+        Self {
+            matcher: Matcher::Literal(text.into()),
+            replacement: replacement.into(),
+        }
+    }
+
+    /// Any other transformation of the whole message.
+    pub fn custom(f: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        Self::new(Matcher::Custom(Arc::new(f)), "")
+    }
+
+    /// Replace matches with `replacement` instead of the default
+    /// placeholder. Has no effect on [`custom`](Self::custom) rules.
+    pub fn with_replacement(mut self, replacement: impl Into<String>) -> Self {
+        self.replacement = replacement.into();
+        self
+    }
+
+    /// Apply this rule alone.
+    pub fn apply(&self, message: &str) -> String {
+        let scan = |matcher: fn(&[u8], usize) -> Option<usize>| {
+            replace_matches(message, &self.replacement, matcher)
+        };
+        match &self.matcher {
+            Matcher::Uuid => scan(match_uuid),
+            Matcher::Timestamp => scan(match_timestamp),
+            Matcher::Path => scan(match_path),
+            Matcher::Number => scan(match_number),
+            Matcher::Literal(text) if text.is_empty() => message.to_string(),
+            Matcher::Literal(text) => message.replace(text.as_str(), &self.replacement),
+            Matcher::Custom(f) => f(message),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self.matcher {
+            Matcher::Uuid => "uuids",
+            Matcher::Timestamp => "timestamps",
+            Matcher::Path => "paths",
+            Matcher::Number => "numbers",
+            Matcher::Literal(_) => "literal",
+            Matcher::Custom(_) => "custom",
+        }
+    }
+}
+
+impl fmt::Debug for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Rule");
+        debug.field("matcher", &self.name());
+        if let Matcher::Literal(text) = &self.matcher {
+            debug.field("text", text);
+        }
+        debug.field("replacement", &self.replacement).finish()
+    }
+}
+
+/// An ordered list of [`Rule`]s.
+///
+/// Rules run in the order they were added, each on the output of the
+/// previous one, so put the specific rules (UUIDs, timestamps, paths)
+/// before [`Rule::numbers`].
+#[derive(Debug, Clone, Default)]
+pub struct Normalizer {
+    rules: Vec<Rule>,
+}
+
+impl Normalizer {
+    /// A normalizer with no rules, which returns messages unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// UUIDs, timestamps, paths, then numbers, with the default
+    /// placeholders.
+    pub fn standard() -> Self {
+        Self::new()
+            .with_rule(Rule::uuids())
+            .with_rule(Rule::timestamps())
+            .with_rule(Rule::paths())
+            .with_rule(Rule::numbers())
+    }
+
+    /// Add a rule after the existing ones.
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// The rules, in the order they run.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Apply every rule to `message`.
+    pub fn normalize(&self, message: &str) -> String {
+        let mut message = message.to_string();
+        for rule in &self.rules {
+            message = rule.apply(&message);
+        }
+        message
+    }
+}
+
+static NORMALIZER: RwLock<Option<Arc<Normalizer>>> = RwLock::new(None);
+
+/// Install `normalizer` process-wide, replacing any previous one.
+pub fn set_normalizer(normalizer: Normalizer) {
+    *NORMALIZER.write() = Some(Arc::new(normalizer));
+}
+
+/// The process-wide normalizer: the one installed with
+/// [`set_normalizer`], or [`Normalizer::standard`].
+pub fn normalizer() -> Arc<Normalizer> {
+    if let Some(normalizer) = NORMALIZER.read().as_ref() {
+        return Arc::clone(normalizer);
+    }
+    NORMALIZER
+        .write()
+        .get_or_insert_with(|| Arc::new(Normalizer::standard()))
+        .clone()
+}
+
+/// Apply the process-wide [`normalizer`] to `message`.
+pub fn normalize(message: &str) -> String {
+    normalizer().normalize(message)
+}
+
+/// Replace each span `matcher` accepts with `replacement`. Matchers
+/// only start and end on ASCII bytes, so every slice is on a `char`
+/// boundary.
+fn replace_matches(
+    input: &str,
+    replacement: &str,
+    matcher: fn(&[u8], usize) -> Option<usize>,
+) -> String {
+    let bytes = input.as_bytes();
+    let mut out = String::with_capacity(input.len());
+    let (mut i, mut copied) = (0, 0);
+    while i < bytes.len() {
+        match matcher(bytes, i) {
+            Some(end) => {
+                out.push_str(&input[copied..i]);
+                out.push_str(replacement);
+                i = end;
+                copied = end;
+            }
+            None => i += 1,
+        }
+    }
+    out.push_str(&input[copied..]);
+    out
+}
+
+fn is_word(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+/// True if a word may start at `i` and end at `end`.
+fn bounded(bytes: &[u8], i: usize, end: usize) -> bool {
+    (i == 0 || !is_word(bytes[i - 1])) && !bytes.get(end).is_some_and(|&b| is_word(b))
+}
+
+/// Length of the run of bytes from `i` satisfying `f`.
+fn run(bytes: &[u8], i: usize, f: fn(u8) -> bool) -> usize {
+    bytes[i.min(bytes.len())..]
+        .iter()
+        .take_while(|&&b| f(b))
+        .count()
+}
+
+/// The end of exactly `n` ASCII digits at `i`.
+fn digits(bytes: &[u8], i: usize, n: usize) -> Option<usize> {
+    (run(bytes, i, |b| b.is_ascii_digit()) >= n).then_some(i + n)
+}
+
+fn match_uuid(bytes: &[u8], i: usize) -> Option<usize> {
+    let mut at = i;
+    for (group, len) in [8, 4, 4, 4, 12].into_iter().enumerate() {
+        if group > 0 {
+            (bytes.get(at) == Some(&b'-')).then_some(())?;
+            at += 1;
+        }
+        (run(bytes, at, |b| b.is_ascii_hexdigit()) >= len).then_some(())?;
+        at += len;
+    }
+    bounded(bytes, i, at).then_some(at)
+}
+
+fn match_timestamp(bytes: &[u8], i: usize) -> Option<usize> {
+    let end = match match_date(bytes, i) {
+        Some(date) => match bytes.get(date) {
+            Some(b'T' | b' ') => match_time(bytes, date + 1).unwrap_or(date),
+            _ => date,
+        },
+        None => {
+            match_time(bytes, i).filter(|&end| bytes.get(i + 5) == Some(&b':') && end >= i + 8)?
+        }
+    };
+    bounded(bytes, i, end).then_some(end)
+}
+
+/// `YYYY-MM-DD`.
+fn match_date(bytes: &[u8], i: usize) -> Option<usize> {
+    let at = digits(bytes, i, 4)?;
+    (bytes.get(at) == Some(&b'-')).then_some(())?;
+    let at = digits(bytes, at + 1, 2)?;
+    (bytes.get(at) == Some(&b'-')).then_some(())?;
+    digits(bytes, at + 1, 2)
+}
+
+/// `HH:MM[:SS[.fff]][Z|±HH[:]MM]`.
+fn match_time(bytes: &[u8], i: usize) -> Option<usize> {
+    let at = digits(bytes, i, 2)?;
+    (bytes.get(at) == Some(&b':')).then_some(())?;
+    let mut at = digits(bytes, at + 1, 2)?;
+    if bytes.get(at) == Some(&b':') {
+        if let Some(secs) = digits(bytes, at + 1, 2) {
+            at = secs;
+            if bytes.get(at) == Some(&b'.') {
+                let fraction = run(bytes, at + 1, |b| b.is_ascii_digit());
+                if fraction > 0 {
+                    at += 1 + fraction;
+                }
+            }
+        }
+    }
+    match bytes.get(at) {
+        Some(b'Z') => Some(at + 1),
+        Some(b'+' | b'-') => {
+            let hours = digits(bytes, at + 1, 2)?;
+            let colon = usize::from(bytes.get(hours) == Some(&b':'));
+            Some(digits(bytes, hours + colon, 2).unwrap_or(hours))
+        }
+        _ => Some(at),
+    }
+}
+
+fn match_path(bytes: &[u8], i: usize) -> Option<usize> {
+    if i > 0
+        && !matches!(
+            bytes[i - 1],
+            b' ' | b'\t' | b'\n' | b'"' | b'\'' | b'(' | b'=' | b'`'
+        )
+    {
+        return None;
+    }
+    let rest = &bytes[i..];
+    let prefix = if rest.starts_with(b"../") {
+        3
+    } else if rest.starts_with(b"./") || rest.starts_with(b"~/") {
+        2
+    } else if rest.len() >= 3
+        && rest[0].is_ascii_alphabetic()
+        && rest[1] == b':'
+        && matches!(rest[2], b'\\' | b'/')
+    {
+        3
+    } else if rest.first() == Some(&b'/') && rest.get(1).is_some_and(|&b| is_word(b) || b == b'.') {
+        1
+    } else {
+        return None;
+    };
+    let body = run(bytes, i + prefix, |b| {
+        !b.is_ascii_whitespace() && !matches!(b, b'"' | b'\'' | b')' | b'`' | b',' | b';')
+    });
+    let mut end = i + prefix + body;
+    // A trailing full stop or colon ends the sentence, not the path.
+    while end > i + prefix && matches!(bytes[end - 1], b'.' | b':') {
+        end -= 1;
+    }
+    Some(end)
+}
+
+fn match_number(bytes: &[u8], i: usize) -> Option<usize> {
+    let end = if bytes[i..].starts_with(b"0x") || bytes[i..].starts_with(b"0X") {
+        let hex = run(bytes, i + 2, |b| b.is_ascii_hexdigit());
+        (hex > 0).then_some(i + 2 + hex)?
+    } else {
+        let whole = run(bytes, i, |b| b.is_ascii_digit());
+        (whole > 0).then_some(())?;
+        let at = i + whole;
+        let fraction = match bytes.get(at) {
+            Some(b'.') => run(bytes, at + 1, |b| b.is_ascii_digit()),
+            _ => 0,
+        };
+        if fraction > 0 {
+            at + 1 + fraction
+        } else {
+            at
+        }
+    };
+    bounded(bytes, i, end).then_some(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_rules() {
+        let normalizer = Normalizer::standard();
+        let cases = [
+            (
+                "job 0F8FAD5B-D9CB-469F-A165-70867728950E failed",
+                "job <uuid> failed",
+            ),
+            (
+                "at 2024-03-01 10:15:00.250+02:00, then 23:59:59Z",
+                "at <timestamp>, then <timestamp>",
+            ),
+            (
+                "read (./conf/app.toml) and C:\\data\\x.db.",
+                "read (<path>) and <path>.",
+            ),
+            (
+                "v2 utf8 took 12.5ms, code 0x1F, retry 3/5",
+                "v2 utf8 took 12.5ms, code <n>, retry <n>/<n>",
+            ),
+            ("naïve 42 café", "naïve <n> café"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(normalizer.normalize(input), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let normalizer = Normalizer::new()
+            .with_rule(Rule::numbers().with_replacement("#"))
+            .with_rule(Rule::literal("", "x"))
+            .with_rule(Rule::custom(|message| message.to_lowercase()));
+        assert_eq!(normalizer.normalize("Pool 7 FULL"), "pool # full");
+        assert_eq!(normalizer.rules().len(), 3);
+        assert!(format!("{:?}", normalizer.rules()[0]).contains("numbers"));
+    }
+}
@@ -0,0 +1,417 @@
+//! Output-format negotiation for printed errors.
+//!
+//! The same error can be shown as themed text for a person at a
+//! terminal or as a stable JSON object for a script consuming the
+//! program's output. [`OutputFormat`] names the choice and
+//! [`ErrorPrinter`] applies it.
+//!
+//! The format is selected with the conventional `--output <format>`
+//! (or `--output=<format>`) command-line flag, falling back to the
+//! `ERROR_FORGE_OUTPUT` environment variable, falling back to
+//! [`OutputFormat::Human`]. Recognised values are `human`, `json`,
+//! `json-pretty`, and `quiet`.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::output::{ErrorPrinter, OutputFormat};
+//! use error_forge::AppError;
+//!
+//! let printer = ErrorPrinter::new(OutputFormat::Json);
+//! let rendered = printer.render(&AppError::config("missing host"));
+//! assert!(rendered.starts_with("{\"kind\":\"Config\""));
+//! ```
+//!
+//! # Exit codes from `main`
+//!
+//! [`Failure`] wraps an error so it can end `main` through its own
+//! [`Termination`] impl: it prints the error with [`report`] and exits
+//! with the error's own exit code. Return it (or the [`ExitCode`] it
+//! reports) from `main` directly:
+//!
+//! ```
+//! use error_forge::output::Failure;
+//! use error_forge::AppError;
+//! use std::process::{ExitCode, Termination};
+//!
+//! fn run() -> Result<(), AppError> {
+//!     Ok(())
+//! }
+//!
+//! fn main() -> ExitCode {
+//!     match run() {
+//!         Ok(()) => ExitCode::SUCCESS,
+//!         Err(err) => Failure::new(err).report(),
+//!     }
+//! }
+//! ```
+//!
+//! The `#[error_forge::main]` attribute (with the `derive` feature)
+//! writes that `match` for a `fn main() -> Result<(), E>`.
+//!
+//! Do not return `Result<(), Failure<E>>` from `main`: the standard
+//! library's `Termination` impl for `Result` never calls the one on
+//! [`Failure`], so the process always exits with status `1`.
+
+use crate::console_theme::ConsoleTheme;
+use crate::error::ForgeError;
+use crate::snapshot::ErrorSnapshot;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::{ExitCode, Termination};
+use std::str::FromStr;
+
+/// Environment variable consulted by [`OutputFormat::from_env`].
+pub const OUTPUT_ENV_VAR: &str = "ERROR_FORGE_OUTPUT";
+
+/// How an error is rendered by [`ErrorPrinter`].
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// formats without breaking callers that `match` on the enum.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OutputFormat {
+    /// Themed, human-readable text (the [`ConsoleTheme`] layout)
+    #[default]
+    Human,
+    /// A single-line JSON object
+    Json,
+    /// An indented JSON object
+    JsonPretty,
+    /// Nothing is printed; only the exit code reports the failure
+    Quiet,
+}
+
+impl OutputFormat {
+    /// Read the format from the `ERROR_FORGE_OUTPUT` environment
+    /// variable. Returns `None` if the variable is unset or holds an
+    /// unrecognised value.
+    pub fn from_env() -> Option<Self> {
+        std::env::var(OUTPUT_ENV_VAR).ok()?.parse().ok()
+    }
+
+    /// Find an `--output <format>` or `--output=<format>` flag in
+    /// `args`. Returns `None` if the flag is absent or its value is
+    /// unrecognised.
+    pub fn from_args<I, S>(args: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let arg = arg.as_ref();
+            if arg == "--output" {
+                return args.next()?.as_ref().parse().ok();
+            }
+            if let Some(value) = arg.strip_prefix("--output=") {
+                return value.parse().ok();
+            }
+        }
+        None
+    }
+
+    /// Select the format for the current process: the command-line
+    /// flag wins over the environment variable, and
+    /// [`OutputFormat::Human`] is the fallback.
+    pub fn detect() -> Self {
+        Self::from_args(std::env::args().skip(1))
+            .or_else(Self::from_env)
+            .unwrap_or_default()
+    }
+
+    /// The canonical name of the format, as accepted by `FromStr`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Human => "human",
+            Self::Json => "json",
+            Self::JsonPretty => "json-pretty",
+            Self::Quiet => "quiet",
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "human" | "text" => Ok(Self::Human),
+            "json" => Ok(Self::Json),
+            "json-pretty" | "pretty-json" => Ok(Self::JsonPretty),
+            "quiet" | "none" => Ok(Self::Quiet),
+            other => Err(format!("unknown output format '{other}'")),
+        }
+    }
+}
+
+/// Renders errors in a negotiated [`OutputFormat`].
+pub struct ErrorPrinter {
+    format: OutputFormat,
+    theme: ConsoleTheme,
+    max_display_bytes: Option<usize>,
+    report_file: Option<PathBuf>,
+}
+
+impl ErrorPrinter {
+    /// Create a printer for `format` using the default (auto-detected)
+    /// console theme for human output.
+    pub fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            theme: ConsoleTheme::default(),
+            max_display_bytes: None,
+            report_file: None,
+        }
+    }
+
+    /// Create a printer using [`OutputFormat::detect`].
+    pub fn detect() -> Self {
+        Self::new(OutputFormat::detect())
+    }
+
+    /// Use `theme` for [`OutputFormat::Human`] output.
+    pub fn with_theme(mut self, theme: ConsoleTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Cut human-readable messages to `max` bytes instead of the
+    /// process-wide
+    /// [`max_display_bytes`](crate::limits::PayloadLimits::max_display_bytes).
+    pub fn with_max_display_bytes(mut self, max: usize) -> Self {
+        self.max_display_bytes = Some(max);
+        self
+    }
+
+    /// When [`print`](Self::print) cuts a message, append the full
+    /// rendering to the file at `path` and name it in the marker.
+    pub fn with_report_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.report_file = Some(path.into());
+        self
+    }
+
+    /// The format this printer renders.
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
+    /// Render `err` in this printer's format. Returns an empty string
+    /// for [`OutputFormat::Quiet`].
+    pub fn render<E: ForgeError + ?Sized>(&self, err: &E) -> String {
+        match self.format {
+            OutputFormat::Human => self.theme.format_error_limited(
+                err,
+                self.max_display_bytes(),
+                self.report_file.as_deref(),
+            ),
+            OutputFormat::Json => ErrorSnapshot::capture(err).to_json(false),
+            OutputFormat::JsonPretty => ErrorSnapshot::capture(err).to_json(true),
+            OutputFormat::Quiet => String::new(),
+        }
+    }
+
+    /// Render `err` and write it to stderr. Prints nothing for
+    /// [`OutputFormat::Quiet`]. With a
+    /// [report file](Self::with_report_file), a rendering that had to
+    /// be cut is also appended there in full, without colors.
+    pub fn print<E: ForgeError + ?Sized>(&self, err: &E) {
+        if self.format == OutputFormat::Quiet {
+            return;
+        }
+        if let (OutputFormat::Human, Some(path)) = (self.format, &self.report_file) {
+            let max = self.max_display_bytes();
+            let cut = err.to_string().len() > max
+                || err
+                    .source()
+                    .is_some_and(|source| source.to_string().len() > max);
+            if cut {
+                let full = ConsoleTheme::plain().format_error_limited(err, usize::MAX, None);
+                let _ = append_report(path, &full);
+            }
+        }
+        eprintln!("{}", self.render(err));
+    }
+
+    fn max_display_bytes(&self) -> usize {
+        self.max_display_bytes
+            .unwrap_or_else(|| crate::limits::payload_limits().max_display_bytes)
+    }
+}
+
+fn append_report(path: &Path, text: &str) -> std::io::Result<()> {
+    use std::io::Write as _;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{text}")
+}
+
+/// Print `err` with [`ErrorPrinter::detect`] and convert its
+/// [`ForgeError::exit_code`] into a process exit code.
+///
+/// Exit codes outside `1..=255` are reported as `1` so a failure is
+/// never mistaken for success. Used by the `#[error_forge::main]`
+/// attribute (with the `derive` feature).
+///
+/// Fatal errors also run the handlers registered with
+/// [`register_fatal_handler`](crate::hooks::register_fatal_handler)
+/// after printing.
+pub fn report<E: ForgeError + ?Sized>(err: &E) -> std::process::ExitCode {
+    ErrorPrinter::detect().print(err);
+    if err.is_fatal() {
+        crate::hooks::run_fatal_handlers(err);
+    }
+    std::process::ExitCode::from(exit_status(err))
+}
+
+/// Like [`report`], then exit the process immediately with the
+/// error's exit code.
+pub fn exit_with<E: ForgeError + ?Sized>(err: &E) -> ! {
+    let _ = report(err);
+    std::process::exit(i32::from(exit_status(err)))
+}
+
+/// An error on its way out of `main`.
+///
+/// Implements [`Termination`] by calling [`report`]: the error is
+/// printed in the [detected](OutputFormat::detect) format, fatal
+/// handlers run, and the process exits with the error's exit code.
+/// That only happens when `main` returns the `Failure` itself (see the
+/// [module docs](self#exit-codes-from-main)); inside a `Result`, only
+/// its `Debug` output, the same rendering, is used.
+pub struct Failure<E> {
+    /// The error being reported
+    pub error: E,
+}
+
+impl<E> Failure<E> {
+    /// Wrap `error` for reporting from `main`.
+    pub fn new(error: E) -> Self {
+        Self { error }
+    }
+}
+
+impl<E> From<E> for Failure<E> {
+    fn from(error: E) -> Self {
+        Self::new(error)
+    }
+}
+
+impl<E: ForgeError> fmt::Debug for Failure<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&ErrorPrinter::detect().render(&self.error))
+    }
+}
+
+impl<E: ForgeError> Termination for Failure<E> {
+    fn report(self) -> ExitCode {
+        report(&self.error)
+    }
+}
+
+/// The error's exit code, without printing anything. Codes outside
+/// `1..=255` become `1`.
+impl From<crate::error::AppError> for ExitCode {
+    fn from(err: crate::error::AppError) -> Self {
+        ExitCode::from(exit_status(&err))
+    }
+}
+
+/// The error's exit code, or `1` if it is outside `1..=255`.
+fn exit_status<E: ForgeError + ?Sized>(err: &E) -> u8 {
+    u8::try_from(err.exit_code())
+        .ok()
+        .filter(|code| *code != 0)
+        .unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppError;
+
+    #[test]
+    fn test_output_format_from_args() {
+        assert_eq!(
+            OutputFormat::from_args(["run", "--output", "json"]),
+            Some(OutputFormat::Json)
+        );
+        assert_eq!(
+            OutputFormat::from_args(["--output=json-pretty"]),
+            Some(OutputFormat::JsonPretty)
+        );
+        assert_eq!(OutputFormat::from_args(["--verbose"]), None);
+    }
+
+    #[test]
+    fn test_error_printer_render() {
+        let err = AppError::network("db.internal", None).with_status(503);
+
+        let json = ErrorPrinter::new(OutputFormat::Json).render(&err);
+        assert!(json.contains("\"status_code\":503"));
+        assert!(json.contains("\"retryable\":true"));
+
+        let pretty = ErrorPrinter::new(OutputFormat::JsonPretty).render(&err);
+        assert!(pretty.contains("\n  \"kind\": \"Network\""));
+
+        assert!(ErrorPrinter::new(OutputFormat::Quiet)
+            .render(&err)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_error_printer_truncates_display() {
+        let query = format!("SELECT {} FROM events", "col, ".repeat(2000));
+        let err = AppError::other(query.clone());
+        let path =
+            std::env::temp_dir().join(format!("error-forge-report-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let printer = ErrorPrinter::new(OutputFormat::Human)
+            .with_theme(ConsoleTheme::plain())
+            .with_max_display_bytes(40)
+            .with_report_file(&path);
+        let rendered = printer.render(&err);
+        let message = rendered.lines().nth(1).unwrap();
+        assert!(message.ends_with(&format!("full text in {})", path.display())));
+        assert!(message
+            .split(" …truncated")
+            .next()
+            .unwrap()
+            .ends_with("col,"));
+
+        printer.print(&err);
+        let report = std::fs::read_to_string(&path).unwrap();
+        assert!(report.contains(&query));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn test_exit_codes() {
+        crate::define_errors! {
+            pub enum CliError {
+                #[kind(Usage, exit = 64)]
+                Usage { flag: String },
+
+                #[kind(Crash, exit = 0)]
+                Crash,
+            }
+        }
+
+        assert_eq!(exit_status(&CliError::usage("--x".to_string())), 64);
+        assert_eq!(exit_status(&CliError::CRASH), 1);
+        assert_eq!(ExitCode::from(AppError::config("bad")), ExitCode::from(1));
+
+        let failure: Failure<AppError> = AppError::config("bad").into();
+        assert!(format!("{failure:?}").contains("bad"));
+    }
+}
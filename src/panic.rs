@@ -0,0 +1,110 @@
+//! Panicking with a typed error.
+//!
+//! Some failures should end the thread rather than propagate, such as
+//! a broken invariant deep inside a worker. `panic!("{err}")` flattens
+//! the error to a string. A [`ForgePanic`] payload keeps it whole:
+//! [`install_panic_hook`] renders the full error (kind, code, docs
+//! link, and every cause), and [`catch_forge`] hands it back as a
+//! typed error.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::panic::{catch_forge, ForgePanic};
+//! use error_forge::{AppError, ForgeError};
+//!
+//! let caught = catch_forge(|| {
+//!     std::panic::panic_any(ForgePanic::from(AppError::config("no shards")))
+//! });
+//!
+//! let err = caught.unwrap_err();
+//! assert_eq!(err.kind(), "Config");
+//! assert!(err.downcast::<AppError>().is_ok());
+//! ```
+//!
+//! [`install_panic_hook`]: crate::console_theme::install_panic_hook
+
+use crate::error::{AppError, BoxedForgeError, ForgeError};
+use std::any::Any;
+use std::fmt;
+use std::panic::UnwindSafe;
+
+/// A panic payload carrying a [`ForgeError`].
+///
+/// Raise it with [`std::panic::panic_any`].
+#[derive(Debug)]
+pub struct ForgePanic {
+    error: BoxedForgeError,
+}
+
+impl ForgePanic {
+    /// The error the panic carries.
+    pub fn error(&self) -> &dyn ForgeError {
+        self.error.as_ref()
+    }
+
+    /// Unwrap the error; recover its type with
+    /// [`downcast`](trait.ForgeError.html#method.downcast).
+    pub fn into_error(self) -> BoxedForgeError {
+        self.error
+    }
+}
+
+impl<E: ForgeError> From<E> for ForgePanic {
+    fn from(error: E) -> Self {
+        Self {
+            error: Box::new(error),
+        }
+    }
+}
+
+impl fmt::Display for ForgePanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+/// Run `f`, turning a panic into an error.
+///
+/// A [`ForgePanic`] payload becomes the error it carries, which
+/// downcasts to its original type. Any other panic becomes an
+/// [`AppError::Other`] with the panic message.
+pub fn catch_forge<T>(f: impl FnOnce() -> T + UnwindSafe) -> Result<T, BoxedForgeError> {
+    std::panic::catch_unwind(f).map_err(|payload| match payload.downcast::<ForgePanic>() {
+        Ok(panic) => panic.error,
+        Err(payload) => Box::new(AppError::other(format!(
+            "panicked: {}",
+            payload_message(payload.as_ref())
+        ))),
+    })
+}
+
+/// The message of a `panic!` payload, or `"Unknown panic"`.
+pub(crate) fn payload_message(payload: &(dyn Any + Send)) -> &str {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message,
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message,
+            None => "Unknown panic",
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catch_forge() {
+        let err = catch_forge(|| std::panic::panic_any(ForgePanic::from(AppError::config("bad"))))
+            .unwrap_err();
+        let app = err.downcast::<AppError>().unwrap();
+        assert_eq!(app.to_string(), "⚙️ Configuration Error: bad");
+
+        let err = catch_forge(|| panic!("index {} out of range", 3)).unwrap_err();
+        assert_eq!(err.kind(), "Other");
+        assert!(err.to_string().contains("panicked: index 3 out of range"));
+
+        assert_eq!(catch_forge(|| 5).unwrap(), 5);
+    }
+}
@@ -0,0 +1,238 @@
+//! Guard rails on which error kinds may be constructed.
+//!
+//! A catch-all kind such as `Other` is convenient while prototyping and
+//! a liability once a service is in production: dashboards cannot
+//! group it and callers cannot match on it. A [`ConstructionPolicy`]
+//! lists the kinds a build must not create. Every error that fires the
+//! error hook — constructors from `define_errors!`, [`AppError`], and
+//! anything calling [`ForgeError::register`] — is checked against it.
+//!
+//! In [`PolicyMode::Report`] a violation raises a [`PolicyViolation`]
+//! error through the error hook (and observers) and the original error
+//! is still returned. In [`PolicyMode::Panic`] construction panics at
+//! the offending call site.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::policy::{self, ConstructionPolicy};
+//! use error_forge::AppError;
+//!
+//! // Ban the catch-all kind, but only in release builds.
+//! policy::set_construction_policy(
+//!     ConstructionPolicy::new()
+//!         .with_denied_kind("Other")
+//!         .with_release_only(true),
+//! );
+//!
+//! let _ = AppError::other("this would be reported in a release build");
+//! # policy::clear_construction_policy();
+//! ```
+//!
+//! [`AppError`]: crate::AppError
+//! [`ForgeError::register`]: crate::error::ForgeError::register
+
+use crate::error::ForgeError;
+use crate::macros::ErrorLevel;
+use parking_lot::RwLock;
+use std::fmt;
+use std::panic::Location;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// The kind reported by [`PolicyViolation`].
+pub const POLICY_VIOLATION_KIND: &str = "PolicyViolation";
+
+/// What happens when a denied kind is constructed.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// modes without breaking callers that `match` on the enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum PolicyMode {
+    /// Raise a [`PolicyViolation`] through the error hook
+    #[default]
+    Report,
+    /// Panic at the constructor's call site
+    Panic,
+}
+
+/// The kinds a build may not construct.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// fields without breaking callers. Build one with
+/// [`ConstructionPolicy::new`] and the `with_*` methods.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ConstructionPolicy {
+    /// Kinds that may not be constructed
+    pub denied_kinds: Vec<String>,
+    /// What to do on a violation
+    pub mode: PolicyMode,
+    /// Only enforce the policy when `debug_assertions` are off
+    pub release_only: bool,
+}
+
+impl ConstructionPolicy {
+    /// An empty policy that denies nothing and reports violations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deny constructing errors of `kind`.
+    pub fn with_denied_kind(mut self, kind: impl Into<String>) -> Self {
+        self.denied_kinds.push(kind.into());
+        self
+    }
+
+    /// Set what happens on a violation.
+    pub fn with_mode(mut self, mode: PolicyMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Only enforce the policy in builds without `debug_assertions`.
+    pub fn with_release_only(mut self, release_only: bool) -> Self {
+        self.release_only = release_only;
+        self
+    }
+
+    /// Returns true if this policy forbids `kind` in the current build.
+    pub fn denies(&self, kind: &str) -> bool {
+        !(self.release_only && cfg!(debug_assertions))
+            && self.denied_kinds.iter().any(|denied| denied == kind)
+    }
+}
+
+static POLICY: RwLock<Option<ConstructionPolicy>> = RwLock::new(None);
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static VIOLATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Install `policy` process-wide, replacing any previous one.
+pub fn set_construction_policy(policy: ConstructionPolicy) {
+    *POLICY.write() = Some(policy);
+    ACTIVE.store(true, Ordering::Release);
+}
+
+/// Remove the installed policy.
+pub fn clear_construction_policy() {
+    ACTIVE.store(false, Ordering::Release);
+    *POLICY.write() = None;
+}
+
+/// The installed policy, if any.
+pub fn construction_policy() -> Option<ConstructionPolicy> {
+    POLICY.read().clone()
+}
+
+/// How many violations have been seen since the process started.
+pub fn violation_count() -> u64 {
+    VIOLATIONS.load(Ordering::Relaxed)
+}
+
+/// Check a newly constructed error of `kind` against the policy.
+#[track_caller]
+pub(crate) fn check(kind: &str) {
+    if !ACTIVE.load(Ordering::Acquire) || kind == POLICY_VIOLATION_KIND {
+        return;
+    }
+    let mode = match POLICY.read().as_ref() {
+        Some(policy) if policy.denies(kind) => policy.mode,
+        _ => return,
+    };
+    VIOLATIONS.fetch_add(1, Ordering::Relaxed);
+
+    let violation = PolicyViolation {
+        denied_kind: kind.to_string(),
+        location: Location::caller(),
+    };
+    match mode {
+        PolicyMode::Panic => panic!("{violation}"),
+        _ => violation.register(),
+    }
+}
+
+/// Raised when an error of a denied kind is constructed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PolicyViolation {
+    /// The kind that was constructed
+    pub denied_kind: String,
+    /// Where it was constructed
+    pub location: &'static Location<'static>,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "error kind `{}` is denied by the construction policy (constructed at {})",
+            self.denied_kind, self.location
+        )
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
+impl ForgeError for PolicyViolation {
+    fn kind(&self) -> &'static str {
+        POLICY_VIOLATION_KIND
+    }
+
+    fn caption(&self) -> &'static str {
+        "Policy violation"
+    }
+
+    fn severity(&self) -> ErrorLevel {
+        ErrorLevel::Critical
+    }
+
+    fn location(&self) -> Option<&'static Location<'static>> {
+        Some(self.location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_denies() {
+        let policy = ConstructionPolicy::new().with_denied_kind("Other");
+        assert!(policy.denies("Other"));
+        assert!(!policy.denies("Config"));
+        assert_eq!(
+            policy.with_release_only(true).denies("Other"),
+            !cfg!(debug_assertions)
+        );
+
+        let violation = PolicyViolation {
+            denied_kind: "Other".to_string(),
+            location: Location::caller(),
+        };
+        assert_eq!(violation.kind(), POLICY_VIOLATION_KIND);
+        assert!(violation.to_string().contains("`Other` is denied"));
+    }
+
+    #[test]
+    fn test_policy_modes() {
+        let raise = || crate::macros::call_error_hook("x", "PolicyTest", false, false);
+        let before = violation_count();
+
+        set_construction_policy(ConstructionPolicy::new().with_denied_kind("PolicyTest"));
+        raise();
+        assert_eq!(violation_count(), before + 1);
+
+        set_construction_policy(
+            ConstructionPolicy::new()
+                .with_denied_kind("PolicyTest")
+                .with_mode(PolicyMode::Panic),
+        );
+        let panic = std::panic::catch_unwind(raise).unwrap_err();
+        clear_construction_policy();
+
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.contains(&format!("constructed at {}", file!())));
+        raise();
+        assert_eq!(violation_count(), before + 2);
+    }
+}
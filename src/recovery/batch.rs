@@ -0,0 +1,182 @@
+//! Retrying bulk work chunk by chunk.
+//!
+//! Bulk ingest sends items in chunks, and one bad item fails its whole
+//! chunk. [`RetryExecutor::retry_chunks`] retries each failed chunk
+//! with the executor's policy; a chunk that still fails is split in
+//! half and each half tried again, down to single items. Good items end
+//! up stored, and only the items that fail on their own are reported.
+
+use crate::collector::ErrorCollector;
+use crate::recovery::{RetryExecutor, RetryPolicy};
+
+/// How one item of a batch ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemOutcome {
+    /// Processed as part of a chunk that succeeded
+    Succeeded,
+    /// Failed on its own; holds the index of its error in
+    /// [`ChunkReport::errors`]
+    Failed(usize),
+}
+
+/// Returned by [`RetryExecutor::retry_chunks`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ChunkReport<E> {
+    /// One outcome per input item, in input order
+    pub outcomes: Vec<ItemOutcome>,
+    /// The final error of each failed item
+    pub errors: ErrorCollector<E>,
+}
+
+impl<E> ChunkReport<E> {
+    /// Returns true if every item succeeded.
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The number of items that succeeded.
+    pub fn succeeded(&self) -> usize {
+        self.outcomes.len() - self.errors.len()
+    }
+
+    /// The input index of each failed item.
+    pub fn failed(&self) -> impl Iterator<Item = usize> + '_ {
+        self.outcomes
+            .iter()
+            .enumerate()
+            .filter(|(_, outcome)| matches!(outcome, ItemOutcome::Failed(_)))
+            .map(|(index, _)| index)
+    }
+}
+
+impl<E> RetryExecutor<E>
+where
+    E: std::error::Error + 'static,
+{
+    /// Process `items` in chunks of `chunk_size`, retrying each failed
+    /// chunk and splitting chunks that keep failing.
+    ///
+    /// Each call to `process` is retried as [`retry`](Self::retry)
+    /// would. When a chunk still fails it is halved and both halves
+    /// are processed the same way, until the failing items are isolated.
+    /// A `chunk_size` of zero is treated as one.
+    ///
+    /// ```
+    /// use error_forge::recovery::RetryExecutor;
+    /// use error_forge::{AppError, ForgeError};
+    ///
+    /// let rows: Vec<u32> = (0..8).collect();
+    /// let report = RetryExecutor::<AppError>::new_fixed(1)
+    ///     .with_retry_if(|err| err.is_retryable())
+    ///     .retry_chunks(&rows, 4, |chunk| match chunk.contains(&5) {
+    ///         true => Err(AppError::config("row 5 is malformed")),
+    ///         false => Ok(()),
+    ///     });
+    ///
+    /// assert_eq!(report.succeeded(), 7);
+    /// assert_eq!(report.failed().collect::<Vec<_>>(), [5]);
+    /// ```
+    pub fn retry_chunks<T, F>(
+        &self,
+        items: &[T],
+        chunk_size: usize,
+        mut process: F,
+    ) -> ChunkReport<E>
+    where
+        F: FnMut(&[T]) -> Result<(), E>,
+    {
+        let mut report = ChunkReport {
+            outcomes: vec![ItemOutcome::Succeeded; items.len()],
+            errors: ErrorCollector::new(),
+        };
+        let chunk_size = chunk_size.max(1);
+        for (index, chunk) in items.chunks(chunk_size).enumerate() {
+            self.run_chunk(chunk, index * chunk_size, &mut process, &mut report);
+        }
+        report
+    }
+
+    // `offset` is the input index of the chunk's first item.
+    fn run_chunk<T, F>(
+        &self,
+        chunk: &[T],
+        offset: usize,
+        process: &mut F,
+        report: &mut ChunkReport<E>,
+    ) where
+        F: FnMut(&[T]) -> Result<(), E>,
+    {
+        let err = match self.retry(|| process(chunk)) {
+            Ok(()) => return,
+            Err(err) => err,
+        };
+        if chunk.len() == 1 {
+            report.outcomes[offset] = ItemOutcome::Failed(report.errors.len());
+            report.errors.push(err);
+            return;
+        }
+        let (left, right) = chunk.split_at(chunk.len() / 2);
+        self.run_chunk(left, offset, process, report);
+        self.run_chunk(right, offset + left.len(), process, report);
+    }
+}
+
+impl RetryPolicy {
+    /// Process `items` in chunks with this policy. See
+    /// [`RetryExecutor::retry_chunks`].
+    pub fn retry_chunks<T, F, E>(
+        &self,
+        items: &[T],
+        chunk_size: usize,
+        process: F,
+    ) -> ChunkReport<E>
+    where
+        F: FnMut(&[T]) -> Result<(), E>,
+        E: std::error::Error + 'static,
+    {
+        self.executor::<E>()
+            .retry_chunks(items, chunk_size, process)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppError, ForgeError};
+    use std::cell::Cell;
+
+    #[test]
+    fn test_retry_chunks_isolates_failures() {
+        let calls = Cell::new(0);
+        let flaky = Cell::new(true);
+        let items: Vec<u32> = (0..10).collect();
+
+        let report = RetryExecutor::<AppError>::new_fixed(1)
+            .with_max_retries(1)
+            .with_retry_if(|err| err.is_retryable())
+            .retry_chunks(&items, 4, |chunk| {
+                calls.set(calls.get() + 1);
+                if chunk.contains(&2) && flaky.replace(false) {
+                    return Err(AppError::network("db", None));
+                }
+                match chunk.iter().find(|&&item| item == 6 || item == 9) {
+                    Some(item) => Err(AppError::other(format!("bad item {item}"))),
+                    None => Ok(()),
+                }
+            });
+
+        // [0..4) fails once and is retried; [4..8) splits down to 6;
+        // [8, 9] splits down to 9.
+        assert_eq!(calls.get(), 2 + 5 + 3);
+        assert_eq!(report.succeeded(), 8);
+        assert_eq!(report.failed().collect::<Vec<_>>(), [6, 9]);
+        assert_eq!(report.outcomes[9], ItemOutcome::Failed(1));
+        assert!(report.errors.errors()[0].to_string().contains("bad item 6"));
+        assert!(!report.is_complete());
+
+        let empty =
+            RetryPolicy::new_fixed(1).retry_chunks(&[] as &[u32], 0, |_| Ok::<_, AppError>(()));
+        assert!(empty.is_complete());
+    }
+}
@@ -1,6 +1,8 @@
+use crate::error::ForgeError;
 use crate::recovery::RecoveryResult;
 use parking_lot::Mutex;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, Weak};
 use std::time::{Duration, Instant};
 
 /// Represents the current state of a circuit breaker.
@@ -93,6 +95,107 @@ struct CircuitBreakerInner {
     state: CircuitState,
     failures: Vec<Instant>,
     last_state_change: Instant,
+    failures_total: u64,
+    rejections_total: u64,
+}
+
+/// Every live breaker, for [`metrics`](crate::recovery::metrics).
+type BreakerRegistry = Mutex<Vec<(String, Weak<Mutex<CircuitBreakerInner>>)>>;
+
+fn registry() -> &'static BreakerRegistry {
+    static REGISTRY: OnceLock<BreakerRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Breakers handed out by [`CircuitBreaker::get_or_create`]. Past
+/// `capacity`, the least recently requested one is dropped, so names
+/// built from request data (tenant IDs) cannot grow it without bound.
+struct SharedBreakers {
+    breakers: HashMap<String, (Arc<Mutex<CircuitBreakerInner>>, u64)>,
+    capacity: usize,
+    uses: u64,
+}
+
+impl SharedBreakers {
+    fn new(capacity: usize) -> Self {
+        Self {
+            breakers: HashMap::new(),
+            capacity,
+            uses: 0,
+        }
+    }
+
+    /// The breaker named `name`, shared with every other caller, or the
+    /// one `create` builds, which is kept if there is room for it.
+    fn get_or_insert(
+        &mut self,
+        name: &str,
+        create: impl FnOnce() -> Arc<Mutex<CircuitBreakerInner>>,
+    ) -> Arc<Mutex<CircuitBreakerInner>> {
+        self.uses += 1;
+        if let Some((inner, used)) = self.breakers.get_mut(name) {
+            *used = self.uses;
+            return inner.clone();
+        }
+        let inner = create();
+        self.evict_to(self.capacity.saturating_sub(1));
+        if self.capacity > 0 {
+            self.breakers
+                .insert(name.to_string(), (inner.clone(), self.uses));
+        }
+        inner
+    }
+
+    /// Drop the least recently requested breakers until at most `len`
+    /// remain.
+    fn evict_to(&mut self, len: usize) {
+        while self.breakers.len() > len {
+            let oldest = self
+                .breakers
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(name, _)| name.clone());
+            match oldest {
+                Some(name) => self.breakers.remove(&name),
+                None => return,
+            };
+        }
+    }
+}
+
+fn shared() -> &'static Mutex<SharedBreakers> {
+    static SHARED: OnceLock<Mutex<SharedBreakers>> = OnceLock::new();
+    SHARED.get_or_init(|| Mutex::new(SharedBreakers::new(CircuitBreaker::DEFAULT_SHARED_CAPACITY)))
+}
+
+/// Point-in-time counters for one breaker.
+pub(crate) struct BreakerSample {
+    pub(crate) name: String,
+    pub(crate) state: CircuitState,
+    pub(crate) failures_total: u64,
+    pub(crate) rejections_total: u64,
+}
+
+/// Sample every live breaker, dropping registry entries for breakers
+/// that no longer exist.
+pub(crate) fn sample_breakers() -> Vec<BreakerSample> {
+    let mut samples = Vec::new();
+    registry()
+        .lock()
+        .retain(|(name, inner)| match inner.upgrade() {
+            Some(inner) => {
+                let inner = inner.lock();
+                samples.push(BreakerSample {
+                    name: name.clone(),
+                    state: inner.state,
+                    failures_total: inner.failures_total,
+                    rejections_total: inner.rejections_total,
+                });
+                true
+            }
+            None => false,
+        });
+    samples
 }
 
 /// Circuit breaker implementation to prevent cascading failures
@@ -105,6 +208,9 @@ pub struct CircuitBreaker {
 }
 
 impl CircuitBreaker {
+    /// Number of shared breakers kept when no capacity has been set.
+    pub const DEFAULT_SHARED_CAPACITY: usize = 1024;
+
     /// Create a new circuit breaker with the given name and default configuration
     pub fn new(name: impl Into<String>) -> Self {
         Self::with_config(name, CircuitBreakerConfig::default())
@@ -112,15 +218,68 @@ impl CircuitBreaker {
 
     /// Create a new circuit breaker with custom configuration
     pub fn with_config(name: impl Into<String>, config: CircuitBreakerConfig) -> Self {
-        Self {
-            name: name.into(),
-            inner: Arc::new(Mutex::new(CircuitBreakerInner {
-                config,
-                state: CircuitState::Closed,
-                failures: Vec::new(),
-                last_state_change: Instant::now(),
-            })),
-        }
+        let name = name.into();
+        let inner = Arc::new(Mutex::new(CircuitBreakerInner {
+            config,
+            state: CircuitState::Closed,
+            failures: Vec::new(),
+            last_state_change: crate::clock::instant(),
+            failures_total: 0,
+            rejections_total: 0,
+        }));
+        // Dropped breakers are pruned here too, so creating short-lived
+        // breakers does not grow the registry between metrics scrapes.
+        let mut breakers = registry().lock();
+        breakers.retain(|(_, inner)| inner.strong_count() > 0);
+        breakers.push((name.clone(), Arc::downgrade(&inner)));
+        drop(breakers);
+        Self { name, inner }
+    }
+
+    /// The process-wide breaker named `name`, created with `config` the
+    /// first time. Every call with the same name shares one circuit, so
+    /// call sites need not pass a breaker around; `config` is ignored
+    /// once the breaker exists.
+    ///
+    /// At most [`DEFAULT_SHARED_CAPACITY`](Self::DEFAULT_SHARED_CAPACITY)
+    /// breakers are kept (see
+    /// [`set_shared_capacity`](Self::set_shared_capacity)); past that,
+    /// the least recently requested one is forgotten and starts closed
+    /// the next time it is asked for.
+    pub fn get_or_create(name: impl Into<String>, config: CircuitBreakerConfig) -> Self {
+        let name = name.into();
+        let inner = shared()
+            .lock()
+            .get_or_insert(&name, || Self::with_config(name.clone(), config).inner);
+        Self { name, inner }
+    }
+
+    /// Keep at most `capacity` shared breakers, forgetting the least
+    /// recently requested ones beyond it. A capacity of 0 stops
+    /// sharing: every [`get_or_create`](Self::get_or_create) call
+    /// returns a new breaker.
+    pub fn set_shared_capacity(capacity: usize) {
+        let mut shared = shared().lock();
+        shared.capacity = capacity;
+        shared.evict_to(capacity);
+    }
+
+    /// Forget the shared breaker named `name`, for example when a
+    /// tenant is offboarded. Returns whether it existed. Handles to it
+    /// keep working, but the next [`get_or_create`](Self::get_or_create)
+    /// builds a new breaker.
+    pub fn remove_shared(name: &str) -> bool {
+        shared().lock().breakers.remove(name).is_some()
+    }
+
+    /// The process-wide breaker for `tenant`'s calls to `name`, named
+    /// `"{name}/{tenant}"`. Each tenant gets its own circuit, so one
+    /// noisy tenant's failures do not fail fast for everyone else.
+    ///
+    /// Uses the default configuration; to tune it, call
+    /// [`get_or_create`](Self::get_or_create) with the same name first.
+    pub fn get_or_create_tenant(name: &str, tenant: &str) -> Self {
+        Self::get_or_create(format!("{name}/{tenant}"), CircuitBreakerConfig::default())
     }
 
     /// Get the current state of the circuit breaker
@@ -140,16 +299,25 @@ impl CircuitBreaker {
         F: FnOnce() -> Result<T, E>,
         E: std::error::Error + Send + Sync + 'static,
     {
-        // First check if we can proceed with the call
-        let can_proceed = {
+        // First check if we can proceed with the call. While open, also
+        // work out how long until the breaker will allow a test request.
+        let open_for = {
             let mut inner = self.inner.lock();
             self.update_state(&mut inner);
-            inner.state != CircuitState::Open
+            let open = inner.state == CircuitState::Open;
+            if open {
+                inner.rejections_total += 1;
+            }
+            open.then(|| {
+                Duration::from_millis(inner.config.reset_timeout_ms).saturating_sub(
+                    crate::clock::instant().saturating_duration_since(inner.last_state_change),
+                )
+            })
         };
 
         // If circuit is open, fail fast
-        if !can_proceed {
-            return Err(Box::new(CircuitOpenError::new(&self.name)));
+        if let Some(retry_after) = open_for {
+            return Err(Box::new(CircuitOpenError::new(&self.name, retry_after)));
         }
 
         // Execute the function
@@ -172,7 +340,7 @@ impl CircuitBreaker {
         let mut inner = self.inner.lock();
         inner.state = CircuitState::Closed;
         inner.failures.clear();
-        inner.last_state_change = Instant::now();
+        inner.last_state_change = crate::clock::instant();
     }
 
     /// Called when an operation succeeds
@@ -182,23 +350,24 @@ impl CircuitBreaker {
             // Successful test request, close the circuit
             inner.state = CircuitState::Closed;
             inner.failures.clear();
-            inner.last_state_change = Instant::now();
+            inner.last_state_change = crate::clock::instant();
         }
     }
 
     /// Called when an operation fails
     fn on_failure(&self) {
         let mut inner = self.inner.lock();
+        inner.failures_total += 1;
 
         if inner.state == CircuitState::HalfOpen {
             // Failed during test request, reopen the circuit
             inner.state = CircuitState::Open;
-            inner.last_state_change = Instant::now();
+            inner.last_state_change = crate::clock::instant();
             return;
         }
 
         // Add the failure
-        let now = Instant::now();
+        let now = crate::clock::instant();
         inner.failures.push(now);
 
         // Remove old failures outside the window
@@ -218,7 +387,7 @@ impl CircuitBreaker {
     /// Update the circuit state based on timing
     fn update_state(&self, inner: &mut CircuitBreakerInner) {
         if inner.state == CircuitState::Open {
-            let now = Instant::now();
+            let now = crate::clock::instant();
             let elapsed = now.duration_since(inner.last_state_change);
 
             if elapsed >= Duration::from_millis(inner.config.reset_timeout_ms) {
@@ -234,14 +403,27 @@ impl CircuitBreaker {
 #[derive(Debug)]
 pub struct CircuitOpenError {
     circuit_name: String,
+    retry_after: Duration,
 }
 
 impl CircuitOpenError {
-    fn new(circuit_name: &str) -> Self {
+    fn new(circuit_name: &str, retry_after: Duration) -> Self {
         Self {
             circuit_name: circuit_name.to_string(),
+            retry_after,
         }
     }
+
+    /// Name of the circuit breaker that rejected the call
+    pub fn circuit_name(&self) -> &str {
+        &self.circuit_name
+    }
+
+    /// Time remaining, when the call was rejected, until the breaker
+    /// moves to half-open and lets a test request through
+    pub fn retry_after(&self) -> Duration {
+        self.retry_after
+    }
 }
 
 impl std::fmt::Display for CircuitOpenError {
@@ -251,3 +433,83 @@ impl std::fmt::Display for CircuitOpenError {
 }
 
 impl std::error::Error for CircuitOpenError {}
+
+impl ForgeError for CircuitOpenError {
+    fn kind(&self) -> &'static str {
+        "CircuitOpen"
+    }
+
+    fn caption(&self) -> &'static str {
+        "Circuit open"
+    }
+
+    fn is_retryable(&self) -> bool {
+        true
+    }
+
+    fn status_code(&self) -> u16 {
+        503
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        Some(self.retry_after)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tenant_breakers() {
+        let config = CircuitBreakerConfig::default().with_failure_threshold(1);
+        let noisy = CircuitBreaker::get_or_create("ledger/noisy", config);
+        let _ = noisy.execute(|| Err::<(), _>(std::io::Error::other("down")));
+
+        // The same circuit, shared by name.
+        let again = CircuitBreaker::get_or_create_tenant("ledger", "noisy");
+        assert_eq!(again.name(), "ledger/noisy");
+        assert_eq!(again.state(), CircuitState::Open);
+
+        let quiet = CircuitBreaker::get_or_create_tenant("ledger", "quiet");
+        assert_eq!(quiet.state(), CircuitState::Closed);
+        assert!(quiet.execute(|| Ok::<_, std::io::Error>(())).is_ok());
+    }
+
+    #[test]
+    fn test_dropped_breakers_leave_registry() {
+        for _ in 0..100 {
+            drop(CircuitBreaker::new("short-lived"));
+        }
+        let _live = CircuitBreaker::new("short-lived");
+        let entries = registry()
+            .lock()
+            .iter()
+            .filter(|(name, _)| name == "short-lived")
+            .count();
+        assert_eq!(entries, 1);
+    }
+
+    #[test]
+    fn test_shared_capacity() {
+        let create = || CircuitBreaker::new("capped").inner;
+        let mut shared = SharedBreakers::new(2);
+        let a = shared.get_or_insert("a", create);
+        shared.get_or_insert("b", create);
+        // Requesting `a` again makes `b` the least recently used.
+        assert!(Arc::ptr_eq(&a, &shared.get_or_insert("a", create)));
+        shared.get_or_insert("c", create);
+        assert_eq!(shared.breakers.len(), 2);
+        assert!(shared.breakers.contains_key("a"));
+        assert!(!shared.breakers.contains_key("b"));
+
+        shared.capacity = 0;
+        shared.evict_to(0);
+        shared.get_or_insert("d", create);
+        assert!(shared.breakers.is_empty());
+
+        let tenant = CircuitBreaker::get_or_create_tenant("ledger", "offboarded");
+        assert!(CircuitBreaker::remove_shared(tenant.name()));
+        assert!(!CircuitBreaker::remove_shared(tenant.name()));
+    }
+}
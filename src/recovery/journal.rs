@@ -0,0 +1,182 @@
+//! A bounded, in-memory journal of retry sequences for postmortems.
+//!
+//! Executors opt in with
+//! [`RetryExecutor::with_journal`](super::RetryExecutor::with_journal).
+//! Each finished retry loop then appends a [`RetryRecord`] — the
+//! operation name, every delay waited, the outcome, and the total
+//! duration — so an incident review can reconstruct what a client did.
+//! The journal keeps the most recent [`DEFAULT_CAPACITY`] records
+//! unless [`set_capacity`] changes it.
+//!
+//! Each finished sequence is also reported to the error hook and its
+//! observers as an event of kind [`RETRY_KIND`], unless
+//! [side effects](crate::side_effects) are disabled.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::recovery::journal::{self, RetryOutcome};
+//! use error_forge::recovery::RetryPolicy;
+//!
+//! let executor = RetryPolicy::new_fixed(1)
+//!     .with_max_retries(2)
+//!     .executor::<std::io::Error>()
+//!     .with_journal("fetch-invoice");
+//! let _ = executor.retry(|| Err::<(), _>(std::io::Error::other("timeout")));
+//!
+//! let record = journal::recent()
+//!     .into_iter()
+//!     .rev()
+//!     .find(|r| r.operation == "fetch-invoice")
+//!     .unwrap();
+//! assert_eq!(record.attempts, 3);
+//! assert_eq!(record.outcome, RetryOutcome::Exhausted);
+//! ```
+
+use crate::macros::ErrorLevel;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+/// Number of records kept when no capacity has been set.
+pub const DEFAULT_CAPACITY: usize = 64;
+
+/// The kind of the hook event sent when a journaled retry sequence
+/// ends. Its caption names the operation, outcome, and attempt count.
+pub const RETRY_KIND: &str = "RetrySequence";
+
+/// How a retry sequence ended.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// outcomes without breaking callers that `match` on the enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RetryOutcome {
+    /// An attempt succeeded
+    Succeeded,
+    /// The last allowed attempt failed
+    Exhausted,
+    /// An error was rejected by the retry predicate
+    NotRetryable,
+    /// An open circuit breaker stopped the sequence
+    CircuitOpen,
+}
+
+impl fmt::Display for RetryOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Succeeded => "succeeded",
+            Self::Exhausted => "exhausted",
+            Self::NotRetryable => "not retryable",
+            Self::CircuitOpen => "circuit open",
+        })
+    }
+}
+
+/// One finished retry sequence.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// fields without breaking callers.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RetryRecord {
+    /// The operation name given to `with_journal`
+    pub operation: String,
+    /// Number of times the operation ran
+    pub attempts: usize,
+    /// Delay waited before each retry, in order
+    pub delays: Vec<Duration>,
+    /// How the sequence ended
+    pub outcome: RetryOutcome,
+    /// `Display` output of the final error, if the sequence failed
+    pub error: Option<String>,
+    /// Wall time from the first attempt to the end of the sequence
+    pub total_duration: Duration,
+    /// When the sequence ended
+    pub finished_at: SystemTime,
+}
+
+impl RetryRecord {
+    pub(crate) fn new(
+        operation: String,
+        delays: Vec<Duration>,
+        outcome: RetryOutcome,
+        error: Option<String>,
+        total_duration: Duration,
+    ) -> Self {
+        Self {
+            operation,
+            attempts: delays.len() + 1,
+            delays,
+            outcome,
+            error,
+            total_duration,
+            finished_at: crate::clock::now(),
+        }
+    }
+}
+
+struct Journal {
+    capacity: usize,
+    records: VecDeque<RetryRecord>,
+}
+
+fn journal() -> &'static Mutex<Journal> {
+    static JOURNAL: OnceLock<Mutex<Journal>> = OnceLock::new();
+    JOURNAL.get_or_init(|| {
+        Mutex::new(Journal {
+            capacity: DEFAULT_CAPACITY,
+            records: VecDeque::new(),
+        })
+    })
+}
+
+pub(crate) fn record(record: RetryRecord) {
+    notify(&record);
+    let mut journal = journal().lock();
+    if journal.capacity == 0 {
+        return;
+    }
+    while journal.records.len() >= journal.capacity {
+        journal.records.pop_front();
+    }
+    journal.records.push_back(record);
+}
+
+/// Send the sequence to the error hook and observers, at `Info` when
+/// it succeeded and `Warning` otherwise.
+fn notify(record: &RetryRecord) {
+    if !crate::side_effects::enabled() {
+        return;
+    }
+    let level = match record.outcome {
+        RetryOutcome::Succeeded => ErrorLevel::Info,
+        _ => ErrorLevel::Warning,
+    };
+    let caption = format!(
+        "{}: {} after {} attempts",
+        record.operation, record.outcome, record.attempts
+    );
+    crate::macros::dispatch_error_hook(level, &caption, RETRY_KIND, false, false);
+}
+
+/// The journaled retry sequences, oldest first.
+pub fn recent() -> Vec<RetryRecord> {
+    journal().lock().records.iter().cloned().collect()
+}
+
+/// Set how many records the journal keeps, discarding the oldest
+/// beyond it. A capacity of 0 stops journaling.
+pub fn set_capacity(capacity: usize) {
+    let mut journal = journal().lock();
+    journal.capacity = capacity;
+    let excess = journal.records.len().saturating_sub(capacity);
+    journal.records.drain(..excess);
+}
+
+/// Remove every record.
+pub fn clear() {
+    journal().lock().records.clear();
+}
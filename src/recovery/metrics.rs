@@ -0,0 +1,224 @@
+//! Circuit-breaker and retry metrics in Prometheus text format.
+//!
+//! [`render_prometheus`] returns the current counters as a string in
+//! the [exposition format] so it can be served from whatever
+//! `/metrics` endpoint an application already has; no HTTP server is
+//! included.
+//!
+//! | Metric | Type | Labels |
+//! |---|---|---|
+//! | `error_forge_circuit_state` | gauge (1 for the current state) | `circuit`, `state` |
+//! | `error_forge_circuit_failures_total` | counter | `circuit` |
+//! | `error_forge_circuit_rejections_total` | counter | `circuit` |
+//! | `error_forge_retry_attempts_total` | counter | |
+//! | `error_forge_retry_give_ups_total` | counter | |
+//! | `error_forge_suppressed_errors_total` | counter | `kind` |
+//!
+//! Every live [`CircuitBreaker`](super::CircuitBreaker) is included.
+//! Retry counters cover every [`RetryExecutor`](super::RetryExecutor)
+//! in the process: an attempt is each retry after a failure, and a
+//! give-up is a retry loop that returned an error. Suppressed errors
+//! are those swallowed by the [`DegradeExt`](crate::degrade::DegradeExt)
+//! helpers.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::recovery::{metrics, CircuitBreaker};
+//!
+//! let breaker = CircuitBreaker::new("billing");
+//! let _ = breaker.execute(|| Ok::<_, std::io::Error>(()));
+//!
+//! let text = metrics::render_prometheus();
+//! assert!(text.contains(r#"error_forge_circuit_state{circuit="billing",state="closed"} 1"#));
+//! ```
+//!
+//! [exposition format]: https://prometheus.io/docs/instrumenting/exposition_formats/
+
+use super::circuit_breaker::sample_breakers;
+use super::CircuitState;
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static RETRY_ATTEMPTS: AtomicU64 = AtomicU64::new(0);
+static RETRY_GIVE_UPS: AtomicU64 = AtomicU64::new(0);
+static SUPPRESSED: Mutex<BTreeMap<&'static str, u64>> = Mutex::new(BTreeMap::new());
+
+pub(crate) fn record_retry() {
+    if !crate::side_effects::enabled() {
+        return;
+    }
+    RETRY_ATTEMPTS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub(crate) fn record_give_up() {
+    if !crate::side_effects::enabled() {
+        return;
+    }
+    RETRY_GIVE_UPS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub(crate) fn record_suppressed(kind: &'static str) {
+    if !crate::side_effects::enabled() {
+        return;
+    }
+    *SUPPRESSED.lock().entry(kind).or_insert(0) += 1;
+}
+
+/// Render breaker and retry metrics in Prometheus text exposition
+/// format.
+pub fn render_prometheus() -> String {
+    let breakers = sample_breakers();
+    let mut out = String::with_capacity(512 + breakers.len() * 256);
+
+    header(
+        &mut out,
+        "error_forge_circuit_state",
+        "gauge",
+        "Circuit breaker state; 1 for the current state.",
+    );
+    for breaker in &breakers {
+        let name = escape_label(&breaker.name);
+        for state in [
+            CircuitState::Closed,
+            CircuitState::Open,
+            CircuitState::HalfOpen,
+        ] {
+            let _ = writeln!(
+                out,
+                "error_forge_circuit_state{{circuit=\"{name}\",state=\"{}\"}} {}",
+                state_label(state),
+                u8::from(breaker.state == state)
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "error_forge_circuit_failures_total",
+        "counter",
+        "Operations that failed through a circuit breaker.",
+    );
+    for breaker in &breakers {
+        let _ = writeln!(
+            out,
+            "error_forge_circuit_failures_total{{circuit=\"{}\"}} {}",
+            escape_label(&breaker.name),
+            breaker.failures_total
+        );
+    }
+
+    header(
+        &mut out,
+        "error_forge_circuit_rejections_total",
+        "counter",
+        "Calls rejected because a circuit breaker was open.",
+    );
+    for breaker in &breakers {
+        let _ = writeln!(
+            out,
+            "error_forge_circuit_rejections_total{{circuit=\"{}\"}} {}",
+            escape_label(&breaker.name),
+            breaker.rejections_total
+        );
+    }
+
+    header(
+        &mut out,
+        "error_forge_retry_attempts_total",
+        "counter",
+        "Retries performed after a failed attempt.",
+    );
+    let _ = writeln!(
+        out,
+        "error_forge_retry_attempts_total {}",
+        RETRY_ATTEMPTS.load(Ordering::Relaxed)
+    );
+
+    header(
+        &mut out,
+        "error_forge_retry_give_ups_total",
+        "counter",
+        "Retry loops that gave up and returned an error.",
+    );
+    let _ = writeln!(
+        out,
+        "error_forge_retry_give_ups_total {}",
+        RETRY_GIVE_UPS.load(Ordering::Relaxed)
+    );
+
+    header(
+        &mut out,
+        "error_forge_suppressed_errors_total",
+        "counter",
+        "Errors logged and replaced by a fallback value.",
+    );
+    for (kind, count) in SUPPRESSED.lock().iter() {
+        let _ = writeln!(
+            out,
+            "error_forge_suppressed_errors_total{{kind=\"{}\"}} {count}",
+            escape_label(kind)
+        );
+    }
+
+    out
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn state_label(state: CircuitState) -> &'static str {
+    match state {
+        CircuitState::Closed => "closed",
+        CircuitState::Open => "open",
+        CircuitState::HalfOpen => "half_open",
+    }
+}
+
+/// Escape a label value: backslash, double quote, and newline.
+fn escape_label(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recovery::{CircuitBreaker, CircuitBreakerConfig};
+
+    #[test]
+    fn test_render_prometheus() {
+        let breaker = CircuitBreaker::with_config(
+            "metrics \"test\"",
+            CircuitBreakerConfig::new(1, 60_000, 60_000),
+        );
+        let _ = breaker.execute(|| Err::<(), _>(std::io::Error::other("down")));
+        let _ = breaker.execute(|| Ok::<_, std::io::Error>(()));
+
+        let text = render_prometheus();
+        let label = r#"circuit="metrics \"test\"""#;
+        assert!(text.contains(&format!(
+            "error_forge_circuit_state{{{label},state=\"open\"}} 1"
+        )));
+        assert!(text.contains(&format!("error_forge_circuit_failures_total{{{label}}} 1")));
+        assert!(text.contains(&format!(
+            "error_forge_circuit_rejections_total{{{label}}} 1"
+        )));
+        assert!(text.contains("# TYPE error_forge_retry_give_ups_total counter"));
+
+        drop(breaker);
+        assert!(!render_prometheus().contains(label));
+    }
+}
@@ -9,6 +9,10 @@
 //! - Circuit breaker pattern to prevent cascading failures
 //! - Retry policies for flexible retry behaviors
 //! - `ForgeError`-aware retry executors for sync workloads
+//! - Chunked batch retries that isolate the failing items
+//! - A [`Supervisor`] that restarts or aborts workers by error kind
+//! - Prometheus-format [`metrics`] for breakers and retries
+//! - An opt-in [`journal`] of retry sequences for postmortems
 //!
 //! # Examples
 //!
@@ -24,14 +28,20 @@
 //! ```
 
 mod backoff;
+mod batch;
 mod circuit_breaker;
 mod forge_extensions;
+pub mod journal;
+pub mod metrics;
 mod retry;
+mod supervisor;
 
 pub use backoff::{Backoff, ExponentialBackoff, FixedBackoff, LinearBackoff};
+pub use batch::{ChunkReport, ItemOutcome};
 pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitOpenError, CircuitState};
 pub use forge_extensions::ForgeErrorRecovery;
-pub use retry::{RetryExecutor, RetryPolicy};
+pub use retry::{CircuitOpenPolicy, RetryExecutor, RetryExhausted, RetryPolicy};
+pub use supervisor::{ExitAction, ExitStrategy, Supervisor};
 
 /// Result type for recovery operations
 pub type RecoveryResult<T> =
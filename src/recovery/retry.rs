@@ -1,8 +1,13 @@
+use crate::error::Chain;
 use crate::error::ForgeError;
+use crate::metadata::MetadataError;
 use crate::recovery::backoff::{Backoff, ExponentialBackoff, FixedBackoff, LinearBackoff};
+use crate::recovery::journal::{self, RetryOutcome, RetryRecord};
+use crate::recovery::{metrics, CircuitOpenError};
+use std::fmt;
 use std::marker::PhantomData;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Predicate function to determine if an error is retryable
 pub type RetryPredicate<E> = Box<dyn Fn(&E) -> bool + Send + Sync + 'static>;
@@ -24,11 +29,38 @@ impl BackoffStrategy {
     }
 }
 
+/// How a [`RetryExecutor`] reacts to an error caused by a
+/// [`CircuitOpenError`].
+///
+/// The rejection is detected anywhere in the error's source chain, so
+/// it is recognised whether the operation returns it directly or
+/// wrapped in an application error.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// policies without breaking callers that `match` on the enum.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CircuitOpenPolicy {
+    /// Return the rejection immediately; retrying an open circuit only
+    /// burns attempts on calls that cannot succeed
+    #[default]
+    FailFast,
+    /// Sleep until the breaker moves to half-open
+    /// ([`CircuitOpenError::retry_after`]) instead of using the backoff
+    /// delay, then retry. Still bounded by the maximum retry count.
+    WaitForReset,
+    /// Treat the rejection like any other error (the behaviour before
+    /// this policy existed)
+    Retry,
+}
+
 /// Executor for retry operations
 pub struct RetryExecutor<E> {
     max_retries: usize,
     backoff: BackoffStrategy,
     retry_if: Option<RetryPredicate<E>>,
+    circuit_open: CircuitOpenPolicy,
+    journal: Option<String>,
     _marker: PhantomData<E>,
 }
 
@@ -42,6 +74,8 @@ where
             max_retries: 3,
             backoff: BackoffStrategy::Exponential(ExponentialBackoff::default()),
             retry_if: None,
+            circuit_open: CircuitOpenPolicy::default(),
+            journal: None,
             _marker: PhantomData,
         }
     }
@@ -52,6 +86,8 @@ where
             max_retries: 3,
             backoff: BackoffStrategy::Linear(LinearBackoff::default()),
             retry_if: None,
+            circuit_open: CircuitOpenPolicy::default(),
+            journal: None,
             _marker: PhantomData,
         }
     }
@@ -62,6 +98,8 @@ where
             max_retries: 3,
             backoff: BackoffStrategy::Fixed(FixedBackoff::new(delay_ms)),
             retry_if: None,
+            circuit_open: CircuitOpenPolicy::default(),
+            journal: None,
             _marker: PhantomData,
         }
     }
@@ -81,43 +119,177 @@ where
         self
     }
 
+    /// Set how errors caused by an open circuit breaker are handled
+    pub fn with_circuit_open_policy(mut self, policy: CircuitOpenPolicy) -> Self {
+        self.circuit_open = policy;
+        self
+    }
+
+    /// Record every retry sequence run by this executor in the
+    /// [`journal`](crate::recovery::journal) under `operation`.
+    pub fn with_journal(mut self, operation: impl Into<String>) -> Self {
+        self.journal = Some(operation.into());
+        self
+    }
+
     /// Execute a fallible operation with retries
-    pub fn retry<F, T>(&self, mut operation: F) -> Result<T, E>
+    pub fn retry<F, T>(&self, operation: F) -> Result<T, E>
     where
         F: FnMut() -> Result<T, E>,
     {
-        let mut attempt = 0;
-        loop {
-            match operation() {
-                Ok(value) => return Ok(value),
-                Err(err) => {
-                    // Check if we've reached max retries
-                    if attempt >= self.max_retries {
-                        return Err(err);
-                    }
-
-                    // Check if this error is retryable
-                    let should_retry = match &self.retry_if {
-                        Some(predicate) => predicate(&err),
-                        None => true,
-                    };
+        self.retry_with_handler(operation, |_, _, _| {})
+    }
 
-                    if !should_retry {
-                        return Err(err);
-                    }
+    /// Execute a fallible operation with retries using a custom error handler
+    pub fn retry_with_handler<F, H, T>(&self, operation: F, on_error: H) -> Result<T, E>
+    where
+        F: FnMut() -> Result<T, E>,
+        H: FnMut(&E, usize, Duration),
+    {
+        let started = Instant::now();
+        let mut delays = Vec::new();
+        let (result, outcome) = self.run(operation, on_error, &mut delays);
 
-                    // Wait according to backoff strategy
-                    let delay = self.backoff.next_delay(attempt);
-                    thread::sleep(delay);
+        if outcome != RetryOutcome::Succeeded {
+            metrics::record_give_up();
+        }
+        if let Some(operation) = &self.journal {
+            journal::record(RetryRecord::new(
+                operation.clone(),
+                delays,
+                outcome,
+                result.as_ref().err().map(ToString::to_string),
+                started.elapsed(),
+            ));
+        }
+        result
+    }
 
-                    attempt += 1;
+    /// Execute a fallible operation with retries, attaching the attempt
+    /// history to the error it gives up with.
+    ///
+    /// The returned error carries `retry.attempts` plus, for each
+    /// attempt `N`, `retry.N.kind`, `retry.N.duration_ms`, and (before
+    /// every retry) `retry.N.delay_ms` as
+    /// [metadata](ForgeError::metadata), so the final log line or
+    /// snapshot shows the whole sequence.
+    ///
+    /// ```
+    /// use error_forge::recovery::RetryPolicy;
+    /// use error_forge::{AppError, ForgeError};
+    ///
+    /// let err = RetryPolicy::new_fixed(1)
+    ///     .with_max_retries(1)
+    ///     .forge_executor()
+    ///     .retry_with_history(|| Err::<(), _>(AppError::network("billing", None)))
+    ///     .unwrap_err();
+    ///
+    /// let meta = err.metadata();
+    /// assert_eq!(meta.get("retry.attempts").unwrap().to_string(), "2");
+    /// assert_eq!(meta.get("retry.1.kind").unwrap().to_string(), "Network");
+    /// assert_eq!(meta.get("retry.1.delay_ms").unwrap().to_string(), "1");
+    /// assert!(meta.get("retry.2.delay_ms").is_none());
+    /// ```
+    pub fn retry_with_history<F, T>(&self, mut operation: F) -> Result<T, MetadataError<E>>
+    where
+        F: FnMut() -> Result<T, E>,
+        E: ForgeError,
+    {
+        let mut attempts = Vec::new();
+        let mut delays = Vec::new();
+        let result = self.retry_with_handler(
+            || {
+                let started = Instant::now();
+                let result = operation();
+                if let Err(err) = &result {
+                    attempts.push((err.kind(), started.elapsed()));
+                }
+                result
+            },
+            |_, _, delay| delays.push(delay),
+        );
+
+        result.map_err(|err| {
+            let mut err = MetadataError::new(err).with_meta("retry.attempts", attempts.len());
+            for (index, (kind, duration)) in attempts.into_iter().enumerate() {
+                let n = index + 1;
+                err = err.with_meta(format!("retry.{n}.kind"), kind).with_meta(
+                    format!("retry.{n}.duration_ms"),
+                    duration.as_millis() as u64,
+                );
+                if let Some(delay) = delays.get(index) {
+                    err = err.with_meta(format!("retry.{n}.delay_ms"), delay.as_millis() as u64);
                 }
             }
+            err
+        })
+    }
+
+    /// Poll `operation` until it returns `Some`, waiting out the backoff
+    /// between attempts.
+    ///
+    /// Suits readiness checks and job-status polling, which have no
+    /// error to report while they wait. Gives up with a
+    /// [`RetryExhausted`] after the maximum number of retries.
+    pub fn retry_until_some<F, T>(&self, mut operation: F) -> Result<T, RetryExhausted>
+    where
+        F: FnMut() -> Option<T>,
+    {
+        let started = Instant::now();
+        let mut delays = Vec::new();
+        let mut attempt = 0;
+        let result = loop {
+            if let Some(value) = operation() {
+                break Ok(value);
+            }
+            if attempt >= self.max_retries {
+                break Err(RetryExhausted {
+                    attempts: attempt + 1,
+                    elapsed: started.elapsed(),
+                });
+            }
+            let delay = self.backoff.next_delay(attempt);
+            metrics::record_retry();
+            delays.push(delay);
+            thread::sleep(delay);
+            attempt += 1;
+        };
+
+        let outcome = match &result {
+            Ok(_) => RetryOutcome::Succeeded,
+            Err(_) => {
+                metrics::record_give_up();
+                RetryOutcome::Exhausted
+            }
+        };
+        if let Some(operation) = &self.journal {
+            journal::record(RetryRecord::new(
+                operation.clone(),
+                delays,
+                outcome,
+                result.as_ref().err().map(ToString::to_string),
+                started.elapsed(),
+            ));
         }
+        result
     }
 
-    /// Execute a fallible operation with retries using a custom error handler
-    pub fn retry_with_handler<F, H, T>(&self, mut operation: F, mut on_error: H) -> Result<T, E>
+    /// Poll `condition` until it returns `true`, waiting out the backoff
+    /// between attempts. See [`retry_until_some`](Self::retry_until_some).
+    pub fn retry_until<F>(&self, mut condition: F) -> Result<(), RetryExhausted>
+    where
+        F: FnMut() -> bool,
+    {
+        self.retry_until_some(|| condition().then_some(()))
+    }
+
+    // The retry loop; pushes each delay it waits onto `delays`.
+    fn run<F, H, T>(
+        &self,
+        mut operation: F,
+        mut on_error: H,
+        delays: &mut Vec<Duration>,
+    ) -> (Result<T, E>, RetryOutcome)
     where
         F: FnMut() -> Result<T, E>,
         H: FnMut(&E, usize, Duration),
@@ -125,30 +297,38 @@ where
         let mut attempt = 0;
         loop {
             match operation() {
-                Ok(value) => return Ok(value),
+                Ok(value) => return (Ok(value), RetryOutcome::Succeeded),
                 Err(err) => {
                     // Check if we've reached max retries
                     if attempt >= self.max_retries {
-                        return Err(err);
+                        return (Err(err), RetryOutcome::Exhausted);
                     }
 
-                    // Check if this error is retryable
-                    let should_retry = match &self.retry_if {
-                        Some(predicate) => predicate(&err),
-                        None => true,
+                    let delay = match self.circuit_open_delay(&err) {
+                        // Rejected by an open circuit: the policy decides
+                        Some(Some(delay)) => delay,
+                        Some(None) => return (Err(err), RetryOutcome::CircuitOpen),
+                        None => {
+                            // Check if this error is retryable
+                            let should_retry = match &self.retry_if {
+                                Some(predicate) => predicate(&err),
+                                None => true,
+                            };
+
+                            if !should_retry {
+                                return (Err(err), RetryOutcome::NotRetryable);
+                            }
+
+                            self.backoff.next_delay(attempt)
+                        }
                     };
 
-                    if !should_retry {
-                        return Err(err);
-                    }
-
-                    // Get the delay for this attempt
-                    let delay = self.backoff.next_delay(attempt);
-
                     // Call the error handler
                     on_error(&err, attempt, delay);
 
                     // Wait according to backoff strategy
+                    metrics::record_retry();
+                    delays.push(delay);
                     thread::sleep(delay);
 
                     attempt += 1;
@@ -156,6 +336,56 @@ where
             }
         }
     }
+
+    // `None` if `err` is not a circuit rejection handled by the policy;
+    // otherwise `Some` of the delay to wait, or `Some(None)` to stop.
+    fn circuit_open_delay(&self, err: &E) -> Option<Option<Duration>> {
+        if self.circuit_open == CircuitOpenPolicy::Retry {
+            return None;
+        }
+        let open = Chain::new(err).find_map(|e| e.downcast_ref::<CircuitOpenError>())?;
+        match self.circuit_open {
+            CircuitOpenPolicy::WaitForReset => Some(Some(open.retry_after())),
+            _ => Some(None),
+        }
+    }
+}
+
+/// Returned by [`RetryExecutor::retry_until_some`] and
+/// [`RetryExecutor::retry_until`] when the condition never held.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RetryExhausted {
+    /// How many times the operation was polled
+    pub attempts: usize,
+    /// Time spent polling, including backoff delays
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for RetryExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "condition not met after {} attempts ({:?})",
+            self.attempts, self.elapsed
+        )
+    }
+}
+
+impl std::error::Error for RetryExhausted {}
+
+impl ForgeError for RetryExhausted {
+    fn kind(&self) -> &'static str {
+        "RetryExhausted"
+    }
+
+    fn caption(&self) -> &'static str {
+        "Retries exhausted"
+    }
+
+    fn status_code(&self) -> u16 {
+        504
+    }
 }
 
 /// Policy for retrying operations
@@ -217,11 +447,15 @@ impl RetryPolicy {
     }
 
     /// Create a retry executor specifically for ForgeError types
+    ///
+    /// Errors are retried when their [`class`](ForgeError::class) is
+    /// retryable (`Transient` or `RateLimited`).
     pub fn forge_executor<E>(&self) -> RetryExecutor<E>
     where
         E: ForgeError,
     {
-        self.executor::<E>().with_retry_if(|err| err.is_retryable())
+        self.executor::<E>()
+            .with_retry_if(|err| err.class().is_retryable())
     }
 
     /// Execute a fallible operation with retries
@@ -239,3 +473,201 @@ impl Default for RetryPolicy {
         Self::new_exponential()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recovery::{CircuitBreaker, CircuitBreakerConfig};
+    use crate::{AppError, ErrorClass};
+    use std::cell::Cell;
+
+    fn tripped_breaker(reset_timeout_ms: u64) -> CircuitBreaker {
+        let config = CircuitBreakerConfig::new(1, 60_000, reset_timeout_ms);
+        let breaker = CircuitBreaker::with_config("svc", config);
+        let _ = breaker.execute(|| Err::<(), _>(std::io::Error::other("down")));
+        breaker
+    }
+
+    #[test]
+    fn test_circuit_open_policies() {
+        let breaker = tripped_breaker(60_000);
+        let calls = Cell::new(0);
+        let call = || {
+            calls.set(calls.get() + 1);
+            breaker
+                .execute(|| Ok::<_, std::io::Error>(()))
+                .map_err(|err| AppError::network("svc", Some(err)))
+        };
+
+        let executor = RetryExecutor::<AppError>::new_fixed(1).with_max_retries(3);
+        assert!(executor.retry(call).is_err());
+        assert_eq!(calls.get(), 1);
+
+        calls.set(0);
+        let executor = executor.with_circuit_open_policy(CircuitOpenPolicy::Retry);
+        assert!(executor.retry(call).is_err());
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn test_circuit_open_wait_for_reset() {
+        let breaker = tripped_breaker(20);
+        let mut waited = Duration::ZERO;
+
+        let result = RetryExecutor::<AppError>::new_fixed(1)
+            .with_circuit_open_policy(CircuitOpenPolicy::WaitForReset)
+            .retry_with_handler(
+                || {
+                    breaker
+                        .execute(|| Ok::<_, std::io::Error>(()))
+                        .map_err(|err| AppError::network("svc", Some(err)))
+                },
+                |_, _, delay| waited += delay,
+            );
+
+        assert!(result.is_ok());
+        assert!(waited <= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_journal_records_outcome() {
+        let calls = Cell::new(0);
+        let result: Result<(), _> = RetryExecutor::<AppError>::new_fixed(1)
+            .with_retry_if(|err| err.is_retryable())
+            .with_journal("journal-test")
+            .retry(|| {
+                calls.set(calls.get() + 1);
+                match calls.get() {
+                    1 => Err(AppError::network("svc", None)),
+                    _ => Err(AppError::config("bad")),
+                }
+            });
+        assert!(result.is_err());
+
+        let record = journal::recent()
+            .into_iter()
+            .rev()
+            .find(|record| record.operation == "journal-test")
+            .expect("sequence journaled");
+        assert_eq!(record.attempts, 2);
+        assert_eq!(record.delays, vec![Duration::from_millis(1)]);
+        assert_eq!(record.outcome, RetryOutcome::NotRetryable);
+        assert!(record.error.unwrap().contains("bad"));
+    }
+
+    #[test]
+    fn test_journal_notifies_observers() {
+        let seen = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let sink = std::sync::Arc::clone(&seen);
+        let id = crate::hooks::subscribe(move |ctx: &crate::macros::ErrorContext<'_>| {
+            if ctx.kind == journal::RETRY_KIND && ctx.caption.starts_with("notify-test") {
+                sink.lock().push((ctx.caption.to_string(), ctx.level));
+            }
+        })
+        .unwrap();
+
+        let _ = RetryExecutor::<AppError>::new_fixed(1)
+            .with_max_retries(1)
+            .with_journal("notify-test")
+            .retry(|| Err::<(), _>(AppError::network("svc", None)));
+        crate::hooks::unsubscribe(id);
+
+        assert_eq!(
+            *seen.lock(),
+            vec![(
+                "notify-test: exhausted after 2 attempts".to_string(),
+                crate::ErrorLevel::Warning
+            )]
+        );
+    }
+
+    #[test]
+    fn test_retry_with_history() {
+        let executor = RetryExecutor::<AppError>::new_fixed(1).with_max_retries(2);
+        let calls = Cell::new(0);
+        let err = executor
+            .retry_with_history(|| {
+                calls.set(calls.get() + 1);
+                Err::<(), _>(match calls.get() {
+                    1 => AppError::network("svc", None),
+                    2 => AppError::config("reloading"),
+                    _ => AppError::other("gave up"),
+                })
+            })
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), AppError::other("gave up").to_string());
+        let meta = err.metadata();
+        let get = |key: &str| meta.get(key).map(ToString::to_string);
+        assert_eq!(get("retry.attempts").as_deref(), Some("3"));
+        assert_eq!(get("retry.1.kind").as_deref(), Some("Network"));
+        assert_eq!(get("retry.2.kind").as_deref(), Some("Config"));
+        assert_eq!(get("retry.3.kind").as_deref(), Some("Other"));
+        for n in 1..=3 {
+            assert!(get(&format!("retry.{n}.duration_ms")).is_some());
+        }
+        assert_eq!(get("retry.1.delay_ms").as_deref(), Some("1"));
+        assert_eq!(get("retry.2.delay_ms").as_deref(), Some("1"));
+        assert_eq!(get("retry.3.delay_ms"), None);
+
+        calls.set(0);
+        let ok = executor.retry_with_history(|| {
+            calls.set(calls.get() + 1);
+            if calls.get() < 2 {
+                Err(AppError::network("svc", None))
+            } else {
+                Ok(calls.get())
+            }
+        });
+        assert_eq!(ok.ok(), Some(2));
+    }
+
+    #[test]
+    fn test_retry_until() {
+        let executor = RetryExecutor::<AppError>::new_fixed(1).with_max_retries(3);
+        let polls = Cell::new(0);
+        let ready = executor.retry_until_some(|| {
+            polls.set(polls.get() + 1);
+            (polls.get() == 3).then_some("ready")
+        });
+        assert_eq!(ready, Ok("ready"));
+
+        let err = executor.retry_until(|| false).unwrap_err();
+        assert_eq!(err.attempts, 4);
+        assert_eq!(err.kind(), "RetryExhausted");
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn test_forge_executor_uses_class() {
+        crate::define_errors! {
+            pub enum ApiError {
+                #[kind(Api, class = RateLimited)]
+                Throttled { retry_after: u64 },
+
+                #[kind(Api, retryable = true, class = Cancelled)]
+                Cancelled { request: u64 },
+            }
+        }
+
+        let policy = RetryPolicy::new_fixed(1).with_max_retries(2);
+        let calls = Cell::new(0);
+        let _ = policy.forge_executor().retry(|| {
+            calls.set(calls.get() + 1);
+            Err::<(), _>(ApiError::throttled(1))
+        });
+        assert_eq!(calls.get(), 3);
+
+        calls.set(0);
+        let _ = policy.forge_executor().retry(|| {
+            calls.set(calls.get() + 1);
+            Err::<(), _>(ApiError::cancelled(7))
+        });
+        assert_eq!(calls.get(), 1);
+        assert_eq!(
+            AppError::network("svc", None).class(),
+            ErrorClass::Transient
+        );
+    }
+}
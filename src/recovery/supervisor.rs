@@ -0,0 +1,215 @@
+//! Restarting or aborting worker loops by error kind.
+//!
+//! A worker that stops with a filesystem error should usually be
+//! restarted after a pause, while one that stops with a configuration
+//! error will fail the same way every time and should take the process
+//! down. An [`ExitStrategy`] states that once, as rules from error
+//! kinds (or any [`ErrorMatcher`]) to an [`ExitAction`], and a
+//! [`Supervisor`] applies it each time the worker returns an error.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::recovery::{ExitAction, ExitStrategy, FixedBackoff, Supervisor};
+//! use error_forge::AppError;
+//!
+//! let strategy = ExitStrategy::new()
+//!     .with_kind("Filesystem", ExitAction::Restart)
+//!     .with_kind("Config", ExitAction::Abort);
+//! let supervisor = Supervisor::new(strategy).with_backoff(FixedBackoff::new(1));
+//!
+//! let mut runs = 0;
+//! let result = supervisor.run(|| {
+//!     runs += 1;
+//!     match runs {
+//!         1 => Err(AppError::filesystem("spool", None)),
+//!         _ => Err(AppError::config("missing QUEUE_URL")),
+//!     }
+//! });
+//!
+//! // Restarted once, then aborted; `output::exit_with(&err)` would end
+//! // the process here.
+//! assert_eq!(runs, 2);
+//! assert!(result.is_err());
+//! ```
+
+use crate::error::ForgeError;
+use crate::matcher::ErrorMatcher;
+use crate::recovery::backoff::{Backoff, ExponentialBackoff};
+use std::thread;
+
+/// What a [`Supervisor`] does when its worker returns an error.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// actions without breaking callers that `match` on the enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExitAction {
+    /// Run the worker again after the backoff delay
+    Restart,
+    /// Stop and return the error, for the caller to exit the process
+    Abort,
+    /// Run the worker again at once, without a delay and without
+    /// counting towards the restart limit
+    Ignore,
+}
+
+/// Rules mapping errors to the [`ExitAction`] a [`Supervisor`] takes.
+///
+/// Rules are tried in the order they were added. An error matching
+/// none of them aborts if it [is fatal](ForgeError::is_fatal) and
+/// restarts otherwise, unless [`with_default`](Self::with_default)
+/// says differently.
+#[derive(Debug, Clone, Default)]
+pub struct ExitStrategy {
+    rules: Vec<(ErrorMatcher, ExitAction)>,
+    default: Option<ExitAction>,
+}
+
+impl ExitStrategy {
+    /// A strategy with no rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take `action` for errors of `kind`.
+    pub fn with_kind(self, kind: impl Into<String>, action: ExitAction) -> Self {
+        self.with_rule(ErrorMatcher::kind(kind), action)
+    }
+
+    /// Take `action` for errors matching `matcher`.
+    pub fn with_rule(mut self, matcher: ErrorMatcher, action: ExitAction) -> Self {
+        self.rules.push((matcher, action));
+        self
+    }
+
+    /// Take `action` for errors no rule matches.
+    pub fn with_default(mut self, action: ExitAction) -> Self {
+        self.default = Some(action);
+        self
+    }
+
+    /// The action for `err`.
+    pub fn action_for<E: ForgeError + ?Sized>(&self, err: &E) -> ExitAction {
+        self.rules
+            .iter()
+            .find(|(matcher, _)| matcher.matches(err))
+            .map(|(_, action)| *action)
+            .or(self.default)
+            .unwrap_or(match err.is_fatal() {
+                true => ExitAction::Abort,
+                false => ExitAction::Restart,
+            })
+    }
+}
+
+/// Runs a worker, restarting or stopping it as an [`ExitStrategy`]
+/// directs.
+pub struct Supervisor {
+    strategy: ExitStrategy,
+    backoff: Box<dyn Backoff>,
+    max_restarts: Option<usize>,
+}
+
+impl Supervisor {
+    /// Supervise with `strategy`, exponential backoff between restarts,
+    /// and no restart limit.
+    pub fn new(strategy: ExitStrategy) -> Self {
+        Self {
+            strategy,
+            backoff: Box::new(ExponentialBackoff::default()),
+            max_restarts: None,
+        }
+    }
+
+    /// Set the delay before each restart.
+    pub fn with_backoff(mut self, backoff: impl Backoff) -> Self {
+        self.backoff = Box::new(backoff);
+        self
+    }
+
+    /// Abort once the worker has been restarted `max_restarts` times.
+    pub fn with_max_restarts(mut self, max_restarts: usize) -> Self {
+        self.max_restarts = Some(max_restarts);
+        self
+    }
+
+    /// The strategy in use.
+    pub fn strategy(&self) -> &ExitStrategy {
+        &self.strategy
+    }
+
+    /// Run `worker` until it returns `Ok`, or until an error it returns
+    /// is to abort, which is then returned.
+    pub fn run<F, E>(&self, mut worker: F) -> Result<(), E>
+    where
+        F: FnMut() -> Result<(), E>,
+        E: ForgeError,
+    {
+        let mut restarts = 0;
+        loop {
+            let err = match worker() {
+                Ok(()) => return Ok(()),
+                Err(err) => err,
+            };
+            match self.strategy.action_for(&err) {
+                ExitAction::Ignore => continue,
+                ExitAction::Restart if self.max_restarts.is_some_and(|max| restarts >= max) => {
+                    return Err(err)
+                }
+                ExitAction::Restart => {
+                    thread::sleep(self.backoff.next_delay(restarts));
+                    restarts += 1;
+                }
+                ExitAction::Abort => return Err(err),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recovery::FixedBackoff;
+    use crate::AppError;
+
+    #[test]
+    fn test_exit_strategy() {
+        let strategy = ExitStrategy::new()
+            .with_kind("Network", ExitAction::Ignore)
+            .with_rule(ErrorMatcher::any().code("FS-1"), ExitAction::Abort);
+        assert_eq!(
+            strategy.action_for(&AppError::network("db", None)),
+            ExitAction::Ignore
+        );
+        assert_eq!(
+            strategy.action_for(&AppError::other("x").with_code("FS-1")),
+            ExitAction::Abort
+        );
+        assert_eq!(
+            strategy.action_for(&AppError::other("x")),
+            ExitAction::Restart
+        );
+        assert_eq!(
+            strategy.action_for(&AppError::other("x").with_fatal(true)),
+            ExitAction::Abort
+        );
+
+        let supervisor = Supervisor::new(strategy)
+            .with_backoff(FixedBackoff::new(1))
+            .with_max_restarts(2);
+        let mut runs = 0;
+        let err = supervisor
+            .run(|| {
+                runs += 1;
+                match runs % 2 {
+                    0 => Err(AppError::network("db", None)),
+                    _ => Err(AppError::other("crashed")),
+                }
+            })
+            .unwrap_err();
+        // Three crashes (two restarts), with an ignored error between each.
+        assert_eq!(runs, 5);
+        assert_eq!(err.kind(), "Other");
+    }
+}
@@ -1,13 +1,77 @@
 use crate::error::ForgeError;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt;
+use std::ops::Bound;
 use std::sync::OnceLock;
 use std::sync::RwLock;
 
 /// A central registry for error codes and metadata
 pub struct ErrorRegistry {
     /// Maps error codes to their descriptions
-    codes: RwLock<HashMap<String, ErrorCodeInfo>>,
+    codes: RwLock<CodeIndex>,
+}
+
+/// The registered codes, sorted for prefix queries, with secondary
+/// indexes for case-insensitive and description lookups.
+#[derive(Default)]
+struct CodeIndex {
+    by_code: BTreeMap<String, ErrorCodeInfo>,
+    // Lowercased code to the first code registered with that spelling.
+    by_folded: HashMap<String, String>,
+    // Lowercased description word to the codes whose description has it.
+    by_word: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl CodeIndex {
+    fn insert(&mut self, info: ErrorCodeInfo) {
+        self.by_folded
+            .entry(info.code.to_lowercase())
+            .or_insert_with(|| info.code.clone());
+        for word in words(&info.description.to_lowercase()) {
+            self.by_word
+                .entry(word.to_string())
+                .or_default()
+                .insert(info.code.clone());
+        }
+        self.by_code.insert(info.code.clone(), info);
+    }
+}
+
+// The alphanumeric runs of `text`.
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+// Whether `text` matches `pattern`, where `*` matches any run of
+// characters and `?` any one character.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // The last `*` seen, and the text position it currently ends at.
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
 }
 
 /// Metadata for a registered error code.
@@ -27,11 +91,36 @@ pub struct ErrorCodeInfo {
     pub retryable: bool,
 }
 
+impl ErrorCodeInfo {
+    /// Construct an [`ErrorCodeInfo`] from its components.
+    ///
+    /// Provided so external callers (catalog loaders, tests) can
+    /// build the struct without depending on its field list, which
+    /// may grow over the `1.x` line.
+    pub fn new(
+        code: impl Into<String>,
+        description: impl Into<String>,
+        documentation_url: Option<String>,
+        retryable: bool,
+    ) -> Self {
+        Self {
+            code: code.into(),
+            description: description.into(),
+            documentation_url,
+            retryable,
+        }
+    }
+}
+
 impl ErrorRegistry {
-    /// Create a new empty error registry
-    fn new() -> Self {
+    /// Create a new empty error registry.
+    ///
+    /// Generated errors register their codes in the
+    /// [`global`](Self::global) registry; a separate one is useful for
+    /// tooling and tests that load a catalog on their own.
+    pub fn new() -> Self {
         Self {
-            codes: RwLock::new(HashMap::new()),
+            codes: RwLock::new(CodeIndex::default()),
         }
     }
 
@@ -48,19 +137,16 @@ impl ErrorRegistry {
             Err(_) => return Err("Failed to acquire write lock on error registry".to_string()),
         };
 
-        if codes.contains_key(&code) {
+        if codes.by_code.contains_key(&code) {
             return Err(format!("Error code '{code}' is already registered"));
         }
 
-        codes.insert(
-            code.clone(),
-            ErrorCodeInfo {
-                code,
-                description,
-                documentation_url,
-                retryable,
-            },
-        );
+        codes.insert(ErrorCodeInfo {
+            code,
+            description,
+            documentation_url,
+            retryable,
+        });
 
         Ok(())
     }
@@ -68,19 +154,89 @@ impl ErrorRegistry {
     /// Get info about a registered error code
     pub fn get_code_info(&self, code: &str) -> Option<ErrorCodeInfo> {
         match self.codes.read() {
-            Ok(codes) => codes.get(code).cloned(),
+            Ok(codes) => codes.by_code.get(code).cloned(),
             Err(_) => None,
         }
     }
 
+    /// Get info about a registered error code, ignoring ASCII and
+    /// Unicode case: `"auth-001"` finds `"AUTH-001"`. An exact match
+    /// wins; otherwise the first code registered with that spelling.
+    pub fn get_code_info_ci(&self, code: &str) -> Option<ErrorCodeInfo> {
+        let codes = self.codes.read().ok()?;
+        match codes.by_code.get(code) {
+            Some(info) => Some(info.clone()),
+            None => codes
+                .by_folded
+                .get(&code.to_lowercase())
+                .and_then(|code| codes.by_code.get(code))
+                .cloned(),
+        }
+    }
+
+    /// Return the codes matching `pattern`, sorted by code. `*` matches
+    /// any run of characters and `?` any one, so `"AUTH-*"` lists every
+    /// `AUTH-` code; a pattern without wildcards matches only itself.
+    ///
+    /// Only codes starting with the text before the first wildcard are
+    /// examined, so prefix queries do not scan the whole registry.
+    pub fn find_codes(&self, pattern: &str) -> Vec<ErrorCodeInfo> {
+        let Ok(codes) = self.codes.read() else {
+            return Vec::new();
+        };
+        let prefix = &pattern[..pattern.find(['*', '?']).unwrap_or(pattern.len())];
+        codes
+            .by_code
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(|(code, _)| code.starts_with(prefix))
+            .filter(|(code, _)| glob_match(pattern, code))
+            .map(|(_, info)| info.clone())
+            .collect()
+    }
+
+    /// Return the codes whose description contains `text`, ignoring
+    /// case, sorted by code.
+    ///
+    /// Descriptions are indexed by word. Only codes with a word
+    /// containing the longest word of `text` are checked.
+    pub fn find_by_description(&self, text: &str) -> Vec<ErrorCodeInfo> {
+        let Ok(codes) = self.codes.read() else {
+            return Vec::new();
+        };
+        let text = text.to_lowercase();
+        let matches = |info: &&ErrorCodeInfo| info.description.to_lowercase().contains(&text);
+        match words(&text).max_by_key(|word| word.len()) {
+            Some(longest) => codes
+                .by_word
+                .iter()
+                .filter(|(word, _)| word.contains(longest))
+                .flat_map(|(_, matching)| matching)
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .filter_map(|code| codes.by_code.get(code))
+                .filter(matches)
+                .cloned()
+                .collect(),
+            None => codes.by_code.values().filter(matches).cloned().collect(),
+        }
+    }
+
     /// Check if an error code is registered
     pub fn is_registered(&self, code: &str) -> bool {
         match self.codes.read() {
-            Ok(codes) => codes.contains_key(code),
+            Ok(codes) => codes.by_code.contains_key(code),
             Err(_) => false,
         }
     }
 
+    /// Return every registered code, sorted by code.
+    pub fn codes(&self) -> Vec<ErrorCodeInfo> {
+        match self.codes.read() {
+            Ok(codes) => codes.by_code.values().cloned().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
     /// Get the global error registry instance
     pub fn global() -> &'static ErrorRegistry {
         static REGISTRY: OnceLock<ErrorRegistry> = OnceLock::new();
@@ -88,6 +244,12 @@ impl ErrorRegistry {
     }
 }
 
+impl Default for ErrorRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// An error with an associated error code.
 ///
 /// Marked `#[non_exhaustive]` so future minor releases can add new
@@ -204,6 +366,27 @@ impl<E: ForgeError> ForgeError for CodedError<E> {
         self.error.exit_code()
     }
 
+    fn severity(&self) -> crate::macros::ErrorLevel {
+        // Per-instance overrides change the flags the inner error's
+        // severity was based on, so derive it from the new flags.
+        if self.fatal || self.retryable.is_some() {
+            crate::macros::ErrorLevel::from_flags(self.is_fatal(), self.is_retryable())
+        } else {
+            self.error.severity()
+        }
+    }
+
+    fn class(&self) -> crate::error::ErrorClass {
+        // When the override or the registered code changes the
+        // retryable flag, the inner error's class no longer applies.
+        let retryable = self.is_retryable();
+        if retryable == self.error.is_retryable() {
+            self.error.class()
+        } else {
+            crate::error::ErrorClass::from_retryable(retryable)
+        }
+    }
+
     fn user_message(&self) -> String {
         format!("[{}] {}", self.code, self.error.user_message())
     }
@@ -220,6 +403,40 @@ impl<E: ForgeError> ForgeError for CodedError<E> {
     fn backtrace(&self) -> Option<&std::backtrace::Backtrace> {
         self.error.backtrace()
     }
+
+    fn code(&self) -> Option<&str> {
+        Some(&self.code)
+    }
+
+    fn docs_url(&self) -> Option<String> {
+        self.code_info()
+            .and_then(|info| info.documentation_url)
+            .or_else(|| self.error.docs_url())
+    }
+
+    fn retry_after(&self) -> Option<std::time::Duration> {
+        self.error.retry_after()
+    }
+
+    fn error_id(&self) -> Option<crate::instance::ErrorId> {
+        self.error.error_id()
+    }
+
+    fn timestamp(&self) -> Option<std::time::SystemTime> {
+        self.error.timestamp()
+    }
+
+    fn location(&self) -> Option<&'static std::panic::Location<'static>> {
+        self.error.location()
+    }
+
+    fn context_layer(&self) -> Option<(&dyn fmt::Display, &dyn ForgeError)> {
+        self.error.context_layer()
+    }
+
+    fn metadata(&self) -> &crate::metadata::Metadata {
+        self.error.metadata()
+    }
 }
 
 /// Extension trait for adding error codes
@@ -287,4 +504,47 @@ mod tests {
         );
         assert!(info.retryable);
     }
+
+    #[test]
+    fn test_code_lookups() {
+        let registry = ErrorRegistry::new();
+        for (code, description) in [
+            ("AUTH-001", "Invalid credentials"),
+            ("AUTH-002", "Session expired; sign in again"),
+            ("AUTHZ-001", "Missing permission"),
+            ("DB-010", "Connection pool exhausted"),
+        ] {
+            registry
+                .register_code(code.into(), description.into(), None, false)
+                .unwrap();
+        }
+        let codes = |infos: Vec<ErrorCodeInfo>| -> Vec<String> {
+            infos.into_iter().map(|info| info.code).collect()
+        };
+
+        assert_eq!(
+            registry.get_code_info_ci("auth-002").unwrap().code,
+            "AUTH-002"
+        );
+        assert!(registry.get_code_info_ci("auth-003").is_none());
+        assert_eq!(
+            codes(registry.find_codes("AUTH-*")),
+            ["AUTH-001", "AUTH-002"]
+        );
+        assert_eq!(
+            codes(registry.find_codes("AUTH*-001")),
+            ["AUTH-001", "AUTHZ-001"]
+        );
+        assert_eq!(codes(registry.find_codes("DB-01?")), ["DB-010"]);
+        assert_eq!(codes(registry.find_codes("DB-01")), [] as [&str; 0]);
+        assert_eq!(
+            codes(registry.find_by_description("PIRED; SIGN")),
+            ["AUTH-002"]
+        );
+        assert_eq!(
+            codes(registry.find_by_description("ssi")),
+            ["AUTH-002", "AUTHZ-001"]
+        );
+        assert_eq!(codes(registry.find_by_description("; ")), ["AUTH-002"]);
+    }
 }
@@ -0,0 +1,553 @@
+//! Request-scoped fields carried by every error built during a request.
+//!
+//! Request IDs, routes, and user IDs belong on every error a handler
+//! produces, but threading them through each call to
+//! [`with_meta`](crate::WithMetadata::with_meta) is tedious and easy to
+//! forget. A [`RequestContext`] is installed once per request, by the
+//! server's middleware, and every error constructed while it is active
+//! (by the [`AppError`](crate::AppError) constructors, those of
+//! `#[tracked]` `define_errors!` enums, and the other error types that
+//! record an [`ErrorId`]) captures it. Its fields are then
+//! returned by [`ForgeError::metadata`], so they reach the logging
+//! adapters, [`ErrorSnapshot`], and JSON output with no per-handler
+//! plumbing.
+//!
+//! [`RequestContext::scope`] installs the context for synchronous code;
+//! [`RequestContext::instrument`] wraps a future so the context is
+//! active on every poll, whichever thread runs it.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::request::RequestContext;
+//! use error_forge::{define_errors, ForgeError};
+//!
+//! define_errors! {
+//!     #[tracked]
+//!     pub enum OrderError {
+//!         #[error(display = "bad order id {id}")]
+//!         #[kind(Invalid, status = 400)]
+//!         Invalid { id: String },
+//!     }
+//! }
+//!
+//! let err = RequestContext::new()
+//!     .with_request_id("req-7")
+//!     .with_route("/orders/{id}")
+//!     .with_user_id("u-42")
+//!     .scope(|| OrderError::invalid("x".into()));
+//!
+//! let fields = err.metadata();
+//! assert_eq!(fields.get("request_id").unwrap().to_string(), "req-7");
+//! assert_eq!(fields.get("route").unwrap().to_string(), "/orders/{id}");
+//! ```
+//!
+//! # Middleware
+//!
+//! With the `tower` or `actix` feature, `RequestContextLayer` is ready
+//! made middleware: a tower `Layer` for axum, tonic, and hyper, or an
+//! actix `Transform` for actix-web's `wrap`. It takes a closure that
+//! builds the context from each request. The features depend only on
+//! the frameworks' middleware traits, not the frameworks themselves.
+//!
+//! Without them, an adapter is a few lines around
+//! [`RequestContext::instrument`]. With axum's `middleware::from_fn`:
+//!
+//! ```ignore
+//! async fn error_context(request: Request, next: Next) -> Response {
+//!     let context = RequestContext::new()
+//!         .with_request_id(request_id(&request))
+//!         .with_route(request.uri().path());
+//!     context.instrument(next.run(request)).await
+//! }
+//! ```
+//!
+//! [`ErrorId`]: crate::ErrorId
+//! [`ForgeError::metadata`]: crate::ForgeError::metadata
+//! [`ErrorSnapshot`]: crate::ErrorSnapshot
+
+use crate::error::ForgeError;
+use crate::metadata::{MetaValue, Metadata};
+use std::cell::RefCell;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Metadata key for [`RequestContext::with_request_id`].
+pub const REQUEST_ID_KEY: &str = "request_id";
+/// Metadata key for [`RequestContext::with_route`].
+pub const ROUTE_KEY: &str = "route";
+/// Metadata key for [`RequestContext::with_user_id`].
+pub const USER_ID_KEY: &str = "user_id";
+/// Metadata key for [`RequestContext::with_tenant_id`].
+pub const TENANT_ID_KEY: &str = "tenant_id";
+
+thread_local! {
+    /// The request being handled on this thread, if any.
+    static CURRENT: RefCell<Option<Arc<RequestContext>>> = const { RefCell::new(None) };
+}
+
+/// The fields describing one request.
+///
+/// Build one with [`RequestContext::new`] and the `with_*` methods,
+/// then install it with [`scope`](Self::scope) or
+/// [`instrument`](Self::instrument).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestContext {
+    metadata: Metadata,
+}
+
+impl RequestContext {
+    /// An empty context.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the request ID, recorded under [`REQUEST_ID_KEY`].
+    pub fn with_request_id(self, request_id: impl Into<String>) -> Self {
+        self.with_field(REQUEST_ID_KEY, request_id.into())
+    }
+
+    /// Set the matched route, recorded under [`ROUTE_KEY`].
+    pub fn with_route(self, route: impl Into<String>) -> Self {
+        self.with_field(ROUTE_KEY, route.into())
+    }
+
+    /// Set the authenticated user, recorded under [`USER_ID_KEY`].
+    pub fn with_user_id(self, user_id: impl Into<String>) -> Self {
+        self.with_field(USER_ID_KEY, user_id.into())
+    }
+
+    /// Set the tenant the request is made for, recorded under
+    /// [`TENANT_ID_KEY`]. Per-tenant escalation rules and circuit
+    /// breakers key on it.
+    pub fn with_tenant_id(self, tenant_id: impl Into<String>) -> Self {
+        self.with_field(TENANT_ID_KEY, tenant_id.into())
+    }
+
+    /// Add any other field.
+    pub fn with_field(mut self, key: impl Into<String>, value: impl Into<MetaValue>) -> Self {
+        self.metadata.insert(key, value);
+        self
+    }
+
+    /// The request ID, if set.
+    pub fn request_id(&self) -> Option<&str> {
+        self.string(REQUEST_ID_KEY)
+    }
+
+    /// The route, if set.
+    pub fn route(&self) -> Option<&str> {
+        self.string(ROUTE_KEY)
+    }
+
+    /// The user ID, if set.
+    pub fn user_id(&self) -> Option<&str> {
+        self.string(USER_ID_KEY)
+    }
+
+    /// The tenant ID, if set.
+    pub fn tenant_id(&self) -> Option<&str> {
+        self.string(TENANT_ID_KEY)
+    }
+
+    /// Every field, in the order they were set.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// The context active on the current thread or task, if any.
+    pub fn current() -> Option<Arc<RequestContext>> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    /// Run `f` with this context active.
+    pub fn scope<R>(self, f: impl FnOnce() -> R) -> R {
+        let _guard = enter(Some(Arc::new(self)));
+        f()
+    }
+
+    /// Wrap `future` so this context is active each time it is polled.
+    pub fn instrument<F: Future>(self, future: F) -> InRequest<F> {
+        InRequest {
+            context: Arc::new(self),
+            future: Box::pin(future),
+        }
+    }
+
+    fn string(&self, key: &str) -> Option<&str> {
+        string(&self.metadata, key)
+    }
+}
+
+/// The tenant recorded on `err`, from the [`RequestContext`] active
+/// when it was created or a `tenant_id` field added with
+/// [`with_meta`](crate::WithMetadata::with_meta).
+pub fn tenant_id<E: ForgeError + ?Sized>(err: &E) -> Option<&str> {
+    string(err.metadata(), TENANT_ID_KEY)
+}
+
+fn string<'a>(metadata: &'a Metadata, key: &str) -> Option<&'a str> {
+    match metadata.get(key) {
+        Some(MetaValue::String(value)) => Some(value),
+        _ => None,
+    }
+}
+
+/// Install `context` until the guard drops.
+fn enter(context: Option<Arc<RequestContext>>) -> RequestGuard {
+    let previous = CURRENT.with(|current| current.replace(context));
+    RequestGuard { previous }
+}
+
+/// Restores the previous context on drop, including during unwinding.
+struct RequestGuard {
+    previous: Option<Arc<RequestContext>>,
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+/// A future that runs with a [`RequestContext`] active. Returned by
+/// [`RequestContext::instrument`].
+pub struct InRequest<F> {
+    context: Arc<RequestContext>,
+    future: Pin<Box<F>>,
+}
+
+impl<F> fmt::Debug for InRequest<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InRequest")
+            .field("context", &self.context)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F: Future> Future for InRequest<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let _guard = enter(Some(self.context.clone()));
+        self.future.as_mut().poll(cx)
+    }
+}
+
+/// Middleware that installs a [`RequestContext`] around every request
+/// a service handles.
+///
+/// `extract` builds the context from the incoming request. The context
+/// is active while the inner service's `call` runs and on every poll of
+/// the future it returns, so errors built anywhere in the handler carry
+/// its fields.
+///
+/// With the `tower` feature this is a tower `Layer` (axum, tonic,
+/// hyper); with the `actix` feature it is an actix `Transform` for
+/// actix-web's `wrap`:
+///
+/// ```ignore
+/// let app = Router::new()
+///     .route("/orders/{id}", get(order))
+///     .layer(RequestContextLayer::new(|request: &Request| {
+///         RequestContext::new()
+///             .with_request_id(request_id(request))
+///             .with_route(request.uri().path())
+///     }));
+/// ```
+#[cfg(any(feature = "tower", feature = "actix"))]
+#[derive(Clone)]
+pub struct RequestContextLayer<F> {
+    extract: F,
+}
+
+#[cfg(any(feature = "tower", feature = "actix"))]
+impl<F> RequestContextLayer<F> {
+    /// Middleware that builds each request's context with `extract`.
+    pub fn new(extract: F) -> Self {
+        Self { extract }
+    }
+}
+
+#[cfg(any(feature = "tower", feature = "actix"))]
+impl<F> fmt::Debug for RequestContextLayer<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestContextLayer")
+            .finish_non_exhaustive()
+    }
+}
+
+/// A service wrapped by [`RequestContextLayer`].
+#[cfg(any(feature = "tower", feature = "actix"))]
+#[derive(Clone)]
+pub struct RequestContextService<S, F> {
+    inner: S,
+    extract: F,
+}
+
+#[cfg(any(feature = "tower", feature = "actix"))]
+impl<S, F> RequestContextService<S, F> {
+    /// The wrapped service.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Unwrap the inner service.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+/// Build `request`'s context with `extract` and make the inner
+/// service's `call` under it.
+#[cfg(any(feature = "tower", feature = "actix"))]
+fn call_in_context<Req, Fut>(
+    extract: impl Fn(&Req) -> RequestContext,
+    request: Req,
+    call: impl FnOnce(Req) -> Fut,
+) -> InRequest<Fut> {
+    let context = Arc::new(extract(&request));
+    let future = {
+        let _guard = enter(Some(context.clone()));
+        call(request)
+    };
+    InRequest {
+        context,
+        future: Box::pin(future),
+    }
+}
+
+#[cfg(any(feature = "tower", feature = "actix"))]
+impl<S: fmt::Debug, F> fmt::Debug for RequestContextService<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestContextService")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "tower")]
+impl<S, F: Clone> tower_layer::Layer<S> for RequestContextLayer<F> {
+    type Service = RequestContextService<S, F>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestContextService {
+            inner,
+            extract: self.extract.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "tower")]
+impl<S, F, Req> tower_service::Service<Req> for RequestContextService<S, F>
+where
+    S: tower_service::Service<Req>,
+    F: Fn(&Req) -> RequestContext,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = InRequest<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Req) -> Self::Future {
+        call_in_context(&self.extract, request, |request| self.inner.call(request))
+    }
+}
+
+#[cfg(feature = "actix")]
+impl<S, F, Req> actix_service::Transform<S, Req> for RequestContextLayer<F>
+where
+    S: actix_service::Service<Req>,
+    F: Fn(&Req) -> RequestContext + Clone,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Transform = RequestContextService<S, F>;
+    type InitError = ();
+    type Future = std::future::Ready<Result<Self::Transform, ()>>;
+
+    fn new_transform(&self, inner: S) -> Self::Future {
+        std::future::ready(Ok(RequestContextService {
+            inner,
+            extract: self.extract.clone(),
+        }))
+    }
+}
+
+#[cfg(feature = "actix")]
+impl<S, F, Req> actix_service::Service<Req> for RequestContextService<S, F>
+where
+    S: actix_service::Service<Req>,
+    F: Fn(&Req) -> RequestContext,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = InRequest<S::Future>;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&self, request: Req) -> Self::Future {
+        call_in_context(&self.extract, request, |request| self.inner.call(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppError, ForgeError, WithMetadata};
+
+    crate::define_errors! {
+        #[tracked]
+        enum HandlerError {
+            #[kind(Handler)]
+            Failed { step: String },
+        }
+    }
+
+    #[test]
+    fn test_request_scope() {
+        let context = RequestContext::new()
+            .with_request_id("req-1")
+            .with_user_id("u-1")
+            .with_tenant_id("acme");
+        assert_eq!(context.request_id(), Some("req-1"));
+        assert_eq!(context.route(), None);
+
+        let err = context.scope(|| {
+            assert!(RequestContext::current().is_some());
+            HandlerError::failed("db".into()).with_meta("attempt", 2)
+        });
+        assert!(RequestContext::current().is_none());
+        assert_eq!(err.metadata().len(), 4);
+        assert_eq!(err.metadata().get(USER_ID_KEY).unwrap().to_string(), "u-1");
+        assert_eq!(tenant_id(&err), Some("acme"));
+
+        assert!(HandlerError::failed("outside".into()).metadata().is_empty());
+        let app = RequestContext::new()
+            .with_request_id("req-2")
+            .with_route("/jobs")
+            .with_user_id("u-2")
+            .scope(|| AppError::other("app"));
+        let fields = app.metadata();
+        assert_eq!(fields.get(REQUEST_ID_KEY).unwrap().to_string(), "req-2");
+        assert_eq!(fields.get(ROUTE_KEY).unwrap().to_string(), "/jobs");
+        assert_eq!(fields.get(USER_ID_KEY).unwrap().to_string(), "u-2");
+        assert!(AppError::other("outside").metadata().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_request_instrument() {
+        let handler = async {
+            tokio::task::yield_now().await;
+            HandlerError::failed("late".into())
+        };
+        let err = RequestContext::new()
+            .with_route("/health")
+            .instrument(handler)
+            .await;
+        assert_eq!(
+            err.metadata().get(ROUTE_KEY).unwrap().to_string(),
+            "/health"
+        );
+        assert!(RequestContext::current().is_none());
+    }
+
+    /// Fails every request, in `call` for odd paths and in the returned
+    /// future for even ones.
+    #[cfg(any(feature = "tower", feature = "actix"))]
+    #[derive(Clone)]
+    struct Handler;
+
+    #[cfg(any(feature = "tower", feature = "actix"))]
+    impl Handler {
+        fn handle(&self, path: u32) -> impl Future<Output = Result<(), HandlerError>> {
+            let early = (path % 2 == 1).then(|| HandlerError::failed("call".into()));
+            async move {
+                tokio::task::yield_now().await;
+                Err(early.unwrap_or_else(|| HandlerError::failed("poll".into())))
+            }
+        }
+    }
+
+    #[cfg(feature = "tower")]
+    impl tower_service::Service<u32> for Handler {
+        type Response = ();
+        type Error = HandlerError;
+        type Future = Pin<Box<dyn Future<Output = Result<(), HandlerError>> + Send>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), HandlerError>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, path: u32) -> Self::Future {
+            Box::pin(self.handle(path))
+        }
+    }
+
+    #[cfg(feature = "actix")]
+    impl actix_service::Service<u32> for Handler {
+        type Response = ();
+        type Error = HandlerError;
+        type Future = Pin<Box<dyn Future<Output = Result<(), HandlerError>>>>;
+
+        fn poll_ready(&self, _: &mut Context<'_>) -> Poll<Result<(), HandlerError>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&self, path: u32) -> Self::Future {
+            Box::pin(self.handle(path))
+        }
+    }
+
+    #[cfg(any(feature = "tower", feature = "actix"))]
+    fn route_context(path: &u32) -> RequestContext {
+        RequestContext::new().with_route(format!("/items/{path}"))
+    }
+
+    #[cfg(any(feature = "tower", feature = "actix"))]
+    fn route_of(err: HandlerError) -> String {
+        err.metadata().get(ROUTE_KEY).unwrap().to_string()
+    }
+
+    #[cfg(feature = "tower")]
+    #[tokio::test]
+    async fn test_tower_layer() {
+        use tower_layer::Layer;
+        use tower_service::Service;
+
+        let mut service = RequestContextLayer::new(route_context).layer(Handler);
+        std::future::poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .unwrap();
+        let early = service.call(1);
+        let late = service.call(2);
+        assert!(RequestContext::current().is_none());
+        assert_eq!(route_of(early.await.unwrap_err()), "/items/1");
+        assert_eq!(route_of(late.await.unwrap_err()), "/items/2");
+    }
+
+    #[cfg(feature = "actix")]
+    #[tokio::test]
+    async fn test_actix_transform() {
+        use actix_service::{Service, Transform};
+
+        let service = RequestContextLayer::new(route_context)
+            .new_transform(Handler)
+            .await
+            .unwrap();
+        let early = service.call(3);
+        let late = service.call(4);
+        assert!(RequestContext::current().is_none());
+        assert_eq!(route_of(early.await.unwrap_err()), "/items/3");
+        assert_eq!(route_of(late.await.unwrap_err()), "/items/4");
+    }
+}
@@ -0,0 +1,286 @@
+//! Machine-readable descriptions of `define_errors!` enums.
+//!
+//! Client SDKs that mirror a server's errors otherwise copy kinds, codes,
+//! and statuses by hand and drift out of date. An [`ErrorSchema`] lists
+//! the variants of one or more enums through [`ErrorTable`] and renders
+//! them as JSON or as TypeScript declarations, ready for a generator
+//! or to be checked in.
+//!
+//! A build script can regenerate the file on each build. The enums must
+//! live in a crate the build script depends on, since a build script
+//! cannot use the crate it builds:
+//!
+//! ```no_run
+//! # error_forge::define_errors! {
+//! #     pub enum ApiError {
+//! #         #[kind(Auth, status = 401, code = "API-401")]
+//! #         Unauthorized,
+//! #     }
+//! # }
+//! // build.rs
+//! use error_forge::schema::{ErrorSchema, SchemaFormat};
+//!
+//! fn main() -> std::io::Result<()> {
+//!     ErrorSchema::new()
+//!         .with_enum::<ApiError>()
+//!         .write("sdk/errors.ts", SchemaFormat::TypeScript)
+//! }
+//! ```
+//!
+//! Only variants with a `#[kind(...)]` are listed, as in
+//! [`lint`](crate::lint).
+
+use crate::lint::{ErrorTable, VariantInfo};
+use crate::snapshot::{push_json_string, JsonObject};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::{fs, io};
+
+/// The output format of [`ErrorSchema::write`].
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// formats without breaking callers that `match` on the enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SchemaFormat {
+    /// [`ErrorSchema::to_json`], pretty-printed
+    Json,
+    /// [`ErrorSchema::to_typescript`]
+    TypeScript,
+}
+
+/// The variants of a set of error enums, rendered for client SDKs.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorSchema {
+    enums: Vec<(&'static str, Vec<VariantInfo>)>,
+}
+
+impl ErrorSchema {
+    /// A schema with no enums.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the variants of `E`, named after its type without the module
+    /// path or generic arguments.
+    pub fn with_enum<E: ErrorTable>(self) -> Self {
+        let name = std::any::type_name::<E>();
+        let name = name.split('<').next().unwrap_or(name);
+        let name = name.rsplit("::").next().unwrap_or(name);
+        self.with_variants(name, E::variants())
+    }
+
+    /// Add an enum by name from a list of variants, for types not built
+    /// with `define_errors!`.
+    pub fn with_variants(mut self, name: &'static str, variants: Vec<VariantInfo>) -> Self {
+        self.enums.push((name, variants));
+        self
+    }
+
+    /// Render as a JSON object with an `enums` array. Each enum has a
+    /// `name` and a `variants` array; each variant has its `name`,
+    /// `kind`, `caption`, `code`, `status`, `retryable`, `fatal`,
+    /// `severity`, `docs_url`, `message` template, and `fields` (each a
+    /// `name` and Rust `type`).
+    pub fn to_json(&self, pretty: bool) -> String {
+        let (newline, indent) = match pretty {
+            true => ("\n", "  "),
+            false => ("", ""),
+        };
+        let mut out = String::from("{");
+        let _ = write!(out, "{newline}{indent}\"enums\":[");
+        for (i, (name, variants)) in self.enums.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(out, "{newline}{indent}{indent}{{\"name\":");
+            push_json_string(&mut out, name);
+            out.push_str(",\"variants\":[");
+            for (j, info) in variants.iter().enumerate() {
+                if j > 0 {
+                    out.push(',');
+                }
+                let _ = write!(out, "{newline}{indent}{indent}{indent}");
+                out.push_str(&variant_json(info));
+            }
+            let _ = write!(out, "{newline}{indent}{indent}]}}");
+        }
+        let _ = write!(out, "{newline}{indent}]{newline}}}");
+        out
+    }
+
+    /// Render as TypeScript: a `const` object per enum mapping variant
+    /// names to their metadata, a union type of the variant names and
+    /// of the declared codes, and an interface for the fields of each
+    /// variant that has any.
+    ///
+    /// Field types map to `string`, `number`, `boolean`, arrays, and
+    /// `| null` for `Option`; anything else is `unknown`.
+    pub fn to_typescript(&self) -> String {
+        let mut out = String::from("// Generated by error-forge. Do not edit.\n");
+        for (name, variants) in &self.enums {
+            let _ = writeln!(out, "\nexport const {name} = {{");
+            for info in variants {
+                let _ = write!(out, "  {}: {{ kind: ", info.name);
+                push_json_string(&mut out, info.kind);
+                out.push_str(", code: ");
+                push_json_or_null(&mut out, info.code);
+                let _ = write!(
+                    out,
+                    ", status: {}, retryable: {}, fatal: {}, message: ",
+                    info.status_code, info.retryable, info.fatal
+                );
+                push_json_or_null(&mut out, info.message);
+                out.push_str(" },\n");
+            }
+            out.push_str("} as const;\n");
+
+            let _ = writeln!(out, "\nexport type {name}Variant = keyof typeof {name};");
+            let codes: Vec<_> = variants.iter().filter_map(|info| info.code).collect();
+            let _ = write!(out, "export type {name}Code =");
+            if codes.is_empty() {
+                out.push_str(" never");
+            }
+            for (i, code) in codes.iter().enumerate() {
+                out.push_str(if i > 0 { " | " } else { " " });
+                push_json_string(&mut out, code);
+            }
+            out.push_str(";\n");
+
+            for info in variants.iter().filter(|info| !info.fields.is_empty()) {
+                let _ = writeln!(out, "\nexport interface {name}{}Fields {{", info.name);
+                for (field, ty) in info.fields {
+                    let _ = writeln!(out, "  {field}: {};", typescript_type(ty));
+                }
+                out.push_str("}\n");
+            }
+        }
+        out
+    }
+
+    /// Write the schema to `path` in `format`, creating parent
+    /// directories. The file is left untouched when its contents would
+    /// not change, so build scripts do not trigger needless rebuilds.
+    pub fn write(&self, path: impl AsRef<Path>, format: SchemaFormat) -> io::Result<()> {
+        let path = path.as_ref();
+        let contents = match format {
+            SchemaFormat::Json => self.to_json(true) + "\n",
+            SchemaFormat::TypeScript => self.to_typescript(),
+        };
+        if fs::read_to_string(path).is_ok_and(|existing| existing == contents) {
+            return Ok(());
+        }
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)
+    }
+}
+
+fn variant_json(info: &VariantInfo) -> String {
+    let mut json = JsonObject::new(false);
+    json.string("name", info.name);
+    json.string("kind", info.kind);
+    json.string("caption", info.caption);
+    match info.code {
+        Some(code) => json.string("code", code),
+        None => json.raw("code", "null"),
+    }
+    json.raw("status", info.status_code);
+    json.raw("retryable", info.retryable);
+    json.raw("fatal", info.fatal);
+    json.string("severity", info.severity.as_str());
+    match info.docs_url {
+        Some(url) => json.string("docs_url", url),
+        None => json.raw("docs_url", "null"),
+    }
+    match info.message {
+        Some(message) => json.string("message", message),
+        None => json.raw("message", "null"),
+    }
+    let mut fields = String::from("[");
+    for (i, (name, ty)) in info.fields.iter().enumerate() {
+        if i > 0 {
+            fields.push(',');
+        }
+        fields.push_str("{\"name\":");
+        push_json_string(&mut fields, name);
+        fields.push_str(",\"type\":");
+        push_json_string(&mut fields, &ty.replace(' ', ""));
+        fields.push('}');
+    }
+    fields.push(']');
+    json.raw("fields", fields);
+    json.finish()
+}
+
+fn push_json_or_null(out: &mut String, value: Option<&str>) {
+    match value {
+        Some(value) => push_json_string(out, value),
+        None => out.push_str("null"),
+    }
+}
+
+// The TypeScript type for a Rust type as written in the enum.
+fn typescript_type(ty: &str) -> String {
+    let ty = ty.replace(' ', "");
+    let ty = ty.trim_start_matches("&'static").trim_start_matches('&');
+    if let Some(inner) = ty.strip_prefix("Option<").and_then(|t| t.strip_suffix('>')) {
+        return format!("{} | null", typescript_type(inner));
+    }
+    if let Some(inner) = ty.strip_prefix("Vec<").and_then(|t| t.strip_suffix('>')) {
+        return match typescript_type(inner) {
+            inner if inner.contains(' ') => format!("({inner})[]"),
+            inner => format!("{inner}[]"),
+        };
+    }
+    match ty {
+        "String" | "str" | "char" | "PathBuf" | "Cow<str>" => "string",
+        "bool" => "boolean",
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128"
+        | "isize" | "f32" | "f64" => "number",
+        _ => "unknown",
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(dead_code)]
+    fn test_schema_output() {
+        crate::define_errors! {
+            pub enum SdkError {
+                #[kind(Auth, status = 401, code = "SDK-401")]
+                Unauthorized,
+
+                #[error(display = "{service} is down", service)]
+                #[kind(Upstream, retryable = true, status = 503, code = "SDK-503")]
+                Unavailable { service: String, attempts: Option<u32>, hosts: Vec<String> },
+            }
+        }
+
+        let schema = ErrorSchema::new().with_enum::<SdkError>();
+        let json: serde_json::Value = serde_json::from_str(&schema.to_json(true)).unwrap();
+        let variant = &json["enums"][0]["variants"][1];
+        assert_eq!(json["enums"][0]["name"], "SdkError");
+        assert_eq!(variant["code"], "SDK-503");
+        assert_eq!(variant["message"], "{service} is down");
+        assert_eq!(variant["fields"][1]["type"], "Option<u32>");
+        assert_eq!(schema.to_json(false).lines().count(), 1);
+
+        let ts = schema.to_typescript();
+        assert!(ts.contains(
+            "  Unauthorized: { kind: \"Auth\", code: \"SDK-401\", status: 401, retryable: false, fatal: false, message: null },"
+        ));
+        assert!(ts.contains("export type SdkErrorCode = \"SDK-401\" | \"SDK-503\";"));
+        assert!(ts.contains(
+            "export interface SdkErrorUnavailableFields {\n  service: string;\n  attempts: number | null;\n  hosts: string[];\n}"
+        ));
+    }
+}
@@ -0,0 +1,230 @@
+//! Re-raising an error with a different severity.
+//!
+//! Whether an error is serious depends on who receives it. A cache
+//! miss reported as a `Warning` upstream may be fatal to a caller that
+//! cannot run without the cache, and a `Critical` upstream outage may
+//! be routine to a caller with a fallback. [`WithSeverity::escalate`]
+//! and [`WithSeverity::demote`] wrap the error in a [`SeverityError`]
+//! that reports the new severity and fatality, keeps the original as
+//! its [`source`](std::error::Error::source), and fires the error hook
+//! again with a caption noting the change.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::macros::ErrorLevel;
+//! use error_forge::severity::WithSeverity;
+//! use error_forge::{AppError, ForgeError};
+//!
+//! let err = AppError::network("cache", None).escalate();
+//! assert!(err.is_fatal());
+//! assert_eq!(err.severity(), ErrorLevel::Critical);
+//! assert_eq!(err.error.severity(), ErrorLevel::Warning);
+//!
+//! let err = AppError::config("optional plugin missing").demote(ErrorLevel::Info);
+//! assert!(!err.is_fatal());
+//! assert_eq!(err.severity(), ErrorLevel::Info);
+//! ```
+
+use crate::error::{ErrorClass, ForgeError};
+use crate::macros::ErrorLevel;
+use crate::metadata::Metadata;
+use std::fmt;
+
+/// An error re-raised with a different severity and fatality.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// fields without breaking callers. Build one with
+/// [`SeverityError::new`] or the [`WithSeverity`] extension methods.
+/// Every [`ForgeError`] method other than
+/// [`severity`](ForgeError::severity), [`is_fatal`](ForgeError::is_fatal),
+/// and, for fatal errors, [`is_retryable`](ForgeError::is_retryable) and
+/// [`class`](ForgeError::class) delegates to the wrapped error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct SeverityError<E> {
+    /// The original error
+    pub error: E,
+    /// The severity reported in place of the original's
+    pub severity: ErrorLevel,
+    /// Whether the error is now fatal
+    pub fatal: bool,
+}
+
+impl<E: ForgeError> SeverityError<E> {
+    /// Wrap `error` as `severity` and `fatal`, firing the error hook
+    /// with the original caption followed by the change, such as
+    /// `"Network Error (escalated from warning)"`.
+    #[track_caller]
+    pub fn new(error: E, severity: ErrorLevel, fatal: bool) -> Self {
+        let previous = error.severity();
+        let change = match severity.cmp(&previous) {
+            std::cmp::Ordering::Greater => "escalated",
+            std::cmp::Ordering::Less => "demoted",
+            std::cmp::Ordering::Equal => "re-raised",
+        };
+        let err = Self {
+            error,
+            severity,
+            fatal,
+        };
+        crate::macros::call_error_hook_with_level(
+            severity,
+            &format!("{} ({change} from {previous})", err.caption()),
+            err.kind(),
+            fatal,
+            err.is_retryable(),
+        );
+        err
+    }
+
+    /// Extract the original error
+    pub fn into_error(self) -> E {
+        self.error
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for SeverityError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for SeverityError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl<E: ForgeError> ForgeError for SeverityError<E> {
+    fn kind(&self) -> &'static str {
+        self.error.kind()
+    }
+
+    fn caption(&self) -> &'static str {
+        self.error.caption()
+    }
+
+    fn is_retryable(&self) -> bool {
+        !self.fatal && self.error.is_retryable()
+    }
+
+    fn is_fatal(&self) -> bool {
+        self.fatal
+    }
+
+    fn status_code(&self) -> u16 {
+        self.error.status_code()
+    }
+
+    fn exit_code(&self) -> i32 {
+        self.error.exit_code()
+    }
+
+    fn severity(&self) -> ErrorLevel {
+        self.severity
+    }
+
+    fn class(&self) -> ErrorClass {
+        match self.fatal {
+            true => ErrorClass::Permanent,
+            false => self.error.class(),
+        }
+    }
+
+    fn user_message(&self) -> String {
+        self.error.user_message()
+    }
+
+    fn dev_message(&self) -> String {
+        self.error.dev_message()
+    }
+
+    fn backtrace(&self) -> Option<&std::backtrace::Backtrace> {
+        self.error.backtrace()
+    }
+
+    fn code(&self) -> Option<&str> {
+        self.error.code()
+    }
+
+    fn docs_url(&self) -> Option<String> {
+        self.error.docs_url()
+    }
+
+    fn retry_after(&self) -> Option<std::time::Duration> {
+        self.error.retry_after()
+    }
+
+    fn error_id(&self) -> Option<crate::instance::ErrorId> {
+        self.error.error_id()
+    }
+
+    fn timestamp(&self) -> Option<std::time::SystemTime> {
+        self.error.timestamp()
+    }
+
+    fn location(&self) -> Option<&'static std::panic::Location<'static>> {
+        self.error.location()
+    }
+
+    fn metadata(&self) -> &Metadata {
+        self.error.metadata()
+    }
+}
+
+/// Extension trait for re-raising any [`ForgeError`] with a different
+/// severity
+pub trait WithSeverity: ForgeError + Sized {
+    /// Re-raise as a fatal, `Critical` error
+    #[track_caller]
+    fn escalate(self) -> SeverityError<Self> {
+        SeverityError::new(self, ErrorLevel::Critical, true)
+    }
+
+    /// Re-raise as a non-fatal error at `level`
+    #[track_caller]
+    fn demote(self, level: ErrorLevel) -> SeverityError<Self> {
+        SeverityError::new(self, level, false)
+    }
+}
+
+impl<E: ForgeError> WithSeverity for E {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppError;
+    use std::error::Error as _;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_escalate_and_demote() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let id = crate::hooks::subscribe(move |ctx: &crate::macros::ErrorContext<'_>| {
+            if ctx.kind == "Network" && ctx.caption.contains(" from ") {
+                sink.lock()
+                    .unwrap()
+                    .push((ctx.caption.to_string(), ctx.is_fatal));
+            }
+        })
+        .unwrap();
+
+        let err = AppError::network("primary-db", None).escalate();
+        assert!(err.is_fatal() && !err.is_retryable());
+        assert_eq!(err.class(), ErrorClass::Permanent);
+        assert_eq!(err.to_string(), err.error.to_string());
+        assert_eq!(err.source().unwrap().to_string(), err.error.to_string());
+
+        let err = err.into_error().demote(ErrorLevel::Debug);
+        assert!(err.is_retryable());
+        assert_eq!(err.severity(), ErrorLevel::Debug);
+        crate::hooks::unsubscribe(id);
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert!(seen[0].0.ends_with("(escalated from warning)") && seen[0].1);
+        assert!(seen[1].0.ends_with("(demoted from warning)") && !seen[1].1);
+    }
+}
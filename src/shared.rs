@@ -0,0 +1,211 @@
+//! Reference-counted errors that can be handed to several consumers.
+//!
+//! Reporting one failure often means passing it to a logger, a metrics
+//! sink, and the response that goes back to the caller. [`SharedError`]
+//! wraps any [`ForgeError`] in an [`Arc`] so each of them can hold a
+//! clone while the error keeps its kind, status, id, and source chain.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::{AppError, ForgeError, SharedError};
+//!
+//! let err = SharedError::new(AppError::network("payments", None));
+//! let for_metrics = err.clone();
+//!
+//! assert_eq!(for_metrics.kind(), "Network");
+//! assert_eq!(err.error_id(), for_metrics.error_id());
+//! ```
+//!
+//! Error enums that own only cloneable fields can instead derive
+//! `Clone` directly; `define_errors!` forwards any attributes written
+//! above the enum, including `#[derive(Clone)]`.
+
+use crate::error::ForgeError;
+use crate::metadata::Metadata;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// A cheaply cloneable [`ForgeError`] backed by an [`Arc`].
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// fields without breaking callers. Build one with
+/// [`SharedError::new`] or `From`. Every [`ForgeError`] method
+/// delegates to the shared error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct SharedError<E> {
+    /// The shared original error
+    pub error: Arc<E>,
+}
+
+impl<E> SharedError<E> {
+    /// Move `error` behind a new reference count.
+    pub fn new(error: E) -> Self {
+        Self {
+            error: Arc::new(error),
+        }
+    }
+
+    /// Returns the original error if this is the only handle to it,
+    /// and the handle unchanged otherwise.
+    pub fn try_into_error(self) -> Result<E, Self> {
+        Arc::try_unwrap(self.error).map_err(|error| Self { error })
+    }
+}
+
+impl<E> Clone for SharedError<E> {
+    fn clone(&self) -> Self {
+        Self {
+            error: Arc::clone(&self.error),
+        }
+    }
+}
+
+impl<E> Deref for SharedError<E> {
+    type Target = E;
+
+    fn deref(&self) -> &E {
+        &self.error
+    }
+}
+
+impl<E> From<E> for SharedError<E> {
+    fn from(error: E) -> Self {
+        Self::new(error)
+    }
+}
+
+impl<E> From<Arc<E>> for SharedError<E> {
+    fn from(error: Arc<E>) -> Self {
+        Self { error }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for SharedError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<E: std::error::Error> std::error::Error for SharedError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+impl<E: ForgeError> ForgeError for SharedError<E> {
+    fn kind(&self) -> &'static str {
+        self.error.kind()
+    }
+
+    fn caption(&self) -> &'static str {
+        self.error.caption()
+    }
+
+    fn is_retryable(&self) -> bool {
+        self.error.is_retryable()
+    }
+
+    fn is_fatal(&self) -> bool {
+        self.error.is_fatal()
+    }
+
+    fn status_code(&self) -> u16 {
+        self.error.status_code()
+    }
+
+    fn exit_code(&self) -> i32 {
+        self.error.exit_code()
+    }
+
+    fn severity(&self) -> crate::macros::ErrorLevel {
+        self.error.severity()
+    }
+
+    fn class(&self) -> crate::error::ErrorClass {
+        self.error.class()
+    }
+
+    fn user_message(&self) -> String {
+        self.error.user_message()
+    }
+
+    fn dev_message(&self) -> String {
+        self.error.dev_message()
+    }
+
+    fn backtrace(&self) -> Option<&std::backtrace::Backtrace> {
+        self.error.backtrace()
+    }
+
+    fn code(&self) -> Option<&str> {
+        self.error.code()
+    }
+
+    fn docs_url(&self) -> Option<String> {
+        self.error.docs_url()
+    }
+
+    fn retry_after(&self) -> Option<std::time::Duration> {
+        self.error.retry_after()
+    }
+
+    fn error_id(&self) -> Option<crate::instance::ErrorId> {
+        self.error.error_id()
+    }
+
+    fn timestamp(&self) -> Option<std::time::SystemTime> {
+        self.error.timestamp()
+    }
+
+    fn location(&self) -> Option<&'static std::panic::Location<'static>> {
+        self.error.location()
+    }
+
+    fn context_layer(&self) -> Option<(&dyn fmt::Display, &dyn ForgeError)> {
+        self.error.context_layer()
+    }
+
+    fn metadata(&self) -> &Metadata {
+        self.error.metadata()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppError;
+
+    #[test]
+    fn test_shared_error() {
+        let err = SharedError::new(AppError::config("bad").with_meta("tenant", "acme"));
+        let other = err.clone();
+
+        assert_eq!(other.kind(), "Config");
+        assert_eq!(other.to_string(), err.to_string());
+        assert_eq!(other.metadata().len(), 1);
+
+        let other = other.try_into_error().unwrap_err();
+        drop(err);
+        assert!(other.try_into_error().is_ok());
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn test_define_errors_clone() {
+        crate::define_errors! {
+            #[derive(Clone)]
+            pub enum CacheError {
+                #[kind(Cache, retryable = true)]
+                Miss { key: String },
+            }
+        }
+
+        let err = CacheError::miss("user:1".to_string());
+        let copy = err.clone();
+        assert_eq!(copy.error_id(), err.error_id());
+        assert_eq!(copy.to_string(), err.to_string());
+    }
+}
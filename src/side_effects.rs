@@ -0,0 +1,116 @@
+//! A process-wide switch for everything error-forge does besides
+//! building errors.
+//!
+//! Benchmarks want to measure error construction alone, some test
+//! environments must not reach shared loggers, and security-sensitive
+//! deployments may not emit telemetry at all. With side effects
+//! [`Disabled`](SideEffects::Disabled), the error hook, observers,
+//! deferred sections, loggers, fatal handlers, and retry metrics are
+//! all skipped; errors still display, classify, and convert as usual.
+//!
+//! The initial state comes from the `ERROR_FORGE_SIDE_EFFECTS`
+//! environment variable (`off`, `disabled`, `false`, or `0` disable
+//! them), read the first time it is needed. [`set_side_effects`]
+//! overrides it at runtime.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::hooks;
+//! use error_forge::macros::ErrorContext;
+//! use error_forge::{set_side_effects, AppError, SideEffects};
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use std::sync::Arc;
+//!
+//! let seen = Arc::new(AtomicUsize::new(0));
+//! let counter = Arc::clone(&seen);
+//! hooks::subscribe(move |_: &ErrorContext<'_>| {
+//!     counter.fetch_add(1, Ordering::Relaxed);
+//! })
+//! .unwrap();
+//!
+//! set_side_effects(SideEffects::Disabled);
+//! let err = AppError::config("missing key");
+//! assert_eq!(err.to_string(), "⚙️ Configuration Error: missing key");
+//! assert_eq!(seen.load(Ordering::Relaxed), 0);
+//!
+//! set_side_effects(SideEffects::Enabled);
+//! let _ = AppError::config("missing key");
+//! assert_eq!(seen.load(Ordering::Relaxed), 1);
+//! ```
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Environment variable consulted for the initial [`SideEffects`].
+pub const SIDE_EFFECTS_ENV_VAR: &str = "ERROR_FORGE_SIDE_EFFECTS";
+
+/// Whether error-forge runs hooks, loggers, and metrics.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// modes without breaking callers that `match` on the enum.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SideEffects {
+    /// Hooks, observers, loggers, fatal handlers, and metrics run
+    #[default]
+    Enabled,
+    /// Only the errors themselves are built
+    Disabled,
+}
+
+impl SideEffects {
+    /// Read the mode from the `ERROR_FORGE_SIDE_EFFECTS` environment
+    /// variable. Returns `None` if the variable is unset or holds an
+    /// unrecognised value.
+    pub fn from_env() -> Option<Self> {
+        match std::env::var(SIDE_EFFECTS_ENV_VAR)
+            .ok()?
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "on" | "enabled" | "true" | "1" => Some(Self::Enabled),
+            "off" | "disabled" | "false" | "0" => Some(Self::Disabled),
+            _ => None,
+        }
+    }
+}
+
+const UNSET: u8 = 0;
+const ENABLED: u8 = 1;
+const DISABLED: u8 = 2;
+
+static STATE: AtomicU8 = AtomicU8::new(UNSET);
+
+fn state(mode: SideEffects) -> u8 {
+    match mode {
+        SideEffects::Enabled => ENABLED,
+        SideEffects::Disabled => DISABLED,
+    }
+}
+
+/// Turn error-forge side effects on or off for the whole process.
+pub fn set_side_effects(mode: SideEffects) {
+    STATE.store(state(mode), Ordering::Relaxed);
+}
+
+/// The current mode, initialized from the environment on first use.
+pub fn side_effects() -> SideEffects {
+    match STATE.load(Ordering::Relaxed) {
+        ENABLED => SideEffects::Enabled,
+        DISABLED => SideEffects::Disabled,
+        _ => {
+            let mode = SideEffects::from_env().unwrap_or_default();
+            // A concurrent `set_side_effects` wins over the environment.
+            match STATE.compare_exchange(UNSET, state(mode), Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => mode,
+                Err(_) => side_effects(),
+            }
+        }
+    }
+}
+
+/// Whether side effects are enabled; checked before each one.
+pub(crate) fn enabled() -> bool {
+    side_effects() == SideEffects::Enabled
+}
@@ -0,0 +1,265 @@
+//! Owned, serializable snapshots of errors.
+//!
+//! [`ErrorSnapshot`] captures the metadata of any [`ForgeError`] into
+//! plain owned fields. Snapshots outlive the original error, can be
+//! shipped across threads or processes (with the `serde` feature),
+//! and can be rendered again later with
+//! [`ConsoleTheme::format_snapshot`](crate::console_theme::ConsoleTheme::format_snapshot)
+//! — which is how tooling pretty-prints an error that was serialized
+//! by another process.
+
+use crate::error::{Chain, ForgeError};
+use crate::limits::PayloadLimits;
+use crate::macros::ErrorLevel;
+use crate::metadata::Metadata;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Recorded as the last cause when the source chain was cut off at
+/// [`max_chain_depth`](crate::error::max_chain_depth),
+/// [`PayloadLimits::max_causes`], or a cycle.
+pub const TRUNCATED_CAUSES: &str = "… (source chain truncated)";
+
+/// An owned copy of an error's metadata and message.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// fields without breaking callers. Build one with
+/// [`ErrorSnapshot::capture`] (or deserialize one with the `serde`
+/// feature).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct ErrorSnapshot {
+    /// The error kind
+    pub kind: String,
+    /// The error caption
+    pub caption: String,
+    /// The error's `Display` output
+    pub message: String,
+    /// The user-facing message
+    pub user_message: String,
+    /// HTTP status code
+    pub status_code: u16,
+    /// Process exit code
+    pub exit_code: i32,
+    /// Whether the error is retryable
+    pub retryable: bool,
+    /// Whether the error is fatal
+    pub fatal: bool,
+    /// The error's severity
+    pub severity: ErrorLevel,
+    /// Documentation link for the error, if one is known
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub docs_url: Option<String>,
+    /// The unique ID of the error instance, if it carries one
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub error_id: Option<String>,
+    /// When the error was created, in milliseconds since the Unix
+    /// epoch, if it records it
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub timestamp: Option<u64>,
+    /// `Display` output of each error in the source chain, outermost first
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub causes: Vec<String>,
+    /// Structured metadata attached to the error
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub metadata: Metadata,
+}
+
+impl ErrorSnapshot {
+    /// Capture the metadata of `err` into an owned snapshot, within the
+    /// process-wide [`payload_limits`](crate::limits::payload_limits).
+    pub fn capture<E: ForgeError + ?Sized>(err: &E) -> Self {
+        Self::capture_with_limits(err, &crate::limits::payload_limits())
+    }
+
+    /// Capture `err` with explicit size limits. Oversized messages,
+    /// causes, and metadata values end in a truncation marker; dropped
+    /// causes are replaced by [`TRUNCATED_CAUSES`].
+    pub fn capture_with_limits<E: ForgeError + ?Sized>(err: &E, limits: &PayloadLimits) -> Self {
+        let mut causes = Vec::new();
+        if let Some(source) = err.source() {
+            let mut chain = Chain::new(source);
+            causes.extend(
+                chain
+                    .by_ref()
+                    .take(limits.max_causes)
+                    .map(|cause| limits.truncate(&cause.to_string()).into_owned()),
+            );
+            if chain.is_truncated() || chain.next().is_some() {
+                causes.push(TRUNCATED_CAUSES.to_string());
+            }
+        }
+
+        Self {
+            kind: err.kind().to_string(),
+            caption: err.caption().to_string(),
+            message: limits.truncate(&err.to_string()).into_owned(),
+            user_message: limits.truncate(&err.user_message()).into_owned(),
+            status_code: err.status_code(),
+            exit_code: err.exit_code(),
+            retryable: err.is_retryable(),
+            fatal: err.is_fatal(),
+            severity: err.severity(),
+            docs_url: err.docs_url(),
+            error_id: err.error_id().map(|id| id.to_string()),
+            timestamp: err.timestamp().map(crate::instance::unix_millis),
+            causes,
+            metadata: limits.limit_metadata(err.metadata()).into_owned(),
+        }
+    }
+}
+
+impl<E: ForgeError> From<&E> for ErrorSnapshot {
+    fn from(err: &E) -> Self {
+        Self::capture(err)
+    }
+}
+
+impl ErrorSnapshot {
+    /// Render the snapshot as a JSON object.
+    ///
+    /// The field names match the `serde` representation, so output
+    /// produced here can be read back with `serde_json` and the
+    /// `serde` feature. With `pretty` set, members are placed on
+    /// separate lines with two-space indentation.
+    pub fn to_json(&self, pretty: bool) -> String {
+        let mut json = JsonObject::new(pretty);
+        json.string("kind", &self.kind);
+        json.string("caption", &self.caption);
+        json.string("message", &self.message);
+        json.string("user_message", &self.user_message);
+        json.raw("status_code", self.status_code);
+        json.raw("exit_code", self.exit_code);
+        json.raw("retryable", self.retryable);
+        json.raw("fatal", self.fatal);
+        json.string("severity", self.severity.as_str());
+        match &self.docs_url {
+            Some(url) => json.string("docs_url", url),
+            None => json.raw("docs_url", "null"),
+        }
+        match &self.error_id {
+            Some(id) => json.string("error_id", id),
+            None => json.raw("error_id", "null"),
+        }
+        match self.timestamp {
+            Some(millis) => json.raw("timestamp", millis),
+            None => json.raw("timestamp", "null"),
+        }
+        json.strings("causes", &self.causes);
+        json.metadata("metadata", &self.metadata);
+        json.finish()
+    }
+}
+
+/// Minimal JSON object writer used by [`ErrorSnapshot::to_json`].
+pub(crate) struct JsonObject {
+    out: String,
+    pretty: bool,
+    empty: bool,
+}
+
+impl JsonObject {
+    pub(crate) fn new(pretty: bool) -> Self {
+        let mut out = String::with_capacity(256);
+        out.push('{');
+        Self {
+            out,
+            pretty,
+            empty: true,
+        }
+    }
+
+    fn key(&mut self, key: &str) {
+        if !self.empty {
+            self.out.push(',');
+        }
+        self.empty = false;
+        if self.pretty {
+            self.out.push_str("\n  ");
+        }
+        push_json_string(&mut self.out, key);
+        self.out.push(':');
+        if self.pretty {
+            self.out.push(' ');
+        }
+    }
+
+    /// Write a string member.
+    pub(crate) fn string(&mut self, key: &str, value: &str) {
+        self.key(key);
+        push_json_string(&mut self.out, value);
+    }
+
+    /// Write a member whose `Display` output is already valid JSON
+    /// (numbers and booleans).
+    pub(crate) fn raw(&mut self, key: &str, value: impl std::fmt::Display) {
+        use std::fmt::Write as _;
+        self.key(key);
+        let _ = write!(self.out, "{value}");
+    }
+
+    /// Write an array-of-strings member.
+    pub(crate) fn strings(&mut self, key: &str, values: &[String]) {
+        self.key(key);
+        self.out.push('[');
+        for (i, value) in values.iter().enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            push_json_string(&mut self.out, value);
+        }
+        self.out.push(']');
+    }
+
+    /// Write a metadata member as a nested object on one line.
+    pub(crate) fn metadata(&mut self, key: &str, metadata: &Metadata) {
+        use crate::metadata::MetaValue;
+        use std::fmt::Write as _;
+        self.key(key);
+        self.out.push('{');
+        for (i, (key, value)) in metadata.iter().enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            push_json_string(&mut self.out, key);
+            self.out.push(':');
+            match value {
+                MetaValue::String(value) => push_json_string(&mut self.out, value),
+                other => {
+                    let _ = write!(self.out, "{other}");
+                }
+            }
+        }
+        self.out.push('}');
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        if self.pretty && !self.empty {
+            self.out.push('\n');
+        }
+        self.out.push('}');
+        self.out
+    }
+}
+
+/// Append `value` to `out` as a quoted, escaped JSON string.
+pub(crate) fn push_json_string(out: &mut String, value: &str) {
+    use std::fmt::Write as _;
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,124 @@
+//! The integration surface for crates that plug into error-forge.
+//!
+//! Integration crates such as an error-tracker exporter or a web
+//! framework adapter should depend only on the items re-exported here.
+//! They are covered by semver: a breaking change to any of them needs
+//! a major release, while the rest of the crate's public API may gain
+//! fields and methods more freely.
+//!
+//! - [`ForgeError`], [`ErrorClass`], and [`ErrorLevel`] describe an error.
+//! - [`ErrorMetadata`] and [`MetaValue`] are its structured fields.
+//! - [`ErrorEvent`] is an owned record of one logged error, handed to
+//!   every [`Exporter`] registered with [`register_exporter`].
+//! - [`ErrorLogger`] and [`ErrorObserver`] receive log lines and
+//!   error-hook events.
+//! - [`Backoff`] and [`TimeProvider`] plug into retries and timestamps.
+//!
+//! Exporters run synchronously, in registration order, each time an
+//! error is logged through [`log_error`](crate::logging::log_error) or
+//! [`log_with_level`](crate::logging::log_with_level), after the
+//! registered logger. Like loggers, they are skipped when
+//! [side effects](crate::side_effects) are disabled.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::logging::log_error;
+//! use error_forge::spi::{self, ErrorEvent, ErrorLevel};
+//! use error_forge::{AppError, WithMetadata};
+//! use std::sync::{Arc, Mutex};
+//!
+//! let exported = Arc::new(Mutex::new(Vec::new()));
+//! let sink = Arc::clone(&exported);
+//! spi::register_exporter(move |event: &ErrorEvent| {
+//!     sink.lock().unwrap().push((event.level, event.error.kind.clone()));
+//! });
+//!
+//! log_error(&AppError::network("billing", None).with_meta("tenant", "acme"));
+//! assert_eq!(
+//!     exported.lock().unwrap().as_slice(),
+//!     [(ErrorLevel::Warning, "Network".to_string())]
+//! );
+//! ```
+
+use parking_lot::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+pub use crate::clock::TimeProvider;
+pub use crate::error::{ErrorClass, ForgeError};
+pub use crate::hooks::ErrorObserver;
+pub use crate::logging::ErrorLogger;
+pub use crate::macros::{ErrorContext, ErrorLevel};
+pub use crate::metadata::{MetaValue, Metadata as ErrorMetadata};
+pub use crate::recovery::Backoff;
+pub use crate::snapshot::ErrorSnapshot;
+
+/// One logged error, as handed to an [`Exporter`].
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// fields without breaking exporters. Construct via
+/// [`ErrorEvent::new`] from outside the crate.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ErrorEvent {
+    /// The level the error was logged at, which may differ from its
+    /// own [`severity`](ForgeError::severity)
+    pub level: ErrorLevel,
+    /// The error's kind, messages, metadata, and cause chain
+    pub error: ErrorSnapshot,
+}
+
+impl ErrorEvent {
+    /// Build an event from its components.
+    pub fn new(level: ErrorLevel, error: ErrorSnapshot) -> Self {
+        Self { level, error }
+    }
+
+    /// Capture `error` as logged at `level`.
+    pub fn capture(error: &dyn ForgeError, level: ErrorLevel) -> Self {
+        Self::new(level, ErrorSnapshot::capture(error))
+    }
+}
+
+/// Ships logged errors somewhere else, such as an error tracker.
+///
+/// Implemented for closures taking `&ErrorEvent`.
+pub trait Exporter: Send + Sync + 'static {
+    /// Called once for each logged error.
+    fn export(&self, event: &ErrorEvent);
+}
+
+impl<F> Exporter for F
+where
+    F: Fn(&ErrorEvent) + Send + Sync + 'static,
+{
+    fn export(&self, event: &ErrorEvent) {
+        self(event)
+    }
+}
+
+// The registered exporters, and whether there are any, so logging
+// skips the snapshot when there are none.
+static EXPORTERS: RwLock<Vec<Arc<dyn Exporter>>> = RwLock::new(Vec::new());
+static ANY_EXPORTER: AtomicBool = AtomicBool::new(false);
+
+/// Register `exporter` for the rest of the process.
+pub fn register_exporter(exporter: impl Exporter) {
+    EXPORTERS.write().push(Arc::new(exporter));
+    ANY_EXPORTER.store(true, Ordering::Release);
+}
+
+/// Hand `error`, logged at `level`, to every exporter.
+pub(crate) fn export(error: &dyn ForgeError, level: ErrorLevel) {
+    if !ANY_EXPORTER.load(Ordering::Acquire) {
+        return;
+    }
+    // Clone the list so an exporter may register another without
+    // deadlocking.
+    let exporters = EXPORTERS.read().clone();
+    let event = ErrorEvent::capture(error, level);
+    for exporter in &exporters {
+        exporter.export(&event);
+    }
+}
@@ -0,0 +1,146 @@
+//! Helpers for testing error reporting.
+//!
+//! [`install_panic_hook`](crate::console_theme::install_panic_hook)
+//! writes to stderr from inside the panic machinery, so its output
+//! could only be checked by running a subprocess.
+//! [`capture_panic_output`] runs a closure that is expected to panic
+//! and returns what the hook would have printed, along with the panic's
+//! message, location, and carried error.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::panic::ForgePanic;
+//! use error_forge::testing::capture_panic_output;
+//! use error_forge::AppError;
+//!
+//! let captured = capture_panic_output(|| {
+//!     std::panic::panic_any(ForgePanic::from(AppError::config("no shards")))
+//! });
+//!
+//! assert!(captured.formatted.starts_with("💥 PANIC\n"));
+//! assert_eq!(captured.error.unwrap().kind, "Config");
+//! ```
+
+use crate::console_theme::ConsoleTheme;
+use crate::snapshot::ErrorSnapshot;
+use parking_lot::Mutex;
+use std::panic::{self, PanicHookInfo, UnwindSafe};
+use std::sync::Arc;
+use std::thread::{self, ThreadId};
+
+/// A panic recorded by [`capture_panic_output`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CapturedPanic {
+    /// The panic message, or the carried error's display
+    pub message: String,
+    /// The output of the themed panic hook
+    pub formatted: String,
+    /// Where the panic was raised, as `file:line:column`
+    pub location: Option<String>,
+    /// The error carried by a [`ForgePanic`](crate::panic::ForgePanic)
+    /// payload
+    pub error: Option<ErrorSnapshot>,
+}
+
+impl CapturedPanic {
+    fn record(theme: &ConsoleTheme, info: &PanicHookInfo<'_>) -> Self {
+        let forge = info.payload().downcast_ref::<crate::panic::ForgePanic>();
+        Self {
+            message: match forge {
+                Some(panic) => panic.to_string(),
+                None => crate::panic::payload_message(info.payload()).to_string(),
+            },
+            formatted: theme.format_panic(info),
+            location: info.location().map(ToString::to_string),
+            error: forge.map(|panic| ErrorSnapshot::capture(panic.error())),
+        }
+    }
+}
+
+/// Run `f`, which must panic, and capture the panic with the plain
+/// theme. See [`capture_panic_output_with`].
+#[track_caller]
+pub fn capture_panic_output<R>(f: impl FnOnce() -> R + UnwindSafe) -> CapturedPanic {
+    capture_panic_output_with(ConsoleTheme::plain(), f)
+}
+
+/// Run `f`, which must panic, and capture what the themed panic hook
+/// prints for it instead of writing it to stderr.
+///
+/// The panic hook is swapped for the duration of the call and the
+/// previous hook restored afterwards. Panics on other threads in the
+/// meantime still reach the previous hook. Calls are serialized, so
+/// tests running in parallel do not restore each other's hooks.
+///
+/// # Panics
+///
+/// Panics if `f` returns without panicking.
+#[track_caller]
+pub fn capture_panic_output_with<R>(
+    theme: ConsoleTheme,
+    f: impl FnOnce() -> R + UnwindSafe,
+) -> CapturedPanic {
+    static SWAP: Mutex<()> = Mutex::new(());
+    let _swap = SWAP.lock();
+
+    let previous = Arc::new(panic::take_hook());
+    let captured = Arc::new(Mutex::new(None));
+    let current: ThreadId = thread::current().id();
+    {
+        let previous = Arc::clone(&previous);
+        let captured = Arc::clone(&captured);
+        panic::set_hook(Box::new(move |info| {
+            if thread::current().id() == current {
+                *captured.lock() = Some(CapturedPanic::record(&theme, info));
+            } else {
+                previous(info);
+            }
+        }));
+    }
+
+    let result = panic::catch_unwind(f);
+
+    // Dropping the capturing hook releases its handle on `previous`.
+    drop(panic::take_hook());
+    match Arc::try_unwrap(previous) {
+        Ok(previous) => panic::set_hook(previous),
+        Err(previous) => panic::set_hook(Box::new(move |info| previous(info))),
+    }
+
+    let captured = captured.lock().take();
+    match (result, captured) {
+        (Err(_), Some(captured)) => captured,
+        _ => panic!("capture_panic_output: the closure did not panic"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::panic::ForgePanic;
+    use crate::AppError;
+
+    #[test]
+    fn test_capture_panic_output() {
+        let captured = capture_panic_output(|| panic!("index {} out of range", 3));
+        assert_eq!(captured.message, "index 3 out of range");
+        let location = captured.location.unwrap();
+        assert!(location.starts_with(file!()), "{location}");
+        assert!(captured.formatted.contains("index 3 out of range at "));
+        assert!(captured.error.is_none());
+
+        let captured = capture_panic_output(|| {
+            std::panic::panic_any(ForgePanic::from(
+                AppError::config("no shards").with_code("CFG-9"),
+            ))
+        });
+        assert!(captured.formatted.contains("Code: CFG-9\n"));
+        assert_eq!(captured.error.unwrap().kind, "Config");
+        assert!(captured.message.ends_with("no shards"));
+
+        let missing = std::panic::catch_unwind(|| capture_panic_output(|| 1));
+        assert!(missing.is_err());
+    }
+}
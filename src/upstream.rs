@@ -0,0 +1,391 @@
+//! Wrapping failed responses from downstream HTTP services.
+//!
+//! A gateway that calls other services needs to report their failures
+//! without losing what the downstream said. [`UpstreamError::from_response`]
+//! takes the status and body of a failed response, reads the error code
+//! and detail from a JSON or `application/problem+json` (RFC 9457) body
+//! when there is one, and keeps them as fields and [`Metadata`] so they
+//! reach logs, snapshots, and JSON output.
+//!
+//! The local status is derived for a gateway: `429`, `503`, and `504`
+//! pass through and every other failure becomes `502 Bad Gateway`.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::upstream::UpstreamError;
+//! use error_forge::{AppError, ForgeError};
+//!
+//! let body = r#"{"type": "https://billing.example.com/card-declined",
+//!               "title": "Card declined", "code": "CARD_DECLINED",
+//!               "detail": "The card has insufficient funds"}"#;
+//! let err = UpstreamError::from_response("billing", 402, body);
+//!
+//! assert_eq!(err.code.as_deref(), Some("CARD_DECLINED"));
+//! assert_eq!(err.detail.as_deref(), Some("The card has insufficient funds"));
+//! assert_eq!(err.status_code(), 502);
+//! assert!(!err.is_retryable());
+//!
+//! // Into the crate's general error type, keeping the upstream error
+//! // as the source.
+//! let app: AppError = err.into();
+//! assert!(app.find_in_chain::<UpstreamError>().is_some());
+//! ```
+
+use crate::error::{AppError, ErrorClass, ForgeError};
+use crate::instance::Instance;
+use crate::metadata::Metadata;
+use std::fmt;
+
+/// A failed response from a downstream service.
+///
+/// Marked `#[non_exhaustive]` so future minor releases can add new
+/// fields without breaking callers. Build one with
+/// [`UpstreamError::from_response`] or [`UpstreamError::new`] and the
+/// `with_*` methods.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct UpstreamError {
+    /// The downstream service
+    pub service: String,
+    /// The HTTP status it returned
+    pub status: u16,
+    /// The service's own error code, if the body carried one
+    pub code: Option<String>,
+    /// The service's description of the failure, if any
+    pub detail: Option<String>,
+    metadata: Metadata,
+    instance: Instance,
+}
+
+impl UpstreamError {
+    /// A failure of `service` with `status` and no body.
+    #[track_caller]
+    pub fn new(service: impl Into<String>, status: u16) -> Self {
+        let error = Self::build(service.into(), status, None, None);
+        error.register();
+        error
+    }
+
+    /// Parse a failed response.
+    ///
+    /// A JSON object body is read for `code` (or `error_code`, or the
+    /// problem `type`) and `detail` (or `message`, `title`, or a string
+    /// `error`). Any other non-empty body is kept whole as the detail.
+    #[track_caller]
+    pub fn from_response(service: impl Into<String>, status: u16, body: &str) -> Self {
+        let body = body.trim();
+        let (code, detail) = match top_level_strings(body) {
+            Some(fields) => {
+                let field = |keys: &[&str]| {
+                    keys.iter().find_map(|key| {
+                        fields
+                            .iter()
+                            .find(|(name, _)| name == key)
+                            .map(|(_, value)| value.clone())
+                    })
+                };
+                (
+                    field(&["code", "error_code", "type"]),
+                    field(&["detail", "message", "title", "error"]),
+                )
+            }
+            None => (None, (!body.is_empty()).then(|| body.to_string())),
+        };
+        let error = Self::build(service.into(), status, code, detail);
+        error.register();
+        error
+    }
+
+    /// Set the upstream error code.
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        let code = code.into();
+        self.metadata.insert("upstream.code", code.clone());
+        self.code = Some(code);
+        self
+    }
+
+    /// Set the upstream description of the failure.
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    #[track_caller]
+    fn build(service: String, status: u16, code: Option<String>, detail: Option<String>) -> Self {
+        let instance = Instance::new();
+        let mut metadata = instance.metadata().clone();
+        metadata.insert("upstream.service", service.clone());
+        metadata.insert("upstream.status", status);
+        if let Some(code) = &code {
+            metadata.insert("upstream.code", code.clone());
+        }
+        Self {
+            service,
+            status,
+            code,
+            detail,
+            metadata,
+            instance,
+        }
+    }
+}
+
+impl fmt::Display for UpstreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} returned {}", self.service, self.status)?;
+        if let Some(code) = &self.code {
+            write!(f, " [{code}]")?;
+        }
+        if let Some(detail) = &self.detail {
+            write!(f, ": {detail}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UpstreamError {}
+
+impl ForgeError for UpstreamError {
+    fn kind(&self) -> &'static str {
+        "Upstream"
+    }
+
+    fn caption(&self) -> &'static str {
+        "Upstream"
+    }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self.status, 408 | 429) || (self.status >= 500 && self.status != 501)
+    }
+
+    fn status_code(&self) -> u16 {
+        match self.status {
+            429 | 503 | 504 => self.status,
+            _ => 502,
+        }
+    }
+
+    fn class(&self) -> ErrorClass {
+        match self.status {
+            429 => ErrorClass::RateLimited,
+            _ => ErrorClass::from_retryable(self.is_retryable()),
+        }
+    }
+
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn error_id(&self) -> Option<crate::instance::ErrorId> {
+        Some(self.instance.id())
+    }
+
+    fn timestamp(&self) -> Option<std::time::SystemTime> {
+        Some(self.instance.timestamp())
+    }
+
+    fn location(&self) -> Option<&'static std::panic::Location<'static>> {
+        Some(self.instance.location())
+    }
+
+    fn backtrace(&self) -> Option<&std::backtrace::Backtrace> {
+        self.instance.backtrace()
+    }
+}
+
+/// A `Network` error for the service, with the same status and
+/// retryability, whose source is the upstream error.
+impl From<UpstreamError> for AppError {
+    #[track_caller]
+    fn from(error: UpstreamError) -> Self {
+        AppError::Network {
+            endpoint: error.service.clone(),
+            retryable: error.is_retryable(),
+            fatal: false,
+            status: error.status_code(),
+            source: Some(Box::new(error)),
+            __forge: Instance::new(),
+        }
+    }
+}
+
+/// The string-valued members of a JSON object, or `None` if `body` is
+/// not one. Nested values are skipped; numbers, booleans, and `null`
+/// are ignored.
+fn top_level_strings(body: &str) -> Option<Vec<(String, String)>> {
+    let mut reader = JsonReader {
+        chars: body.chars().peekable(),
+    };
+    reader.expect('{')?;
+    let mut fields = Vec::new();
+    if reader.eat('}') {
+        return Some(fields);
+    }
+    loop {
+        let key = reader.string()?;
+        reader.expect(':')?;
+        if reader.peek()? == '"' {
+            fields.push((key, reader.string()?));
+        } else {
+            reader.skip_value(1)?;
+        }
+        if reader.eat('}') {
+            return Some(fields);
+        }
+        reader.expect(',')?;
+    }
+}
+
+/// Deepest nesting [`top_level_strings`] follows before giving up on
+/// the body, so a hostile response cannot exhaust the stack.
+const MAX_DEPTH: usize = 64;
+
+struct JsonReader<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl JsonReader<'_> {
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+        self.chars.peek().copied()
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        self.peek() == Some(expected) && self.chars.next().is_some()
+    }
+
+    fn expect(&mut self, expected: char) -> Option<()> {
+        self.eat(expected).then_some(())
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            match self.chars.next()? {
+                '"' => return Some(out),
+                '\\' => match self.chars.next()? {
+                    'n' => out.push('\n'),
+                    't' => out.push('\t'),
+                    'r' => out.push('\r'),
+                    'b' => out.push('\u{8}'),
+                    'f' => out.push('\u{c}'),
+                    'u' => {
+                        let hex: String = (0..4).filter_map(|_| self.chars.next()).collect();
+                        let code = u32::from_str_radix(&hex, 16).ok()?;
+                        out.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                    }
+                    other => out.push(other),
+                },
+                c => out.push(c),
+            }
+        }
+    }
+
+    /// Skip one value nested `depth` levels deep, or fail past
+    /// [`MAX_DEPTH`].
+    fn skip_value(&mut self, depth: usize) -> Option<()> {
+        match self.peek()? {
+            '"' => self.string().map(drop),
+            open @ ('{' | '[') => {
+                if depth >= MAX_DEPTH {
+                    return None;
+                }
+                let close = if open == '{' { '}' } else { ']' };
+                self.chars.next();
+                if self.eat(close) {
+                    return Some(());
+                }
+                loop {
+                    if open == '{' {
+                        self.string()?;
+                        self.expect(':')?;
+                    }
+                    self.skip_value(depth + 1)?;
+                    if self.eat(close) {
+                        return Some(());
+                    }
+                    self.expect(',')?;
+                }
+            }
+            _ => {
+                let mut any = false;
+                while self
+                    .chars
+                    .next_if(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '+' | '.'))
+                    .is_some()
+                {
+                    any = true;
+                }
+                any.then_some(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_response() {
+        let body = r#"{"error": {"nested": [1, {"a": null}]}, "status": 503,
+                       "error_code": "DB_DOWN", "message": "db \"primary\" unreachable"}"#;
+        let err = UpstreamError::from_response("orders", 503, body);
+        assert_eq!(err.code.as_deref(), Some("DB_DOWN"));
+        assert_eq!(err.detail.as_deref(), Some("db \"primary\" unreachable"));
+        assert_eq!(err.status_code(), 503);
+        assert!(err.is_retryable());
+        assert_eq!(err.metadata().len(), 3);
+        assert_eq!(
+            err.to_string(),
+            "orders returned 503 [DB_DOWN]: db \"primary\" unreachable"
+        );
+
+        let err = UpstreamError::from_response("orders", 429, "slow down\n");
+        assert_eq!(err.code, None);
+        assert_eq!(err.detail.as_deref(), Some("slow down"));
+        assert_eq!(err.class(), ErrorClass::RateLimited);
+
+        let err = UpstreamError::from_response("orders", 400, "{}");
+        assert_eq!((err.code, err.detail), (None, None));
+        assert!(top_level_strings(r#"{"a": }"#).is_none());
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let nested = |depth: usize| {
+            format!(
+                r#"{{"code": "E1", "x": {}{}}}"#,
+                "[".repeat(depth),
+                "]".repeat(depth)
+            )
+        };
+        let err = UpstreamError::from_response("orders", 500, &nested(MAX_DEPTH - 1));
+        assert_eq!(err.code.as_deref(), Some("E1"));
+
+        let hostile = nested(1_000_000);
+        let err = UpstreamError::from_response("orders", 500, &hostile);
+        assert_eq!(err.code, None);
+        assert_eq!(err.detail.as_deref(), Some(hostile.as_str()));
+    }
+
+    #[test]
+    fn test_into_app_error() {
+        let app: AppError = UpstreamError::new("search", 500).with_code("E1").into();
+        assert_eq!(app.kind(), "Network");
+        assert_eq!(app.status_code(), 502);
+        assert!(app.is_retryable());
+
+        let upstream = app.find_in_chain::<UpstreamError>().unwrap();
+        assert_eq!(
+            upstream
+                .metadata()
+                .get("upstream.code")
+                .unwrap()
+                .to_string(),
+            "E1"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Non-fatal diagnostics routed like errors.
+//!
+//! A deprecated configuration key or a soft limit being approached is
+//! worth reporting, but nothing failed, so it should not be an `Error`.
+//! A [`ForgeWarning`] carries a kind, a message, and a level (by default
+//! [`ErrorLevel::Warning`]), and [`emit`](ForgeWarning::emit) sends it
+//! through the same error hook, observers, and logger as errors. Gather
+//! several in an [`ErrorCollector`] and print one with
+//! [`print_warning`](crate::console_theme::print_warning).
+//!
+//! Warnings skip the [construction policy](crate::policy): a denied
+//! kind panics when an error of that kind is built, not when a warning
+//! is reported.
+//!
+//! # Example
+//!
+//! ```
+//! use error_forge::collector::ErrorCollector;
+//! use error_forge::warning::ForgeWarning;
+//!
+//! let mut warnings = ErrorCollector::new();
+//! warnings.push(ForgeWarning::new("Config", "`db_url` is deprecated; use `database.url`"));
+//! warnings.push(ForgeWarning::new("Limits", "queue is 90% full").with_code("LIM-090"));
+//!
+//! warnings.emit_all();
+//! assert_eq!(
+//!     warnings.errors()[1].to_string(),
+//!     "[LIM-090] queue is 90% full"
+//! );
+//! ```
+
+use crate::collector::ErrorCollector;
+use crate::macros::ErrorLevel;
+use std::fmt;
+
+/// A diagnostic that is reported like an error but does not implement
+/// `Error`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ForgeWarning {
+    kind: &'static str,
+    message: String,
+    level: ErrorLevel,
+    code: Option<String>,
+}
+
+impl ForgeWarning {
+    /// A `Warning`-level diagnostic of `kind`.
+    pub fn new(kind: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            level: ErrorLevel::Warning,
+            code: None,
+        }
+    }
+
+    /// Report at `level` instead, such as `Info` for a notice.
+    pub fn with_level(mut self, level: ErrorLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Attach a code, shown before the message.
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// The kind, used by hooks, observers, and console styles.
+    pub fn kind(&self) -> &'static str {
+        self.kind
+    }
+
+    /// The message, without the code.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The level the warning is reported at.
+    pub fn level(&self) -> ErrorLevel {
+        self.level
+    }
+
+    /// The code, if one was attached.
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    /// Send the warning to the error hook, hook observers, and the
+    /// registered logger.
+    ///
+    /// The hook sees it as neither fatal nor retryable. Inside a
+    /// [`deferred`](crate::hooks::deferred) section it is held back like
+    /// an error.
+    pub fn emit(&self) {
+        crate::macros::dispatch_error_hook(self.level, &self.message, self.kind, false, false);
+        crate::logging::log_message(&self.to_string(), self.level);
+    }
+}
+
+impl fmt::Display for ForgeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.code {
+            Some(code) => write!(f, "[{code}] {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl ErrorCollector<ForgeWarning> {
+    /// [`Emit`](ForgeWarning::emit) each warning, in the order they were
+    /// collected.
+    pub fn emit_all(&self) {
+        for warning in self.errors() {
+            warning.emit();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hooks;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_warning_reaches_observers() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let id = hooks::subscribe(move |ctx: &crate::macros::ErrorContext<'_>| {
+            if ctx.kind == "DeprecatedKey" {
+                sink.lock()
+                    .unwrap()
+                    .push((ctx.caption.to_string(), ctx.level));
+            }
+        })
+        .unwrap();
+
+        let warnings = ErrorCollector::new()
+            .with(ForgeWarning::new(
+                "DeprecatedKey",
+                "`timeout` is deprecated",
+            ))
+            .with(
+                ForgeWarning::new("DeprecatedKey", "`retries` moved").with_level(ErrorLevel::Info),
+            );
+        warnings.emit_all();
+        hooks::unsubscribe(id);
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                ("`timeout` is deprecated".to_string(), ErrorLevel::Warning),
+                ("`retries` moved".to_string(), ErrorLevel::Info),
+            ]
+        );
+        assert_eq!(warnings.to_string().lines().count(), 3);
+        assert_eq!(
+            crate::ConsoleTheme::plain().format_warning(&warnings.errors()[0]),
+            "⚠️  DeprecatedKey Warning\n`timeout` is deprecated\n"
+        );
+    }
+}
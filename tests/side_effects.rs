@@ -0,0 +1,83 @@
+//! The side-effect switch is process-wide, so it is tested in its own
+//! binary, as a single test, where flipping it cannot hide events from
+//! other tests.
+
+use error_forge::hooks;
+use error_forge::logging::{log_error, register_logger, ErrorLogger};
+use error_forge::macros::{ErrorContext, ErrorLevel};
+use error_forge::recovery::{metrics, RetryPolicy};
+use error_forge::side_effects::{side_effects, SIDE_EFFECTS_ENV_VAR};
+use error_forge::{set_side_effects, AppError, ForgeError, SideEffects};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+static LOGGED: AtomicUsize = AtomicUsize::new(0);
+
+struct CountingLogger;
+
+impl ErrorLogger for CountingLogger {
+    fn log_error(&self, _: &dyn ForgeError, _: ErrorLevel) {
+        LOGGED.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn log_message(&self, _: &str, _: ErrorLevel) {
+        LOGGED.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn log_panic(&self, _: &std::panic::PanicHookInfo) {}
+}
+
+fn give_ups() -> String {
+    metrics::render_prometheus()
+        .lines()
+        .find(|line| line.starts_with("error_forge_retry_give_ups_total "))
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn test_side_effects_switch() {
+    // The environment sets the initial mode, read on first use.
+    std::env::set_var(SIDE_EFFECTS_ENV_VAR, "off");
+    assert_eq!(SideEffects::from_env(), Some(SideEffects::Disabled));
+    assert_eq!(side_effects(), SideEffects::Disabled);
+    std::env::set_var(SIDE_EFFECTS_ENV_VAR, "sometimes");
+    assert_eq!(SideEffects::from_env(), None);
+
+    let seen = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&seen);
+    hooks::subscribe(move |_: &ErrorContext<'_>| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    })
+    .unwrap();
+    register_logger(CountingLogger).unwrap();
+    let executor = RetryPolicy::new_fixed(1)
+        .with_max_retries(1)
+        .forge_executor()
+        .with_journal("side-effects");
+    let give_ups_before = give_ups();
+
+    // Disabled: errors still work, but nothing is reported.
+    let err = AppError::config("missing key");
+    assert_eq!(err.to_string(), "⚙️ Configuration Error: missing key");
+    log_error(&err);
+    let _ = executor.retry(|| Err::<(), _>(AppError::network("svc", None)));
+    assert_eq!(seen.load(Ordering::Relaxed), 0);
+    assert_eq!(LOGGED.load(Ordering::Relaxed), 0);
+    assert_eq!(give_ups(), give_ups_before);
+
+    // Enabled at runtime: the same calls reach every sink.
+    set_side_effects(SideEffects::Enabled);
+    assert_eq!(side_effects(), SideEffects::Enabled);
+    let err = AppError::config("missing key");
+    log_error(&err);
+    let _ = executor.retry(|| Err::<(), _>(AppError::network("svc", None)));
+    // Config, two Network attempts, and the journaled sequence.
+    assert_eq!(seen.load(Ordering::Relaxed), 4);
+    assert_eq!(LOGGED.load(Ordering::Relaxed), 1);
+    assert_ne!(give_ups(), give_ups_before);
+
+    set_side_effects(SideEffects::Disabled);
+    let _ = AppError::config("missing key");
+    assert_eq!(seen.load(Ordering::Relaxed), 4);
+}